@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use outlook_mapi_core::Checkpoint;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(checkpoint) = Checkpoint::parse(data) {
+        assert_eq!(Checkpoint::parse(&checkpoint.to_bytes()), Ok(checkpoint));
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use outlook_mapi_core::MapiUri;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(uri) = core::str::from_utf8(data) {
+        if let Ok(parsed) = MapiUri::parse(uri) {
+            let formatted = parsed.to_string();
+            assert_eq!(MapiUri::parse(&formatted).ok(), Some(parsed));
+        }
+    }
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use outlook_mapi_core::PredecessorChangeList;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(list) = PredecessorChangeList::parse(data) {
+        assert_eq!(list.to_bytes().as_deref(), Ok(data));
+    }
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use outlook_mapi_core::VerbStream;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(verbs) = VerbStream::parse(data) {
+        // Anything which parses should survive a round trip, apart from the ANSI strings which
+        // are replaced by the Unicode display names.
+        if let Ok(bytes) = verbs.to_bytes() {
+            let reparsed = VerbStream::parse(&bytes).expect("serialized verb stream should parse");
+            assert_eq!(reparsed.options.len(), verbs.options.len());
+        }
+    }
+});
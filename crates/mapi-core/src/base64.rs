@@ -0,0 +1,117 @@
+//! Define [`to_base64`] and [`from_base64`].
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+const BASE64_DIGITS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Errors which can be returned from [`from_base64`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Base64Error {
+    /// Every 3 bytes take 4 digits, and the last group is padded with `=`, so the input length
+    /// should be a multiple of 4.
+    InvalidLength,
+
+    /// The character at this offset is not a base64 digit, or it is padding in the wrong place.
+    InvalidDigit(usize),
+}
+
+impl fmt::Display for Base64Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength => write!(f, "base64 length is not a multiple of 4"),
+            Self::InvalidDigit(offset) => write!(f, "invalid base64 digit at offset {offset}"),
+        }
+    }
+}
+
+/// Format a binary value with the standard base64 alphabet and `=` padding (RFC 4648), which is
+/// more compact than [`crate::to_hex`] for values which are stored as text.
+pub fn to_base64(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0_u32, |group, (idx, byte)| {
+            group | ((*byte as u32) << (16 - idx * 8))
+        });
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                let digit = (group >> (18 - idx * 6)) & 0x3F;
+                result.push(BASE64_DIGITS[digit as usize] as char);
+            } else {
+                result.push('=');
+            }
+        }
+    }
+    result
+}
+
+/// Parse a padded base64 string back into a binary value. This is the inverse of [`to_base64`].
+pub fn from_base64(value: &str) -> Result<Vec<u8>, Base64Error> {
+    let digits = value.as_bytes();
+    if digits.len() % 4 != 0 {
+        return Err(Base64Error::InvalidLength);
+    }
+
+    let padding = digits
+        .iter()
+        .rev()
+        .take(2)
+        .filter(|&&digit| digit == b'=')
+        .count();
+    let mut result = Vec::with_capacity(digits.len() / 4 * 3);
+    for (chunk_idx, chunk) in digits.chunks(4).enumerate() {
+        let last = (chunk_idx + 1) * 4 == digits.len();
+        let count = if last { 4 - padding } else { 4 };
+        let mut group = 0_u32;
+        for (idx, digit) in chunk.iter().enumerate() {
+            let offset = chunk_idx * 4 + idx;
+            let value = match digit {
+                b'A'..=b'Z' if idx < count => digit - b'A',
+                b'a'..=b'z' if idx < count => digit - b'a' + 26,
+                b'0'..=b'9' if idx < count => digit - b'0' + 52,
+                b'+' if idx < count => 62,
+                b'/' if idx < count => 63,
+                b'=' if idx >= count => 0,
+                _ => return Err(Base64Error::InvalidDigit(offset)),
+            };
+            group = (group << 6) | value as u32;
+        }
+        let bytes = group.to_be_bytes();
+        result.extend_from_slice(&bytes[1..count]);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn base64_round_trip() {
+        for (bytes, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"fooba", "Zm9vYmE="),
+            (b"foobar", "Zm9vYmFy"),
+            (&[0xFB, 0xFF, 0x00], "+/8A"),
+        ] {
+            assert_eq!(to_base64(bytes), encoded);
+            assert_eq!(from_base64(encoded), Ok(bytes.to_vec()));
+        }
+    }
+
+    #[test]
+    fn base64_errors() {
+        assert_eq!(from_base64("Zm9"), Err(Base64Error::InvalidLength));
+        assert_eq!(from_base64("Zm9!"), Err(Base64Error::InvalidDigit(3)));
+        assert_eq!(from_base64("Z=9v"), Err(Base64Error::InvalidDigit(1)));
+        assert_eq!(from_base64("Zg==Zg=="), Err(Base64Error::InvalidDigit(2)));
+        assert_eq!(from_base64("===="), Err(Base64Error::InvalidDigit(0)));
+        assert_eq!(from_base64("AAAA"), Ok(vec![0, 0, 0]));
+    }
+}
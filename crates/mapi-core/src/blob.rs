@@ -0,0 +1,253 @@
+//! Define [`BlobReader`], [`BlobWriter`], and [`BlobError`].
+//!
+//! Most of the structured binary properties in MAPI (e.g. `PidLidVerbStream` or
+//! `PR_PREDECESSOR_CHANGE_LIST`) are sequences of little-endian integers and counted strings.
+//! These helpers do the bounds checking once, so the parsers built on them never index past the
+//! end of a value, no matter what the input looks like.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// Errors which can be returned by [`BlobReader`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlobError {
+    /// The value ended in the middle of a field.
+    Truncated {
+        /// Offset of the field which could not be read.
+        offset: usize,
+
+        /// Number of bytes the field needed.
+        needed: usize,
+    },
+
+    /// A string is not valid UTF-16.
+    InvalidString {
+        /// Offset of the string.
+        offset: usize,
+    },
+}
+
+impl fmt::Display for BlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated { offset, needed } => {
+                write!(
+                    f,
+                    "value is truncated: needed {needed} bytes at offset {offset}"
+                )
+            }
+            Self::InvalidString { offset } => {
+                write!(f, "invalid UTF-16 string at offset {offset}")
+            }
+        }
+    }
+}
+
+/// Bounds checked reader for little-endian binary property values.
+#[derive(Clone, Debug)]
+pub struct BlobReader<'a> {
+    value: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> BlobReader<'a> {
+    /// Start reading at the beginning of `value`.
+    pub fn new(value: &'a [u8]) -> Self {
+        Self { value, offset: 0 }
+    }
+
+    /// Number of bytes which have already been read.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Number of bytes which have not been read yet.
+    pub fn remaining(&self) -> usize {
+        self.value.len() - self.offset
+    }
+
+    /// Test if every byte has been read.
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Read the next `len` bytes.
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], BlobError> {
+        if self.remaining() < len {
+            return Err(BlobError::Truncated {
+                offset: self.offset,
+                needed: len,
+            });
+        }
+        let result = &self.value[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(result)
+    }
+
+    /// Read a fixed size array of bytes.
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], BlobError> {
+        let mut result = [0; N];
+        result.copy_from_slice(self.read_bytes(N)?);
+        Ok(result)
+    }
+
+    /// Skip over the next `len` bytes.
+    pub fn skip(&mut self, len: usize) -> Result<(), BlobError> {
+        self.read_bytes(len).map(|_| ())
+    }
+
+    /// Read a `u8`.
+    pub fn read_u8(&mut self) -> Result<u8, BlobError> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    /// Read a little-endian `u16`.
+    pub fn read_u16(&mut self) -> Result<u16, BlobError> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    /// Read a little-endian `u32`.
+    pub fn read_u32(&mut self) -> Result<u32, BlobError> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    /// Read a little-endian `u64`.
+    pub fn read_u64(&mut self) -> Result<u64, BlobError> {
+        Ok(u64::from_le_bytes(self.read_array()?))
+    }
+
+    /// Read a string of single byte characters with a 1 byte character count. The bytes are
+    /// decoded as Latin-1, since the code page of the value is not known.
+    pub fn read_ansi_string_u8(&mut self) -> Result<String, BlobError> {
+        let count = self.read_u8()? as usize;
+        Ok(self
+            .read_bytes(count)?
+            .iter()
+            .map(|&ch| char::from(ch))
+            .collect())
+    }
+
+    /// Read a UTF-16 string with a 1 byte count of UTF-16 code units.
+    pub fn read_unicode_string_u8(&mut self) -> Result<String, BlobError> {
+        let offset = self.offset;
+        let count = self.read_u8()? as usize;
+        let units: Vec<u16> = self
+            .read_bytes(count * 2)?
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+        String::from_utf16(&units).map_err(|_| BlobError::InvalidString { offset })
+    }
+}
+
+/// Writer for little-endian binary property values, which mirrors [`BlobReader`].
+#[derive(Clone, Debug, Default)]
+pub struct BlobWriter {
+    buffer: Vec<u8>,
+}
+
+impl BlobWriter {
+    /// Start with an empty buffer.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Append raw bytes.
+    pub fn write_bytes(&mut self, value: &[u8]) {
+        self.buffer.extend_from_slice(value);
+    }
+
+    /// Append a `u8`.
+    pub fn write_u8(&mut self, value: u8) {
+        self.buffer.push(value);
+    }
+
+    /// Append a little-endian `u16`.
+    pub fn write_u16(&mut self, value: u16) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Append a little-endian `u32`.
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Append a little-endian `u64`.
+    pub fn write_u64(&mut self, value: u64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Append a string of single byte characters with a 1 byte character count. Characters which
+    /// are not ASCII are replaced with `?`. Returns `None` without writing anything if the string
+    /// has more than 255 characters.
+    pub fn write_ansi_string_u8(&mut self, value: &str) -> Option<()> {
+        let count = u8::try_from(value.chars().count()).ok()?;
+        self.write_u8(count);
+        self.buffer.extend(
+            value
+                .chars()
+                .map(|ch| if ch.is_ascii() { ch as u8 } else { b'?' }),
+        );
+        Some(())
+    }
+
+    /// Append a UTF-16 string with a 1 byte count of UTF-16 code units. Returns `None` without
+    /// writing anything if the string has more than 255 code units.
+    pub fn write_unicode_string_u8(&mut self, value: &str) -> Option<()> {
+        let count = u8::try_from(value.encode_utf16().count()).ok()?;
+        self.write_u8(count);
+        for unit in value.encode_utf16() {
+            self.write_u16(unit);
+        }
+        Some(())
+    }
+
+    /// Get the serialized value.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_integers() {
+        let mut reader = BlobReader::new(&[1, 2, 0, 3, 0, 0, 0, 4]);
+        assert_eq!(reader.read_u8(), Ok(1));
+        assert_eq!(reader.read_u16(), Ok(2));
+        assert_eq!(reader.read_u32(), Ok(3));
+        assert_eq!(reader.remaining(), 1);
+        assert_eq!(
+            reader.read_u16(),
+            Err(BlobError::Truncated {
+                offset: 7,
+                needed: 2
+            })
+        );
+        assert_eq!(reader.read_u8(), Ok(4));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn string_round_trip() {
+        let mut writer = BlobWriter::new();
+        writer.write_ansi_string_u8("caf\u{e9}").unwrap();
+        writer.write_unicode_string_u8("caf\u{e9}").unwrap();
+        let bytes = writer.into_bytes();
+        let mut reader = BlobReader::new(&bytes);
+        assert_eq!(reader.read_ansi_string_u8().unwrap(), "caf?");
+        assert_eq!(reader.read_unicode_string_u8().unwrap(), "caf\u{e9}");
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn string_too_long() {
+        let mut writer = BlobWriter::new();
+        let value = "x".repeat(256);
+        assert_eq!(writer.write_ansi_string_u8(&value), None);
+        assert_eq!(writer.write_unicode_string_u8(&value), None);
+        assert!(writer.into_bytes().is_empty());
+    }
+}
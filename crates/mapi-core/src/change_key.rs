@@ -0,0 +1,256 @@
+//! Define [`Xid`], [`PredecessorChangeList`], and [`ChangeKeyError`].
+
+use crate::{BlobError, BlobReader};
+use alloc::vec::Vec;
+use core::{cmp::Ordering, fmt};
+
+/// Size of the namespace GUID at the beginning of an [`Xid`].
+pub const XID_NAMESPACE_SIZE: usize = 16;
+
+/// Maximum size of the [`Xid::local_id`].
+pub const XID_MAX_LOCAL_ID_SIZE: usize = 8;
+
+/// Errors which can be returned when parsing a `PR_CHANGE_KEY` or `PR_PREDECESSOR_CHANGE_LIST`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeKeyError {
+    /// An [`Xid`] must have a namespace GUID and at least 1 byte of local ID.
+    TooShort,
+
+    /// The local ID of an [`Xid`] must not be larger than [`XID_MAX_LOCAL_ID_SIZE`].
+    TooLong,
+
+    /// The size prefix of an entry in the [`PredecessorChangeList`] runs past the end of the value.
+    Truncated,
+}
+
+impl From<BlobError> for ChangeKeyError {
+    fn from(_: BlobError) -> Self {
+        Self::Truncated
+    }
+}
+
+impl fmt::Display for ChangeKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooShort => write!(f, "XID is too short"),
+            Self::TooLong => write!(f, "XID local ID is too long"),
+            Self::Truncated => write!(f, "predecessor change list is truncated"),
+        }
+    }
+}
+
+/// External identifier, used as the format of `PR_CHANGE_KEY` and of each entry in a
+/// `PR_PREDECESSOR_CHANGE_LIST`.
+///
+/// An [`Xid`] is a 16 byte namespace GUID identifying the replica which made the change, followed
+/// by a variable length (usually 6 byte) big-endian change number in that namespace.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Xid {
+    /// Namespace GUID, in the same byte order as the value on the wire.
+    pub namespace: [u8; XID_NAMESPACE_SIZE],
+
+    /// Big-endian change number within the [`Xid::namespace`].
+    pub local_id: Vec<u8>,
+}
+
+impl Xid {
+    /// Parse a `PR_CHANGE_KEY` value.
+    pub fn parse(value: &[u8]) -> Result<Self, ChangeKeyError> {
+        if value.len() <= XID_NAMESPACE_SIZE {
+            return Err(ChangeKeyError::TooShort);
+        }
+        if value.len() > XID_NAMESPACE_SIZE + XID_MAX_LOCAL_ID_SIZE {
+            return Err(ChangeKeyError::TooLong);
+        }
+
+        let (namespace, local_id) = value.split_at(XID_NAMESPACE_SIZE);
+        let mut result = Self {
+            namespace: [0; XID_NAMESPACE_SIZE],
+            local_id: local_id.to_vec(),
+        };
+        result.namespace.copy_from_slice(namespace);
+        Ok(result)
+    }
+
+    /// Serialize the [`Xid`] back into a `PR_CHANGE_KEY` value. Fails with the same errors as
+    /// [`Xid::parse`] if the [`Xid::local_id`] is empty or longer than [`XID_MAX_LOCAL_ID_SIZE`].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ChangeKeyError> {
+        if self.local_id.is_empty() {
+            return Err(ChangeKeyError::TooShort);
+        }
+        if self.local_id.len() > XID_MAX_LOCAL_ID_SIZE {
+            return Err(ChangeKeyError::TooLong);
+        }
+
+        let mut result = Vec::with_capacity(XID_NAMESPACE_SIZE + self.local_id.len());
+        result.extend_from_slice(&self.namespace);
+        result.extend_from_slice(&self.local_id);
+        Ok(result)
+    }
+
+    /// Compare the [`Xid::local_id`] values of two [`Xid`] in the same namespace. Returns `None`
+    /// if the namespaces differ, since change numbers from different replicas are not ordered.
+    pub fn compare_local_id(&self, other: &Self) -> Option<Ordering> {
+        if self.namespace != other.namespace {
+            return None;
+        }
+
+        // Compare as big-endian numbers, ignoring leading zeroes if the sizes differ.
+        let trim = |value: &[u8]| -> Vec<u8> {
+            value
+                .iter()
+                .copied()
+                .skip_while(|byte| *byte == 0)
+                .collect()
+        };
+        let (lhs, rhs) = (trim(&self.local_id), trim(&other.local_id));
+        Some(lhs.len().cmp(&rhs.len()).then_with(|| lhs.cmp(&rhs)))
+    }
+}
+
+/// Parsed `PR_PREDECESSOR_CHANGE_LIST` value, which holds the most recent [`Xid`] from every
+/// namespace that has modified the object.
+///
+/// Each entry is serialized as a 1 byte size prefix followed by that many bytes of [`Xid`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PredecessorChangeList {
+    /// The [`Xid`] entries in the same order as the serialized value.
+    pub xids: Vec<Xid>,
+}
+
+impl PredecessorChangeList {
+    /// Parse a `PR_PREDECESSOR_CHANGE_LIST` value.
+    pub fn parse(value: &[u8]) -> Result<Self, ChangeKeyError> {
+        let mut xids = Vec::new();
+        let mut reader = BlobReader::new(value);
+        while !reader.is_empty() {
+            let size = reader.read_u8()? as usize;
+            xids.push(Xid::parse(reader.read_bytes(size)?)?);
+        }
+        Ok(Self { xids })
+    }
+
+    /// Serialize the [`PredecessorChangeList`] back into a `PR_PREDECESSOR_CHANGE_LIST` value.
+    /// Fails if any of the [`PredecessorChangeList::xids`] fails [`Xid::to_bytes`], which also
+    /// keeps every entry small enough for its 1 byte size prefix.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ChangeKeyError> {
+        let mut result = Vec::new();
+        for xid in self.xids.iter() {
+            let xid = xid.to_bytes()?;
+            result.push(xid.len() as u8);
+            result.extend(xid);
+        }
+        Ok(result)
+    }
+
+    /// Get the [`Xid`] for a namespace, if that namespace has modified the object.
+    pub fn find(&self, namespace: &[u8; XID_NAMESPACE_SIZE]) -> Option<&Xid> {
+        self.xids.iter().find(|xid| xid.namespace == *namespace)
+    }
+
+    /// Test if the change identified by `change_key` is already reflected in this list, i.e. the
+    /// list has an [`Xid`] in the same namespace with a local ID at least as large.
+    ///
+    /// If this returns `false` for the `PR_CHANGE_KEY` of another copy of the object, the copies
+    /// have diverged and a sync engine should treat it as a conflict.
+    pub fn includes(&self, change_key: &Xid) -> bool {
+        self.find(&change_key.namespace)
+            .and_then(|xid| xid.compare_local_id(change_key))
+            .is_some_and(|ordering| ordering != Ordering::Less)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const NAMESPACE_A: [u8; XID_NAMESPACE_SIZE] = [0xAA; XID_NAMESPACE_SIZE];
+    const NAMESPACE_B: [u8; XID_NAMESPACE_SIZE] = [0xBB; XID_NAMESPACE_SIZE];
+
+    fn xid(namespace: [u8; XID_NAMESPACE_SIZE], local_id: &[u8]) -> Xid {
+        Xid {
+            namespace,
+            local_id: local_id.to_vec(),
+        }
+    }
+
+    #[test]
+    fn xid_round_trip() {
+        let expected = xid(NAMESPACE_A, &[0, 0, 0, 0, 1, 2]);
+        let bytes = expected.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 22);
+        assert_eq!(Xid::parse(&bytes), Ok(expected));
+    }
+
+    #[test]
+    fn xid_errors() {
+        assert_eq!(Xid::parse(&NAMESPACE_A), Err(ChangeKeyError::TooShort));
+        assert_eq!(
+            Xid::parse(&[0; XID_NAMESPACE_SIZE + XID_MAX_LOCAL_ID_SIZE + 1]),
+            Err(ChangeKeyError::TooLong)
+        );
+        assert_eq!(
+            xid(NAMESPACE_A, &[]).to_bytes(),
+            Err(ChangeKeyError::TooShort)
+        );
+        assert_eq!(
+            xid(NAMESPACE_A, &[1; XID_MAX_LOCAL_ID_SIZE + 1]).to_bytes(),
+            Err(ChangeKeyError::TooLong)
+        );
+    }
+
+    #[test]
+    fn xid_compare_local_id() {
+        let lhs = xid(NAMESPACE_A, &[0, 0, 0, 0, 0, 2]);
+        assert_eq!(
+            lhs.compare_local_id(&xid(NAMESPACE_A, &[0, 1])),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            lhs.compare_local_id(&xid(NAMESPACE_A, &[2])),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(lhs.compare_local_id(&xid(NAMESPACE_B, &[2])), None);
+    }
+
+    #[test]
+    fn pcl_round_trip() {
+        let expected = PredecessorChangeList {
+            xids: vec![
+                xid(NAMESPACE_A, &[0, 0, 0, 0, 0, 5]),
+                xid(NAMESPACE_B, &[0, 0, 0, 0, 1, 0]),
+            ],
+        };
+        let bytes = expected.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 46);
+        assert_eq!(bytes[0], 22);
+        assert_eq!(PredecessorChangeList::parse(&bytes), Ok(expected));
+        assert_eq!(
+            PredecessorChangeList::parse(&bytes[..45]),
+            Err(ChangeKeyError::Truncated)
+        );
+    }
+
+    #[test]
+    fn pcl_too_long() {
+        let pcl = PredecessorChangeList {
+            xids: vec![
+                xid(NAMESPACE_A, &[0, 0, 0, 0, 0, 5]),
+                xid(NAMESPACE_B, &[1; 300]),
+            ],
+        };
+        assert_eq!(pcl.to_bytes(), Err(ChangeKeyError::TooLong));
+    }
+
+    #[test]
+    fn pcl_includes() {
+        let pcl = PredecessorChangeList {
+            xids: vec![xid(NAMESPACE_A, &[0, 0, 0, 0, 0, 5])],
+        };
+        assert!(pcl.includes(&xid(NAMESPACE_A, &[0, 0, 0, 0, 0, 4])));
+        assert!(pcl.includes(&xid(NAMESPACE_A, &[0, 0, 0, 0, 0, 5])));
+        assert!(!pcl.includes(&xid(NAMESPACE_A, &[0, 0, 0, 0, 0, 6])));
+        assert!(!pcl.includes(&xid(NAMESPACE_B, &[0, 0, 0, 0, 0, 1])));
+    }
+}
@@ -0,0 +1,250 @@
+//! Define [`Checkpoint`], [`FolderProgress`], and [`CheckpointError`].
+
+use crate::{BlobError, BlobReader, BlobWriter};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::fmt;
+
+/// Signature at the beginning of a serialized [`Checkpoint`].
+const CHECKPOINT_SIGNATURE: [u8; 4] = *b"MCKP";
+
+/// Version of the serialized [`Checkpoint`] format written by [`Checkpoint::to_bytes`].
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+/// Errors which can be returned by [`Checkpoint::parse`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckpointError {
+    /// The value does not start with the [`Checkpoint`] signature.
+    InvalidSignature,
+
+    /// The value was written by a newer version of the format.
+    UnsupportedVersion(u32),
+
+    /// The value ended in the middle of a field.
+    Truncated,
+
+    /// There are bytes left over after the last folder.
+    TrailingData,
+}
+
+impl From<BlobError> for CheckpointError {
+    fn from(_: BlobError) -> Self {
+        Self::Truncated
+    }
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSignature => write!(f, "checkpoint signature is invalid"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "checkpoint version {version} is not supported")
+            }
+            Self::Truncated => write!(f, "checkpoint is truncated"),
+            Self::TrailingData => write!(f, "checkpoint has trailing data"),
+        }
+    }
+}
+
+/// Progress through the contents of a single folder, recorded in a [`Checkpoint`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FolderProgress {
+    /// Number of rows from the start of the contents table which have already been processed.
+    /// Resuming skips this many rows, so it is only accurate if the folder has not changed in the
+    /// meantime.
+    pub rows: u64,
+
+    /// The contents table was read to the end, so the folder can be skipped entirely.
+    pub done: bool,
+
+    /// Opaque incremental change synchronization (ICS) state for the folder, e.g. the contents of
+    /// the `IStream` passed to `IExchangeExportChanges::Config`. The crawl does not interpret
+    /// this, it only carries it along with the rest of the checkpoint.
+    pub sync_state: Vec<u8>,
+}
+
+/// Serializable record of how far a crawl or export got through each folder, so an interrupted
+/// run can resume where it left off instead of starting over.
+///
+/// Folders are identified by the `PR_ENTRYID` of the store and of the folder, and the record is
+/// serialized with [`Checkpoint::to_bytes`] so it can be saved to a file between runs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    folders: BTreeMap<(Vec<u8>, Vec<u8>), FolderProgress>,
+}
+
+impl Checkpoint {
+    /// Start with an empty checkpoint, which resumes nothing.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Get the [`FolderProgress`] for a folder, if anything has been recorded for it.
+    pub fn progress(&self, store: &[u8], folder: &[u8]) -> Option<&FolderProgress> {
+        self.folders.get(&(store.to_vec(), folder.to_vec()))
+    }
+
+    /// Get the [`FolderProgress`] for a folder, and add an empty one if it is missing.
+    pub fn progress_mut(&mut self, store: &[u8], folder: &[u8]) -> &mut FolderProgress {
+        self.folders
+            .entry((store.to_vec(), folder.to_vec()))
+            .or_default()
+    }
+
+    /// Add `rows` to the number of rows processed in a folder.
+    pub fn record_rows(&mut self, store: &[u8], folder: &[u8], rows: u64) {
+        let progress = self.progress_mut(store, folder);
+        progress.rows = progress.rows.saturating_add(rows);
+    }
+
+    /// Record that a folder was read to the end.
+    pub fn mark_done(&mut self, store: &[u8], folder: &[u8]) {
+        self.progress_mut(store, folder).done = true;
+    }
+
+    /// Test if a folder was read to the end.
+    pub fn is_done(&self, store: &[u8], folder: &[u8]) -> bool {
+        self.progress(store, folder)
+            .is_some_and(|progress| progress.done)
+    }
+
+    /// Forget the progress for a folder, so it is read from the beginning again.
+    pub fn reset(&mut self, store: &[u8], folder: &[u8]) -> Option<FolderProgress> {
+        self.folders.remove(&(store.to_vec(), folder.to_vec()))
+    }
+
+    /// Iterate over the recorded folders as `(store, folder, progress)`.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &[u8], &FolderProgress)> {
+        self.folders
+            .iter()
+            .map(|((store, folder), progress)| (store.as_slice(), folder.as_slice(), progress))
+    }
+
+    /// Number of folders with recorded progress.
+    pub fn len(&self) -> usize {
+        self.folders.len()
+    }
+
+    /// Test if no progress has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.folders.is_empty()
+    }
+
+    /// Parse a value written by [`Checkpoint::to_bytes`].
+    pub fn parse(value: &[u8]) -> Result<Self, CheckpointError> {
+        let mut reader = BlobReader::new(value);
+        if reader.read_array::<4>()? != CHECKPOINT_SIGNATURE {
+            return Err(CheckpointError::InvalidSignature);
+        }
+        let version = reader.read_u32()?;
+        if version != CHECKPOINT_VERSION {
+            return Err(CheckpointError::UnsupportedVersion(version));
+        }
+
+        let count = reader.read_u32()?;
+        let mut result = Self::new();
+        for _ in 0..count {
+            let store = read_counted_bytes(&mut reader)?;
+            let folder = read_counted_bytes(&mut reader)?;
+            let rows = reader.read_u64()?;
+            let done = reader.read_u8()? != 0;
+            let sync_state = read_counted_bytes(&mut reader)?;
+            result.folders.insert(
+                (store, folder),
+                FolderProgress {
+                    rows,
+                    done,
+                    sync_state,
+                },
+            );
+        }
+        if !reader.is_empty() {
+            return Err(CheckpointError::TrailingData);
+        }
+        Ok(result)
+    }
+
+    /// Serialize the checkpoint, so it can be saved and passed to [`Checkpoint::parse`] later.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = BlobWriter::new();
+        writer.write_bytes(&CHECKPOINT_SIGNATURE);
+        writer.write_u32(CHECKPOINT_VERSION);
+        writer.write_u32(self.folders.len() as u32);
+        for ((store, folder), progress) in self.folders.iter() {
+            write_counted_bytes(&mut writer, store);
+            write_counted_bytes(&mut writer, folder);
+            writer.write_u64(progress.rows);
+            writer.write_u8(u8::from(progress.done));
+            write_counted_bytes(&mut writer, &progress.sync_state);
+        }
+        writer.into_bytes()
+    }
+}
+
+fn read_counted_bytes(reader: &mut BlobReader) -> Result<Vec<u8>, CheckpointError> {
+    let count = reader.read_u32()? as usize;
+    Ok(reader.read_bytes(count)?.to_vec())
+}
+
+fn write_counted_bytes(writer: &mut BlobWriter, value: &[u8]) {
+    writer.write_u32(value.len() as u32);
+    writer.write_bytes(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORE: &[u8] = &[0x01, 0x02, 0x03];
+    const FOLDER_A: &[u8] = &[0xAA; 4];
+    const FOLDER_B: &[u8] = &[0xBB; 4];
+
+    #[test]
+    fn record_progress() {
+        let mut checkpoint = Checkpoint::new();
+        assert!(checkpoint.progress(STORE, FOLDER_A).is_none());
+        checkpoint.record_rows(STORE, FOLDER_A, 100);
+        checkpoint.record_rows(STORE, FOLDER_A, 25);
+        checkpoint.mark_done(STORE, FOLDER_B);
+        assert_eq!(checkpoint.progress(STORE, FOLDER_A).unwrap().rows, 125);
+        assert!(!checkpoint.is_done(STORE, FOLDER_A));
+        assert!(checkpoint.is_done(STORE, FOLDER_B));
+        assert_eq!(checkpoint.len(), 2);
+        assert!(checkpoint.reset(STORE, FOLDER_B).is_some());
+        assert!(!checkpoint.is_done(STORE, FOLDER_B));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut expected = Checkpoint::new();
+        expected.record_rows(STORE, FOLDER_A, 42);
+        expected.mark_done(STORE, FOLDER_B);
+        expected.progress_mut(STORE, FOLDER_B).sync_state = [1, 2, 3, 4].to_vec();
+        let bytes = expected.to_bytes();
+        assert_eq!(&bytes[..4], b"MCKP");
+        assert_eq!(Checkpoint::parse(&bytes), Ok(expected));
+        assert_eq!(
+            Checkpoint::parse(&bytes[..bytes.len() - 1]),
+            Err(CheckpointError::Truncated)
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Checkpoint::parse(b"XXXX\x01\0\0\0\0\0\0\0"),
+            Err(CheckpointError::InvalidSignature)
+        );
+        assert_eq!(
+            Checkpoint::parse(b"MCKP\x02\0\0\0\0\0\0\0"),
+            Err(CheckpointError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            Checkpoint::parse(b"MCKP\x01\0\0\0\0\0\0\0\0"),
+            Err(CheckpointError::TrailingData)
+        );
+        assert_eq!(
+            Checkpoint::parse(b"MCKP\x01\0\0\0\0\0\0\0"),
+            Ok(Checkpoint::new())
+        );
+    }
+}
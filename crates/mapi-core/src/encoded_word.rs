@@ -0,0 +1,262 @@
+//! Define [`decode_encoded_words`], [`decode_encoded_words_with`], [`decode_charset`],
+//! [`find_header`], and [`find_header_with`].
+
+use crate::from_base64;
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Unicode code points for the `0x80..=0x9F` range of `windows-1252`, which `iso-8859-1` leaves
+/// as control characters. The 5 unassigned bytes map to the matching control character, like the
+/// WHATWG encoding standard.
+const WINDOWS_1252_HIGH: [u16; 32] = [
+    0x20AC, 0x0081, 0x201A, 0x0192, 0x201E, 0x2026, 0x2020, 0x2021, 0x02C6, 0x2030, 0x0160, 0x2039,
+    0x0152, 0x008D, 0x017D, 0x008F, 0x0090, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014,
+    0x02DC, 0x2122, 0x0161, 0x203A, 0x0153, 0x009D, 0x017E, 0x0178,
+];
+
+/// Decode `bytes` in one of the charsets which do not need a large table: `utf-8`, `us-ascii`,
+/// `iso-8859-1`, and `windows-1252`. The charset name is matched case-insensitively. Returns `None`
+/// for any other charset.
+pub fn decode_charset(charset: &str, bytes: &[u8]) -> Option<String> {
+    let charset = charset.to_ascii_lowercase();
+    match charset.as_str() {
+        "utf-8" | "utf8" => Some(String::from_utf8_lossy(bytes).into_owned()),
+        "us-ascii" | "ascii" | "iso-8859-1" | "latin1" | "windows-1252" | "cp1252" => Some(
+            bytes
+                .iter()
+                .map(|&byte| match byte {
+                    0x80..=0x9F => char::from_u32(WINDOWS_1252_HIGH[(byte - 0x80) as usize] as u32)
+                        .unwrap_or(char::REPLACEMENT_CHARACTER),
+                    _ => byte as char,
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+fn hex_digit(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        _ => None,
+    }
+}
+
+fn decode_q(text: &str) -> Option<Vec<u8>> {
+    let mut result = Vec::with_capacity(text.len());
+    let mut bytes = text.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'_' => result.push(b' '),
+            b'=' => {
+                let high = hex_digit(bytes.next()?)?;
+                let low = hex_digit(bytes.next()?)?;
+                result.push((high << 4) | low);
+            }
+            _ => result.push(byte),
+        }
+    }
+    Some(result)
+}
+
+fn decode_b(text: &str) -> Option<Vec<u8>> {
+    // Some senders drop the `=` padding, which [`from_base64`] requires.
+    let mut text = text.to_string();
+    while text.len() % 4 != 0 {
+        text.push('=');
+    }
+    from_base64(&text).ok()
+}
+
+/// Decode a single `=?charset?encoding?text?=` encoded-word, or return `None` if it is malformed
+/// or `decode` does not recognize the charset.
+fn decode_word<F>(word: &str, decode: &mut F) -> Option<String>
+where
+    F: FnMut(&str, &[u8]) -> Option<String>,
+{
+    let inner = word.strip_prefix("=?")?.strip_suffix("?=")?;
+    let mut parts = inner.splitn(3, '?');
+    let charset = parts.next()?;
+    let encoding = parts.next()?;
+    let text = parts.next()?;
+    if text.contains('?') || text.contains(char::is_whitespace) {
+        return None;
+    }
+
+    // RFC 2231 allows a language suffix, e.g. `us-ascii*en`.
+    let charset = charset.split('*').next()?;
+    let bytes = match encoding {
+        "Q" | "q" => decode_q(text)?,
+        "B" | "b" => decode_b(text)?,
+        _ => return None,
+    };
+    decode(charset, &bytes)
+}
+
+/// Find the end of the encoded-word which starts at the beginning of `value`, i.e. the offset
+/// just past the closing `?=`.
+fn encoded_word_len(value: &str) -> Option<usize> {
+    let bytes = value.as_bytes();
+    if !value.starts_with("=?") {
+        return None;
+    }
+
+    // Skip the charset and encoding, so the `?=` search starts in the encoded text.
+    let mut offset = 2;
+    for _ in 0..2 {
+        offset += bytes[offset..].iter().position(|&byte| byte == b'?')? + 1;
+    }
+    let end = value[offset..].find("?=")?;
+    Some(offset + end + 2)
+}
+
+/// Decode the RFC 2047 encoded-words in a header value, such as a `Subject` or the display name
+/// in a `From` address, with [`decode_charset`]. Whitespace between adjacent encoded-words is
+/// dropped, and anything which is not a valid encoded-word is copied as-is.
+pub fn decode_encoded_words(value: &str) -> String {
+    decode_encoded_words_with(value, decode_charset)
+}
+
+/// Decode the RFC 2047 encoded-words in a header value like [`decode_encoded_words`], but with a
+/// custom `decode` function for the charsets, e.g. one which falls back to the Windows code pages.
+pub fn decode_encoded_words_with<F>(value: &str, mut decode: F) -> String
+where
+    F: FnMut(&str, &[u8]) -> Option<String>,
+{
+    let mut result = String::with_capacity(value.len());
+    let mut pending_space = "";
+    let mut after_word = false;
+    let mut rest = value;
+    while !rest.is_empty() {
+        if let Some(len) = encoded_word_len(rest) {
+            if let Some(decoded) = decode_word(&rest[..len], &mut decode) {
+                if !after_word {
+                    result.push_str(pending_space);
+                }
+                result.push_str(&decoded);
+                pending_space = "";
+                after_word = true;
+                rest = &rest[len..];
+                continue;
+            }
+        }
+
+        let space = rest.len() - rest.trim_start().len();
+        if space > 0 {
+            result.push_str(pending_space);
+            pending_space = &rest[..space];
+            rest = &rest[space..];
+            continue;
+        }
+
+        // Stop at the next whitespace or `=?`, so an encoded-word which is quoted, e.g. in the
+        // display name of an address, is still decoded.
+        let first = rest.chars().next().map_or(0, char::len_utf8);
+        let len = rest[first..]
+            .char_indices()
+            .find(|&(offset, ch)| ch.is_whitespace() || rest[first + offset..].starts_with("=?"))
+            .map_or(rest.len(), |(offset, _)| first + offset);
+        result.push_str(pending_space);
+        result.push_str(&rest[..len]);
+        pending_space = "";
+        after_word = false;
+        rest = &rest[len..];
+    }
+    result.push_str(pending_space);
+    result
+}
+
+/// Find the first header named `name` (case-insensitive) in a block of RFC 5322 headers, such as
+/// [`PR_TRANSPORT_MESSAGE_HEADERS`], then unfold it and decode its encoded-words with
+/// [`decode_encoded_words`].
+///
+/// [`PR_TRANSPORT_MESSAGE_HEADERS`]: https://learn.microsoft.com/en-us/office/client-developer/outlook/mapi/pidtagtransportmessageheaders-canonical-property
+pub fn find_header(headers: &str, name: &str) -> Option<String> {
+    find_header_with(headers, name, decode_charset)
+}
+
+/// Find and unfold a header like [`find_header`], but decode its encoded-words with
+/// [`decode_encoded_words_with`] and a custom `decode` function for the charsets.
+pub fn find_header_with<F>(headers: &str, name: &str, decode: F) -> Option<String>
+where
+    F: FnMut(&str, &[u8]) -> Option<String>,
+{
+    let mut lines = headers.lines();
+    while let Some(line) = lines.next() {
+        let Some((field, value)) = line.split_once(':') else {
+            continue;
+        };
+        if line.starts_with(char::is_whitespace) || !field.trim_end().eq_ignore_ascii_case(name) {
+            continue;
+        }
+
+        let mut unfolded = value.trim().to_string();
+        for line in lines.by_ref() {
+            if !line.starts_with(char::is_whitespace) || line.trim().is_empty() {
+                break;
+            }
+            unfolded.push(' ');
+            unfolded.push_str(line.trim());
+        }
+        return Some(decode_encoded_words_with(&unfolded, decode));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_words() {
+        for (value, decoded) in [
+            ("plain subject", "plain subject"),
+            ("=?utf-8?Q?caf=C3=A9?=", "café"),
+            ("=?UTF-8?B?Y2Fmw6k=?=", "café"),
+            ("=?utf-8?b?Y2Fmw6k?=", "café"),
+            ("=?iso-8859-1?q?a_b?= c", "a b c"),
+            ("=?iso-8859-1?q?a?= =?iso-8859-1?q?b?=", "ab"),
+            ("x =?iso-8859-1?q?a?=\t =?iso-8859-1?q?b?= y", "x ab y"),
+            ("=?windows-1252?Q?=93quoted=94?=", "\u{201C}quoted\u{201D}"),
+            ("=?us-ascii*en?Q?hello?=", "hello"),
+            ("=?unknown?Q?hello?=", "=?unknown?Q?hello?="),
+            ("=?utf-8?X?hello?=", "=?utf-8?X?hello?="),
+            ("=?utf-8?Q?bad=ZZ?=", "=?utf-8?Q?bad=ZZ?="),
+            ("trailing  ", "trailing  "),
+        ] {
+            assert_eq!(decode_encoded_words(value), decoded, "{value}");
+        }
+    }
+
+    #[test]
+    fn decode_words_with() {
+        let decoded = decode_encoded_words_with("=?x-test?Q?abc?=", |charset, bytes| {
+            (charset == "x-test").then(|| String::from_utf8_lossy(bytes).to_uppercase())
+        });
+        assert_eq!(decoded, "ABC");
+    }
+
+    #[test]
+    fn find_headers() {
+        let headers = "Received: from a\r\n\tby b\r\n\
+            Subject: =?utf-8?Q?caf=C3=A9?=\r\n =?utf-8?Q?_au_lait?=\r\n\
+            From: \"=?iso-8859-1?q?Jos=E9?=\" <jose@example.com>\r\n\r\n";
+        assert_eq!(
+            find_header(headers, "subject").as_deref(),
+            Some("café au lait")
+        );
+        assert_eq!(
+            find_header(headers, "FROM").as_deref(),
+            Some("\"José\" <jose@example.com>")
+        );
+        assert_eq!(
+            find_header(headers, "Received").as_deref(),
+            Some("from a by b")
+        );
+        assert_eq!(find_header(headers, "To"), None);
+    }
+}
@@ -0,0 +1,219 @@
+//! Define [`EntryId`] and [`EntryIdError`].
+
+use crate::{from_base64, from_hex, to_base64, to_hex, Base64Error, HexDisplay, HexError};
+use alloc::{string::String, vec::Vec};
+use core::{fmt, ops::Deref, str::FromStr};
+
+/// `MAPI_SHORTTERM` flag in the first byte of [`EntryId::flags`].
+pub const MAPI_SHORTTERM: u8 = 0x80;
+
+/// `MAPI_NOTRECIP` flag in the first byte of [`EntryId::flags`].
+pub const MAPI_NOTRECIP: u8 = 0x40;
+
+/// `MAPI_THISSESSION` flag in the first byte of [`EntryId::flags`].
+pub const MAPI_THISSESSION: u8 = 0x20;
+
+/// `MAPI_NOW` flag in the first byte of [`EntryId::flags`].
+pub const MAPI_NOW: u8 = 0x10;
+
+/// `MAPI_NOTRESERVED` flag in the first byte of [`EntryId::flags`].
+pub const MAPI_NOTRESERVED: u8 = 0x08;
+
+/// Errors which can be returned when parsing an [`EntryId`] from a string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryIdError {
+    /// The value passed to [`EntryId::from_hex`] is not valid hex.
+    InvalidHex(HexError),
+
+    /// The value passed to [`EntryId::from_base64`] is not valid base64.
+    InvalidBase64(Base64Error),
+}
+
+impl fmt::Display for EntryIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHex(err) => write!(f, "invalid entry ID: {err}"),
+            Self::InvalidBase64(err) => write!(f, "invalid entry ID: {err}"),
+        }
+    }
+}
+
+impl From<HexError> for EntryIdError {
+    fn from(value: HexError) -> Self {
+        Self::InvalidHex(value)
+    }
+}
+
+impl From<Base64Error> for EntryIdError {
+    fn from(value: Base64Error) -> Self {
+        Self::InvalidBase64(value)
+    }
+}
+
+/// Owned MAPI `ENTRYID`, e.g. the value of a `PR_ENTRYID` property.
+///
+/// An [`EntryId`] dereferences to `&[u8]`, so it can be passed directly to any of the functions
+/// which take an entry ID as a byte slice. The [`fmt::Display`] and [`FromStr`] implementations
+/// use the same format as [`crate::to_hex`], so an entry ID can be persisted as a string and
+/// parsed back without loss. Byte-wise equality is only a hint, though: the same object can have
+/// several entry IDs, so use `CompareEntryIDs` to test whether two entry IDs refer to the same
+/// object.
+#[derive(Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EntryId(Vec<u8>);
+
+impl EntryId {
+    /// Wrap the bytes of an entry ID.
+    pub fn new(value: Vec<u8>) -> Self {
+        Self(value)
+    }
+
+    /// Borrow the bytes of the entry ID.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Take the bytes of the entry ID.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// Format the entry ID with [`crate::to_hex`], the format MFCMAPI and most logs use.
+    pub fn to_hex(&self) -> String {
+        to_hex(&self.0)
+    }
+
+    /// Parse an entry ID which was formatted with [`EntryId::to_hex`].
+    pub fn from_hex(value: &str) -> Result<Self, EntryIdError> {
+        Ok(Self(from_hex(value)?))
+    }
+
+    /// Format the entry ID with [`crate::to_base64`], which is shorter than hex.
+    pub fn to_base64(&self) -> String {
+        to_base64(&self.0)
+    }
+
+    /// Parse an entry ID which was formatted with [`EntryId::to_base64`].
+    pub fn from_base64(value: &str) -> Result<Self, EntryIdError> {
+        Ok(Self(from_base64(value)?))
+    }
+
+    /// Get the 4 flag bytes at the start of the entry ID, i.e. `ENTRYID::abFlags`. Returns `None`
+    /// if the entry ID is too short to have them.
+    pub fn flags(&self) -> Option<[u8; 4]> {
+        self.0.get(..4)?.try_into().ok()
+    }
+
+    /// Test if the entry ID has the [`MAPI_SHORTTERM`] flag, which means it is only valid for the
+    /// current session and should not be persisted.
+    pub fn is_short_term(&self) -> bool {
+        self.flags()
+            .is_some_and(|flags| flags[0] & MAPI_SHORTTERM != 0)
+    }
+
+    /// Get the `MAPIUID` of the provider which follows the flag bytes in most entry IDs. Returns
+    /// `None` if the entry ID is too short to have one.
+    pub fn provider_uid(&self) -> Option<[u8; 16]> {
+        self.0.get(4..20)?.try_into().ok()
+    }
+}
+
+impl Deref for EntryId {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for EntryId {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for EntryId {
+    fn from(value: Vec<u8>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&[u8]> for EntryId {
+    fn from(value: &[u8]) -> Self {
+        Self(value.to_vec())
+    }
+}
+
+impl From<EntryId> for Vec<u8> {
+    fn from(value: EntryId) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for EntryId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&HexDisplay::new(&self.0), f)
+    }
+}
+
+impl fmt::Debug for EntryId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EntryId")
+            .field(&HexDisplay::new(&self.0))
+            .finish()
+    }
+}
+
+impl FromStr for EntryId {
+    type Err = EntryIdError;
+
+    /// Parse the output of [`fmt::Display`], which is the same as [`EntryId::from_hex`].
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, string::ToString, vec};
+
+    #[test]
+    fn entry_id_round_trip() {
+        let entry_id = EntryId::new(vec![0x00, 0x00, 0x00, 0x00, 0xAB, 0xCD]);
+        assert_eq!(entry_id.to_string(), "00000000ABCD");
+        assert_eq!("00000000ABCD".parse(), Ok(entry_id.clone()));
+        assert_eq!(
+            EntryId::from_base64(&entry_id.to_base64()),
+            Ok(entry_id.clone())
+        );
+        assert_eq!(format!("{entry_id:?}"), "EntryId(00000000ABCD)");
+        assert_eq!(&*entry_id, &[0x00, 0x00, 0x00, 0x00, 0xAB, 0xCD]);
+    }
+
+    #[test]
+    fn entry_id_flags() {
+        let mut bytes = vec![MAPI_SHORTTERM, 0, 0, 0];
+        bytes.extend(1..=16);
+        let entry_id = EntryId::from(bytes);
+        assert_eq!(entry_id.flags(), Some([MAPI_SHORTTERM, 0, 0, 0]));
+        assert!(entry_id.is_short_term());
+        assert_eq!(entry_id.provider_uid().map(|uid| uid[0]), Some(1));
+
+        let entry_id = EntryId::from(&[0_u8, 0][..]);
+        assert_eq!(entry_id.flags(), None);
+        assert!(!entry_id.is_short_term());
+        assert_eq!(entry_id.provider_uid(), None);
+    }
+
+    #[test]
+    fn entry_id_errors() {
+        assert_eq!(
+            EntryId::from_hex("ABC"),
+            Err(EntryIdError::InvalidHex(HexError::OddLength))
+        );
+        assert_eq!(
+            EntryId::from_base64("AB"),
+            Err(EntryIdError::InvalidBase64(Base64Error::InvalidLength))
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Define [`to_hex`], [`from_hex`], and [`HexDisplay`].
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Default number of bytes [`HexDisplay`] will print with [`fmt::Debug`] before truncating.
+pub const DEFAULT_HEX_DEBUG_LIMIT: usize = 32;
+
+/// Errors which can be returned from [`from_hex`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexError {
+    /// Every byte takes 2 hex digits, so the input should have an even length.
+    OddLength,
+
+    /// The character at this offset is not a hex digit.
+    InvalidDigit(usize),
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OddLength => write!(f, "odd number of hex digits"),
+            Self::InvalidDigit(offset) => write!(f, "invalid hex digit at offset {offset}"),
+        }
+    }
+}
+
+/// Format a binary value, such as a `PR_ENTRYID`, as upper case hex digits with no separators.
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        result.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+        result.push(HEX_DIGITS[(byte & 0xF) as usize] as char);
+    }
+    result
+}
+
+/// Parse a string of hex digits (upper or lower case, with no separators) back into a binary
+/// value. This is the inverse of [`to_hex`].
+pub fn from_hex(value: &str) -> Result<Vec<u8>, HexError> {
+    let digits = value.as_bytes();
+    if digits.len() % 2 != 0 {
+        return Err(HexError::OddLength);
+    }
+
+    let digit = |offset: usize| -> Result<u8, HexError> {
+        match digits[offset] {
+            digit @ b'0'..=b'9' => Ok(digit - b'0'),
+            digit @ b'a'..=b'f' => Ok(digit - b'a' + 10),
+            digit @ b'A'..=b'F' => Ok(digit - b'A' + 10),
+            _ => Err(HexError::InvalidDigit(offset)),
+        }
+    };
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|offset| Ok((digit(offset)? << 4) | digit(offset + 1)?))
+        .collect()
+}
+
+/// Wrapper for a binary value which implements [`fmt::Display`] and [`fmt::Debug`] with the same
+/// format as [`to_hex`], without allocating a [`String`].
+///
+/// The [`fmt::Display`] implementation prints every byte, unless a limit was set with
+/// [`HexDisplay::with_limit`]. The [`fmt::Debug`] implementation is meant for logs, so it stops
+/// after [`DEFAULT_HEX_DEBUG_LIMIT`] bytes by default. Either one adds the total size of the value
+/// when it is truncated.
+#[derive(Clone, Copy)]
+pub struct HexDisplay<'a> {
+    bytes: &'a [u8],
+    limit: Option<usize>,
+}
+
+impl<'a> HexDisplay<'a> {
+    /// Wrap a binary value.
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, limit: None }
+    }
+
+    /// Stop printing after `limit` bytes.
+    pub const fn with_limit(self, limit: usize) -> Self {
+        Self {
+            bytes: self.bytes,
+            limit: Some(limit),
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, limit: Option<usize>) -> fmt::Result {
+        let count = limit.map_or(self.bytes.len(), |limit| limit.min(self.bytes.len()));
+        for byte in &self.bytes[..count] {
+            write!(f, "{byte:02X}")?;
+        }
+        if count < self.bytes.len() {
+            write!(f, "... ({} bytes)", self.bytes.len())?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for HexDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, self.limit)
+    }
+}
+
+impl fmt::Debug for HexDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, Some(self.limit.unwrap_or(DEFAULT_HEX_DEBUG_LIMIT)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, vec};
+
+    #[test]
+    fn hex_round_trip() {
+        let bytes = [0x00, 0x01, 0xAB, 0xCD, 0xEF, 0xFF];
+        let hex = to_hex(&bytes);
+        assert_eq!(hex, "0001ABCDEFFF");
+        assert_eq!(from_hex(&hex), Ok(bytes.to_vec()));
+        assert_eq!(from_hex("0001abcdefff"), Ok(bytes.to_vec()));
+    }
+
+    #[test]
+    fn hex_empty() {
+        assert_eq!(to_hex(&[]), "");
+        assert_eq!(from_hex(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn hex_errors() {
+        assert_eq!(from_hex("ABC"), Err(HexError::OddLength));
+        assert_eq!(from_hex("ABCG"), Err(HexError::InvalidDigit(3)));
+        assert_eq!(from_hex("-1"), Err(HexError::InvalidDigit(0)));
+    }
+
+    #[test]
+    fn hex_display() {
+        let bytes = [0x12, 0x34, 0x56];
+        assert_eq!(format!("{}", HexDisplay::new(&bytes)), "123456");
+        assert_eq!(
+            format!("{}", HexDisplay::new(&bytes).with_limit(2)),
+            "1234... (3 bytes)"
+        );
+        assert_eq!(format!("{:?}", HexDisplay::new(&bytes)), "123456");
+    }
+
+    #[test]
+    fn hex_debug_truncates() {
+        let bytes = [0xAA; DEFAULT_HEX_DEBUG_LIMIT + 1];
+        let debug = format!("{:?}", HexDisplay::new(&bytes));
+        assert!(debug.ends_with(&format!("... ({} bytes)", bytes.len())));
+        assert_eq!(
+            debug.len(),
+            DEFAULT_HEX_DEBUG_LIMIT * 2 + format!("... ({} bytes)", bytes.len()).len()
+        );
+        assert_eq!(format!("{}", HexDisplay::new(&bytes)), to_hex(&bytes));
+    }
+}
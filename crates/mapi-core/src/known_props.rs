@@ -0,0 +1,207 @@
+//! Define [`KnownProps`].
+
+use crate::PropTag;
+use core::fmt;
+
+/// Well-known `PR_*` property tags and their names, sorted by tag. The values are copied from
+/// the `outlook-mapi-sys` bindings, so this crate does not depend on the Windows crates.
+const KNOWN_PROPS: &[(u32, &str)] = &[
+    (0x0017_0003, "PR_IMPORTANCE"),
+    (0x001A_001F, "PR_MESSAGE_CLASS_W"),
+    (0x0026_0003, "PR_PRIORITY"),
+    (0x0029_000B, "PR_READ_RECEIPT_REQUESTED"),
+    (0x0036_0003, "PR_SENSITIVITY"),
+    (0x0037_001F, "PR_SUBJECT_W"),
+    (0x0039_0040, "PR_CLIENT_SUBMIT_TIME"),
+    (0x003D_001F, "PR_SUBJECT_PREFIX_W"),
+    (0x0040_001F, "PR_RECEIVED_BY_NAME_W"),
+    (0x0041_0102, "PR_SENT_REPRESENTING_ENTRYID"),
+    (0x0042_001F, "PR_SENT_REPRESENTING_NAME_W"),
+    (0x0049_001F, "PR_ORIGINAL_SUBJECT_W"),
+    (0x0050_001F, "PR_REPLY_RECIPIENT_NAMES_W"),
+    (0x0060_0040, "PR_START_DATE"),
+    (0x0061_0040, "PR_END_DATE"),
+    (0x0064_001F, "PR_SENT_REPRESENTING_ADDRTYPE_W"),
+    (0x0065_001F, "PR_SENT_REPRESENTING_EMAIL_ADDRESS_W"),
+    (0x0070_001F, "PR_CONVERSATION_TOPIC_W"),
+    (0x0071_0102, "PR_CONVERSATION_INDEX"),
+    (0x0076_001F, "PR_RECEIVED_BY_EMAIL_ADDRESS_W"),
+    (0x007D_001F, "PR_TRANSPORT_MESSAGE_HEADERS_W"),
+    (0x0C15_0003, "PR_RECIPIENT_TYPE"),
+    (0x0C19_0102, "PR_SENDER_ENTRYID"),
+    (0x0C1A_001F, "PR_SENDER_NAME_W"),
+    (0x0C1E_001F, "PR_SENDER_ADDRTYPE_W"),
+    (0x0C1F_001F, "PR_SENDER_EMAIL_ADDRESS_W"),
+    (0x0E02_001F, "PR_DISPLAY_BCC_W"),
+    (0x0E03_001F, "PR_DISPLAY_CC_W"),
+    (0x0E04_001F, "PR_DISPLAY_TO_W"),
+    (0x0E06_0040, "PR_MESSAGE_DELIVERY_TIME"),
+    (0x0E07_0003, "PR_MESSAGE_FLAGS"),
+    (0x0E08_0003, "PR_MESSAGE_SIZE"),
+    (0x0E09_0102, "PR_PARENT_ENTRYID"),
+    (0x0E0F_000B, "PR_RESPONSIBILITY"),
+    (0x0E12_000D, "PR_MESSAGE_RECIPIENTS"),
+    (0x0E13_000D, "PR_MESSAGE_ATTACHMENTS"),
+    (0x0E17_0003, "PR_MSG_STATUS"),
+    (0x0E1B_000B, "PR_HASATTACH"),
+    (0x0E1D_001F, "PR_NORMALIZED_SUBJECT_W"),
+    (0x0E1F_000B, "PR_RTF_IN_SYNC"),
+    (0x0E20_0003, "PR_ATTACH_SIZE"),
+    (0x0E21_0003, "PR_ATTACH_NUM"),
+    (0x0FF4_0003, "PR_ACCESS"),
+    (0x0FF6_0102, "PR_INSTANCE_KEY"),
+    (0x0FF7_0003, "PR_ACCESS_LEVEL"),
+    (0x0FF8_0102, "PR_MAPPING_SIGNATURE"),
+    (0x0FF9_0102, "PR_RECORD_KEY"),
+    (0x0FFA_0102, "PR_STORE_RECORD_KEY"),
+    (0x0FFB_0102, "PR_STORE_ENTRYID"),
+    (0x0FFE_0003, "PR_OBJECT_TYPE"),
+    (0x0FFF_0102, "PR_ENTRYID"),
+    (0x1000_001F, "PR_BODY_W"),
+    (0x1009_0102, "PR_RTF_COMPRESSED"),
+    (0x1013_0102, "PR_HTML"),
+    (0x1016_0003, "PR_NATIVE_BODY_INFO"),
+    (0x1035_001F, "PR_INTERNET_MESSAGE_ID_W"),
+    (0x1080_0003, "PR_ICON_INDEX"),
+    (0x1090_0003, "PR_FLAG_STATUS"),
+    (0x10F4_000B, "PR_ATTR_HIDDEN"),
+    (0x10F6_000B, "PR_ATTR_READONLY"),
+    (0x3000_0003, "PR_ROWID"),
+    (0x3001_001F, "PR_DISPLAY_NAME_W"),
+    (0x3002_001F, "PR_ADDRTYPE_W"),
+    (0x3003_001F, "PR_EMAIL_ADDRESS_W"),
+    (0x3004_001F, "PR_COMMENT_W"),
+    (0x3005_0003, "PR_DEPTH"),
+    (0x3006_001F, "PR_PROVIDER_DISPLAY_W"),
+    (0x3007_0040, "PR_CREATION_TIME"),
+    (0x3008_0040, "PR_LAST_MODIFICATION_TIME"),
+    (0x3009_0003, "PR_RESOURCE_FLAGS"),
+    (0x300B_0102, "PR_SEARCH_KEY"),
+    (0x3013_0102, "PR_CONVERSATION_ID"),
+    (0x3400_000B, "PR_DEFAULT_STORE"),
+    (0x340D_0003, "PR_STORE_SUPPORT_MASK"),
+    (0x3414_0102, "PR_MDB_PROVIDER"),
+    (0x35DF_0003, "PR_VALID_FOLDER_MASK"),
+    (0x35E0_0102, "PR_IPM_SUBTREE_ENTRYID"),
+    (0x35E2_0102, "PR_IPM_OUTBOX_ENTRYID"),
+    (0x35E3_0102, "PR_IPM_WASTEBASKET_ENTRYID"),
+    (0x35E4_0102, "PR_IPM_SENTMAIL_ENTRYID"),
+    (0x3600_0003, "PR_CONTAINER_FLAGS"),
+    (0x3601_0003, "PR_FOLDER_TYPE"),
+    (0x3602_0003, "PR_CONTENT_COUNT"),
+    (0x3603_0003, "PR_CONTENT_UNREAD"),
+    (0x360A_000B, "PR_SUBFOLDERS"),
+    (0x360E_000D, "PR_CONTAINER_HIERARCHY"),
+    (0x360F_000D, "PR_CONTAINER_CONTENTS"),
+    (0x3610_000D, "PR_FOLDER_ASSOCIATED_CONTENTS"),
+    (0x3613_001F, "PR_CONTAINER_CLASS_W"),
+    (0x3617_0003, "PR_ASSOC_CONTENT_COUNT"),
+    (0x36D0_0102, "PR_IPM_APPOINTMENT_ENTRYID"),
+    (0x36D1_0102, "PR_IPM_CONTACT_ENTRYID"),
+    (0x36D4_0102, "PR_IPM_TASK_ENTRYID"),
+    (0x36D7_0102, "PR_IPM_DRAFTS_ENTRYID"),
+    (0x3701_0102, "PR_ATTACH_DATA_BIN"),
+    (0x3703_001F, "PR_ATTACH_EXTENSION_W"),
+    (0x3704_001F, "PR_ATTACH_FILENAME_W"),
+    (0x3705_0003, "PR_ATTACH_METHOD"),
+    (0x3707_001F, "PR_ATTACH_LONG_FILENAME_W"),
+    (0x370B_0003, "PR_RENDERING_POSITION"),
+    (0x370E_001F, "PR_ATTACH_MIME_TAG_W"),
+    (0x3712_001F, "PR_ATTACH_CONTENT_ID_W"),
+    (0x3900_0003, "PR_DISPLAY_TYPE"),
+    (0x39FE_001F, "PR_SMTP_ADDRESS_W"),
+    (0x3A00_001F, "PR_ACCOUNT_W"),
+    (0x3A20_001F, "PR_TRANSMITABLE_DISPLAY_NAME_W"),
+    (0x3A40_000B, "PR_SEND_RICH_INFO"),
+    (0x3FDE_0003, "PR_INTERNET_CPID"),
+    (0x3FF1_0003, "PR_MESSAGE_LOCALE_ID"),
+    (0x3FFA_001F, "PR_LAST_MODIFIER_NAME_W"),
+    (0x3FFD_0003, "PR_MESSAGE_CODEPAGE"),
+    (0x65E0_0102, "PR_SOURCE_KEY"),
+    (0x65E1_0102, "PR_PARENT_SOURCE_KEY"),
+    (0x65E2_0102, "PR_CHANGE_KEY"),
+    (0x65E3_0102, "PR_PREDECESSOR_CHANGE_LIST"),
+    (0x662C_000D, "PR_HIERARCHY_SYNCHRONIZER"),
+    (0x662D_000D, "PR_CONTENTS_SYNCHRONIZER"),
+    (0x6638_0003, "PR_FOLDER_CHILD_COUNT"),
+    (0x6670_0102, "PR_LONGTERM_ENTRYID_FROM_TABLE"),
+    (0x668F_0040, "PR_DELETED_ON"),
+];
+
+/// Lookup table which maps common `PR_*` property tags to their symbolic names, for diagnostic
+/// output such as the [`fmt::Display`] implementation for [`PropTag`].
+pub struct KnownProps;
+
+impl KnownProps {
+    /// Look up the name of a tag which matches one of the known properties exactly, including the
+    /// `PROP_TYPE`, e.g. `PR_SUBJECT_W`.
+    pub fn name(tag: PropTag) -> Option<&'static str> {
+        KNOWN_PROPS
+            .binary_search_by_key(&tag.0, |(known, _)| *known)
+            .ok()
+            .map(|index| KNOWN_PROPS[index].1)
+    }
+
+    /// Look up the name of a known property by `PROP_ID`, without the `_W` suffix, so any
+    /// `PROP_TYPE` matches, e.g. `PR_SUBJECT` for both `PT_UNICODE` and `PT_STRING8`. Returns
+    /// `None` for named properties and properties which are not in the table.
+    pub fn base_name(tag: PropTag) -> Option<&'static str> {
+        let prop_id = tag.prop_id();
+        KNOWN_PROPS
+            .iter()
+            .find(|(known, _)| PropTag(*known).prop_id() == prop_id)
+            .map(|(_, name)| name.strip_suffix("_W").unwrap_or(name))
+    }
+
+    /// Iterate over every known property tag and its name.
+    pub fn iter() -> impl Iterator<Item = (PropTag, &'static str)> {
+        KNOWN_PROPS.iter().map(|(tag, name)| (PropTag(*tag), *name))
+    }
+}
+
+impl fmt::Display for PropTag {
+    /// Format the tag with its [`KnownProps`] name (or base name, if only the `PROP_ID` matches)
+    /// followed by the hex value, e.g. `PR_SUBJECT_W (0x0037001F)`, or just the hex value if the
+    /// property is not known.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match KnownProps::name(*self).or_else(|| KnownProps::base_name(*self)) {
+            Some(name) => write!(f, "{name} (0x{:08X})", self.0),
+            None => write!(f, "0x{:08X}", self.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn sorted_by_tag() {
+        assert!(KNOWN_PROPS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(KnownProps::name(PropTag(0x0037_001F)), Some("PR_SUBJECT_W"));
+        assert_eq!(KnownProps::name(PropTag(0x0037_001E)), None);
+        assert_eq!(
+            KnownProps::base_name(PropTag(0x0037_001E)),
+            Some("PR_SUBJECT")
+        );
+        assert_eq!(KnownProps::base_name(PropTag(0x8001_001F)), None);
+    }
+
+    #[test]
+    fn display_format() {
+        assert_eq!(
+            format!("{}", PropTag(0x0037_001F)),
+            "PR_SUBJECT_W (0x0037001F)"
+        );
+        assert_eq!(
+            format!("{}", PropTag(0x0037_001E)),
+            "PR_SUBJECT (0x0037001E)"
+        );
+        assert_eq!(format!("{}", PropTag(0x8001_001F)), "0x8001001F");
+    }
+}
@@ -0,0 +1,34 @@
+//! Platform independent parsers for MAPI data types, which do not depend on COM or any of the
+//! Windows APIs.
+//!
+//! This crate is `#![no_std]` compatible, so the parsers can be reused in sandboxed analyzers that
+//! never load `olmapi32.dll`. Everything in this crate is re-exported from the root module of
+//! [outlook-mapi](https://crates.io/crates/outlook-mapi), which adds the COM-backed wrappers.
+
+#![no_std]
+
+extern crate alloc;
+
+pub mod base64;
+pub mod blob;
+pub mod change_key;
+pub mod checkpoint;
+pub mod encoded_word;
+pub mod entry_id;
+pub mod hex;
+pub mod known_props;
+pub mod mapi_uri;
+pub mod prop_tag;
+pub mod verb_stream;
+
+pub use base64::*;
+pub use blob::*;
+pub use change_key::*;
+pub use checkpoint::*;
+pub use encoded_word::*;
+pub use entry_id::*;
+pub use hex::*;
+pub use known_props::*;
+pub use mapi_uri::*;
+pub use prop_tag::*;
+pub use verb_stream::*;
@@ -0,0 +1,208 @@
+//! Define [`MapiUri`] and [`MapiUriError`].
+
+use crate::{from_hex, HexDisplay, HexError};
+use alloc::vec::Vec;
+use core::{fmt, str::FromStr};
+
+/// URI scheme used by [`MapiUri`].
+pub const MAPI_URI_SCHEME: &str = "mapi://";
+
+/// Errors which can be returned from [`MapiUri::parse`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapiUriError {
+    /// The URI does not start with [`MAPI_URI_SCHEME`].
+    InvalidScheme,
+
+    /// The URI should have between 1 and 3 path segments: the store, an optional folder, and an
+    /// optional message. Only the folder segment may be empty, and only if a message follows.
+    InvalidSegmentCount,
+
+    /// One of the path segments is not a valid hex string.
+    InvalidHex(HexError),
+}
+
+impl fmt::Display for MapiUriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidScheme => write!(f, "expected a {MAPI_URI_SCHEME} URI"),
+            Self::InvalidSegmentCount => write!(f, "expected store[/folder[/message]] segments"),
+            Self::InvalidHex(err) => write!(f, "invalid segment: {err}"),
+        }
+    }
+}
+
+impl From<HexError> for MapiUriError {
+    fn from(value: HexError) -> Self {
+        Self::InvalidHex(value)
+    }
+}
+
+/// Stable, reversible textual identifier for a store, folder, or message, e.g.
+/// `mapi://<store>/<folder>/<message>`.
+///
+/// The store is identified by its `PR_RECORD_KEY` (the same value as `PR_STORE_RECORD_KEY` on the
+/// objects inside it), which unlike the store `PR_ENTRYID` does not depend on the profile. The
+/// folder and message are identified by their `PR_ENTRYID`. Each segment is rendered with
+/// [`crate::to_hex`], so the [`fmt::Display`] and [`MapiUri::parse`] round trip exactly.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MapiUri {
+    /// Store `PR_RECORD_KEY`.
+    pub store: Vec<u8>,
+
+    /// Folder `PR_ENTRYID`.
+    pub folder: Option<Vec<u8>>,
+
+    /// Message `PR_ENTRYID`. If [`MapiUri::folder`] is not set, the folder segment is left empty,
+    /// e.g. `mapi://<store>//<message>`.
+    pub message: Option<Vec<u8>>,
+}
+
+impl MapiUri {
+    /// Create a [`MapiUri`] which refers to a store.
+    pub fn new(store: &[u8]) -> Self {
+        Self {
+            store: store.to_vec(),
+            folder: None,
+            message: None,
+        }
+    }
+
+    /// Refer to a folder in the store.
+    pub fn with_folder(self, folder: &[u8]) -> Self {
+        Self {
+            folder: Some(folder.to_vec()),
+            ..self
+        }
+    }
+
+    /// Refer to a message in the folder.
+    pub fn with_message(self, message: &[u8]) -> Self {
+        Self {
+            message: Some(message.to_vec()),
+            ..self
+        }
+    }
+
+    /// Parse the output of [`fmt::Display`] back into a [`MapiUri`].
+    pub fn parse(value: &str) -> Result<Self, MapiUriError> {
+        let path = value
+            .get(..MAPI_URI_SCHEME.len())
+            .filter(|scheme| scheme.eq_ignore_ascii_case(MAPI_URI_SCHEME))
+            .map(|_| &value[MAPI_URI_SCHEME.len()..])
+            .ok_or(MapiUriError::InvalidScheme)?;
+
+        let segment = |segment: &str| -> Result<Vec<u8>, MapiUriError> {
+            if segment.is_empty() {
+                Err(MapiUriError::InvalidSegmentCount)
+            } else {
+                Ok(from_hex(segment)?)
+            }
+        };
+        let segments: Vec<_> = path.split('/').collect();
+        let (store, folder, message) = match segments.as_slice() {
+            [store] => (segment(store)?, None, None),
+            [store, folder] => (segment(store)?, Some(segment(folder)?), None),
+            [store, "", message] => (segment(store)?, None, Some(segment(message)?)),
+            [store, folder, message] => (
+                segment(store)?,
+                Some(segment(folder)?),
+                Some(segment(message)?),
+            ),
+            _ => return Err(MapiUriError::InvalidSegmentCount),
+        };
+
+        Ok(Self {
+            store,
+            folder,
+            message,
+        })
+    }
+}
+
+impl fmt::Display for MapiUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{MAPI_URI_SCHEME}{}", HexDisplay::new(&self.store))?;
+        match (self.folder.as_ref(), self.message.as_ref()) {
+            (Some(folder), Some(message)) => write!(
+                f,
+                "/{}/{}",
+                HexDisplay::new(folder),
+                HexDisplay::new(message)
+            ),
+            (Some(folder), None) => write!(f, "/{}", HexDisplay::new(folder)),
+            (None, Some(message)) => write!(f, "//{}", HexDisplay::new(message)),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl FromStr for MapiUri {
+    type Err = MapiUriError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn uri_round_trip() {
+        let uri = MapiUri::new(&[0x01, 0x02])
+            .with_folder(&[0xAB, 0xCD])
+            .with_message(&[0xEF]);
+        let value = uri.to_string();
+        assert_eq!(value, "mapi://0102/ABCD/EF");
+        assert_eq!(MapiUri::parse(&value), Ok(uri));
+    }
+
+    #[test]
+    fn uri_message_without_folder() {
+        let uri = MapiUri::new(&[0x01, 0x02]).with_message(&[0xEF]);
+        let value = uri.to_string();
+        assert_eq!(value, "mapi://0102//EF");
+        assert_eq!(MapiUri::parse(&value), Ok(uri));
+    }
+
+    #[test]
+    fn uri_store_only() {
+        let uri = MapiUri::new(&[0x01, 0x02]);
+        assert_eq!(uri.to_string(), "mapi://0102");
+        assert_eq!("MAPI://0102".parse(), Ok(uri));
+    }
+
+    #[test]
+    fn uri_errors() {
+        assert_eq!(
+            MapiUri::parse("http://0102"),
+            Err(MapiUriError::InvalidScheme)
+        );
+        assert_eq!(
+            MapiUri::parse("mapi://"),
+            Err(MapiUriError::InvalidSegmentCount)
+        );
+        assert_eq!(
+            MapiUri::parse("mapi://01/02/"),
+            Err(MapiUriError::InvalidSegmentCount)
+        );
+        assert_eq!(
+            MapiUri::parse("mapi:///02"),
+            Err(MapiUriError::InvalidSegmentCount)
+        );
+        assert_eq!(
+            MapiUri::parse("mapi://01//"),
+            Err(MapiUriError::InvalidSegmentCount)
+        );
+        assert_eq!(
+            MapiUri::parse("mapi://01/02/03/04"),
+            Err(MapiUriError::InvalidSegmentCount)
+        );
+        assert_eq!(
+            MapiUri::parse("mapi://0"),
+            Err(MapiUriError::InvalidHex(HexError::OddLength))
+        );
+    }
+}
@@ -0,0 +1,392 @@
+//! Define [`PropTag`] and [`PropType`].
+
+use crate::KnownProps;
+use core::fmt;
+
+pub const PROP_ID_MASK: u32 = 0xFFFF_0000;
+pub const PROP_TYPE_MASK: u32 = 0xFFFF;
+
+// These values match the definitions in `MAPIDefs.h`. They are duplicated here (rather than using
+// the `outlook-mapi-sys` bindings) so this crate does not depend on the Windows crates.
+const MV_FLAG: u32 = 0x1000;
+const MV_INSTANCE: u32 = 0x2000;
+
+const PT_UNSPECIFIED: u32 = 0;
+const PT_NULL: u32 = 1;
+const PT_SHORT: u32 = 2;
+const PT_LONG: u32 = 3;
+const PT_FLOAT: u32 = 4;
+const PT_DOUBLE: u32 = 5;
+const PT_CURRENCY: u32 = 6;
+const PT_APPTIME: u32 = 7;
+const PT_ERROR: u32 = 10;
+const PT_BOOLEAN: u32 = 11;
+const PT_OBJECT: u32 = 13;
+const PT_LONGLONG: u32 = 20;
+const PT_STRING8: u32 = 30;
+const PT_UNICODE: u32 = 31;
+const PT_SYSTIME: u32 = 64;
+const PT_CLSID: u32 = 72;
+const PT_BINARY: u32 = 258;
+const PT_PTR: u32 = 259;
+
+const PT_MV_SHORT: u32 = MV_FLAG | PT_SHORT;
+const PT_MV_LONG: u32 = MV_FLAG | PT_LONG;
+const PT_MV_FLOAT: u32 = MV_FLAG | PT_FLOAT;
+const PT_MV_DOUBLE: u32 = MV_FLAG | PT_DOUBLE;
+const PT_MV_CURRENCY: u32 = MV_FLAG | PT_CURRENCY;
+const PT_MV_APPTIME: u32 = MV_FLAG | PT_APPTIME;
+const PT_MV_SYSTIME: u32 = MV_FLAG | PT_SYSTIME;
+const PT_MV_BINARY: u32 = MV_FLAG | PT_BINARY;
+const PT_MV_STRING8: u32 = MV_FLAG | PT_STRING8;
+const PT_MV_UNICODE: u32 = MV_FLAG | PT_UNICODE;
+const PT_MV_CLSID: u32 = MV_FLAG | PT_CLSID;
+const PT_MV_LONGLONG: u32 = MV_FLAG | PT_LONGLONG;
+
+/// Simple wrapper for a MAPI `PROP_TAG`.
+///
+/// The [`fmt::Debug`] output matches the [`fmt::Display`] output for a tag in [`KnownProps`],
+/// e.g. `PR_SUBJECT_W (0x0037001F)`. Any other tag shows the `PROP_ID` in hex and the `PROP_TYPE`
+/// by name, e.g. `PropTag { id: 0x8001, prop_type: PT_UNICODE }`.
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PropTag(pub u32);
+
+impl PropTag {
+    /// Combine the `PROP_TYPE` and `PROP_ID` to form a [`PropTag`]. Equivalent to the MAPI
+    /// `PROP_TAG` macro.
+    pub const fn new(prop_type: PropType, prop_id: u16) -> Self {
+        Self(((prop_id as u32) << 16) | (prop_type.0 as u32))
+    }
+
+    /// Extract the `PROP_ID` portion of the [`PropTag`]. Equivalent to the MAPI `PROP_ID` macro.
+    pub const fn prop_id(&self) -> u16 {
+        ((self.0 & PROP_ID_MASK) >> 16) as u16
+    }
+
+    /// Extract the `PROP_TYPE` portion of the [`PropTag`]. Equivalent to the MAPI `PROP_TYPE`
+    /// macro.
+    pub const fn prop_type(&self) -> PropType {
+        PropType::new((self.0 & PROP_TYPE_MASK) as u16)
+    }
+
+    /// Replace the `PROP_TYPE` portion of the [`PropTag`]. Equalivalent to the MAPI
+    /// `CHANGE_PROP_TYPE` macro.
+    pub const fn change_prop_type(self, prop_type: PropType) -> Self {
+        Self::new(prop_type, self.prop_id())
+    }
+
+    /// Test if the `PROP_TYPE` has the `MV_FLAG`. See [`PropType::is_multi_valued`].
+    pub const fn is_multi_valued(&self) -> bool {
+        self.prop_type().is_multi_valued()
+    }
+
+    /// Test if the `PROP_TYPE` has the `MV_INSTANCE` flag. See [`PropType::is_mv_instance`].
+    pub const fn is_mv_instance(&self) -> bool {
+        self.prop_type().is_mv_instance()
+    }
+
+    /// Get the single-valued `PROP_TYPE`. See [`PropType::base_type`].
+    pub const fn base_type(&self) -> PropType {
+        self.prop_type().base_type()
+    }
+
+    /// Test if the `PROP_TYPE` is a string. See [`PropType::is_string`].
+    pub const fn is_string(&self) -> bool {
+        self.prop_type().is_string()
+    }
+
+    /// Test if the `PROP_TYPE` is `PT_UNSPECIFIED`. See [`PropType::is_unspecified`].
+    pub const fn is_unspecified(&self) -> bool {
+        self.prop_type().is_unspecified()
+    }
+
+    /// Build a tag with the same `PROP_ID` and a different `PROP_TYPE`, e.g. to add the type to a
+    /// named property tag when building a column set. Same as [`PropTag::change_prop_type`].
+    pub const fn with_type(self, prop_type: PropType) -> Self {
+        self.change_prop_type(prop_type)
+    }
+
+    /// Convert a tag from a response, e.g. a column in a row or a value from `GetProps`, back to
+    /// a tag which could have requested it. A `PT_ERROR` response does not say which type was
+    /// requested, so it becomes `PT_UNSPECIFIED` with the same `PROP_ID`, which asks for any type.
+    /// Any other tag is returned as is.
+    pub const fn as_requested(self) -> Self {
+        if self.0 & PROP_TYPE_MASK == PT_ERROR {
+            self.change_prop_type(PropType(PT_UNSPECIFIED as u16))
+        } else {
+            self
+        }
+    }
+
+    /// Test if `response` is the answer to a request for this tag. The `PROP_ID` must match, and
+    /// either the `PROP_TYPE` matches, the response is `PT_ERROR` because the value is missing or
+    /// could not be read, or this tag is `PT_UNSPECIFIED` and accepts any type.
+    pub const fn matches_response(self, response: PropTag) -> bool {
+        if self.prop_id() != response.prop_id() {
+            return false;
+        }
+        let requested_type = self.0 & PROP_TYPE_MASK;
+        let response_type = response.0 & PROP_TYPE_MASK;
+        requested_type == response_type
+            || response_type == PT_ERROR
+            || requested_type == PT_UNSPECIFIED
+    }
+}
+
+impl fmt::Debug for PropTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if KnownProps::name(*self).is_some() {
+            return fmt::Display::fmt(self, f);
+        }
+        f.debug_struct("PropTag")
+            .field("id", &format_args!("0x{:04X}", self.prop_id()))
+            .field("prop_type", &self.prop_type())
+            .finish()
+    }
+}
+
+impl From<PropTag> for u32 {
+    /// Get a constant `PROP_TAG` value from a [`PropTag`].
+    fn from(value: PropTag) -> Self {
+        value.0
+    }
+}
+
+/// Simple wrapper for a MAPI `PROP_TYPE`.
+///
+/// The [`fmt::Debug`] output is the name of the `PT_*` constant, followed by `| MV_INSTANCE` if
+/// that flag is set.
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PropType(u16);
+
+impl PropType {
+    /// Map invalid property types to `PT_UNSPECIFIED`.
+    pub const fn new(prop_type: u16) -> Self {
+        Self(match (prop_type as u32) & !MV_INSTANCE {
+            PT_NULL | PT_SHORT | PT_LONG | PT_PTR | PT_FLOAT | PT_DOUBLE | PT_BOOLEAN
+            | PT_CURRENCY | PT_APPTIME | PT_SYSTIME | PT_STRING8 | PT_BINARY | PT_UNICODE
+            | PT_CLSID | PT_LONGLONG | PT_MV_SHORT | PT_MV_LONG | PT_MV_FLOAT | PT_MV_DOUBLE
+            | PT_MV_CURRENCY | PT_MV_APPTIME | PT_MV_SYSTIME | PT_MV_BINARY | PT_MV_STRING8
+            | PT_MV_UNICODE | PT_MV_CLSID | PT_MV_LONGLONG | PT_ERROR | PT_OBJECT => prop_type,
+            _ => PT_UNSPECIFIED as u16,
+        })
+    }
+
+    /// Set `PROP_TYPE` flags.
+    pub const fn add_flags(self, mask: u32) -> Self {
+        let mask = (mask & PROP_TYPE_MASK) as u16;
+        Self(self.0 | mask)
+    }
+
+    /// Clear `PROP_TYPE` flags.
+    pub const fn remove_flags(self, mask: u32) -> Self {
+        let mask = (mask & PROP_TYPE_MASK) as u16;
+        Self(self.0 & !mask)
+    }
+
+    /// Test if this is one of the `PT_MV_*` types, i.e. it has the `MV_FLAG`.
+    pub const fn is_multi_valued(&self) -> bool {
+        (self.0 as u32) & MV_FLAG != 0
+    }
+
+    /// Test if the `MV_INSTANCE` flag is set. A column requested with `MVI_PROP` comes back from
+    /// an instanced table with this flag, and each row holds a single value of the
+    /// [`PropType::base_type`] rather than an array.
+    pub const fn is_mv_instance(&self) -> bool {
+        (self.0 as u32) & MV_INSTANCE != 0
+    }
+
+    /// Clear both the `MV_FLAG` and the `MV_INSTANCE` flag, e.g. `PT_MV_UNICODE` becomes
+    /// `PT_UNICODE`.
+    pub const fn base_type(self) -> Self {
+        self.remove_flags(MV_FLAG | MV_INSTANCE)
+    }
+
+    /// Test if the [`PropType::base_type`] is `PT_STRING8` or `PT_UNICODE`, so multi-valued
+    /// strings match as well.
+    pub const fn is_string(&self) -> bool {
+        matches!(self.base_type().0 as u32, PT_STRING8 | PT_UNICODE)
+    }
+
+    /// Test if this is `PT_UNSPECIFIED`, which asks for any type in a request, or is the result
+    /// of [`PropType::new`] with an invalid type.
+    pub const fn is_unspecified(&self) -> bool {
+        self.0 as u32 == PT_UNSPECIFIED
+    }
+}
+
+impl fmt::Debug for PropType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prop_type = self.0 as u32;
+        let name = match prop_type & !MV_INSTANCE {
+            PT_NULL => "PT_NULL",
+            PT_SHORT => "PT_SHORT",
+            PT_LONG => "PT_LONG",
+            PT_FLOAT => "PT_FLOAT",
+            PT_DOUBLE => "PT_DOUBLE",
+            PT_CURRENCY => "PT_CURRENCY",
+            PT_APPTIME => "PT_APPTIME",
+            PT_ERROR => "PT_ERROR",
+            PT_BOOLEAN => "PT_BOOLEAN",
+            PT_OBJECT => "PT_OBJECT",
+            PT_LONGLONG => "PT_LONGLONG",
+            PT_STRING8 => "PT_STRING8",
+            PT_UNICODE => "PT_UNICODE",
+            PT_SYSTIME => "PT_SYSTIME",
+            PT_CLSID => "PT_CLSID",
+            PT_BINARY => "PT_BINARY",
+            PT_PTR => "PT_PTR",
+            PT_MV_SHORT => "PT_MV_SHORT",
+            PT_MV_LONG => "PT_MV_LONG",
+            PT_MV_FLOAT => "PT_MV_FLOAT",
+            PT_MV_DOUBLE => "PT_MV_DOUBLE",
+            PT_MV_CURRENCY => "PT_MV_CURRENCY",
+            PT_MV_APPTIME => "PT_MV_APPTIME",
+            PT_MV_SYSTIME => "PT_MV_SYSTIME",
+            PT_MV_BINARY => "PT_MV_BINARY",
+            PT_MV_STRING8 => "PT_MV_STRING8",
+            PT_MV_UNICODE => "PT_MV_UNICODE",
+            PT_MV_CLSID => "PT_MV_CLSID",
+            PT_MV_LONGLONG => "PT_MV_LONGLONG",
+            _ => "PT_UNSPECIFIED",
+        };
+        f.write_str(name)?;
+        if prop_type & MV_INSTANCE != 0 {
+            f.write_str(" | MV_INSTANCE")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<PropType> for u32 {
+    /// Get a constant `PROP_TYPE` value from a [`PropType`].
+    fn from(value: PropType) -> Self {
+        value.0 as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn prop_tag_parts() {
+        let tag = PropTag(0x0037_001F);
+        assert_eq!(tag.prop_id(), 0x0037);
+        assert_eq!(u32::from(tag.prop_type()), PT_UNICODE);
+        assert_eq!(
+            u32::from(PropTag::new(PropType::new(0x001F), 0x0037)),
+            0x0037_001F
+        );
+    }
+
+    #[test]
+    fn change_prop_type() {
+        let tag = PropTag(0x0037_001F).change_prop_type(PropType::new(PT_STRING8 as u16));
+        assert_eq!(u32::from(tag), 0x0037_001E);
+    }
+
+    #[test]
+    fn invalid_prop_type() {
+        assert_eq!(u32::from(PropType::new(0x0008)), PT_UNSPECIFIED);
+        assert_eq!(u32::from(PropType::new(0x1001)), PT_UNSPECIFIED);
+    }
+
+    #[test]
+    fn mv_flag_helpers() {
+        let tag = PropTag(0x8001_101F);
+        assert!(tag.is_multi_valued());
+        assert!(!tag.is_mv_instance());
+        assert_eq!(u32::from(tag.base_type()), PT_UNICODE);
+
+        let tag = PropTag(0x8001_301F);
+        assert!(tag.is_multi_valued());
+        assert!(tag.is_mv_instance());
+        assert_eq!(u32::from(tag.base_type()), PT_UNICODE);
+
+        let tag = PropTag(0x0037_001F);
+        assert!(!tag.is_multi_valued());
+        assert!(!tag.is_mv_instance());
+        assert_eq!(u32::from(tag.base_type()), PT_UNICODE);
+    }
+
+    #[test]
+    fn type_predicates() {
+        assert!(PropTag(0x0037_001F).is_string());
+        assert!(PropTag(0x0037_001E).is_string());
+        assert!(PropTag(0x8001_101F).is_string());
+        assert!(!PropTag(0x0FF9_0102).is_string());
+        assert!(PropTag(0x0037_0000).is_unspecified());
+        assert!(!PropTag(0x0037_001F).is_unspecified());
+
+        let tag = PropTag(0x8001_0000).with_type(PropType::new(PT_MV_UNICODE as u16));
+        assert_eq!(u32::from(tag), 0x8001_101F);
+        assert!(tag.is_multi_valued());
+    }
+
+    #[test]
+    fn as_requested() {
+        assert_eq!(u32::from(PropTag(0x0037_000A).as_requested()), 0x0037_0000);
+        assert_eq!(u32::from(PropTag(0x0037_001F).as_requested()), 0x0037_001F);
+    }
+
+    #[test]
+    fn matches_response() {
+        let requested = PropTag(0x0037_001F);
+        assert!(requested.matches_response(PropTag(0x0037_001F)));
+        assert!(requested.matches_response(PropTag(0x0037_000A)));
+        assert!(!requested.matches_response(PropTag(0x0037_001E)));
+        assert!(!requested.matches_response(PropTag(0x0E1D_001F)));
+        assert!(PropTag(0x0037_0000).matches_response(PropTag(0x0037_001E)));
+    }
+
+    #[test]
+    fn debug_format() {
+        assert_eq!(
+            format!("{:?}", PropTag(0x0037_001F)),
+            "PR_SUBJECT_W (0x0037001F)"
+        );
+        assert_eq!(
+            format!("{:?}", PropTag(0x8001_001F)),
+            "PropTag { id: 0x8001, prop_type: PT_UNICODE }"
+        );
+        assert_eq!(
+            format!("{:?}", PropType::new((PT_MV_LONG | MV_INSTANCE) as u16)),
+            "PT_MV_LONG | MV_INSTANCE"
+        );
+        assert_eq!(format!("{:?}", PropType::new(0x0008)), "PT_UNSPECIFIED");
+    }
+
+    #[test]
+    fn ordering() {
+        let mut tags = [
+            PropTag(0x0E08_0003),
+            PropTag(0x0037_001F),
+            PropTag(0x0037_001E),
+        ];
+        tags.sort();
+        assert_eq!(
+            tags,
+            [
+                PropTag(0x0037_001E),
+                PropTag(0x0037_001F),
+                PropTag(0x0E08_0003)
+            ]
+        );
+        assert_ne!(PropTag(0x0037_001F), PropTag(0x0037_001E));
+    }
+
+    #[test]
+    fn mv_instance_prop_type() {
+        let prop_type = PropType::new((PT_MV_LONG | MV_INSTANCE) as u16);
+        assert_eq!(u32::from(prop_type), PT_MV_LONG | MV_INSTANCE);
+        assert_eq!(u32::from(prop_type.remove_flags(MV_INSTANCE)), PT_MV_LONG);
+        assert_eq!(
+            u32::from(PropType::new(PT_MV_LONG as u16).add_flags(MV_INSTANCE)),
+            PT_MV_LONG | MV_INSTANCE
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! Define [`VerbStream`], [`VoteOption`], and [`VerbStreamError`].
+
+use crate::{BlobError, BlobReader, BlobWriter};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// Value of the first version field in a `PidLidVerbStream`.
+pub const VERB_STREAM_VERSION: u16 = 0x0102;
+
+/// Value of the second version field in a `PidLidVerbStream`, which precedes the Unicode display
+/// names.
+pub const VERB_STREAM_VERSION2: u16 = 0x0104;
+
+/// [`VoteOption::verb_type`] for a voting button.
+pub const VERB_TYPE_VOTE: u32 = 4;
+
+/// Default [`VoteOption::send_behavior`], which is what Outlook uses for voting buttons.
+pub const VERB_SEND_BEHAVIOR_DEFAULT: u32 = 2;
+
+/// Errors which can be returned when parsing or serializing a `PidLidVerbStream`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerbStreamError {
+    /// The value could not be read with a [`BlobReader`].
+    Blob(BlobError),
+
+    /// The version field did not match [`VERB_STREAM_VERSION`] or [`VERB_STREAM_VERSION2`].
+    UnsupportedVersion(u16),
+
+    /// A string is too long to fit in the 1 byte character count.
+    StringTooLong,
+}
+
+impl From<BlobError> for VerbStreamError {
+    fn from(value: BlobError) -> Self {
+        Self::Blob(value)
+    }
+}
+
+impl fmt::Display for VerbStreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Blob(err) => write!(f, "invalid verb stream: {err}"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported verb stream version: 0x{version:04X}")
+            }
+            Self::StringTooLong => write!(f, "verb stream string is too long"),
+        }
+    }
+}
+
+/// One verb in a `PidLidVerbStream`. The voting buttons on a message are the entries with
+/// [`VERB_TYPE_VOTE`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoteOption {
+    /// Type of verb, e.g. [`VERB_TYPE_VOTE`].
+    pub verb_type: u32,
+
+    /// Label of the button, which is also copied to `PidLidVerbResponse` on the response.
+    pub display_name: String,
+
+    /// Message class of the response, usually `IPM.Note`.
+    pub message_class: String,
+
+    /// Set if the response should use US English prefixes in the subject.
+    pub use_us_headers: bool,
+
+    /// What Outlook does when the button is clicked, e.g. [`VERB_SEND_BEHAVIOR_DEFAULT`].
+    pub send_behavior: u32,
+
+    /// Identifier of the verb, which is unique within the [`VerbStream`].
+    pub id: u32,
+}
+
+impl VoteOption {
+    /// Create a voting button with the default values that Outlook uses.
+    pub fn new(id: u32, display_name: impl Into<String>) -> Self {
+        Self {
+            verb_type: VERB_TYPE_VOTE,
+            display_name: display_name.into(),
+            message_class: String::from("IPM.Note"),
+            use_us_headers: false,
+            send_behavior: VERB_SEND_BEHAVIOR_DEFAULT,
+            id,
+        }
+    }
+
+    /// Test if this is a voting button.
+    pub fn is_vote(&self) -> bool {
+        self.verb_type == VERB_TYPE_VOTE
+    }
+}
+
+/// Parsed `PidLidVerbStream` value, which holds the voting buttons on a message.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerbStream {
+    /// All of the verbs, in the order they appear in the value.
+    pub options: Vec<VoteOption>,
+}
+
+impl VerbStream {
+    /// Build a [`VerbStream`] with a voting button for each label, numbered from 1.
+    pub fn from_labels<S: Into<String>>(labels: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            options: labels
+                .into_iter()
+                .zip(1..)
+                .map(|(label, id)| VoteOption::new(id, label))
+                .collect(),
+        }
+    }
+
+    /// Iterate over the entries with [`VERB_TYPE_VOTE`].
+    pub fn votes(&self) -> impl Iterator<Item = &VoteOption> {
+        self.options.iter().filter(|option| option.is_vote())
+    }
+
+    /// Parse a `PidLidVerbStream` value.
+    ///
+    /// The ANSI display names are replaced with the Unicode display names which follow
+    /// [`VERB_STREAM_VERSION2`], if they are present. Otherwise, the ANSI strings are decoded as
+    /// Latin-1.
+    pub fn parse(value: &[u8]) -> Result<Self, VerbStreamError> {
+        let mut reader = BlobReader::new(value);
+        let version = reader.read_u16()?;
+        if version != VERB_STREAM_VERSION {
+            return Err(VerbStreamError::UnsupportedVersion(version));
+        }
+        let count = reader.read_u32()?;
+        let mut options = Vec::new();
+        for _ in 0..count {
+            let verb_type = reader.read_u32()?;
+            let display_name = reader.read_ansi_string_u8()?;
+            let message_class = reader.read_ansi_string_u8()?;
+            // Internal1String, DisplayNameRepeat, Internal2, and Internal3
+            reader.read_ansi_string_u8()?;
+            reader.read_ansi_string_u8()?;
+            reader.skip(4 + 1)?;
+            let use_us_headers = reader.read_u32()? != 0;
+            // Internal4
+            reader.skip(4)?;
+            let send_behavior = reader.read_u32()?;
+            // Internal5
+            reader.skip(4)?;
+            let id = reader.read_u32()?;
+            // Internal6
+            reader.skip(4)?;
+            options.push(VoteOption {
+                verb_type,
+                display_name,
+                message_class,
+                use_us_headers,
+                send_behavior,
+                id,
+            });
+        }
+
+        if !reader.is_empty() {
+            let version = reader.read_u16()?;
+            if version != VERB_STREAM_VERSION2 {
+                return Err(VerbStreamError::UnsupportedVersion(version));
+            }
+            for option in options.iter_mut() {
+                option.display_name = reader.read_unicode_string_u8()?;
+                // DisplayNameRepeat
+                reader.read_unicode_string_u8()?;
+            }
+        }
+
+        Ok(Self { options })
+    }
+
+    /// Serialize the [`VerbStream`] into a `PidLidVerbStream` value. Characters in the ANSI
+    /// strings which are not ASCII are replaced with `?`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, VerbStreamError> {
+        let mut writer = BlobWriter::new();
+        let ansi_string = |writer: &mut BlobWriter, value: &str| {
+            writer
+                .write_ansi_string_u8(value)
+                .ok_or(VerbStreamError::StringTooLong)
+        };
+        let unicode_string = |writer: &mut BlobWriter, value: &str| {
+            writer
+                .write_unicode_string_u8(value)
+                .ok_or(VerbStreamError::StringTooLong)
+        };
+
+        writer.write_u16(VERB_STREAM_VERSION);
+        writer.write_u32(self.options.len() as u32);
+        for option in self.options.iter() {
+            writer.write_u32(option.verb_type);
+            ansi_string(&mut writer, &option.display_name)?;
+            ansi_string(&mut writer, &option.message_class)?;
+            ansi_string(&mut writer, "")?;
+            ansi_string(&mut writer, &option.display_name)?;
+            writer.write_u32(0);
+            writer.write_u8(0);
+            writer.write_u32(u32::from(option.use_us_headers));
+            writer.write_u32(1);
+            writer.write_u32(option.send_behavior);
+            writer.write_u32(2);
+            writer.write_u32(option.id);
+            writer.write_u32(u32::MAX);
+        }
+        writer.write_u16(VERB_STREAM_VERSION2);
+        for option in self.options.iter() {
+            unicode_string(&mut writer, &option.display_name)?;
+            unicode_string(&mut writer, &option.display_name)?;
+        }
+        Ok(writer.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let verbs = VerbStream::from_labels(["Approve", "Reject", "Vérifier"]);
+        let bytes = verbs.to_bytes().unwrap();
+        assert_eq!(&bytes[..2], &[0x02, 0x01]);
+        let parsed = VerbStream::parse(&bytes).unwrap();
+        assert_eq!(parsed, verbs);
+        let labels: Vec<_> = parsed
+            .votes()
+            .map(|option| option.display_name.as_str())
+            .collect();
+        assert_eq!(labels, ["Approve", "Reject", "Vérifier"]);
+    }
+
+    #[test]
+    fn ansi_only() {
+        let mut bytes = VerbStream::from_labels(["Yes"]).to_bytes().unwrap();
+        // Drop the Unicode names which follow the second version field.
+        bytes.truncate(bytes.len() - 2 - 2 * (1 + 3 * 2));
+        let parsed = VerbStream::parse(&bytes).unwrap();
+        assert_eq!(parsed.options[0].display_name, "Yes");
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = VerbStream::from_labels(["Yes"]).to_bytes().unwrap();
+        assert_eq!(
+            VerbStream::parse(&bytes[..10]),
+            Err(VerbStreamError::Blob(BlobError::Truncated {
+                offset: 10,
+                needed: 1
+            }))
+        );
+        assert_eq!(
+            VerbStream::parse(&[0x04, 0x01, 0, 0, 0, 0]),
+            Err(VerbStreamError::UnsupportedVersion(0x0104))
+        );
+    }
+}
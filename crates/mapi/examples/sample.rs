@@ -38,7 +38,7 @@ fn main() -> Result<()> {
     };
     let mut rows: RowSet = Default::default();
     unsafe {
-        let stores_table = logon.session.GetMsgStoresTable(0)?;
+        let stores_table = logon.session().GetMsgStoresTable(0)?;
         HrQueryAllRows(
             &stores_table,
             prop_tag_array.as_mut_ptr(),
@@ -84,7 +84,7 @@ fn main() -> Result<()> {
 
         unsafe {
             let mut store = None;
-            logon.session.OpenMsgStore(
+            logon.session().OpenMsgStore(
                 0,
                 entry_id.len() as u32,
                 entry_id.as_ptr() as *mut _,
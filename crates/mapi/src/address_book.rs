@@ -0,0 +1,212 @@
+//! Define [`AddressBook`] and [`AddressBookContainer`].
+
+use crate::{
+    sys, AdrList, AdrListBuffer, InterfaceId, Logon, OpenFlags, OwnedPropValue, OwnedPropValueData,
+    PropTag, PropValue, PropValueBuilder, PropValueData, Recipient, RecipientType, TableQuery,
+};
+use core::ptr;
+use windows::Win32::Foundation::E_UNEXPECTED;
+use windows_core::*;
+
+/// Columns requested by [`AddressBook::containers`].
+const CONTAINER_COLUMNS: [u32; 4] = [
+    sys::PR_ENTRYID,
+    sys::PR_DISPLAY_NAME_W,
+    sys::PR_DISPLAY_TYPE,
+    sys::PR_DEPTH,
+];
+
+/// Metadata for a container in the address book hierarchy, read by [`AddressBook::containers`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AddressBookContainer {
+    /// [`sys::PR_ENTRYID`], which identifies the container in [`AddressBook::open_container`].
+    pub entry_id: Vec<u8>,
+
+    /// [`sys::PR_DISPLAY_NAME_W`]
+    pub display_name: String,
+
+    /// [`sys::PR_DISPLAY_TYPE`], e.g. [`sys::DT_GLOBAL`] for the Global Address List.
+    pub display_type: u32,
+
+    /// [`sys::PR_DEPTH`], which is 0 for the top level containers.
+    pub depth: u32,
+}
+
+impl AddressBookContainer {
+    /// Test if this is the Global Address List.
+    pub fn is_global_address_list(&self) -> bool {
+        self.display_type == sys::DT_GLOBAL
+    }
+
+    fn from_props<'a>(values: impl IntoIterator<Item = PropValue<'a>>) -> Option<Self> {
+        let mut container = Self::default();
+        let mut entry_id = None;
+        for PropValue { tag, value } in values {
+            match (u32::from(tag), value) {
+                (sys::PR_ENTRYID, PropValueData::Binary(value)) => entry_id = Some(value.to_vec()),
+                (sys::PR_DISPLAY_NAME_W, PropValueData::Unicode(value)) => {
+                    container.display_name = unsafe { value.to_string() }.unwrap_or_default()
+                }
+                (sys::PR_DISPLAY_TYPE, PropValueData::Long(value)) => {
+                    container.display_type = value as u32
+                }
+                (sys::PR_DEPTH, PropValueData::Long(value)) => container.depth = value as u32,
+                _ => {}
+            }
+        }
+        container.entry_id = entry_id?;
+        Some(container)
+    }
+}
+
+/// Wrapper for a [`sys::IAddrBook`], opened with [`Logon::open_address_book`].
+///
+/// Like [`Logon`], an [`AddressBook`] belongs to the thread which opened it, and it must not
+/// outlive the [`Logon`] it came from.
+pub struct AddressBook {
+    address_book: sys::IAddrBook,
+}
+
+impl AddressBook {
+    /// Borrow the [`sys::IAddrBook`], e.g. to call methods which do not have a wrapper yet.
+    pub fn address_book(&self) -> &sys::IAddrBook {
+        &self.address_book
+    }
+
+    /// Resolve a display name or e-mail address with [`sys::IAddrBook::ResolveName`], and return
+    /// the properties of the matching address book entry.
+    ///
+    /// This never shows a dialog, so it fails with [`sys::MAPI_E_AMBIGUOUS_RECIP`] if more than
+    /// one entry matches, and with [`sys::MAPI_E_NOT_FOUND`] if none do.
+    pub fn resolve_name(&self, name: &str) -> Result<Vec<OwnedPropValue>> {
+        self.resolve(name)?
+            .entry(0)
+            .map(|values| values.iter().map(PropValue::to_owned).collect())
+            .ok_or_else(|| Error::from(E_UNEXPECTED))
+    }
+
+    /// Same as [`AddressBook::resolve_name`], but return the result as a [`Recipient`] which can
+    /// be passed to [`crate::Message::add_recipients`].
+    pub fn resolve_recipient(
+        &self,
+        name: &str,
+        recipient_type: RecipientType,
+    ) -> Result<Recipient> {
+        let adr_list = self.resolve(name)?;
+        let values = adr_list.entry(0).ok_or_else(|| Error::from(E_UNEXPECTED))?;
+        Ok(Recipient {
+            recipient_type,
+            ..Recipient::from_props(values)
+        })
+    }
+
+    fn resolve(&self, name: &str) -> Result<AdrListBuffer> {
+        let mut adr_list = AdrList::new();
+        adr_list.push(PropValueBuilder::new().push(OwnedPropValue {
+            tag: PropTag(sys::PR_DISPLAY_NAME_W),
+            value: OwnedPropValueData::Unicode(name.to_string()),
+        }));
+        let mut adr_list = adr_list
+            .build()
+            .map_err(|_| Error::from(sys::MAPI_E_NOT_ENOUGH_MEMORY))?;
+        unsafe {
+            self.address_book.ResolveName(
+                0,
+                sys::MAPI_UNICODE,
+                ptr::null_mut(),
+                adr_list.as_mut_ptr(),
+            )?;
+        }
+        Ok(adr_list)
+    }
+
+    /// Open an address book entry with [`sys::IAddrBook::OpenEntry`] and cast the result to the
+    /// requested interface, e.g. [`sys::IMailUser`], [`sys::IDistList`], or
+    /// [`sys::IABContainer`]. An empty `entry_id` opens the root container.
+    pub fn open_entry<T>(&self, entry_id: &[u8], flags: OpenFlags) -> Result<T>
+    where
+        T: Interface,
+    {
+        unsafe {
+            let mut obj_type = 0;
+            let mut unknown = None;
+            self.address_book.OpenEntry(
+                entry_id.len() as u32,
+                if entry_id.is_empty() {
+                    ptr::null_mut()
+                } else {
+                    entry_id.as_ptr() as *mut _
+                },
+                InterfaceId::of::<T>().as_mut_ptr(),
+                flags.into(),
+                &mut obj_type,
+                &mut unknown,
+            )?;
+            unknown.ok_or_else(|| Error::from(E_UNEXPECTED))?.cast()
+        }
+    }
+
+    /// Open a container in the address book, e.g. with the [`AddressBookContainer::entry_id`]
+    /// from [`AddressBook::containers`]. Read its entries with [`TableQuery::contents`].
+    pub fn open_container(&self, entry_id: &[u8]) -> Result<sys::IABContainer> {
+        self.open_entry(entry_id, OpenFlags::read_only())
+    }
+
+    /// List every container in the address book hierarchy, including the Global Address List and
+    /// the Contacts folders, by reading the [`sys::IMAPIContainer::GetHierarchyTable`] of the root
+    /// container with [`sys::CONVENIENT_DEPTH`].
+    pub fn containers(&self) -> Result<Vec<AddressBookContainer>> {
+        let root = self.open_container(&[])?;
+        let hierarchy =
+            unsafe { root.GetHierarchyTable(sys::CONVENIENT_DEPTH | sys::MAPI_UNICODE)? };
+        let columns = CONTAINER_COLUMNS.map(PropTag);
+        TableQuery::new(hierarchy)
+            .columns(&columns)
+            .execute()?
+            .filter_map(|row| match row {
+                Ok(row) => AddressBookContainer::from_props(row.iter()).map(Ok),
+                Err(err) => Some(Err(err)),
+            })
+            .collect()
+    }
+
+    /// Find the Global Address List in [`AddressBook::containers`] and open it. Returns `None`
+    /// if the profile does not have one, e.g. if it only has POP or IMAP accounts.
+    pub fn open_global_address_list(&self) -> Result<Option<sys::IABContainer>> {
+        self.containers()?
+            .into_iter()
+            .find(AddressBookContainer::is_global_address_list)
+            .map(|container| self.open_container(&container.entry_id))
+            .transpose()
+    }
+}
+
+impl From<sys::IAddrBook> for AddressBook {
+    fn from(address_book: sys::IAddrBook) -> Self {
+        Self { address_book }
+    }
+}
+
+impl From<AddressBook> for sys::IAddrBook {
+    fn from(value: AddressBook) -> Self {
+        value.address_book
+    }
+}
+
+impl Logon {
+    /// Open the address book for this session with [`sys::IMAPISession::OpenAddressBook`].
+    pub fn open_address_book(&self) -> Result<AddressBook> {
+        unsafe {
+            let mut address_book = None;
+            self.session().OpenAddressBook(
+                0,
+                ptr::null_mut(),
+                sys::AB_NO_DIALOG,
+                &mut address_book,
+            )?;
+            address_book
+                .map(AddressBook::from)
+                .ok_or_else(|| Error::from(E_UNEXPECTED))
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Define [`AdrList`] and [`AdrListBuffer`].
+
+use crate::{sys, CbNewADRLIST, MAPIAllocError, MAPIUninit, PropValue, PropValueBuilder};
+use core::{mem, ptr, slice};
+
+/// Owned set of address entries, each with its own set of property values, which can be packed
+/// into a [`sys::ADRLIST`] with [`AdrList::build`] to pass to APIs like
+/// [`sys::IMessage::ModifyRecipients`] or [`sys::IAddrBook::ResolveName`].
+///
+/// This takes the place of the [`crate::SizedADRLIST`] macro when the number of entries is not
+/// known at compile time, or when the provider needs to free and replace the property values.
+#[derive(Clone, Debug, Default)]
+pub struct AdrList {
+    entries: Vec<PropValueBuilder>,
+}
+
+impl AdrList {
+    /// Start with an empty list.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Append an entry with the property values in `entry`.
+    pub fn push(&mut self, entry: PropValueBuilder) {
+        self.entries.push(entry);
+    }
+
+    /// Number of entries in the list.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Test if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Allocate the [`sys::ADRLIST`] with [`sys::MAPIAllocateBuffer`], and fill in the
+    /// [`sys::ADRENTRY::rgPropVals`] of each entry with a separate [`PropValueBuilder::build`].
+    ///
+    /// Unlike most of the MAPI structures, the property values in an [`sys::ADRLIST`] must not be
+    /// chained to the list itself, because [`sys::IAddrBook::ResolveName`] and similar methods
+    /// free and replace them. The whole list is freed with [`sys::FreePadrlist`] when the
+    /// [`AdrListBuffer`] is dropped.
+    pub fn build(&self) -> Result<AdrListBuffer, MAPIAllocError> {
+        let size = CbNewADRLIST(self.entries.len().max(1));
+        let mut root = MAPIUninit::<u8>::new(size)?.into::<sys::ADRLIST>()?;
+        let adr_list = root.uninit()?.as_mut_ptr();
+        unsafe {
+            ptr::write_bytes(adr_list as *mut u8, 0, size);
+        }
+
+        // From here on, the AdrListBuffer owns the list, and frees any entries which have already
+        // been filled in if one of the later entries fails.
+        mem::forget(root);
+        let result = AdrListBuffer { adr_list };
+        for (index, entry) in self.entries.iter().enumerate() {
+            let values = entry.build()?;
+            let count = values.len() as u32;
+            unsafe {
+                let entries = ptr::addr_of_mut!((*adr_list).aEntries) as *mut sys::ADRENTRY;
+                entries.add(index).write(sys::ADRENTRY {
+                    ulReserved1: 0,
+                    cValues: count,
+                    rgPropVals: values.into_raw(),
+                });
+                (*adr_list).cEntries = index as u32 + 1;
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl FromIterator<PropValueBuilder> for AdrList {
+    fn from_iter<T: IntoIterator<Item = PropValueBuilder>>(iter: T) -> Self {
+        Self {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// A [`sys::ADRLIST`] built by [`AdrList::build`], which owns the list and the property values in
+/// each entry.
+pub struct AdrListBuffer {
+    adr_list: *mut sys::ADRLIST,
+}
+
+impl AdrListBuffer {
+    /// Number of entries in the list. Providers may add or remove entries, e.g.
+    /// [`sys::IAddrBook::ResolveName`] can expand a distribution list.
+    pub fn len(&self) -> usize {
+        unsafe { (*self.adr_list).cEntries as usize }
+    }
+
+    /// Test if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get a pointer to the [`sys::ADRLIST`], which stays valid until the [`AdrListBuffer`] is
+    /// dropped.
+    pub fn as_mut_ptr(&mut self) -> *mut sys::ADRLIST {
+        self.adr_list
+    }
+
+    /// Get the property values of an entry, including any changes made by the provider.
+    pub fn entry(&self, index: usize) -> Option<Vec<PropValue>> {
+        if index >= self.len() {
+            return None;
+        }
+        unsafe {
+            let entries = ptr::addr_of!((*self.adr_list).aEntries) as *const sys::ADRENTRY;
+            let entry = &*entries.add(index);
+            if entry.rgPropVals.is_null() {
+                return Some(Vec::new());
+            }
+            let values = slice::from_raw_parts(entry.rgPropVals, entry.cValues as usize);
+            Some(values.iter().map(PropValue::from).collect())
+        }
+    }
+
+    /// Iterate over the property values of every entry.
+    pub fn iter(&self) -> impl Iterator<Item = Vec<PropValue>> {
+        (0..self.len()).filter_map(|index| self.entry(index))
+    }
+}
+
+impl Drop for AdrListBuffer {
+    /// Free the list and the property values in each entry with [`sys::FreePadrlist`].
+    fn drop(&mut self) {
+        unsafe {
+            sys::FreePadrlist(self.adr_list);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! Define [`ArchiveStatus`], [`ArchiveEvidence`], and [`ArchiveDetector`].
+
+use crate::{sys, Message, PropTag, PropValue, PropValueData, Row};
+use windows_core::*;
+
+/// Message class prefixes which third-party archive products use for the stubs they leave behind
+/// in the mailbox, paired with the name of the product.
+pub const KNOWN_STUB_CLASSES: &[(&str, &str)] = &[
+    ("IPM.Note.EnterpriseVault", "Enterprise Vault"),
+    ("IPM.Note.CommVault", "Commvault"),
+    ("IPM.Note.Mimosa", "Mimosa NearPoint"),
+    ("IPM.Note.Metalogix", "Metalogix Archive Manager"),
+    ("IPM.Note.GFI.MailArchiver", "GFI MailArchiver"),
+];
+
+/// Properties requested by [`ArchiveEvidence::read`].
+const EVIDENCE_TAGS: [u32; 6] = [
+    sys::PR_MESSAGE_CLASS_W,
+    sys::PR_MESSAGE_SIZE,
+    sys::PR_MESSAGE_FLAGS,
+    sys::PR_BODY_W,
+    sys::PR_RTF_COMPRESSED,
+    sys::PR_HTML,
+];
+
+/// Result of [`Message::archive_status`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArchiveStatus {
+    /// None of the detectors recognized the message.
+    NotArchived,
+
+    /// The message is a stub, and the original content lives in the named archive product.
+    Stub {
+        /// Name of the product which left the stub, e.g. from [`KNOWN_STUB_CLASSES`].
+        archiver: String,
+    },
+
+    /// The message looks like a stub, but no detector could tell which product made it, e.g. an
+    /// `IPM.Note` with no body of any kind.
+    Suspected {
+        /// Short description of what made the detector suspicious.
+        reason: String,
+    },
+}
+
+impl ArchiveStatus {
+    /// Test for anything other than [`ArchiveStatus::NotArchived`].
+    pub fn is_archived(&self) -> bool {
+        !matches!(self, Self::NotArchived)
+    }
+}
+
+/// The properties of a message which [`ArchiveDetector`] implementations look at, read once by
+/// [`ArchiveEvidence::read`] and shared by every detector.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArchiveEvidence {
+    /// [`sys::PR_MESSAGE_CLASS_W`]
+    pub message_class: Option<String>,
+
+    /// [`sys::PR_MESSAGE_SIZE`]
+    pub message_size: Option<u32>,
+
+    /// [`sys::PR_MESSAGE_FLAGS`]
+    pub message_flags: u32,
+
+    /// Set if any of [`sys::PR_BODY_W`], [`sys::PR_RTF_COMPRESSED`], or [`sys::PR_HTML`] is
+    /// present and not empty, including a value which is too large to read inline.
+    pub has_body: bool,
+}
+
+impl ArchiveEvidence {
+    /// Read the evidence from a message with [`Message::get_props`].
+    pub fn read(message: &Message) -> Result<Self> {
+        let tags: Vec<_> = EVIDENCE_TAGS.into_iter().map(PropTag).collect();
+        Ok(Self::from_row(&message.get_props(&tags)?))
+    }
+
+    /// Read the evidence from a row with the [`ArchiveEvidence::read`] columns, e.g. from a
+    /// contents table, so a large folder can be triaged without opening each message.
+    pub fn from_row(row: &Row) -> Self {
+        let mut evidence = Self::default();
+        for PropValue { tag, value } in row.iter() {
+            match (u32::from(tag), value) {
+                (sys::PR_MESSAGE_CLASS_W, PropValueData::Unicode(value)) => {
+                    evidence.message_class = unsafe { value.to_string() }.ok()
+                }
+                (sys::PR_MESSAGE_SIZE, PropValueData::Long(value)) => {
+                    evidence.message_size = Some(value as u32)
+                }
+                (sys::PR_MESSAGE_FLAGS, PropValueData::Long(value)) => {
+                    evidence.message_flags = value as u32
+                }
+                (_, PropValueData::Unicode(value)) => {
+                    evidence.has_body |= !unsafe { value.as_wide() }.is_empty()
+                }
+                (_, PropValueData::Binary(value)) => evidence.has_body |= !value.is_empty(),
+                (_, PropValueData::Error(err)) if err == sys::MAPI_E_NOT_ENOUGH_MEMORY => {
+                    evidence.has_body = true
+                }
+                _ => {}
+            }
+        }
+        evidence
+    }
+
+    /// Test if [`sys::MSGFLAG_HASATTACH`] is set.
+    pub fn has_attachments(&self) -> bool {
+        self.message_flags & sys::MSGFLAG_HASATTACH != 0
+    }
+}
+
+/// Heuristic which [`Message::archive_status_with`] runs against the [`ArchiveEvidence`] for a
+/// message. Detectors are tried in order, and the first one which returns `Some` wins.
+///
+/// Any `Fn(&ArchiveEvidence) -> Option<ArchiveStatus>` closure is also a detector, so a migration
+/// tool can add a rule for an in-house archive without defining a type.
+pub trait ArchiveDetector {
+    /// Return the [`ArchiveStatus`] if this detector recognizes the message, or `None` to let
+    /// the next detector decide.
+    fn detect(&self, evidence: &ArchiveEvidence) -> Option<ArchiveStatus>;
+}
+
+impl<F> ArchiveDetector for F
+where
+    F: Fn(&ArchiveEvidence) -> Option<ArchiveStatus>,
+{
+    fn detect(&self, evidence: &ArchiveEvidence) -> Option<ArchiveStatus> {
+        self(evidence)
+    }
+}
+
+/// Recognize the message classes in [`KNOWN_STUB_CLASSES`], ignoring case like MAPI does.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StubClassDetector;
+
+impl ArchiveDetector for StubClassDetector {
+    fn detect(&self, evidence: &ArchiveEvidence) -> Option<ArchiveStatus> {
+        let message_class = evidence.message_class.as_deref()?;
+        KNOWN_STUB_CLASSES
+            .iter()
+            .find(|(prefix, _)| {
+                message_class
+                    .get(..prefix.len())
+                    .is_some_and(|value| value.eq_ignore_ascii_case(prefix))
+            })
+            .map(|(_, archiver)| ArchiveStatus::Stub {
+                archiver: archiver.to_string(),
+            })
+    }
+}
+
+/// Flag an `IPM.Note` (or one of its subclasses) which has no body of any kind and no
+/// attachments, which is what most archive products leave behind after they strip a message.
+///
+/// This also matches an empty draft, so the result is only [`ArchiveStatus::Suspected`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EmptyBodyDetector;
+
+impl ArchiveDetector for EmptyBodyDetector {
+    fn detect(&self, evidence: &ArchiveEvidence) -> Option<ArchiveStatus> {
+        let message_class = evidence.message_class.as_deref()?;
+        let is_note = message_class.eq_ignore_ascii_case("IPM.Note")
+            || message_class
+                .get(..9)
+                .is_some_and(|value| value.eq_ignore_ascii_case("IPM.Note."));
+        (is_note
+            && !evidence.has_body
+            && !evidence.has_attachments()
+            && evidence.message_flags & sys::MSGFLAG_UNSENT == 0)
+            .then(|| ArchiveStatus::Suspected {
+                reason: "message has no body or attachments".to_string(),
+            })
+    }
+}
+
+impl Message {
+    /// Guess whether this message is a stub left behind by an archive product, with the default
+    /// detectors: [`StubClassDetector`] followed by [`EmptyBodyDetector`].
+    pub fn archive_status(&self) -> Result<ArchiveStatus> {
+        self.archive_status_with(&[&StubClassDetector, &EmptyBodyDetector])
+    }
+
+    /// Same as [`Message::archive_status`], but with an explicit list of detectors.
+    pub fn archive_status_with(&self, detectors: &[&dyn ArchiveDetector]) -> Result<ArchiveStatus> {
+        let evidence = ArchiveEvidence::read(self)?;
+        Ok(detect_archive_status(&evidence, detectors))
+    }
+}
+
+/// Run the `detectors` in order, and return the first [`ArchiveStatus`] any of them report.
+pub fn detect_archive_status(
+    evidence: &ArchiveEvidence,
+    detectors: &[&dyn ArchiveDetector],
+) -> ArchiveStatus {
+    detectors
+        .iter()
+        .find_map(|detector| detector.detect(evidence))
+        .unwrap_or(ArchiveStatus::NotArchived)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_stubs() {
+        let detectors: [&dyn ArchiveDetector; 2] = [&StubClassDetector, &EmptyBodyDetector];
+        let mut evidence = ArchiveEvidence {
+            message_class: Some("ipm.note.enterprisevault.shortcut".to_string()),
+            has_body: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            detect_archive_status(&evidence, &detectors),
+            ArchiveStatus::Stub {
+                archiver: "Enterprise Vault".to_string()
+            }
+        );
+
+        evidence.message_class = Some("IPM.Note".to_string());
+        assert_eq!(
+            detect_archive_status(&evidence, &detectors),
+            ArchiveStatus::NotArchived
+        );
+
+        evidence.has_body = false;
+        assert!(matches!(
+            detect_archive_status(&evidence, &detectors),
+            ArchiveStatus::Suspected { .. }
+        ));
+
+        evidence.message_flags = sys::MSGFLAG_UNSENT;
+        assert_eq!(
+            detect_archive_status(&evidence, &detectors),
+            ArchiveStatus::NotArchived
+        );
+
+        let custom = |evidence: &ArchiveEvidence| {
+            (evidence.message_size == Some(0)).then(|| ArchiveStatus::Stub {
+                archiver: "In-house".to_string(),
+            })
+        };
+        evidence.message_size = Some(0);
+        assert!(detect_archive_status(&evidence, &[&custom]).is_archived());
+    }
+}
@@ -0,0 +1,157 @@
+//! Define [`AttachmentInfo`] and [`AttachMethod`].
+
+use crate::{sys, Message, PropTag, PropValue, PropValueData, Row, TableQuery};
+use windows_core::*;
+
+/// `PR_ATTACHMENT_HIDDEN`, which is set on attachments such as inline images which Outlook does
+/// not show in the attachment well.
+pub const PR_ATTACHMENT_HIDDEN: u32 = 0x7FFE_000B;
+
+/// Columns requested by [`Message::attachment_infos`].
+const ATTACHMENT_COLUMNS: [u32; 9] = [
+    sys::PR_ATTACH_NUM,
+    sys::PR_ATTACH_LONG_FILENAME_W,
+    sys::PR_ATTACH_FILENAME_W,
+    sys::PR_DISPLAY_NAME_W,
+    sys::PR_ATTACH_SIZE,
+    sys::PR_ATTACH_METHOD,
+    sys::PR_ATTACH_CONTENT_ID_W,
+    sys::PR_ATTACH_MIME_TAG_W,
+    PR_ATTACHMENT_HIDDEN,
+];
+
+/// Value of [`sys::PR_ATTACH_METHOD`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AttachMethod {
+    /// `NO_ATTACHMENT`, the attachment has not been filled in yet.
+    #[default]
+    None,
+
+    /// [`sys::ATTACH_BY_VALUE`]
+    ByValue,
+
+    /// [`sys::ATTACH_BY_REFERENCE`]
+    ByReference,
+
+    /// [`sys::ATTACH_BY_REF_RESOLVE`]
+    ByRefResolve,
+
+    /// [`sys::ATTACH_BY_REF_ONLY`]
+    ByRefOnly,
+
+    /// [`sys::ATTACH_EMBEDDED_MSG`]
+    EmbeddedMessage,
+
+    /// [`sys::ATTACH_OLE`]
+    Ole,
+
+    /// [`sys::ATTACH_BY_WEBREFERENCE`]
+    ByWebReference,
+
+    /// Any other value.
+    Other(u32),
+}
+
+impl From<u32> for AttachMethod {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Self::None,
+            sys::ATTACH_BY_VALUE => Self::ByValue,
+            sys::ATTACH_BY_REFERENCE => Self::ByReference,
+            sys::ATTACH_BY_REF_RESOLVE => Self::ByRefResolve,
+            sys::ATTACH_BY_REF_ONLY => Self::ByRefOnly,
+            sys::ATTACH_EMBEDDED_MSG => Self::EmbeddedMessage,
+            sys::ATTACH_OLE => Self::Ole,
+            sys::ATTACH_BY_WEBREFERENCE => Self::ByWebReference,
+            value => Self::Other(value),
+        }
+    }
+}
+
+/// Metadata for an attachment, read from the attachment table by
+/// [`Message::attachment_infos`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AttachmentInfo {
+    /// [`sys::PR_ATTACH_NUM`], which identifies the attachment in
+    /// [`sys::IMessage::OpenAttach`].
+    pub attach_num: u32,
+
+    /// [`sys::PR_ATTACH_LONG_FILENAME_W`], or [`sys::PR_ATTACH_FILENAME_W`] if the attachment
+    /// only has a short file name.
+    pub file_name: Option<String>,
+
+    /// [`sys::PR_DISPLAY_NAME_W`]
+    pub display_name: Option<String>,
+
+    /// [`sys::PR_ATTACH_SIZE`], which includes the attachment properties as well as the data.
+    pub size: u32,
+
+    /// [`sys::PR_ATTACH_METHOD`]
+    pub method: AttachMethod,
+
+    /// [`sys::PR_ATTACH_CONTENT_ID_W`], which HTML bodies use to refer to inline images.
+    pub content_id: Option<String>,
+
+    /// [`sys::PR_ATTACH_MIME_TAG_W`]
+    pub mime_tag: Option<String>,
+
+    /// [`PR_ATTACHMENT_HIDDEN`]
+    pub hidden: bool,
+}
+
+impl AttachmentInfo {
+    /// Read an [`AttachmentInfo`] from a row with the [`Message::attachment_infos`] columns.
+    /// Returns `None` if the row does not have a [`sys::PR_ATTACH_NUM`].
+    pub fn from_row(row: &Row) -> Option<Self> {
+        let mut info = Self::default();
+        let mut attach_num = None;
+        let mut short_file_name = None;
+        for PropValue { tag, value } in row.iter() {
+            let string = |value: PCWSTR| unsafe { value.to_string() }.ok();
+            match (u32::from(tag), value) {
+                (sys::PR_ATTACH_NUM, PropValueData::Long(value)) => attach_num = Some(value as u32),
+                (sys::PR_ATTACH_LONG_FILENAME_W, PropValueData::Unicode(value)) => {
+                    info.file_name = string(value)
+                }
+                (sys::PR_ATTACH_FILENAME_W, PropValueData::Unicode(value)) => {
+                    short_file_name = string(value)
+                }
+                (sys::PR_DISPLAY_NAME_W, PropValueData::Unicode(value)) => {
+                    info.display_name = string(value)
+                }
+                (sys::PR_ATTACH_SIZE, PropValueData::Long(value)) => info.size = value as u32,
+                (sys::PR_ATTACH_METHOD, PropValueData::Long(value)) => {
+                    info.method = AttachMethod::from(value as u32)
+                }
+                (sys::PR_ATTACH_CONTENT_ID_W, PropValueData::Unicode(value)) => {
+                    info.content_id = string(value)
+                }
+                (sys::PR_ATTACH_MIME_TAG_W, PropValueData::Unicode(value)) => {
+                    info.mime_tag = string(value)
+                }
+                (PR_ATTACHMENT_HIDDEN, PropValueData::Boolean(value)) => info.hidden = value != 0,
+                _ => {}
+            }
+        }
+        info.attach_num = attach_num?;
+        info.file_name = info.file_name.or(short_file_name);
+        Some(info)
+    }
+}
+
+impl Message {
+    /// List the attachments on this message from the [`Message::attachment_table`], without
+    /// opening any of them. This is much cheaper than calling [`sys::IMessage::OpenAttach`] on
+    /// each attachment, e.g. when scanning a mailbox for large attachments.
+    pub fn attachment_infos(&self) -> Result<Vec<AttachmentInfo>> {
+        let columns = ATTACHMENT_COLUMNS.map(PropTag);
+        TableQuery::new(self.attachment_table()?)
+            .columns(&columns)
+            .execute()?
+            .filter_map(|row| match row {
+                Ok(row) => AttachmentInfo::from_row(&row).map(Ok),
+                Err(err) => Some(Err(err)),
+            })
+            .collect()
+    }
+}
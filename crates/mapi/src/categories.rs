@@ -0,0 +1,385 @@
+//! Define [`CategoryColor`], [`CategoryBatchReport`], and the [`Message`] and [`Folder`]
+//! methods which read and write categories.
+
+use crate::{
+    mapi_prop::{delete_props, get_one_prop, set_sprop_values},
+    sys, EntryList, Folder, MAPIOutParam, Message, OpenFlags, OwnedPropValue, OwnedPropValueData,
+    PropTag, PropType, PropValueBuilder, PropValueData,
+};
+use core::{ptr, slice};
+use windows::Win32::Foundation::E_UNEXPECTED;
+use windows_core::*;
+
+/// Name of the `PidNameKeywords` property in [`sys::PS_PUBLIC_STRINGS`], which holds the
+/// categories of an item as a [`sys::PT_MV_UNICODE`] list.
+pub const NAME_KEYWORDS: &str = "Keywords";
+
+/// Color of a category in the master category list, stored as the `color` attribute of each
+/// `category` element. [`CategoryColor::None`] is `-1`, and the presets are `0` through `24`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CategoryColor {
+    /// No color is assigned.
+    #[default]
+    None,
+
+    /// `preset0`
+    Red,
+
+    /// `preset1`
+    Orange,
+
+    /// `preset2`
+    Peach,
+
+    /// `preset3`
+    Yellow,
+
+    /// `preset4`
+    Green,
+
+    /// `preset5`
+    Teal,
+
+    /// `preset6`
+    Olive,
+
+    /// `preset7`
+    Blue,
+
+    /// `preset8`
+    Purple,
+
+    /// `preset9`
+    Maroon,
+
+    /// `preset10`
+    Steel,
+
+    /// `preset11`
+    DarkSteel,
+
+    /// `preset12`
+    Gray,
+
+    /// `preset13`
+    DarkGray,
+
+    /// `preset14`
+    Black,
+
+    /// `preset15`
+    DarkRed,
+
+    /// `preset16`
+    DarkOrange,
+
+    /// `preset17`
+    DarkPeach,
+
+    /// `preset18`
+    DarkYellow,
+
+    /// `preset19`
+    DarkGreen,
+
+    /// `preset20`
+    DarkTeal,
+
+    /// `preset21`
+    DarkOlive,
+
+    /// `preset22`
+    DarkBlue,
+
+    /// `preset23`
+    DarkPurple,
+
+    /// `preset24`
+    DarkMaroon,
+}
+
+/// Every preset in order, so the index matches [`CategoryColor::preset`].
+const PRESETS: [(CategoryColor, [u8; 3]); 25] = [
+    (CategoryColor::Red, [0xE7, 0xA1, 0xA2]),
+    (CategoryColor::Orange, [0xF9, 0xBA, 0x89]),
+    (CategoryColor::Peach, [0xF7, 0xDD, 0x8F]),
+    (CategoryColor::Yellow, [0xFC, 0xFA, 0x90]),
+    (CategoryColor::Green, [0x78, 0xD1, 0x68]),
+    (CategoryColor::Teal, [0x9F, 0xDC, 0xC9]),
+    (CategoryColor::Olive, [0xC6, 0xD2, 0xB0]),
+    (CategoryColor::Blue, [0x9D, 0xB7, 0xE8]),
+    (CategoryColor::Purple, [0xB5, 0xA1, 0xE2]),
+    (CategoryColor::Maroon, [0xDA, 0xAE, 0xC2]),
+    (CategoryColor::Steel, [0xDA, 0xD9, 0xDC]),
+    (CategoryColor::DarkSteel, [0x6B, 0x79, 0x94]),
+    (CategoryColor::Gray, [0xBF, 0xBF, 0xBF]),
+    (CategoryColor::DarkGray, [0x6F, 0x6F, 0x6F]),
+    (CategoryColor::Black, [0x4F, 0x4F, 0x4F]),
+    (CategoryColor::DarkRed, [0xC1, 0x1A, 0x25]),
+    (CategoryColor::DarkOrange, [0xE2, 0x62, 0x0D]),
+    (CategoryColor::DarkPeach, [0xC7, 0x99, 0x30]),
+    (CategoryColor::DarkYellow, [0xB9, 0xB3, 0x00]),
+    (CategoryColor::DarkGreen, [0x36, 0x8F, 0x2B]),
+    (CategoryColor::DarkTeal, [0x32, 0x9B, 0x7A]),
+    (CategoryColor::DarkOlive, [0x77, 0x8B, 0x45]),
+    (CategoryColor::DarkBlue, [0x28, 0x58, 0xA5]),
+    (CategoryColor::DarkPurple, [0x5C, 0x3F, 0xA3]),
+    (CategoryColor::DarkMaroon, [0x93, 0x44, 0x6B]),
+];
+
+impl CategoryColor {
+    /// Get the `color` attribute value, i.e. `-1` for [`CategoryColor::None`] or the index of the
+    /// preset.
+    pub fn preset(self) -> i32 {
+        PRESETS
+            .iter()
+            .position(|(color, _)| *color == self)
+            .map_or(-1, |index| index as i32)
+    }
+
+    /// Get the color which Outlook uses to render the category, as `[red, green, blue]`. Returns
+    /// `None` for [`CategoryColor::None`].
+    pub fn rgb(self) -> Option<[u8; 3]> {
+        PRESETS
+            .iter()
+            .find(|(color, _)| *color == self)
+            .map(|(_, rgb)| *rgb)
+    }
+
+    /// Format the [`CategoryColor::rgb`] value as an HTML color, e.g. `#E7A1A2`.
+    pub fn to_html(self) -> Option<String> {
+        self.rgb()
+            .map(|[red, green, blue]| format!("#{red:02X}{green:02X}{blue:02X}"))
+    }
+}
+
+impl From<i32> for CategoryColor {
+    /// Map a `color` attribute value to a preset. Anything outside of `0..=24` is
+    /// [`CategoryColor::None`].
+    fn from(value: i32) -> Self {
+        usize::try_from(value)
+            .ok()
+            .and_then(|index| PRESETS.get(index))
+            .map_or(Self::None, |(color, _)| *color)
+    }
+}
+
+impl From<CategoryColor> for i32 {
+    fn from(value: CategoryColor) -> Self {
+        value.preset()
+    }
+}
+
+/// Look up `PidNameKeywords` with [`sys::IMAPIProp::GetIDsFromNames`]. Returns `None` if the name
+/// has never been mapped on this store, unless [`sys::MAPI_CREATE`] is passed in `flags`.
+fn keywords_tag(prop: &sys::IMAPIProp, flags: u32) -> Result<Option<PropTag>> {
+    let guid = sys::PS_PUBLIC_STRINGS;
+    let mut name: Vec<u16> = NAME_KEYWORDS.encode_utf16().chain(Some(0)).collect();
+    let mut name = sys::MAPINAMEID {
+        lpguid: ptr::from_ref(&guid) as *mut _,
+        ulKind: sys::MNID_STRING,
+        Kind: sys::MAPINAMEID_0 {
+            lpwstrName: PWSTR(name.as_mut_ptr()),
+        },
+    };
+    let mut name_ptr = ptr::from_mut(&mut name);
+    let mut tags: MAPIOutParam<sys::SPropTagArray> = Default::default();
+    unsafe {
+        prop.GetIDsFromNames(1, &mut name_ptr, flags, tags.as_mut_ptr())?;
+        let tags = tags.as_mut().ok_or_else(|| Error::from(E_UNEXPECTED))?;
+        let tags = slice::from_raw_parts(tags.aulPropTag.as_ptr(), tags.cValues as usize);
+        Ok(tags
+            .first()
+            .map(|tag| PropTag(*tag))
+            .filter(|tag| u32::from(tag.prop_type()) != sys::PT_ERROR)
+            .map(|tag| tag.change_prop_type(PropType::new(sys::PT_MV_UNICODE as u16))))
+    }
+}
+
+fn read_keywords(message: &sys::IMessage, tag: PropTag) -> Result<Vec<String>> {
+    Ok(get_one_prop(message, u32::from(tag), |value| match value {
+        PropValueData::UnicodeArray(values) => Some(
+            values
+                .iter()
+                .filter_map(|value| unsafe { value.to_string() }.ok())
+                .collect(),
+        ),
+        _ => None,
+    })?
+    .unwrap_or_default())
+}
+
+fn write_keywords(message: &sys::IMessage, tag: PropTag, categories: &[String]) -> Result<()> {
+    if categories.is_empty() {
+        return delete_props(message, &[u32::from(tag)]);
+    }
+
+    let mut values = PropValueBuilder::new()
+        .push(OwnedPropValue {
+            tag,
+            value: OwnedPropValueData::UnicodeArray(categories.to_vec()),
+        })
+        .build()
+        .map_err(|_| Error::from(sys::MAPI_E_NOT_ENOUGH_MEMORY))?;
+    let props = unsafe { slice::from_raw_parts_mut(values.as_mut_ptr(), values.len()) };
+    set_sprop_values(message, props)
+}
+
+/// Append each of `categories` to `existing` unless it is already there, ignoring case like
+/// Outlook does. Returns `true` if anything was added.
+fn merge_categories(existing: &mut Vec<String>, categories: &[&str]) -> bool {
+    let count = existing.len();
+    for category in categories {
+        let category = category.trim();
+        if !category.is_empty()
+            && !existing
+                .iter()
+                .any(|existing| existing.to_lowercase() == category.to_lowercase())
+        {
+            existing.push(category.to_string());
+        }
+    }
+    existing.len() != count
+}
+
+impl Message {
+    /// Read the categories of this message from `PidNameKeywords`. Returns an empty list if the
+    /// message does not have any, without creating the named property mapping.
+    pub fn categories(&self) -> Result<Vec<String>> {
+        match keywords_tag(self.message(), 0)? {
+            Some(tag) => read_keywords(self.message(), tag),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Replace the categories of this message in `PidNameKeywords`, or delete the property if
+    /// `categories` is empty. The changes are not persisted until [`Message::save_changes`] is
+    /// called.
+    pub fn set_categories(&self, categories: &[String]) -> Result<()> {
+        let tag = keywords_tag(self.message(), sys::MAPI_CREATE)?
+            .ok_or_else(|| Error::from(sys::MAPI_E_NOT_FOUND))?;
+        write_keywords(self.message(), tag, categories)
+    }
+
+    /// Add `categories` to this message, skipping any which it already has (ignoring case).
+    /// Returns `true` if the message was modified, in which case the caller still needs to call
+    /// [`Message::save_changes`].
+    pub fn add_categories(&self, categories: &[&str]) -> Result<bool> {
+        let mut existing = self.categories()?;
+        if !merge_categories(&mut existing, categories) {
+            return Ok(false);
+        }
+        self.set_categories(&existing)?;
+        Ok(true)
+    }
+
+    /// Remove `categories` from this message (ignoring case). Returns `true` if the message was
+    /// modified, in which case the caller still needs to call [`Message::save_changes`].
+    pub fn remove_categories(&self, categories: &[&str]) -> Result<bool> {
+        let mut existing = self.categories()?;
+        let count = existing.len();
+        existing.retain(|existing| {
+            !categories
+                .iter()
+                .any(|category| category.trim().to_lowercase() == existing.to_lowercase())
+        });
+        if existing.len() == count {
+            return Ok(false);
+        }
+        self.set_categories(&existing)?;
+        Ok(true)
+    }
+}
+
+/// Summary of a batch operation such as [`Folder::add_category`]. A failure on one message does
+/// not stop the batch, so each one is recorded here with the entry ID of the message.
+#[derive(Debug, Default)]
+pub struct CategoryBatchReport {
+    /// Number of messages which were modified and saved.
+    pub modified: usize,
+
+    /// Number of messages which already had the category.
+    pub unchanged: usize,
+
+    /// Entry ID and error for each message which could not be opened, modified, or saved.
+    pub failures: Vec<(Vec<u8>, Error)>,
+}
+
+impl Folder {
+    /// Add `category` to every message in `entry_ids` with [`Folder::add_category_with`], without
+    /// reporting progress.
+    pub fn add_category(
+        &self,
+        entry_ids: &EntryList,
+        category: &str,
+    ) -> Result<CategoryBatchReport> {
+        self.add_category_with(entry_ids, category, |_, _| {})
+    }
+
+    /// Add `category` to every message in `entry_ids`, skipping the ones which already have it
+    /// (ignoring case). `PidNameKeywords` is resolved once for the whole batch, each message is
+    /// opened with [`OpenFlags::modify`] and [`OpenFlags::deferred_errors`], only that property
+    /// is read, and [`Message::save_changes`] is called once for each message which changed.
+    ///
+    /// `on_progress` is called with `(done, total)` after each message. Per-message errors are
+    /// collected in the [`CategoryBatchReport`]; only a failure to map the named property fails
+    /// the whole batch.
+    pub fn add_category_with(
+        &self,
+        entry_ids: &EntryList,
+        category: &str,
+        mut on_progress: impl FnMut(usize, usize),
+    ) -> Result<CategoryBatchReport> {
+        let tag = keywords_tag(self.folder(), sys::MAPI_CREATE)?
+            .ok_or_else(|| Error::from(sys::MAPI_E_NOT_FOUND))?;
+        let total = entry_ids.len();
+        let mut report = CategoryBatchReport::default();
+        for (done, entry_id) in entry_ids.iter().enumerate() {
+            let result = self
+                .open_message(entry_id, OpenFlags::modify().deferred_errors(true))
+                .and_then(|message| {
+                    let mut existing = read_keywords(message.message(), tag)?;
+                    if !merge_categories(&mut existing, &[category]) {
+                        return Ok(false);
+                    }
+                    write_keywords(message.message(), tag, &existing)?;
+                    message.save_changes(Default::default())?;
+                    Ok(true)
+                });
+            match result {
+                Ok(true) => report.modified += 1,
+                Ok(false) => report.unchanged += 1,
+                Err(err) => report.failures.push((entry_id.to_vec(), err)),
+            }
+            on_progress(done + 1, total);
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn category_colors() {
+        assert_eq!(CategoryColor::from(-1), CategoryColor::None);
+        assert_eq!(CategoryColor::from(25), CategoryColor::None);
+        assert_eq!(CategoryColor::from(0), CategoryColor::Red);
+        assert_eq!(CategoryColor::from(24), CategoryColor::DarkMaroon);
+        for preset in 0..25 {
+            assert_eq!(CategoryColor::from(preset).preset(), preset);
+        }
+        assert_eq!(CategoryColor::None.preset(), -1);
+        assert_eq!(CategoryColor::None.rgb(), None);
+        assert_eq!(CategoryColor::Red.to_html().as_deref(), Some("#E7A1A2"));
+    }
+
+    #[test]
+    fn merge_dedup() {
+        let mut existing = vec!["Work".to_string()];
+        assert!(!merge_categories(&mut existing, &["work", " WORK ", ""]));
+        assert!(merge_categories(&mut existing, &["Home", "home"]));
+        assert_eq!(existing, ["Work", "Home"]);
+    }
+}
@@ -0,0 +1,107 @@
+//! Define [`copy_props`] and [`CopyPropsFlags`].
+
+use crate::{sys, MAPIOutParam, PropProblems, PropTag, PropTagArrayBuf};
+use core::ptr;
+use windows_core::*;
+
+/// Set of flags that can be passed to [`sys::IMAPIProp::CopyProps`] or
+/// [`sys::IMAPIProp::CopyTo`] by [`copy_props`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CopyPropsFlags {
+    /// Pass [`sys::MAPI_MOVE`], which deletes the properties from the source after copying them.
+    pub move_props: bool,
+
+    /// Pass [`sys::MAPI_NOREPLACE`], which leaves properties that are already set on the
+    /// destination alone.
+    pub no_replace: bool,
+
+    /// Pass [`sys::MAPI_DECLINE_OK`], which lets the provider return [`sys::MAPI_E_DECLINE_COPY`]
+    /// instead of falling back to a generic copy, e.g. when the source and destination are in the
+    /// same store and the caller has a faster path for that.
+    pub decline_ok: bool,
+}
+
+impl From<CopyPropsFlags> for u32 {
+    fn from(value: CopyPropsFlags) -> Self {
+        let move_props = if value.move_props { sys::MAPI_MOVE } else { 0 };
+        let no_replace = if value.no_replace {
+            sys::MAPI_NOREPLACE
+        } else {
+            0
+        };
+        let decline_ok = if value.decline_ok {
+            sys::MAPI_DECLINE_OK
+        } else {
+            0
+        };
+
+        move_props | no_replace | decline_ok
+    }
+}
+
+/// Copy properties from `src` to `dst`, which can be in different stores.
+///
+/// If `tags` is empty, this copies everything except the `exclude` tags with
+/// [`sys::IMAPIProp::CopyTo`], including the recipients and attachments of a message or the
+/// contents and subfolders of a folder. Otherwise, it copies just the `tags` which are not also
+/// in `exclude` with [`sys::IMAPIProp::CopyProps`]. Either way, the tag arrays are allocated
+/// internally, and `dst` is passed with the IID of `T`, so pass the most specific interface for
+/// the destination, e.g. a [`sys::IMessage`] rather than a [`sys::IMAPIProp`].
+///
+/// Properties which could not be copied are returned as [`PropProblems`]. The changes to `dst`
+/// are not persisted until it is saved with [`sys::IMAPIProp::SaveChanges`].
+pub fn copy_props<T: Interface>(
+    src: &sys::IMAPIProp,
+    dst: &T,
+    tags: &[PropTag],
+    exclude: &[PropTag],
+    flags: CopyPropsFlags,
+    progress: Option<&sys::IMAPIProgress>,
+) -> Result<PropProblems> {
+    let mut iid = T::IID;
+    let flags = flags.into();
+    let mut problems: MAPIOutParam<sys::SPropProblemArray> = Default::default();
+    unsafe {
+        if tags.is_empty() {
+            let mut exclude = PropTagArrayBuf::from(exclude);
+            src.CopyTo(
+                0,
+                ptr::null_mut(),
+                if exclude.is_empty() {
+                    ptr::null_mut()
+                } else {
+                    exclude.as_mut_ptr()
+                },
+                0,
+                progress,
+                &mut iid,
+                dst.as_raw(),
+                flags,
+                problems.as_mut_ptr(),
+            )?;
+        } else {
+            let mut include: PropTagArrayBuf = tags
+                .iter()
+                .copied()
+                .filter(|tag| !exclude.contains(tag))
+                .collect();
+            if include.is_empty() {
+                return Ok(PropProblems::default());
+            }
+            src.CopyProps(
+                include.as_mut_ptr(),
+                0,
+                progress,
+                &mut iid,
+                dst.as_raw(),
+                flags,
+                problems.as_mut_ptr(),
+            )?;
+        }
+        Ok(PropProblems::from_raw(
+            problems
+                .as_mut()
+                .map_or(ptr::null(), |problems| problems as *const _),
+        ))
+    }
+}
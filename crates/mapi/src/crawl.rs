@@ -0,0 +1,495 @@
+//! Define [`CrawlScheduler`], [`CrawlOptions`], [`CrawlEvent`], and [`CrawlSummary`].
+
+use crate::{
+    is_object_deleted, sys, Checkpoint, DeletedItemPolicy, FolderWalker, Logon, OpenFlags,
+    OpenStoreFlags, OwnedPropValue, PooledSession, PropTag, SessionPool, TableQuery, WalkEvent,
+    DEFAULT_QUERY_BATCH_SIZE,
+};
+use std::{mem, sync::mpsc};
+use windows_core::*;
+
+/// Limits and columns for [`CrawlScheduler::crawl`].
+#[derive(Clone)]
+pub struct CrawlOptions {
+    /// Maximum number of contents tables being read at the same time across all stores. This is
+    /// also capped by [`SessionPool::workers`].
+    pub max_concurrent: usize,
+
+    /// Maximum number of contents tables being read at the same time in any one store, so a single
+    /// large mailbox does not monopolize the pool or its server.
+    pub max_per_store: usize,
+
+    /// Columns to read from each contents table. If this is empty, the tables keep their default
+    /// columns.
+    pub columns: Vec<PropTag>,
+
+    /// Number of rows in each [`CrawlEvent::Rows`], and in each call to
+    /// [`sys::IMAPITable::QueryRows`].
+    pub batch: usize,
+
+    /// What to do when a folder disappears in the middle of the crawl.
+    pub policy: DeletedItemPolicy,
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 4,
+            max_per_store: 2,
+            columns: vec![PropTag(sys::PR_ENTRYID)],
+            batch: DEFAULT_QUERY_BATCH_SIZE,
+            policy: DeletedItemPolicy::Skip,
+        }
+    }
+}
+
+/// Progress reported by [`CrawlScheduler::crawl`]. Every event identifies the store by its index
+/// in the `stores` argument, and the folder by its [`sys::PR_ENTRYID`] (empty for the root folder).
+#[derive(Debug)]
+pub enum CrawlEvent {
+    /// A batch of rows from the contents table of a folder.
+    Rows {
+        /// Index of the store in the `stores` argument.
+        store: usize,
+
+        /// [`sys::PR_ENTRYID`] of the folder, or empty for the root folder.
+        folder: Vec<u8>,
+
+        /// The rows, each with the values of the [`CrawlOptions::columns`].
+        rows: Vec<Vec<OwnedPropValue>>,
+    },
+
+    /// The contents table of a folder was read to the end.
+    FolderDone {
+        /// Index of the store in the `stores` argument.
+        store: usize,
+
+        /// [`sys::PR_ENTRYID`] of the folder, or empty for the root folder.
+        folder: Vec<u8>,
+
+        /// Number of rows read from the folder by this job, not counting the rows skipped when
+        /// resuming from a [`Checkpoint`].
+        rows: usize,
+    },
+
+    /// Reading the contents table of a folder failed, or the job reading it panicked. The crawl
+    /// continues with the other folders.
+    FolderFailed {
+        /// Index of the store in the `stores` argument.
+        store: usize,
+
+        /// [`sys::PR_ENTRYID`] of the folder, or empty for the root folder.
+        folder: Vec<u8>,
+
+        /// The error which ended the read.
+        error: Error,
+    },
+
+    /// A folder disappeared before it could be read, and [`CrawlOptions::policy`] allowed the crawl
+    /// to continue.
+    Skipped {
+        /// Index of the store in the `stores` argument.
+        store: usize,
+
+        /// [`sys::PR_ENTRYID`] of the folder, or empty for the root folder.
+        folder: Vec<u8>,
+
+        /// The error which [`is_object_deleted`] accepted.
+        error: Error,
+    },
+}
+
+/// Totals returned by [`CrawlScheduler::crawl`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CrawlSummary {
+    /// Number of folders whose contents table was read to the end.
+    pub folders: usize,
+
+    /// Total number of rows read.
+    pub rows: usize,
+
+    /// Number of [`CrawlEvent::FolderFailed`] events.
+    pub failed: usize,
+
+    /// Number of [`CrawlEvent::Skipped`] events.
+    pub skipped: usize,
+
+    /// Number of folders which were not read again because the [`Checkpoint`] passed to
+    /// [`CrawlScheduler::resume`] shows they were already read to the end.
+    pub already_done: usize,
+}
+
+/// Crawl every folder in a set of stores, walking the hierarchy on the caller's [`Logon`] and
+/// reading each contents table on a [`SessionPool`] worker.
+///
+/// Hierarchy tables are small, so a single thread can walk them much faster than the workers can
+/// read the contents tables. The scheduler only walks ahead as far as the
+/// [`CrawlOptions::max_concurrent`] and [`CrawlOptions::max_per_store`] limits allow, and it
+/// interleaves the stores so that each one makes progress.
+///
+/// ```no_run
+/// # use outlook_mapi::*;
+/// # use windows_core::*;
+/// # fn example(logon: &Logon, pool: &SessionPool, stores: &[Vec<u8>]) -> Result<()> {
+/// let scheduler = CrawlScheduler::new(pool, Default::default());
+/// let summary = scheduler.crawl(logon, stores, |event| {
+///     if let CrawlEvent::Rows { rows, .. } = event {
+///         // index the rows...
+///     }
+/// })?;
+/// println!("{} rows in {} folders", summary.rows, summary.folders);
+/// # Ok(())
+/// # }
+/// ```
+pub struct CrawlScheduler<'a> {
+    pool: &'a SessionPool,
+    options: CrawlOptions,
+}
+
+struct StoreState {
+    entry_id: Vec<u8>,
+    walker: Option<FolderWalker>,
+    in_flight: usize,
+}
+
+impl<'a> CrawlScheduler<'a> {
+    /// Create a scheduler which reads contents tables on `pool`.
+    pub fn new(pool: &'a SessionPool, options: CrawlOptions) -> Self {
+        Self { pool, options }
+    }
+
+    /// Crawl every folder in each store, identified by its [`sys::PR_ENTRYID`] from the
+    /// [`sys::IMAPISession::GetMsgStoresTable`], and pass each [`CrawlEvent`] to `on_event` on the
+    /// calling thread.
+    ///
+    /// Errors reading a contents table are reported as [`CrawlEvent::FolderFailed`]. Errors
+    /// walking the hierarchy end the crawl: no more folders are scheduled, and the error is
+    /// returned once the contents tables which are already being read have finished.
+    pub fn crawl(
+        &self,
+        logon: &Logon,
+        stores: &[Vec<u8>],
+        mut on_event: impl FnMut(CrawlEvent),
+    ) -> Result<CrawlSummary> {
+        self.resume(logon, stores, &mut Checkpoint::new(), |event, _| {
+            on_event(event)
+        })
+    }
+
+    /// Same as [`CrawlScheduler::crawl`], but skip the work already recorded in `checkpoint`, and
+    /// keep recording progress in it as the crawl goes.
+    ///
+    /// Folders which were read to the end are not read again, and folders which were only read
+    /// part of the way start after the rows which were already reported. The checkpoint is
+    /// updated before each event is passed to `on_event`, so the caller can save it with
+    /// [`Checkpoint::to_bytes`] once the event has been processed, e.g. after each
+    /// [`CrawlEvent::FolderDone`].
+    ///
+    /// Rows are counted from the start of the contents table with the columns and default sort
+    /// order from [`CrawlOptions`], so a folder which changed since the checkpoint was saved may
+    /// repeat or miss rows when it resumes. Use the [`crate::FolderProgress::sync_state`] with
+    /// incremental change synchronization to track changes exactly.
+    pub fn resume(
+        &self,
+        logon: &Logon,
+        stores: &[Vec<u8>],
+        checkpoint: &mut Checkpoint,
+        mut on_event: impl FnMut(CrawlEvent, &Checkpoint),
+    ) -> Result<CrawlSummary> {
+        let max_concurrent = self
+            .options
+            .max_concurrent
+            .clamp(1, self.pool.workers().max(1));
+        let max_per_store = self.options.max_per_store.max(1);
+        let (sender, receiver) = mpsc::channel();
+        let mut summary = CrawlSummary::default();
+        let mut error = None;
+        let mut in_flight = 0;
+
+        let mut states = Vec::with_capacity(stores.len());
+        for entry_id in stores {
+            let store = logon.open_store(
+                entry_id,
+                OpenStoreFlags {
+                    best_access: true,
+                    deferred_errors: true,
+                    no_dialog: true,
+                    no_mail: true,
+                    ..Default::default()
+                },
+            )?;
+            states.push(StoreState {
+                entry_id: entry_id.clone(),
+                walker: Some(FolderWalker::new(store.store(), &[], self.options.policy)),
+                in_flight: 0,
+            });
+        }
+
+        loop {
+            for (index, state) in states.iter_mut().enumerate() {
+                while error.is_none()
+                    && in_flight < max_concurrent
+                    && state.in_flight < max_per_store
+                {
+                    let Some(walker) = state.walker.as_mut() else {
+                        break;
+                    };
+                    match walker.next() {
+                        Some(Ok(WalkEvent::Folder { entry_id, .. })) => {
+                            let skip = match checkpoint.progress(&state.entry_id, &entry_id) {
+                                Some(progress) if progress.done => {
+                                    summary.already_done += 1;
+                                    continue;
+                                }
+                                Some(progress) => progress.rows as usize,
+                                None => 0,
+                            };
+                            if let Err(err) = self.schedule(
+                                index,
+                                &state.entry_id,
+                                entry_id,
+                                skip,
+                                sender.clone(),
+                            ) {
+                                state.walker = None;
+                                error = Some(err);
+                                continue;
+                            }
+                            state.in_flight += 1;
+                            in_flight += 1;
+                        }
+                        Some(Ok(WalkEvent::Skipped {
+                            entry_id, error, ..
+                        })) => {
+                            summary.skipped += 1;
+                            on_event(
+                                CrawlEvent::Skipped {
+                                    store: index,
+                                    folder: entry_id,
+                                    error,
+                                },
+                                checkpoint,
+                            );
+                        }
+                        Some(Err(err)) => {
+                            state.walker = None;
+                            error = Some(err);
+                        }
+                        None => state.walker = None,
+                    }
+                }
+            }
+
+            if in_flight == 0 {
+                break;
+            }
+
+            let Ok(event) = receiver.recv() else {
+                break;
+            };
+            match &event {
+                CrawlEvent::Rows {
+                    store,
+                    folder,
+                    rows,
+                } => {
+                    summary.rows += rows.len();
+                    checkpoint.record_rows(&states[*store].entry_id, folder, rows.len() as u64);
+                }
+                CrawlEvent::FolderDone { store, folder, .. } => {
+                    checkpoint.mark_done(&states[*store].entry_id, folder);
+                    summary.folders += 1;
+                    states[*store].in_flight -= 1;
+                    in_flight -= 1;
+                }
+                CrawlEvent::FolderFailed { store, .. } => {
+                    summary.failed += 1;
+                    states[*store].in_flight -= 1;
+                    in_flight -= 1;
+                }
+                CrawlEvent::Skipped { store, .. } => {
+                    summary.skipped += 1;
+                    states[*store].in_flight -= 1;
+                    in_flight -= 1;
+                }
+            }
+            on_event(event, checkpoint);
+        }
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(summary),
+        }
+    }
+
+    fn schedule(
+        &self,
+        store: usize,
+        store_entry_id: &[u8],
+        folder: Vec<u8>,
+        skip: usize,
+        sender: mpsc::Sender<CrawlEvent>,
+    ) -> Result<()> {
+        let store_entry_id = store_entry_id.to_vec();
+        let columns = self.options.columns.clone();
+        let batch = self.options.batch.max(1);
+        let policy = self.options.policy;
+        let job = FolderJob {
+            store,
+            folder,
+            sender,
+            finished: false,
+        };
+        self.pool.submit(move |session: &PooledSession| {
+            let read_rows = || -> Result<usize> {
+                let contents = session
+                    .store(&store_entry_id)?
+                    .open_folder_with(&job.folder, OpenFlags::default())?
+                    .contents_table()?;
+                let rows = TableQuery::new(contents)
+                    .columns(&columns)
+                    .batch(batch)
+                    .skip(skip)
+                    .execute()?;
+                let mut count = 0;
+                let mut pending = Vec::with_capacity(batch);
+                for row in rows {
+                    pending.push(row?.iter().map(OwnedPropValue::from).collect());
+                    count += 1;
+                    if pending.len() >= batch {
+                        job.send(CrawlEvent::Rows {
+                            store,
+                            folder: job.folder.clone(),
+                            rows: mem::take(&mut pending),
+                        });
+                    }
+                }
+                if !pending.is_empty() {
+                    job.send(CrawlEvent::Rows {
+                        store,
+                        folder: job.folder.clone(),
+                        rows: pending,
+                    });
+                }
+                Ok(count)
+            };
+
+            let folder = job.folder.clone();
+            let event = match read_rows() {
+                Ok(rows) => CrawlEvent::FolderDone {
+                    store,
+                    folder,
+                    rows,
+                },
+                Err(error) if is_object_deleted(&error) && policy != DeletedItemPolicy::Abort => {
+                    CrawlEvent::Skipped {
+                        store,
+                        folder,
+                        error,
+                    }
+                }
+                Err(error) => CrawlEvent::FolderFailed {
+                    store,
+                    folder,
+                    error,
+                },
+            };
+            job.finish(event);
+        })
+    }
+}
+
+/// Reports the end of a scheduled folder to [`CrawlScheduler::resume`]. If the job panics, or the
+/// pool drops it without running it, the guard sends [`CrawlEvent::FolderFailed`] when it is
+/// dropped, so the scheduler does not wait for the folder forever.
+struct FolderJob {
+    store: usize,
+    folder: Vec<u8>,
+    sender: mpsc::Sender<CrawlEvent>,
+    finished: bool,
+}
+
+impl FolderJob {
+    fn send(&self, event: CrawlEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    fn finish(mut self, event: CrawlEvent) {
+        self.finished = true;
+        self.send(event);
+    }
+}
+
+impl Drop for FolderJob {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.send(CrawlEvent::FolderFailed {
+                store: self.store,
+                folder: mem::take(&mut self.folder),
+                error: Error::new(
+                    sys::MAPI_E_CALL_FAILED,
+                    "the job reading the folder did not finish",
+                ),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+
+    fn job(sender: mpsc::Sender<CrawlEvent>) -> FolderJob {
+        FolderJob {
+            store: 1,
+            folder: vec![0x01, 0x02],
+            sender,
+            finished: false,
+        }
+    }
+
+    #[test]
+    fn finished_job() {
+        let (sender, receiver) = mpsc::channel();
+        job(sender).finish(CrawlEvent::FolderDone {
+            store: 1,
+            folder: vec![0x01, 0x02],
+            rows: 3,
+        });
+        assert!(matches!(
+            receiver.recv(),
+            Ok(CrawlEvent::FolderDone { rows: 3, .. })
+        ));
+        assert!(receiver.recv().is_err());
+    }
+
+    #[test]
+    fn panicking_job() {
+        let (sender, receiver) = mpsc::channel();
+        let job = job(sender);
+        let result = panic::catch_unwind(AssertUnwindSafe(move || {
+            job.send(CrawlEvent::Rows {
+                store: job.store,
+                folder: job.folder.clone(),
+                rows: Vec::new(),
+            });
+            panic!("job panicked");
+        }));
+        assert!(result.is_err());
+        assert!(matches!(receiver.recv(), Ok(CrawlEvent::Rows { .. })));
+        match receiver.recv() {
+            Ok(CrawlEvent::FolderFailed {
+                store,
+                folder,
+                error,
+            }) => {
+                assert_eq!(store, 1);
+                assert_eq!(folder, [0x01, 0x02]);
+                assert_eq!(error.code(), sys::MAPI_E_CALL_FAILED);
+            }
+            _ => panic!("expected FolderFailed"),
+        }
+        assert!(receiver.recv().is_err());
+    }
+}
@@ -0,0 +1,322 @@
+//! Define [`Delegate`], [`DelegateFolder`], and [`FolderRights`].
+
+use crate::{
+    mapi_prop::{open_inbox, open_store_entry},
+    sys, Logon, MapiProp, OpenFlags, PropTag, PropValue, PropValueData, RowSet, SizedSPropTagArray,
+    Store, StringPropOptions,
+};
+use core::ptr;
+use windows::Win32::Foundation::E_UNEXPECTED;
+use windows_core::*;
+
+/// `PR_IPM_JOURNAL_ENTRYID` is missing from the generated bindings.
+pub const PR_IPM_JOURNAL_ENTRYID: u32 = 0x36D2_0102;
+
+/// `PR_IPM_NOTE_ENTRYID` is missing from the generated bindings.
+pub const PR_IPM_NOTE_ENTRYID: u32 = 0x36D3_0102;
+
+/// The folders which Outlook lets a user share with a delegate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DelegateFolder {
+    /// The default receive folder for `IPM` messages.
+    Inbox,
+
+    /// [`sys::PR_IPM_APPOINTMENT_ENTRYID`]
+    Calendar,
+
+    /// [`sys::PR_IPM_CONTACT_ENTRYID`]
+    Contacts,
+
+    /// [`sys::PR_IPM_TASK_ENTRYID`]
+    Tasks,
+
+    /// [`PR_IPM_NOTE_ENTRYID`]
+    Notes,
+
+    /// [`PR_IPM_JOURNAL_ENTRYID`]
+    Journal,
+}
+
+impl DelegateFolder {
+    /// All of the [`DelegateFolder`] values, in the order Outlook displays them.
+    pub const ALL: [Self; 6] = [
+        Self::Inbox,
+        Self::Calendar,
+        Self::Contacts,
+        Self::Tasks,
+        Self::Notes,
+        Self::Journal,
+    ];
+
+    /// Open the folder in a store. The entry IDs of every folder except the Inbox are stored on
+    /// the Inbox.
+    pub fn open(self, store: &sys::IMsgStore) -> Result<sys::IMAPIFolder> {
+        let inbox = open_inbox(store)?;
+        let tag = match self {
+            Self::Inbox => return Ok(inbox),
+            Self::Calendar => sys::PR_IPM_APPOINTMENT_ENTRYID,
+            Self::Contacts => sys::PR_IPM_CONTACT_ENTRYID,
+            Self::Tasks => sys::PR_IPM_TASK_ENTRYID,
+            Self::Notes => PR_IPM_NOTE_ENTRYID,
+            Self::Journal => PR_IPM_JOURNAL_ENTRYID,
+        };
+        let entry_id = inbox.get_binary(PropTag(tag))?;
+        open_store_entry(store, &entry_id, OpenFlags::default())
+    }
+}
+
+/// Set of `frights*` flags in the [`sys::PR_MEMBER_RIGHTS`] column of a folder ACL table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FolderRights {
+    /// Set if [`sys::frightsReadAny`] is present.
+    pub read_any: bool,
+
+    /// Set if [`sys::frightsCreate`] is present.
+    pub create: bool,
+
+    /// Set if [`sys::frightsEditOwned`] is present.
+    pub edit_owned: bool,
+
+    /// Set if [`sys::frightsDeleteOwned`] is present.
+    pub delete_owned: bool,
+
+    /// Set if [`sys::frightsEditAny`] is present.
+    pub edit_any: bool,
+
+    /// Set if [`sys::frightsDeleteAny`] is present.
+    pub delete_any: bool,
+
+    /// Set if [`sys::frightsCreateSubfolder`] is present.
+    pub create_subfolder: bool,
+
+    /// Set if [`sys::frightsOwner`] is present.
+    pub owner: bool,
+
+    /// Set if [`sys::frightsContact`] is present.
+    pub contact: bool,
+
+    /// Set if [`sys::frightsVisible`] is present.
+    pub visible: bool,
+
+    /// Set if [`sys::frightsFreeBusySimple`] is present.
+    pub free_busy_simple: bool,
+
+    /// Set if [`sys::frightsFreeBusyDetailed`] is present.
+    pub free_busy_detailed: bool,
+}
+
+impl FolderRights {
+    /// Test for an empty set of rights, e.g. the `Default` member with [`sys::rightsNone`].
+    pub fn is_empty(&self) -> bool {
+        u32::from(*self) == sys::rightsNone
+    }
+}
+
+impl From<u32> for FolderRights {
+    fn from(value: u32) -> Self {
+        let has = |flag: i32| value & (flag as u32) != 0;
+        Self {
+            read_any: has(sys::frightsReadAny),
+            create: has(sys::frightsCreate),
+            edit_owned: has(sys::frightsEditOwned),
+            delete_owned: has(sys::frightsDeleteOwned),
+            edit_any: has(sys::frightsEditAny),
+            delete_any: has(sys::frightsDeleteAny),
+            create_subfolder: has(sys::frightsCreateSubfolder),
+            owner: has(sys::frightsOwner),
+            contact: has(sys::frightsContact),
+            visible: has(sys::frightsVisible),
+            free_busy_simple: has(sys::frightsFreeBusySimple),
+            free_busy_detailed: has(sys::frightsFreeBusyDetailed),
+        }
+    }
+}
+
+impl From<FolderRights> for u32 {
+    fn from(value: FolderRights) -> Self {
+        [
+            (value.read_any, sys::frightsReadAny),
+            (value.create, sys::frightsCreate),
+            (value.edit_owned, sys::frightsEditOwned),
+            (value.delete_owned, sys::frightsDeleteOwned),
+            (value.edit_any, sys::frightsEditAny),
+            (value.delete_any, sys::frightsDeleteAny),
+            (value.create_subfolder, sys::frightsCreateSubfolder),
+            (value.owner, sys::frightsOwner),
+            (value.contact, sys::frightsContact),
+            (value.visible, sys::frightsVisible),
+            (value.free_busy_simple, sys::frightsFreeBusySimple),
+            (value.free_busy_detailed, sys::frightsFreeBusyDetailed),
+        ]
+        .into_iter()
+        .filter_map(|(set, flag)| set.then_some(flag as u32))
+        .fold(0, |acc, flag| acc | flag)
+    }
+}
+
+/// One member of the folder ACLs in a store, with the rights they have on each
+/// [`DelegateFolder`].
+#[derive(Clone, Debug, Default)]
+pub struct Delegate {
+    /// [`sys::PR_MEMBER_ID`] from the ACL table. The `Default` member is 0, and the `Anonymous`
+    /// member is -1.
+    pub member_id: i64,
+
+    /// [`sys::PR_MEMBER_NAME_W`] from the ACL table.
+    pub member_name: String,
+
+    /// Address book entry ID of the member, which is empty for the `Default` and `Anonymous`
+    /// members.
+    pub entry_id: Vec<u8>,
+
+    /// [`sys::PR_DISPLAY_NAME_W`] resolved through the address book, if available.
+    pub display_name: Option<String>,
+
+    /// [`sys::PR_SMTP_ADDRESS_W`] resolved through the address book, if available.
+    pub smtp_address: Option<String>,
+
+    /// Effective rights on each [`DelegateFolder`] which has an ACL entry for this member.
+    pub rights: Vec<(DelegateFolder, FolderRights)>,
+}
+
+impl Store {
+    /// Summarize the folder permissions in this store for audit tooling.
+    ///
+    /// This reads the ACL table of each [`DelegateFolder`], merges the entries by
+    /// [`sys::PR_MEMBER_ID`], and resolves each member through the address book of the `logon`
+    /// which opened the store. Folders which are missing from the store are skipped, and members
+    /// which cannot be resolved are returned with `None` in [`Delegate::display_name`] and
+    /// [`Delegate::smtp_address`].
+    pub fn delegates(&self, logon: &Logon) -> Result<Vec<Delegate>> {
+        let store = self.store();
+        let mut delegates: Vec<Delegate> = Vec::new();
+        for folder_type in DelegateFolder::ALL {
+            let folder = match folder_type.open(store) {
+                Err(err) if err.code() == sys::MAPI_E_NOT_FOUND => continue,
+                result => result?,
+            };
+            for (member, rights) in read_acl_table(&folder)? {
+                let rights = (folder_type, rights);
+                match delegates
+                    .iter_mut()
+                    .find(|delegate| delegate.member_id == member.member_id)
+                {
+                    Some(delegate) => delegate.rights.push(rights),
+                    None => delegates.push(Delegate {
+                        rights: vec![rights],
+                        ..member
+                    }),
+                }
+            }
+        }
+
+        let address_book = logon.open_address_book()?;
+        for delegate in delegates
+            .iter_mut()
+            .filter(|delegate| !delegate.entry_id.is_empty())
+        {
+            let Ok(names) = resolve_member(address_book.address_book(), &delegate.entry_id) else {
+                continue;
+            };
+            let mut names = names.into_iter().map(Result::ok);
+            delegate.display_name = names.next().flatten();
+            delegate.smtp_address = names.next().flatten();
+        }
+
+        Ok(delegates)
+    }
+}
+
+fn read_acl_table(folder: &sys::IMAPIFolder) -> Result<Vec<(Delegate, FolderRights)>> {
+    SizedSPropTagArray! { PropTagArray[4] }
+    let mut prop_tag_array = PropTagArray {
+        aulPropTag: [
+            sys::PR_MEMBER_ID,
+            sys::PR_MEMBER_NAME_W,
+            sys::PR_MEMBER_RIGHTS,
+            sys::PR_ENTRYID,
+        ],
+        ..Default::default()
+    };
+    let mut rows: RowSet = Default::default();
+    unsafe {
+        let acl: sys::IExchangeModifyTable = folder.open_property(
+            PropTag(sys::PR_ACL_TABLE),
+            OpenFlags::read_only().deferred_errors(true),
+        )?;
+        let table = acl.GetTable(sys::ACLTABLE_FREEBUSY)?;
+        sys::HrQueryAllRows(
+            &table,
+            prop_tag_array.as_mut_ptr(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            0,
+            rows.as_mut_ptr(),
+        )?;
+    }
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let mut member = Delegate::default();
+            let mut rights = FolderRights::default();
+            for PropValue { tag, value } in row.iter() {
+                match (u32::from(tag), value) {
+                    (sys::PR_MEMBER_ID, PropValueData::LargeInteger(value)) => {
+                        member.member_id = value
+                    }
+                    (sys::PR_MEMBER_NAME_W, PropValueData::Unicode(value)) => {
+                        member.member_name = unsafe { value.to_string() }.unwrap_or_default()
+                    }
+                    (sys::PR_MEMBER_RIGHTS, PropValueData::Long(value)) => {
+                        rights = FolderRights::from(value as u32)
+                    }
+                    (sys::PR_ENTRYID, PropValueData::Binary(value)) => {
+                        member.entry_id = value.to_vec()
+                    }
+                    _ => {}
+                }
+            }
+            (member, rights)
+        })
+        .collect())
+}
+
+fn resolve_member(address_book: &sys::IAddrBook, entry_id: &[u8]) -> Result<Vec<Result<String>>> {
+    let entry = unsafe {
+        let mut obj_type = 0;
+        let mut unknown = None;
+        address_book.OpenEntry(
+            entry_id.len() as u32,
+            entry_id.as_ptr() as *mut _,
+            ptr::null_mut(),
+            0,
+            &mut obj_type,
+            &mut unknown,
+        )?;
+        unknown.ok_or_else(|| Error::from(E_UNEXPECTED))?
+    };
+    let entry: sys::IMAPIProp = entry.cast()?;
+    entry.get_string_props(
+        &[
+            PropTag(sys::PR_DISPLAY_NAME_W),
+            PropTag(sys::PR_SMTP_ADDRESS_W),
+        ],
+        StringPropOptions::default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folder_rights_round_trip() {
+        let rights = FolderRights::from(sys::rightsAll as u32);
+        assert!(rights.read_any);
+        assert!(rights.owner);
+        assert!(!rights.free_busy_detailed);
+        assert_eq!(u32::from(rights), sys::rightsAll as u32);
+        assert!(FolderRights::from(sys::rightsNone).is_empty());
+    }
+}
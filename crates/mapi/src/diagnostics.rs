@@ -0,0 +1,341 @@
+//! Define [`dump_named_props`], [`dump_props`], [`diff_messages`], and [`diff_messages_with`].
+
+use crate::{
+    render_prop, sys, MAPIOutParam, OwnedPropValue, PropTag, PropTagArrayBuf, PropValue,
+    RenderOptions,
+};
+use core::{fmt, ptr, slice};
+use windows::Win32::Foundation::E_UNEXPECTED;
+use windows_core::*;
+
+/// First `PROP_ID` in the named property range.
+pub const FIRST_NAMED_PROP_ID: u16 = 0x8000;
+
+/// Last `PROP_ID` in the named property range. Stores cannot map more names than fit in
+/// `FIRST_NAMED_PROP_ID..=LAST_NAMED_PROP_ID`, and PSTs fail with [`sys::MAPI_E_NOT_ENOUGH_MEMORY`]
+/// or similar errors once they run out.
+pub const LAST_NAMED_PROP_ID: u16 = 0xFFFE;
+
+/// The name half of a [`NamedPropMapping`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NamedPropName {
+    /// [`sys::MNID_ID`]
+    Id(i32),
+
+    /// [`sys::MNID_STRING`]
+    String(String),
+}
+
+impl fmt::Display for NamedPropName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id(id) => write!(f, "0x{id:04X}"),
+            Self::String(name) => write!(f, "{name:?}"),
+        }
+    }
+}
+
+/// One entry in the named property map of a store.
+#[derive(Clone, Debug)]
+pub struct NamedPropMapping {
+    /// `PROP_ID` which the store assigned to the name.
+    pub prop_id: u16,
+
+    /// Property set GUID of the name.
+    pub property_set: GUID,
+
+    /// Numeric or string name within the [`NamedPropMapping::property_set`].
+    pub name: NamedPropName,
+}
+
+/// Result of [`dump_named_props`].
+#[derive(Clone, Debug, Default)]
+pub struct NamedPropReport {
+    /// Every mapping in the store, sorted by [`NamedPropMapping::prop_id`].
+    pub mappings: Vec<NamedPropMapping>,
+
+    /// Number of mappings in each property set, sorted with the largest count first.
+    pub usage: Vec<(GUID, usize)>,
+}
+
+impl NamedPropReport {
+    /// Total number of `PROP_ID` values in the named property range.
+    pub const CAPACITY: usize = (LAST_NAMED_PROP_ID - FIRST_NAMED_PROP_ID) as usize + 1;
+
+    /// Number of `PROP_ID` values which have already been mapped.
+    pub fn used(&self) -> usize {
+        self.mappings.len()
+    }
+
+    /// Number of `PROP_ID` values which are still available.
+    pub fn remaining(&self) -> usize {
+        Self::CAPACITY.saturating_sub(self.used())
+    }
+}
+
+impl fmt::Display for NamedPropReport {
+    /// Print a summary of the usage by property set, followed by every mapping.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{used} of {capacity} named property IDs used ({remaining} remaining)",
+            used = self.used(),
+            capacity = Self::CAPACITY,
+            remaining = self.remaining()
+        )?;
+        for (property_set, count) in self.usage.iter() {
+            writeln!(f, "  {property_set:?}: {count}")?;
+        }
+        for mapping in self.mappings.iter() {
+            writeln!(
+                f,
+                "0x{prop_id:04X} {property_set:?} {name}",
+                prop_id = mapping.prop_id,
+                property_set = mapping.property_set,
+                name = mapping.name
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Enumerate the named property map of a store with [`sys::IMAPIProp::GetNamesFromIDs`], which
+/// helps diagnose stores (particularly PSTs) that have run out of named property IDs.
+pub fn dump_named_props(store: &sys::IMsgStore) -> Result<NamedPropReport> {
+    let mut tags: MAPIOutParam<sys::SPropTagArray> = Default::default();
+    let mut count = 0;
+    let mut names: MAPIOutParam<*mut sys::MAPINAMEID> = Default::default();
+    let mut mappings = unsafe {
+        store.GetNamesFromIDs(
+            tags.as_mut_ptr(),
+            ptr::null_mut(),
+            0,
+            &mut count,
+            names.as_mut_ptr(),
+        )?;
+        let tags = tags.as_mut().ok_or_else(|| Error::from(E_UNEXPECTED))?;
+        let tags = slice::from_raw_parts(tags.aulPropTag.as_ptr(), tags.cValues as usize);
+        let names = names
+            .as_mut_slice(count as usize)
+            .ok_or_else(|| Error::from(E_UNEXPECTED))?;
+        tags.iter()
+            .zip(names.iter())
+            .filter_map(|(tag, name)| {
+                let name = name.as_ref()?;
+                let property_set = *name.lpguid.as_ref()?;
+                let prop_id = PropTag(*tag).prop_id();
+                let name = match name.ulKind {
+                    sys::MNID_ID => NamedPropName::Id(name.Kind.lID),
+                    sys::MNID_STRING => {
+                        NamedPropName::String(name.Kind.lpwstrName.to_string().ok()?)
+                    }
+                    _ => return None,
+                };
+                Some(NamedPropMapping {
+                    prop_id,
+                    property_set,
+                    name,
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+    mappings.sort_by_key(|mapping| mapping.prop_id);
+
+    let mut usage: Vec<(GUID, usize)> = Vec::new();
+    for mapping in mappings.iter() {
+        match usage
+            .iter_mut()
+            .find(|(property_set, _)| *property_set == mapping.property_set)
+        {
+            Some((_, count)) => *count += 1,
+            None => usage.push((mapping.property_set, 1)),
+        }
+    }
+    usage.sort_by(|(_, lhs), (_, rhs)| rhs.cmp(lhs));
+
+    Ok(NamedPropReport { mappings, usage })
+}
+
+/// Read every property on an object with [`sys::IMAPIProp::GetPropList`] and
+/// [`sys::IMAPIProp::GetProps`], like the property pane in MFCMAPI, sorted by tag so the output
+/// is stable enough for logging or snapshot tests.
+///
+/// Each entry pairs the tag from the property list with the value the provider returned for it.
+/// Values which the provider will not return inline (e.g. large bodies) are kept as a
+/// [`sys::PT_ERROR`] value, typically [`sys::MAPI_E_NOT_ENOUGH_MEMORY`], rather than failing the
+/// whole dump.
+pub fn dump_props(prop: &sys::IMAPIProp) -> Result<Vec<(PropTag, OwnedPropValue)>> {
+    let mut tags = PropTagArrayBuf::new(get_prop_list(prop)?);
+    if tags.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut count = 0;
+    let mut values: MAPIOutParam<sys::SPropValue> = Default::default();
+    let mut props: Vec<_> = unsafe {
+        prop.GetProps(
+            tags.as_mut_ptr(),
+            sys::MAPI_UNICODE,
+            &mut count,
+            values.as_mut_ptr(),
+        )?;
+        let values = values
+            .as_mut_slice(count as usize)
+            .ok_or_else(|| Error::from(E_UNEXPECTED))?;
+        tags.iter()
+            .zip(values.iter())
+            .map(|(tag, value)| (tag, PropValue::from(value).to_owned()))
+            .collect()
+    };
+    props.sort_by_key(|(tag, _)| u32::from(*tag));
+    Ok(props)
+}
+
+/// Call [`sys::IMAPIProp::GetPropList`] to list the tags of every property on an object.
+fn get_prop_list(prop: &sys::IMAPIProp) -> Result<Vec<PropTag>> {
+    let mut tags: MAPIOutParam<sys::SPropTagArray> = Default::default();
+    unsafe {
+        prop.GetPropList(sys::MAPI_UNICODE, tags.as_mut_ptr())?;
+        let tags = tags.as_mut().ok_or_else(|| Error::from(E_UNEXPECTED))?;
+        Ok(
+            slice::from_raw_parts(tags.aulPropTag.as_ptr(), tags.cValues as usize)
+                .iter()
+                .copied()
+                .map(PropTag)
+                .collect(),
+        )
+    }
+}
+
+/// One entry in the result of [`diff_messages`]. The values are rendered with
+/// [`render_prop`].
+#[derive(Clone)]
+pub enum PropDiff {
+    /// The property is only present on the second message.
+    Added { tag: PropTag, value: String },
+
+    /// The property is only present on the first message.
+    Removed { tag: PropTag, value: String },
+
+    /// The property is present on both messages with different values.
+    Changed {
+        tag: PropTag,
+        old_value: String,
+        new_value: String,
+    },
+}
+
+impl PropDiff {
+    /// Get the [`PropTag`] of the property which differs.
+    pub fn tag(&self) -> PropTag {
+        match self {
+            Self::Added { tag, .. } | Self::Removed { tag, .. } | Self::Changed { tag, .. } => *tag,
+        }
+    }
+}
+
+impl fmt::Display for PropDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { tag, value } => write!(f, "+ 0x{:08X}: {value}", u32::from(*tag)),
+            Self::Removed { tag, value } => write!(f, "- 0x{:08X}: {value}", u32::from(*tag)),
+            Self::Changed {
+                tag,
+                old_value,
+                new_value,
+            } => write!(f, "~ 0x{:08X}: {old_value} -> {new_value}", u32::from(*tag)),
+        }
+    }
+}
+
+/// Compare every property on two objects (typically messages), and list the differences sorted by
+/// tag. Properties in `ignore_tags` are skipped, regardless of their `PROP_TYPE`, which is useful
+/// for volatile properties like [`sys::PR_ENTRYID`] or [`sys::PR_LAST_MODIFICATION_TIME`].
+///
+/// Values which the provider will not return inline (e.g. large bodies) show up as a
+/// [`sys::PT_ERROR`] value, so they are only compared by their error code.
+pub fn diff_messages(
+    a: &sys::IMAPIProp,
+    b: &sys::IMAPIProp,
+    ignore_tags: &[PropTag],
+) -> Result<Vec<PropDiff>> {
+    diff_messages_with(a, b, ignore_tags, &RenderOptions::default())
+}
+
+/// Same as [`diff_messages`], but render the values with `options`, e.g. with
+/// [`RenderOptions::redact`] set so the differences can be shared. Redacted values are still
+/// compared by their redacted text, so a [`crate::HashRedactor`] does not hide changes.
+pub fn diff_messages_with(
+    a: &sys::IMAPIProp,
+    b: &sys::IMAPIProp,
+    ignore_tags: &[PropTag],
+    options: &RenderOptions,
+) -> Result<Vec<PropDiff>> {
+    let ignored = |tag: u32| {
+        let prop_id = PropTag(tag).prop_id();
+        ignore_tags.iter().any(|ignore| ignore.prop_id() == prop_id)
+    };
+    let a = get_all_props(a, options)?;
+    let b = get_all_props(b, options)?;
+
+    let mut tags: Vec<_> = a
+        .iter()
+        .chain(b.iter())
+        .map(|(tag, _)| *tag)
+        .filter(|tag| !ignored(*tag))
+        .collect();
+    tags.sort();
+    tags.dedup();
+
+    let find = |props: &[(u32, String)], tag: u32| {
+        props
+            .iter()
+            .find(|(prop_tag, _)| *prop_tag == tag)
+            .map(|(_, value)| value.clone())
+    };
+    Ok(tags
+        .into_iter()
+        .filter_map(|tag| match (find(&a, tag), find(&b, tag)) {
+            (Some(value), None) => Some(PropDiff::Removed {
+                tag: PropTag(tag),
+                value,
+            }),
+            (None, Some(value)) => Some(PropDiff::Added {
+                tag: PropTag(tag),
+                value,
+            }),
+            (Some(old_value), Some(new_value)) if old_value != new_value => {
+                Some(PropDiff::Changed {
+                    tag: PropTag(tag),
+                    old_value,
+                    new_value,
+                })
+            }
+            _ => None,
+        })
+        .collect())
+}
+
+/// Call [`sys::IMAPIProp::GetProps`] with no tag array to get every property, and render them.
+fn get_all_props(prop: &sys::IMAPIProp, options: &RenderOptions) -> Result<Vec<(u32, String)>> {
+    let mut count = 0;
+    let mut values: MAPIOutParam<sys::SPropValue> = Default::default();
+    unsafe {
+        prop.GetProps(
+            ptr::null_mut(),
+            sys::MAPI_UNICODE,
+            &mut count,
+            values.as_mut_ptr(),
+        )?;
+        let values = values
+            .as_mut_slice(count as usize)
+            .ok_or_else(|| Error::from(E_UNEXPECTED))?;
+        Ok(values
+            .iter()
+            .map(|value| {
+                let value = PropValue::from(value);
+                (u32::from(value.tag), render_prop(&value, options))
+            })
+            .collect())
+    }
+}
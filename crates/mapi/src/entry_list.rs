@@ -0,0 +1,135 @@
+//! Define [`EntryList`] and [`EntryListBuffer`].
+
+use crate::{sys, EntryIdBuf, MAPIAllocError, MAPIBuffer, MAPIUninit};
+use core::ptr;
+
+/// Owned set of entry IDs, which can be packed into a [`sys::SBinaryArray`] (also known as an
+/// `ENTRYLIST`) with [`EntryList::build`] to pass to APIs like
+/// [`sys::IMAPIFolder::DeleteMessages`], [`sys::IMAPIFolder::CopyMessages`], or
+/// [`sys::IMAPIFolder::SetReadFlags`].
+///
+/// It can be collected from an iterator of `&[u8]`, or converted from a slice of entry IDs such as
+/// `&[&[u8]]`, `&[Vec<u8>]`, or `&[EntryIdBuf]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EntryList {
+    entry_ids: Vec<Vec<u8>>,
+}
+
+impl EntryList {
+    /// Start with an empty list.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Append an entry ID.
+    pub fn push(&mut self, entry_id: &[u8]) {
+        self.entry_ids.push(entry_id.to_vec());
+    }
+
+    /// Number of entry IDs in the list.
+    pub fn len(&self) -> usize {
+        self.entry_ids.len()
+    }
+
+    /// Test if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entry_ids.is_empty()
+    }
+
+    /// Iterate over the entry IDs in the list.
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        self.entry_ids.iter().map(Vec::as_slice)
+    }
+
+    /// Allocate the [`sys::SBinaryArray`] with [`sys::MAPIAllocateBuffer`], and chain the array of
+    /// [`sys::SBinary`] and each entry ID to it with [`sys::MAPIAllocateMore`], so the whole list
+    /// is freed with a single call to [`sys::MAPIFreeBuffer`] when the [`EntryListBuffer`] is
+    /// dropped.
+    pub fn build(&self) -> Result<EntryListBuffer, MAPIAllocError> {
+        let mut root = MAPIUninit::<sys::SBinaryArray>::new(1)?;
+        let mut lpbin = ptr::null_mut();
+        if !self.entry_ids.is_empty() {
+            let mut entries = root.chain::<sys::SBinary>(self.entry_ids.len())?;
+            lpbin = entries.uninit()?.as_mut_ptr();
+            for (entry_id, mut entry) in self.entry_ids.iter().zip(entries.iter()) {
+                let lpb = if entry_id.is_empty() {
+                    ptr::null_mut()
+                } else {
+                    let mut bytes = root.chain::<u8>(entry_id.len())?;
+                    let lpb = bytes.uninit()?.as_mut_ptr();
+                    unsafe {
+                        ptr::copy_nonoverlapping(entry_id.as_ptr(), lpb, entry_id.len());
+                    }
+                    lpb
+                };
+                entry.uninit()?.write(sys::SBinary {
+                    cb: entry_id.len() as u32,
+                    lpb,
+                });
+            }
+        }
+
+        let entry_list = root.uninit()?;
+        entry_list.write(sys::SBinaryArray {
+            cValues: self.entry_ids.len() as u32,
+            lpbin,
+        });
+        let entry_list = entry_list.as_mut_ptr();
+        Ok(EntryListBuffer {
+            entry_list,
+            _buffer: unsafe { root.assume_init() },
+        })
+    }
+}
+
+impl<'a> FromIterator<&'a [u8]> for EntryList {
+    fn from_iter<T: IntoIterator<Item = &'a [u8]>>(iter: T) -> Self {
+        Self {
+            entry_ids: iter.into_iter().map(<[u8]>::to_vec).collect(),
+        }
+    }
+}
+
+impl<'a> Extend<&'a [u8]> for EntryList {
+    fn extend<T: IntoIterator<Item = &'a [u8]>>(&mut self, iter: T) {
+        self.entry_ids.extend(iter.into_iter().map(<[u8]>::to_vec));
+    }
+}
+
+impl From<&[&[u8]]> for EntryList {
+    fn from(entry_ids: &[&[u8]]) -> Self {
+        entry_ids.iter().copied().collect()
+    }
+}
+
+impl From<&[Vec<u8>]> for EntryList {
+    fn from(entry_ids: &[Vec<u8>]) -> Self {
+        entry_ids.iter().map(Vec::as_slice).collect()
+    }
+}
+
+impl From<&[EntryIdBuf]> for EntryList {
+    fn from(entry_ids: &[EntryIdBuf]) -> Self {
+        entry_ids.iter().map(EntryIdBuf::as_bytes).collect()
+    }
+}
+
+impl From<Vec<Vec<u8>>> for EntryList {
+    fn from(entry_ids: Vec<Vec<u8>>) -> Self {
+        Self { entry_ids }
+    }
+}
+
+/// A [`sys::SBinaryArray`] built by [`EntryList::build`] in a chain of MAPI allocations.
+pub struct EntryListBuffer {
+    entry_list: *mut sys::SBinaryArray,
+    _buffer: MAPIBuffer<'static, sys::SBinaryArray>,
+}
+
+impl EntryListBuffer {
+    /// Get a pointer to the [`sys::SBinaryArray`], which stays valid until the
+    /// [`EntryListBuffer`] is dropped.
+    pub fn as_mut_ptr(&mut self) -> *mut sys::SBinaryArray {
+        self.entry_list
+    }
+}
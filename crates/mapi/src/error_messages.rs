@@ -0,0 +1,142 @@
+//! Define [`ErrorMessages`], [`DefaultErrorMessages`], [`set_error_messages`], and
+//! [`error_messages`].
+
+use crate::MapiError;
+use std::sync::{Arc, RwLock};
+
+static ERROR_MESSAGES: RwLock<Option<Arc<dyn ErrorMessages>>> = RwLock::new(None);
+
+/// Catalog of the messages returned by [`MapiError::user_message`], so an application can reword
+/// or translate them.
+///
+/// `locale` is whatever the application passes to [`MapiError::user_message`], typically a BCP 47
+/// tag such as `en-US`. Return `None` to fall back to [`DefaultErrorMessages`].
+pub trait ErrorMessages: Send + Sync {
+    /// Return the message for `error` in `locale`, or `None` to use the default.
+    fn user_message(&self, error: MapiError, locale: &str) -> Option<String>;
+}
+
+/// The default [`ErrorMessages`], with actionable English text for the errors an end user is most
+/// likely to run into. It ignores the `locale`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DefaultErrorMessages;
+
+impl DefaultErrorMessages {
+    fn message(error: MapiError) -> Option<&'static str> {
+        Some(match error {
+            MapiError::LogonFailed => {
+                "Could not log on to the Outlook profile. Check that the profile exists, and enter \
+                 the password if the profile requires one."
+            }
+            MapiError::PasswordChangeRequired => {
+                "The password for this account must be changed before you can log on."
+            }
+            MapiError::PasswordExpired => {
+                "The password for this account has expired. Change it and try again."
+            }
+            MapiError::Unconfigured => {
+                "The Outlook profile is not completely set up. Open Outlook to finish setting it \
+                 up."
+            }
+            MapiError::NotInitialized => {
+                "MAPI is not available. Make sure Outlook is installed and set as the default mail \
+                 program."
+            }
+            MapiError::EndOfSession => "The Outlook session has ended. Log on again.",
+            MapiError::SessionLimit => {
+                "Too many Outlook sessions are open. Close one and try again."
+            }
+            MapiError::NoAccess => "You do not have permission to access this item.",
+            MapiError::NetworkError => {
+                "Could not connect to the server. Check your network connection and try again."
+            }
+            MapiError::Busy => "The server is busy. Try again later.",
+            MapiError::Timeout => "The server took too long to respond. Try again later.",
+            MapiError::NotEnoughMemory | MapiError::NotEnoughResources => {
+                "There is not enough memory to finish. Close some programs and try again."
+            }
+            MapiError::NotEnoughDisk => "There is not enough disk space to finish.",
+            MapiError::DiskError => {
+                "The data file could not be read or written. It may be in use by another process, \
+                 such as Outlook."
+            }
+            MapiError::CorruptStore => {
+                "The data file is damaged. Repair it with the Inbox Repair Tool (scanpst.exe)."
+            }
+            MapiError::CorruptData => "Some of the data is damaged and could not be read.",
+            MapiError::NotFound | MapiError::ObjectDeleted | MapiError::UnknownEntryId => {
+                "The item could not be found. It may have been moved or deleted."
+            }
+            MapiError::ObjectChanged => {
+                "The item was changed by someone else. Open it again to see the latest version."
+            }
+            MapiError::Collision => "An item with the same name already exists.",
+            MapiError::HasFolders | MapiError::HasMessages => "The folder is not empty.",
+            MapiError::FolderCycle => "A folder cannot be moved into one of its own subfolders.",
+            MapiError::NoRecipients => "The message has no recipients.",
+            MapiError::AmbiguousRecipient => {
+                "A recipient name matches more than one entry in the address book."
+            }
+            MapiError::Submitted => "The message has already been sent.",
+            MapiError::UserCancel | MapiError::Cancel => "The operation was cancelled.",
+            _ => return None,
+        })
+    }
+}
+
+impl ErrorMessages for DefaultErrorMessages {
+    fn user_message(&self, error: MapiError, _locale: &str) -> Option<String> {
+        Self::message(error).map(String::from)
+    }
+}
+
+/// Replace the process-wide [`ErrorMessages`] used by [`MapiError::user_message`].
+pub fn set_error_messages(messages: impl ErrorMessages + 'static) {
+    *ERROR_MESSAGES
+        .write()
+        .unwrap_or_else(|err| err.into_inner()) = Some(Arc::new(messages));
+}
+
+/// Get the process-wide [`ErrorMessages`], which is [`DefaultErrorMessages`] unless it was
+/// replaced with [`set_error_messages`].
+pub fn error_messages() -> Arc<dyn ErrorMessages> {
+    ERROR_MESSAGES
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+        .unwrap_or_else(|| Arc::new(DefaultErrorMessages))
+}
+
+impl MapiError {
+    /// Get a human-readable message for this error, suitable for showing to an end user, from the
+    /// process-wide [`error_messages`] catalog. Errors which are not in the catalog fall back to
+    /// [`DefaultErrorMessages`], and then to the [`core::fmt::Display`] text.
+    pub fn user_message(&self, locale: &str) -> String {
+        error_messages()
+            .user_message(*self, locale)
+            .or_else(|| DefaultErrorMessages.user_message(*self, locale))
+            .unwrap_or_else(|| self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows::Win32::Foundation::E_FAIL;
+
+    #[test]
+    fn default_error_messages() {
+        assert_eq!(
+            DefaultErrorMessages.user_message(MapiError::Busy, "en-US"),
+            Some(String::from("The server is busy. Try again later."))
+        );
+        assert_eq!(
+            DefaultErrorMessages.user_message(MapiError::ObjectDeleted, "fr-FR"),
+            DefaultErrorMessages.user_message(MapiError::NotFound, "fr-FR"),
+        );
+        assert_eq!(
+            DefaultErrorMessages.user_message(MapiError::Other(E_FAIL), "en-US"),
+            None
+        );
+    }
+}
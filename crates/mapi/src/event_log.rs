@@ -0,0 +1,241 @@
+//! Define [`EventLog`], [`LogEvent`], and [`LogEventKind`].
+
+use crate::{is_outlook_mapi_installed, Limits, Logon, NoUi};
+use core::fmt;
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+    thread::{self, ThreadId},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use windows_core::*;
+
+struct Ring {
+    capacity: usize,
+    events: VecDeque<LogEvent>,
+}
+
+static EVENT_LOG: Mutex<Option<Ring>> = Mutex::new(None);
+
+/// What kind of event a [`LogEvent`] records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LogEventKind {
+    /// A wrapper in this crate made a MAPI call which succeeded.
+    Call,
+
+    /// A MAPI call failed.
+    Error,
+
+    /// An advise sink received a notification.
+    Notification,
+}
+
+impl fmt::Display for LogEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Call => "call",
+            Self::Error => "error",
+            Self::Notification => "notification",
+        })
+    }
+}
+
+/// One entry in the [`EventLog`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogEvent {
+    /// When the event was recorded.
+    pub timestamp: SystemTime,
+
+    /// Thread which recorded the event.
+    pub thread: ThreadId,
+
+    /// What kind of event this is.
+    pub kind: LogEventKind,
+
+    /// Name of the MAPI function or method, e.g. `"MAPILogonEx"`.
+    pub name: &'static str,
+
+    /// Details of the event, e.g. the error code.
+    pub detail: String,
+}
+
+impl fmt::Display for LogEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let timestamp = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        write!(
+            f,
+            "{}.{:03} {:?} {} {}",
+            timestamp.as_secs(),
+            timestamp.subsec_millis(),
+            self.thread,
+            self.kind,
+            self.name
+        )?;
+        if !self.detail.is_empty() {
+            write!(f, ": {}", self.detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// Opt-in, process-wide ring buffer of the last few MAPI calls, errors, and notifications made
+/// through this crate, to attach to bug reports with [`Logon::export_support_bundle`].
+///
+/// Nothing is recorded until [`EventLog::enable`] is called. Once the buffer holds `capacity`
+/// events, each new event replaces the oldest one. [`Logon::new`], [`Logon::open_store`], and
+/// every advise sink record their events here, and callers can add their own with
+/// [`EventLog::record_result`], e.g. around calls to methods which do not have a wrapper yet.
+///
+/// ```no_run
+/// # use outlook_mapi::*;
+/// EventLog::enable(1000);
+/// ```
+pub struct EventLog;
+
+impl EventLog {
+    /// Start recording up to `capacity` events. If the log is already enabled, it keeps the most
+    /// recent events which fit in the new `capacity`.
+    pub fn enable(capacity: usize) {
+        let mut log = EVENT_LOG.lock().unwrap_or_else(|err| err.into_inner());
+        let mut events = log.take().map(|ring| ring.events).unwrap_or_default();
+        while events.len() > capacity {
+            events.pop_front();
+        }
+        *log = Some(Ring { capacity, events });
+    }
+
+    /// Stop recording and drop all of the events.
+    pub fn disable() {
+        *EVENT_LOG.lock().unwrap_or_else(|err| err.into_inner()) = None;
+    }
+
+    /// Test if the log is recording events.
+    pub fn is_enabled() -> bool {
+        EVENT_LOG
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .is_some()
+    }
+
+    /// Add an event to the log, if it is enabled.
+    pub fn record(kind: LogEventKind, name: &'static str, detail: impl Into<String>) {
+        let mut log = EVENT_LOG.lock().unwrap_or_else(|err| err.into_inner());
+        let Some(ring) = log.as_mut() else {
+            return;
+        };
+        if ring.capacity == 0 {
+            return;
+        }
+        if ring.events.len() == ring.capacity {
+            ring.events.pop_front();
+        }
+        ring.events.push_back(LogEvent {
+            timestamp: SystemTime::now(),
+            thread: thread::current().id(),
+            kind,
+            name,
+            detail: detail.into(),
+        });
+    }
+
+    /// Record the outcome of a MAPI call as a [`LogEventKind::Call`] or [`LogEventKind::Error`],
+    /// and pass the result through.
+    pub fn record_result<T>(name: &'static str, result: Result<T>) -> Result<T> {
+        match &result {
+            Ok(_) => Self::record(LogEventKind::Call, name, ""),
+            Err(err) => Self::record(LogEventKind::Error, name, err.to_string()),
+        }
+        result
+    }
+
+    /// Copy the events which are currently in the log, oldest first.
+    pub fn snapshot() -> Vec<LogEvent> {
+        EVENT_LOG
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .as_ref()
+            .map(|ring| ring.events.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Drop all of the events, but keep recording.
+    pub fn clear() {
+        if let Some(ring) = EVENT_LOG
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .as_mut()
+        {
+            ring.events.clear();
+        }
+    }
+}
+
+impl Logon {
+    /// Write a plain text support bundle to `path`, with the version of this crate, the results of
+    /// some environment checks, the settings of this session, and the [`EventLog::snapshot`].
+    pub fn export_support_bundle(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.write_support_bundle(File::create(path)?)?;
+        Ok(())
+    }
+
+    fn write_support_bundle(&self, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "outlook-mapi {}", env!("CARGO_PKG_VERSION"))?;
+        writeln!(out)?;
+        writeln!(out, "[environment]")?;
+        writeln!(out, "arch: {}", std::env::consts::ARCH)?;
+        writeln!(out, "pointer width: {}", 8 * core::mem::size_of::<usize>())?;
+        writeln!(out, "olmapi32 loaded: {}", is_outlook_mapi_installed())?;
+        writeln!(out, "no-ui mode: {}", NoUi::is_enabled())?;
+        writeln!(out, "limits: {:?}", Limits::current())?;
+        writeln!(out)?;
+        writeln!(out, "[session]")?;
+        let initialized = self.initialized();
+        writeln!(out, "MAPIInitialize flags: 0x{:08X}", initialized.flags())?;
+        writeln!(out, "MAPIInitialize thread: {:?}", initialized.thread())?;
+        writeln!(out, "current thread: {:?}", thread::current().id())?;
+        writeln!(out)?;
+        writeln!(out, "[events]")?;
+        if !EventLog::is_enabled() {
+            writeln!(out, "(event log is not enabled)")?;
+        }
+        for event in EventLog::snapshot() {
+            writeln!(out, "{event}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_buffer() {
+        EventLog::enable(2);
+        EventLog::clear();
+        EventLog::record(LogEventKind::Call, "first", "");
+        EventLog::record(LogEventKind::Notification, "second", "0x4");
+        let _ = EventLog::record_result::<()>(
+            "third",
+            Err(Error::from(crate::sys::MAPI_E_CALL_FAILED)),
+        );
+
+        let events = EventLog::snapshot();
+        assert_eq!(
+            events.iter().map(|event| event.name).collect::<Vec<_>>(),
+            ["second", "third"]
+        );
+        assert_eq!(events[1].kind, LogEventKind::Error);
+        assert!(events[0].to_string().ends_with("notification second: 0x4"));
+
+        EventLog::disable();
+        EventLog::record(LogEventKind::Call, "ignored", "");
+        assert!(EventLog::snapshot().is_empty());
+    }
+}
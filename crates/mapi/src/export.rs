@@ -0,0 +1,184 @@
+//! Define [`rows_to_writer`], [`rows_to_writer_with`], [`ExportFormat`], and [`ExportSummary`].
+
+use crate::{
+    is_object_deleted, render_prop, DeletedItemPolicy, PropValue, PropValueData, RenderOptions, Row,
+};
+use std::io::Write;
+use windows_core::*;
+
+/// Output format for [`rows_to_writer`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma separated values, with a header row of property names.
+    #[default]
+    Csv,
+
+    /// One JSON object per line, keyed by property name.
+    JsonLines,
+}
+
+/// Write every row from `rows` (e.g. a [`crate::TableRows`]) to `writer`, and return the number of
+/// rows written.
+///
+/// Columns are named with [`crate::format_prop_tag`]. The CSV header comes from the columns of
+/// the first row, since every row in a table has the same columns. Missing values (returned by the
+/// table as [`crate::sys::PT_ERROR`]) are written as empty fields in CSV, and as `null` in JSON.
+pub fn rows_to_writer<I, W>(rows: I, format: ExportFormat, writer: W) -> Result<usize>
+where
+    I: IntoIterator<Item = Result<Row>>,
+    W: Write,
+{
+    rows_to_writer_with(
+        rows,
+        format,
+        &Default::default(),
+        DeletedItemPolicy::Abort,
+        writer,
+    )
+    .map(|summary| summary.rows)
+}
+
+/// Counts returned by [`rows_to_writer_with`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExportSummary {
+    /// Number of rows written.
+    pub rows: usize,
+
+    /// Number of rows which were skipped because [`is_object_deleted`] accepted the error.
+    pub skipped: usize,
+}
+
+/// Same as [`rows_to_writer`], but name the columns and render the values with `options`, e.g.
+/// [`RenderOptions::for_logs`] to keep the output short.
+///
+/// Errors from `rows` which [`is_object_deleted`] accepts are counted in
+/// [`ExportSummary::skipped`] instead of ending the export, unless `policy` is
+/// [`DeletedItemPolicy::Abort`]. A row can't be read again from the iterator, so
+/// [`DeletedItemPolicy::Retry`] behaves like [`DeletedItemPolicy::Skip`] here.
+pub fn rows_to_writer_with<I, W>(
+    rows: I,
+    format: ExportFormat,
+    options: &RenderOptions,
+    policy: DeletedItemPolicy,
+    mut writer: W,
+) -> Result<ExportSummary>
+where
+    I: IntoIterator<Item = Result<Row>>,
+    W: Write,
+{
+    let mut count = 0;
+    let mut skipped = 0;
+    for row in rows {
+        let row = match row {
+            Err(err) if is_object_deleted(&err) && policy != DeletedItemPolicy::Abort => {
+                skipped += 1;
+                continue;
+            }
+            row => row?,
+        };
+        let values: Vec<_> = row.iter().collect();
+        match format {
+            ExportFormat::Csv => {
+                if count == 0 {
+                    let header: Vec<_> = values
+                        .iter()
+                        .map(|value| csv_field(&options.format_tag(value.tag)))
+                        .collect();
+                    writeln!(writer, "{}", header.join(","))?;
+                }
+                let fields: Vec<_> = values
+                    .iter()
+                    .map(|value| match value.value {
+                        PropValueData::Error(_) | PropValueData::Null => String::new(),
+                        _ => csv_field(&export_string(value, options)),
+                    })
+                    .collect();
+                writeln!(writer, "{}", fields.join(","))?;
+            }
+            ExportFormat::JsonLines => {
+                let fields: Vec<_> = values
+                    .iter()
+                    .map(|value| {
+                        format!(
+                            "{}:{}",
+                            json_string(&options.format_tag(value.tag)),
+                            json_value(value, options)
+                        )
+                    })
+                    .collect();
+                writeln!(writer, "{{{}}}", fields.join(","))?;
+            }
+        }
+        count += 1;
+    }
+    writer.flush()?;
+    Ok(ExportSummary {
+        rows: count,
+        skipped,
+    })
+}
+
+/// Render a value without the quotes that [`render_prop`] puts around strings.
+fn export_string(value: &PropValue, options: &RenderOptions) -> String {
+    let tag = Some(value.tag);
+    match &value.value {
+        PropValueData::Unicode(value) => {
+            options.redact_string(tag, unsafe { value.to_string() }.unwrap_or_default())
+        }
+        PropValueData::AnsiString(value) => {
+            options.redact_string(tag, unsafe { value.to_string() }.unwrap_or_default())
+        }
+        _ => render_prop(value, options),
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn json_value(value: &PropValue, options: &RenderOptions) -> String {
+    match &value.value {
+        PropValueData::Error(_) | PropValueData::Null => "null".to_string(),
+        PropValueData::Short(_)
+        | PropValueData::Long(_)
+        | PropValueData::LargeInteger(_)
+        | PropValueData::Boolean(_) => render_prop(value, options),
+        PropValueData::Float(number) if number.is_finite() => number.to_string(),
+        PropValueData::Double(number) if number.is_finite() => number.to_string(),
+        _ => json_string(&export_string(value, options)),
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            ch if u32::from(ch) < 0x20 => result.push_str(&format!("\\u{:04x}", u32::from(ch))),
+            ch => result.push(ch),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_fields() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+        assert_eq!(json_string("a\"b\\\n\u{1}"), "\"a\\\"b\\\\\\n\\u0001\"");
+    }
+}
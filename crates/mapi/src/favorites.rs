@@ -0,0 +1,279 @@
+//! Define [`FavoriteLink`] and helpers for the shortcuts in the Common Views folder.
+//!
+//! Outlook stores each entry in the navigation pane (favorite folders, calendar and contact
+//! groups, etc.) as an associated message with [`WUNDERBAR_LINK_MESSAGE_CLASS`] in the folder
+//! identified by [`sys::PR_COMMON_VIEWS_ENTRYID`] on the store. The layout of those messages is
+//! documented in [MS-OXOCFG](https://learn.microsoft.com/en-us/openspecs/exchange_server_protocols/ms-oxocfg).
+
+use crate::{
+    mapi_prop::{open_store_entry, set_props, SetPropValue},
+    sys, MapiProp, OpenFlags, PropTag, PropValue, PropValueData, RowSet, SizedSPropTagArray,
+};
+use core::ptr;
+use windows_core::*;
+
+/// Message class of the navigation shortcut messages.
+pub const WUNDERBAR_LINK_MESSAGE_CLASS: &str = "IPM.Microsoft.WunderBar.Link";
+
+/// `PidTagWlinkType` is missing from the generated bindings.
+pub const PR_WLINK_TYPE: u32 = 0x6849_0003;
+
+/// `PidTagWlinkFlags` is missing from the generated bindings.
+pub const PR_WLINK_FLAGS: u32 = 0x684A_0003;
+
+/// `PidTagWlinkOrdinal` is missing from the generated bindings.
+pub const PR_WLINK_ORDINAL: u32 = 0x684B_0102;
+
+/// `PidTagWlinkEntryId` is missing from the generated bindings.
+pub const PR_WLINK_ENTRYID: u32 = 0x684C_0102;
+
+/// `PidTagWlinkRecordKey` is missing from the generated bindings.
+pub const PR_WLINK_RECKEY: u32 = 0x684D_0102;
+
+/// `PidTagWlinkStoreEntryId` is missing from the generated bindings.
+pub const PR_WLINK_STORE_ENTRYID: u32 = 0x684E_0102;
+
+/// `PidTagWlinkFolderType` is missing from the generated bindings.
+pub const PR_WLINK_FOLDER_TYPE: u32 = 0x684F_0102;
+
+/// `PidTagWlinkGroupClsid` is missing from the generated bindings.
+pub const PR_WLINK_GROUP_CLSID: u32 = 0x6850_0102;
+
+/// `PidTagWlinkGroupName` is missing from the generated bindings.
+pub const PR_WLINK_GROUP_NAME_W: u32 = 0x6851_001F;
+
+/// `PidTagWlinkSection` is missing from the generated bindings.
+pub const PR_WLINK_SECTION: u32 = 0x6852_0003;
+
+/// `PidTagWlinkCalendarColor` is missing from the generated bindings.
+pub const PR_WLINK_CALENDAR_COLOR: u32 = 0x6853_0003;
+
+/// `PidTagWlinkAddressBookEID` is missing from the generated bindings.
+pub const PR_WLINK_AB_ENTRYID: u32 = 0x6854_0102;
+
+/// Properties of a single navigation shortcut. Any property which is `None` is missing from the
+/// message, and will not be written by [`add_favorite`] or [`update_favorite`].
+#[derive(Clone, Debug, Default)]
+pub struct FavoriteLink {
+    /// [`sys::PR_ENTRYID`] of the shortcut message itself, which is needed to update or remove it.
+    /// This is ignored by [`add_favorite`].
+    pub link_entry_id: Option<Vec<u8>>,
+
+    /// [`PR_WLINK_TYPE`]
+    pub link_type: Option<u32>,
+
+    /// [`PR_WLINK_FLAGS`]
+    pub flags: Option<u32>,
+
+    /// [`PR_WLINK_ORDINAL`], which sorts the shortcuts within a [`FavoriteLink::section`].
+    pub ordinal: Option<Vec<u8>>,
+
+    /// [`PR_WLINK_ENTRYID`] of the target folder.
+    pub entry_id: Option<Vec<u8>>,
+
+    /// [`PR_WLINK_RECKEY`] of the target folder.
+    pub record_key: Option<Vec<u8>>,
+
+    /// [`PR_WLINK_STORE_ENTRYID`] of the store which contains the target folder.
+    pub store_entry_id: Option<Vec<u8>>,
+
+    /// [`PR_WLINK_FOLDER_TYPE`]
+    pub folder_type: Option<Vec<u8>>,
+
+    /// [`PR_WLINK_GROUP_CLSID`] of the group which contains the shortcut.
+    pub group_clsid: Option<Vec<u8>>,
+
+    /// [`PR_WLINK_GROUP_NAME_W`] of the group which contains the shortcut.
+    pub group_name: Option<String>,
+
+    /// [`PR_WLINK_SECTION`]
+    pub section: Option<u32>,
+
+    /// [`PR_WLINK_CALENDAR_COLOR`]
+    pub calendar_color: Option<i32>,
+
+    /// [`PR_WLINK_AB_ENTRYID`]
+    pub address_book_entry_id: Option<Vec<u8>>,
+}
+
+impl FavoriteLink {
+    fn set_value(&mut self, value: PropValue) {
+        let tag = u32::from(value.tag);
+        match value.value {
+            PropValueData::Binary(value) => {
+                let value = Some(value.to_vec());
+                match tag {
+                    sys::PR_ENTRYID => self.link_entry_id = value,
+                    PR_WLINK_ORDINAL => self.ordinal = value,
+                    PR_WLINK_ENTRYID => self.entry_id = value,
+                    PR_WLINK_RECKEY => self.record_key = value,
+                    PR_WLINK_STORE_ENTRYID => self.store_entry_id = value,
+                    PR_WLINK_FOLDER_TYPE => self.folder_type = value,
+                    PR_WLINK_GROUP_CLSID => self.group_clsid = value,
+                    PR_WLINK_AB_ENTRYID => self.address_book_entry_id = value,
+                    _ => {}
+                }
+            }
+            PropValueData::Long(value) => match tag {
+                PR_WLINK_TYPE => self.link_type = Some(value as u32),
+                PR_WLINK_FLAGS => self.flags = Some(value as u32),
+                PR_WLINK_SECTION => self.section = Some(value as u32),
+                PR_WLINK_CALENDAR_COLOR => self.calendar_color = Some(value),
+                _ => {}
+            },
+            PropValueData::Unicode(value) if tag == PR_WLINK_GROUP_NAME_W => {
+                self.group_name = unsafe { value.to_string() }.ok();
+            }
+            _ => {}
+        }
+    }
+
+    fn write(&self, prop: &sys::IMAPIProp) -> Result<()> {
+        let mut values = vec![(
+            sys::PR_MESSAGE_CLASS_W,
+            SetPropValue::Unicode(WUNDERBAR_LINK_MESSAGE_CLASS),
+        )];
+        let longs = [
+            (PR_WLINK_TYPE, self.link_type),
+            (PR_WLINK_FLAGS, self.flags),
+            (PR_WLINK_SECTION, self.section),
+            (
+                PR_WLINK_CALENDAR_COLOR,
+                self.calendar_color.map(|value| value as u32),
+            ),
+        ];
+        values.extend(
+            longs.into_iter().filter_map(|(tag, value)| {
+                value.map(|value| (tag, SetPropValue::Long(value as i32)))
+            }),
+        );
+        let binaries = [
+            (PR_WLINK_ORDINAL, self.ordinal.as_ref()),
+            (PR_WLINK_ENTRYID, self.entry_id.as_ref()),
+            (PR_WLINK_RECKEY, self.record_key.as_ref()),
+            (PR_WLINK_STORE_ENTRYID, self.store_entry_id.as_ref()),
+            (PR_WLINK_FOLDER_TYPE, self.folder_type.as_ref()),
+            (PR_WLINK_GROUP_CLSID, self.group_clsid.as_ref()),
+            (PR_WLINK_AB_ENTRYID, self.address_book_entry_id.as_ref()),
+        ];
+        values.extend(
+            binaries
+                .into_iter()
+                .filter_map(|(tag, value)| value.map(|value| (tag, SetPropValue::Binary(value)))),
+        );
+        if let Some(group_name) = self.group_name.as_ref() {
+            values.push((PR_WLINK_GROUP_NAME_W, SetPropValue::Unicode(group_name)));
+        }
+
+        set_props(prop, &values)
+    }
+}
+
+/// Open the folder identified by [`sys::PR_COMMON_VIEWS_ENTRYID`] on the store.
+pub fn open_common_views(store: &sys::IMsgStore) -> Result<sys::IMAPIFolder> {
+    let entry_id = store.get_binary(PropTag(sys::PR_COMMON_VIEWS_ENTRYID))?;
+    open_store_entry(store, &entry_id, OpenFlags::default())
+}
+
+/// Read all of the [`WUNDERBAR_LINK_MESSAGE_CLASS`] messages in the Common Views folder.
+pub fn read_favorites(store: &sys::IMsgStore) -> Result<Vec<FavoriteLink>> {
+    SizedSPropTagArray! { PropTagArray[14] }
+    let mut prop_tag_array = PropTagArray {
+        aulPropTag: [
+            sys::PR_ENTRYID,
+            sys::PR_MESSAGE_CLASS_W,
+            PR_WLINK_TYPE,
+            PR_WLINK_FLAGS,
+            PR_WLINK_ORDINAL,
+            PR_WLINK_ENTRYID,
+            PR_WLINK_RECKEY,
+            PR_WLINK_STORE_ENTRYID,
+            PR_WLINK_FOLDER_TYPE,
+            PR_WLINK_GROUP_CLSID,
+            PR_WLINK_GROUP_NAME_W,
+            PR_WLINK_SECTION,
+            PR_WLINK_CALENDAR_COLOR,
+            PR_WLINK_AB_ENTRYID,
+        ],
+        ..Default::default()
+    };
+    let folder = open_common_views(store)?;
+    let mut rows: RowSet = Default::default();
+    unsafe {
+        let table = folder.GetContentsTable(sys::MAPI_ASSOCIATED | sys::MAPI_UNICODE)?;
+        sys::HrQueryAllRows(
+            &table,
+            prop_tag_array.as_mut_ptr(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            0,
+            rows.as_mut_ptr(),
+        )?;
+    }
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let is_link = row.iter().any(|value| match value {
+                PropValue {
+                    tag,
+                    value: PropValueData::Unicode(value),
+                } if u32::from(tag) == sys::PR_MESSAGE_CLASS_W => unsafe { value.to_string() }
+                    .is_ok_and(|value| value.eq_ignore_ascii_case(WUNDERBAR_LINK_MESSAGE_CLASS)),
+                _ => false,
+            });
+            is_link.then(|| {
+                let mut link = FavoriteLink::default();
+                for value in row.iter() {
+                    link.set_value(value);
+                }
+                link
+            })
+        })
+        .collect())
+}
+
+/// Create a new shortcut message in the Common Views folder, and return its
+/// [`sys::PR_ENTRYID`].
+pub fn add_favorite(store: &sys::IMsgStore, link: &FavoriteLink) -> Result<Vec<u8>> {
+    let folder = open_common_views(store)?;
+    let message = unsafe {
+        let mut message = None;
+        folder.CreateMessage(ptr::null_mut(), sys::MAPI_ASSOCIATED, &mut message)?;
+        message.ok_or_else(|| Error::from(sys::MAPI_E_CALL_FAILED))?
+    };
+    link.write(&message)?;
+    unsafe {
+        message.SaveChanges(sys::KEEP_OPEN_READONLY)?;
+    }
+    message.get_binary(PropTag(sys::PR_ENTRYID))
+}
+
+/// Overwrite the properties of an existing shortcut message, identified by
+/// [`FavoriteLink::link_entry_id`].
+pub fn update_favorite(store: &sys::IMsgStore, link: &FavoriteLink) -> Result<()> {
+    let entry_id = link
+        .link_entry_id
+        .as_ref()
+        .ok_or_else(|| Error::from(sys::MAPI_E_INVALID_PARAMETER))?;
+    let message: sys::IMessage = open_store_entry(store, entry_id, OpenFlags::modify())?;
+    link.write(&message)?;
+    unsafe { message.SaveChanges(0) }
+}
+
+/// Delete a set of shortcut messages, identified by their [`FavoriteLink::link_entry_id`].
+pub fn remove_favorites(store: &sys::IMsgStore, link_entry_ids: &[&[u8]]) -> Result<()> {
+    let folder = open_common_views(store)?;
+    let mut entry_ids: Vec<_> = link_entry_ids
+        .iter()
+        .map(|entry_id| sys::SBinary {
+            cb: entry_id.len() as u32,
+            lpb: entry_id.as_ptr() as *mut _,
+        })
+        .collect();
+    let mut entry_list = sys::SBinaryArray {
+        cValues: entry_ids.len() as u32,
+        lpbin: entry_ids.as_mut_ptr(),
+    };
+    unsafe { folder.DeleteMessages(&mut entry_list, 0, None, 0) }
+}
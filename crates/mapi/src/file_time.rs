@@ -0,0 +1,93 @@
+//! Define [`to_file_time`] and [`from_file_time`].
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use windows::Win32::Foundation::FILETIME;
+
+/// Number of 100ns intervals between the [`FILETIME`] epoch (1601-01-01) and the Unix epoch.
+const UNIX_EPOCH_INTERVALS: u64 = 116_444_736_000_000_000;
+
+/// Number of 100ns intervals in a second.
+const INTERVALS_PER_SECOND: u64 = 10_000_000;
+
+/// Convert a [`SystemTime`] to a [`FILETIME`] for a [`crate::sys::PT_SYSTIME`] property. Times
+/// before 1601 are clamped to 0.
+pub fn to_file_time(value: SystemTime) -> FILETIME {
+    let intervals = match value.duration_since(UNIX_EPOCH) {
+        Ok(duration) => UNIX_EPOCH_INTERVALS.saturating_add(to_intervals(duration)),
+        Err(err) => UNIX_EPOCH_INTERVALS.saturating_sub(to_intervals(err.duration())),
+    };
+    FILETIME {
+        dwLowDateTime: intervals as u32,
+        dwHighDateTime: (intervals >> 32) as u32,
+    }
+}
+
+/// Convert a [`FILETIME`] from a [`crate::sys::PT_SYSTIME`] property to a [`SystemTime`]. Returns
+/// `None` if the value is outside the range [`SystemTime`] can represent on this platform, e.g. a
+/// corrupt value with the high bit set, which would not fit in the signed 100ns intervals that
+/// [`SystemTime`] uses on Windows.
+pub fn from_file_time(value: FILETIME) -> Option<SystemTime> {
+    let intervals = (u64::from(value.dwHighDateTime) << 32) | u64::from(value.dwLowDateTime);
+    if intervals >= UNIX_EPOCH_INTERVALS {
+        UNIX_EPOCH.checked_add(from_intervals(intervals - UNIX_EPOCH_INTERVALS))
+    } else {
+        UNIX_EPOCH.checked_sub(from_intervals(UNIX_EPOCH_INTERVALS - intervals))
+    }
+}
+
+fn to_intervals(duration: Duration) -> u64 {
+    duration
+        .as_secs()
+        .saturating_mul(INTERVALS_PER_SECOND)
+        .saturating_add(u64::from(duration.subsec_nanos() / 100))
+}
+
+fn from_intervals(intervals: u64) -> Duration {
+    Duration::new(
+        intervals / INTERVALS_PER_SECOND,
+        ((intervals % INTERVALS_PER_SECOND) * 100) as u32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let unix_epoch = to_file_time(UNIX_EPOCH);
+        assert_eq!(
+            (u64::from(unix_epoch.dwHighDateTime) << 32) | u64::from(unix_epoch.dwLowDateTime),
+            UNIX_EPOCH_INTERVALS
+        );
+
+        let value = UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_700);
+        assert_eq!(from_file_time(to_file_time(value)), Some(value));
+
+        let before = UNIX_EPOCH - Duration::from_secs(86_400);
+        assert_eq!(from_file_time(to_file_time(before)), Some(before));
+    }
+
+    #[test]
+    fn out_of_range() {
+        let zero = FILETIME {
+            dwLowDateTime: 0,
+            dwHighDateTime: 0,
+        };
+        assert_eq!(
+            from_file_time(zero),
+            UNIX_EPOCH.checked_sub(Duration::from_secs(
+                UNIX_EPOCH_INTERVALS / INTERVALS_PER_SECOND
+            ))
+        );
+
+        // This does not fit in a Windows SystemTime, but it may on other platforms.
+        let max = FILETIME {
+            dwLowDateTime: 0xFFFF_FFFF,
+            dwHighDateTime: 0xFFFF_FFFF,
+        };
+        if let Some(max) = from_file_time(max) {
+            assert!(max > UNIX_EPOCH);
+        }
+    }
+}
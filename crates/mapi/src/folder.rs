@@ -0,0 +1,256 @@
+//! Define [`Folder`] and [`TableFlags`].
+
+use crate::{
+    mapi_prop::{open_container_entry, open_store_entry, set_props, SetPropValue},
+    sys, Bookmark, EntryList, MapiTable, OpenFlags, PropTag, PropValue, PropValueData, Restriction,
+    RestrictionValue, Store, TableQuery,
+};
+use core::ptr;
+use std::iter;
+use windows_core::*;
+
+/// Set of flags that can be passed to [`Folder::contents_table_with`] and
+/// [`Folder::hierarchy_table_with`], in addition to [`sys::MAPI_UNICODE`].
+#[derive(Default)]
+pub struct TableFlags {
+    /// Pass [`sys::MAPI_ASSOCIATED`], to list the folder associated information (FAI) messages
+    /// instead of the normal messages in a contents table.
+    pub associated: bool,
+
+    /// Pass [`sys::CONVENIENT_DEPTH`], to list every descendant in a hierarchy table instead of
+    /// only the immediate subfolders.
+    pub convenient_depth: bool,
+
+    /// Pass [`sys::MAPI_DEFERRED_ERRORS`].
+    pub deferred_errors: bool,
+
+    /// Pass [`sys::SHOW_SOFT_DELETES`], to list the items which were soft deleted and are still
+    /// in the retention period, instead of the live items. Only supported by Exchange stores.
+    pub show_soft_deletes: bool,
+}
+
+impl From<TableFlags> for u32 {
+    fn from(value: TableFlags) -> Self {
+        let associated = if value.associated {
+            sys::MAPI_ASSOCIATED
+        } else {
+            0
+        };
+        let convenient_depth = if value.convenient_depth {
+            sys::CONVENIENT_DEPTH
+        } else {
+            0
+        };
+        let deferred_errors = if value.deferred_errors {
+            sys::MAPI_DEFERRED_ERRORS
+        } else {
+            0
+        };
+        let show_soft_deletes = if value.show_soft_deletes {
+            sys::SHOW_SOFT_DELETES
+        } else {
+            0
+        };
+
+        associated | convenient_depth | deferred_errors | show_soft_deletes
+    }
+}
+
+/// Wrapper for a [`sys::IMAPIFolder`], opened with [`Store::open_root_folder`],
+/// [`Store::open_folder`], or [`Folder::open_subfolder`].
+///
+/// Unless the `_with` variants are called with other [`OpenFlags`], folders are opened with
+/// [`sys::MAPI_BEST_ACCESS`], so they are writable if the user has permission to modify them.
+///
+/// Cloning a [`Folder`] is cheap: it calls `AddRef` on the same [`sys::IMAPIFolder`]. Property
+/// changes made through one clone are visible through the others, and a
+/// [`sys::IMAPIProp::SaveChanges`] on any of them commits them all.
+#[derive(Clone)]
+pub struct Folder {
+    folder: sys::IMAPIFolder,
+}
+
+impl Folder {
+    /// Borrow the [`sys::IMAPIFolder`], e.g. to pass it to the functions in this crate which take a
+    /// `&sys::IMAPIFolder`, or to call methods which do not have a wrapper yet.
+    pub fn folder(&self) -> &sys::IMAPIFolder {
+        &self.folder
+    }
+
+    /// Open a subfolder of this folder with [`sys::IMAPIContainer::OpenEntry`], e.g. with the
+    /// [`sys::PR_ENTRYID`] from a row in the [`Folder::hierarchy_table`]. Fails with
+    /// `E_NOINTERFACE` if the entry ID belongs to a message instead of a folder.
+    pub fn open_subfolder(&self, entry_id: &[u8]) -> Result<Folder> {
+        self.open_subfolder_with(entry_id, Default::default())
+    }
+
+    /// Same as [`Folder::open_subfolder`], but with explicit [`OpenFlags`].
+    pub fn open_subfolder_with(&self, entry_id: &[u8], flags: OpenFlags) -> Result<Folder> {
+        open_container_entry::<sys::IMAPIFolder>(&self.folder, entry_id, flags).map(Folder::from)
+    }
+
+    /// Get the [`sys::IMAPIContainer::GetHierarchyTable`] with the immediate subfolders of this
+    /// folder, and [`sys::MAPI_UNICODE`] string columns.
+    pub fn hierarchy_table(&self) -> Result<sys::IMAPITable> {
+        self.hierarchy_table_with(Default::default())
+    }
+
+    /// Same as [`Folder::hierarchy_table`], but with additional [`TableFlags`].
+    pub fn hierarchy_table_with(&self, flags: TableFlags) -> Result<sys::IMAPITable> {
+        unsafe {
+            self.folder
+                .GetHierarchyTable(sys::MAPI_UNICODE | u32::from(flags))
+        }
+    }
+
+    /// Get the [`sys::IMAPIContainer::GetContentsTable`] with the messages in this folder, and
+    /// [`sys::MAPI_UNICODE`] string columns.
+    pub fn contents_table(&self) -> Result<sys::IMAPITable> {
+        self.contents_table_with(Default::default())
+    }
+
+    /// Same as [`Folder::contents_table`], but with additional [`TableFlags`].
+    pub fn contents_table_with(&self, flags: TableFlags) -> Result<sys::IMAPITable> {
+        unsafe {
+            self.folder
+                .GetContentsTable(sys::MAPI_UNICODE | u32::from(flags))
+        }
+    }
+
+    /// Start a [`TableQuery`] on the messages in this folder which were soft deleted (e.g. with
+    /// Shift+Delete in Outlook) and can still be recovered, using [`sys::SHOW_SOFT_DELETES`].
+    ///
+    /// Stores which do not keep soft deleted items usually fail with an error such as
+    /// [`sys::MAPI_E_NO_SUPPORT`] when the table is opened.
+    pub fn soft_deleted_messages(&self) -> Result<TableQuery> {
+        self.contents_table_with(TableFlags {
+            show_soft_deletes: true,
+            ..Default::default()
+        })
+        .map(TableQuery::new)
+    }
+
+    /// Find the first message in this folder with a matching [`sys::PR_SEARCH_KEY`], and return
+    /// its [`sys::PR_ENTRYID`], e.g. to pass to [`Folder::open_message`]. The search key is
+    /// preserved when a message is copied, so it can correlate copies across folders and stores.
+    pub fn find_by_search_key(&self, search_key: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.find_entry_id(&Restriction::eq(
+            PropTag(sys::PR_SEARCH_KEY),
+            RestrictionValue::Binary(search_key.to_vec()),
+        ))
+    }
+
+    /// Find the first message in this folder with a matching [`sys::PR_INTERNET_MESSAGE_ID_W`],
+    /// e.g. `<id@example.com>`, and return its [`sys::PR_ENTRYID`]. This is the usual key for
+    /// correlating a message in an external system with the copy in a MAPI store.
+    pub fn find_by_internet_message_id(&self, message_id: &str) -> Result<Option<Vec<u8>>> {
+        self.find_entry_id(&Restriction::And(vec![
+            Restriction::Exists(PropTag(sys::PR_INTERNET_MESSAGE_ID_W)),
+            Restriction::eq(
+                PropTag(sys::PR_INTERNET_MESSAGE_ID_W),
+                RestrictionValue::Unicode(message_id.to_string()),
+            ),
+        ]))
+    }
+
+    /// Create a generic subfolder of this folder with [`sys::IMAPIFolder::CreateFolder`]. If
+    /// `class` is set, e.g. `IPF.Note` or `IPF.Contact`, it is written to
+    /// [`sys::PR_CONTAINER_CLASS_W`] so Outlook shows the folder with the matching view. Fails with
+    /// [`sys::MAPI_E_COLLISION`] if there is already a subfolder with the same name.
+    pub fn create_subfolder(&self, name: &str, class: Option<&str>) -> Result<Folder> {
+        let folder = self.create_folder(sys::FOLDER_GENERIC, name)?;
+        if let Some(class) = class {
+            set_props(
+                &folder,
+                &[(sys::PR_CONTAINER_CLASS_W, SetPropValue::Unicode(class))],
+            )?;
+        }
+        Ok(Folder::from(folder))
+    }
+
+    /// Call [`sys::IMAPIFolder::CreateFolder`] with `folder_type`, e.g. [`sys::FOLDER_GENERIC`] or
+    /// [`sys::FOLDER_SEARCH`].
+    pub(crate) fn create_folder(&self, folder_type: u32, name: &str) -> Result<sys::IMAPIFolder> {
+        let mut name: Vec<_> = name.encode_utf16().chain(iter::once(0)).collect();
+        let mut folder = None;
+        unsafe {
+            self.folder.CreateFolder(
+                folder_type,
+                name.as_mut_ptr() as *mut _,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                sys::MAPI_UNICODE,
+                &mut folder,
+            )?;
+        }
+        folder.ok_or_else(|| Error::from(sys::MAPI_E_CALL_FAILED))
+    }
+
+    /// Delete the messages in `entry_ids` from this folder with
+    /// [`sys::IMAPIFolder::DeleteMessages`]. The provider may skip messages which are not in this
+    /// folder and only report that with the [`sys::MAPI_W_PARTIAL_COMPLETION`] warning, which is
+    /// not treated as an error.
+    pub fn delete_messages(&self, entry_ids: &EntryList) -> Result<()> {
+        self.delete_messages_with(entry_ids, None)
+    }
+
+    /// Copy the messages in `entry_ids` from this folder to `dest` with
+    /// [`sys::IMAPIFolder::CopyMessages`]. If `move_messages` is set, this passes
+    /// [`sys::MAPI_MOVE`], which deletes them from this folder once they have been copied.
+    pub fn copy_messages(
+        &self,
+        entry_ids: &EntryList,
+        dest: &Folder,
+        move_messages: bool,
+    ) -> Result<()> {
+        self.copy_messages_with(entry_ids, dest, move_messages, None)
+    }
+
+    /// Search the [`Folder::contents_table`] with [`MapiTable::find_row`], reading only the
+    /// [`sys::PR_ENTRYID`] column.
+    fn find_entry_id(&self, restriction: &Restriction) -> Result<Option<Vec<u8>>> {
+        let table = MapiTable::from(self.contents_table()?);
+        table.set_columns(&[PropTag(sys::PR_ENTRYID)])?;
+        let Some(row) = table.find_row(restriction, Bookmark::Beginning)? else {
+            return Ok(None);
+        };
+        Ok(row.iter().find_map(|PropValue { tag, value }| match value {
+            PropValueData::Binary(entry_id) if u32::from(tag) == sys::PR_ENTRYID => {
+                Some(entry_id.to_vec())
+            }
+            _ => None,
+        }))
+    }
+}
+
+impl From<sys::IMAPIFolder> for Folder {
+    fn from(folder: sys::IMAPIFolder) -> Self {
+        Self { folder }
+    }
+}
+
+impl From<Folder> for sys::IMAPIFolder {
+    fn from(value: Folder) -> Self {
+        value.folder
+    }
+}
+
+impl Store {
+    /// Open the root folder of the store, which is the parent of the IPM subtree and the other
+    /// top level folders.
+    pub fn open_root_folder(&self) -> Result<Folder> {
+        self.open_folder(&[])
+    }
+
+    /// Open a folder in this store with [`sys::IMsgStore::OpenEntry`]. An empty `entry_id` opens
+    /// the root folder. Fails with `E_NOINTERFACE` if the entry ID belongs to a message instead
+    /// of a folder.
+    pub fn open_folder(&self, entry_id: &[u8]) -> Result<Folder> {
+        self.open_folder_with(entry_id, Default::default())
+    }
+
+    /// Same as [`Store::open_folder`], but with explicit [`OpenFlags`].
+    pub fn open_folder_with(&self, entry_id: &[u8], flags: OpenFlags) -> Result<Folder> {
+        open_store_entry::<sys::IMAPIFolder>(self.store(), entry_id, flags).map(Folder::from)
+    }
+}
@@ -0,0 +1,171 @@
+//! Define [`FolderMonitor`], [`FolderCounts`], and [`FolderCountsChanged`].
+
+use crate::{
+    get_props, sys, AdviseConnection, Folder, NotificationEvents, OwnedPropValue,
+    OwnedPropValueData, PropTag, Store,
+};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+use windows_core::*;
+
+/// [`sys::PR_CONTENT_COUNT`] and [`sys::PR_CONTENT_UNREAD`] of a folder.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FolderCounts {
+    /// Number of messages in the folder.
+    pub total: u32,
+
+    /// Number of unread messages in the folder.
+    pub unread: u32,
+}
+
+impl FolderCounts {
+    /// Read the counts from a folder. Either property may be missing, e.g. in a search folder
+    /// which has not finished populating, in which case it is treated as `0`.
+    pub fn from_folder(folder: &sys::IMAPIFolder) -> Result<Self> {
+        let values = get_props(
+            folder,
+            &[
+                PropTag(sys::PR_CONTENT_COUNT),
+                PropTag(sys::PR_CONTENT_UNREAD),
+            ],
+        )?;
+        let mut counts = Self::default();
+        for (tag, value) in values {
+            let Ok(OwnedPropValue {
+                value: OwnedPropValueData::Long(value),
+                ..
+            }) = value
+            else {
+                continue;
+            };
+            match u32::from(tag) {
+                sys::PR_CONTENT_COUNT => counts.total = value as u32,
+                sys::PR_CONTENT_UNREAD => counts.unread = value as u32,
+                _ => {}
+            }
+        }
+        Ok(counts)
+    }
+}
+
+/// Change event returned by [`FolderMonitor::poll`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FolderCountsChanged {
+    /// Entry ID of the folder, as it was passed to [`FolderMonitor::new`].
+    pub entry_id: Vec<u8>,
+
+    /// Counts before the change.
+    pub old: FolderCounts,
+
+    /// Counts after the change.
+    pub new: FolderCounts,
+}
+
+struct MonitoredFolder {
+    entry_id: Vec<u8>,
+    folder: Folder,
+    counts: FolderCounts,
+    _connection: AdviseConnection,
+}
+
+/// Live unread and total counts for a set of folders in one [`Store`], e.g. for a tray icon.
+///
+/// Each folder is registered for [`sys::fnevObjectModified`] with [`Store::advise`]. Providers do
+/// not include the new property values in that notification, and the [`sys::IMAPIFolder`] cannot
+/// be used from the thread which delivers it, so the advise sink only marks the folder as changed.
+/// Call [`FolderMonitor::poll`] on the thread which created the monitor, e.g. from its message
+/// loop, to read the counts again for the folders which changed and get the change events.
+pub struct FolderMonitor {
+    folders: Vec<MonitoredFolder>,
+    changed: Arc<Mutex<HashSet<usize>>>,
+}
+
+impl FolderMonitor {
+    /// Open each of the folders in `entry_ids`, read their current counts, and register for
+    /// changes.
+    pub fn new<'a>(store: &Store, entry_ids: impl IntoIterator<Item = &'a [u8]>) -> Result<Self> {
+        let changed = Arc::new(Mutex::new(HashSet::new()));
+        let events = NotificationEvents {
+            object_modified: true,
+            ..Default::default()
+        };
+        let folders = entry_ids
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry_id)| {
+                let folder = store.open_folder(entry_id)?;
+                let counts = FolderCounts::from_folder(folder.folder())?;
+                let changed = changed.clone();
+                let connection = store.advise(entry_id, events, move |_| {
+                    changed
+                        .lock()
+                        .unwrap_or_else(|err| err.into_inner())
+                        .insert(index);
+                })?;
+                Ok(MonitoredFolder {
+                    entry_id: entry_id.to_vec(),
+                    folder,
+                    counts,
+                    _connection: connection,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { folders, changed })
+    }
+
+    /// Get the last counts read for a folder, or `None` if it is not in the monitored set.
+    pub fn counts(&self, entry_id: &[u8]) -> Option<FolderCounts> {
+        self.folders
+            .iter()
+            .find(|folder| folder.entry_id == entry_id)
+            .map(|folder| folder.counts)
+    }
+
+    /// Iterate over the entry IDs and last counts read for every monitored folder.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], FolderCounts)> {
+        self.folders
+            .iter()
+            .map(|folder| (folder.entry_id.as_slice(), folder.counts))
+    }
+
+    /// Sum of the last counts read for every monitored folder, saturating at [`u32::MAX`].
+    pub fn total(&self) -> FolderCounts {
+        self.folders
+            .iter()
+            .fold(FolderCounts::default(), |total, folder| FolderCounts {
+                total: total.total.saturating_add(folder.counts.total),
+                unread: total.unread.saturating_add(folder.counts.unread),
+            })
+    }
+
+    /// Read the counts again for each folder which was reported as modified since the last call,
+    /// and return an event for each one whose counts actually changed. Modifications which do not
+    /// affect the counts, e.g. renaming the folder, are dropped here.
+    pub fn poll(&mut self) -> Result<Vec<FolderCountsChanged>> {
+        let mut changed: Vec<_> = self
+            .changed
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .drain()
+            .collect();
+        changed.sort_unstable();
+        let mut events = Vec::new();
+        for index in changed {
+            let Some(folder) = self.folders.get_mut(index) else {
+                continue;
+            };
+            let counts = FolderCounts::from_folder(folder.folder.folder())?;
+            if counts != folder.counts {
+                events.push(FolderCountsChanged {
+                    entry_id: folder.entry_id.clone(),
+                    old: folder.counts,
+                    new: counts,
+                });
+                folder.counts = counts;
+            }
+        }
+        Ok(events)
+    }
+}
@@ -0,0 +1,131 @@
+//! Define [`move_folder`], [`MoveFolderOptions`], and [`NameCollision`].
+
+use crate::{
+    mapi_prop::open_store_entry, sys, InterfaceId, MapiProp, NoUi, OpenFlags, PropTag, PropValue,
+    PropValueData, RowSet, SizedSPropTagArray,
+};
+use core::ptr;
+use std::iter;
+use windows::Win32::Foundation::{E_UNEXPECTED, HWND};
+use windows_core::*;
+
+/// What [`move_folder`] should do if the destination already has a subfolder with the same name.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NameCollision {
+    /// Let [`sys::IMAPIFolder::CopyFolder`] fail with [`sys::MAPI_E_COLLISION`].
+    #[default]
+    Fail,
+
+    /// Append ` (2)`, ` (3)`, etc. to the name until it is unique in the destination.
+    Rename,
+}
+
+/// Options for [`move_folder`].
+#[derive(Default)]
+pub struct MoveFolderOptions {
+    /// Give the folder a new [`sys::PR_DISPLAY_NAME_W`] in the destination.
+    pub new_name: Option<String>,
+
+    /// What to do if the name is already taken in the destination.
+    pub collision: NameCollision,
+
+    /// Report progress to this [`sys::IMAPIProgress`] implementation.
+    pub progress: Option<sys::IMAPIProgress>,
+
+    /// Parent window for the progress UI. If this is set along with
+    /// [`MoveFolderOptions::progress`], this passes [`sys::FOLDER_DIALOG`] to let the provider
+    /// display its own progress. Ignored if [`NoUi`] is enabled.
+    pub ui_param: Option<HWND>,
+}
+
+/// Move a folder and all of its subfolders to a new parent folder in the same store, with
+/// [`sys::IMAPIFolder::CopyFolder`] and [`sys::FOLDER_MOVE`] | [`sys::COPY_SUBFOLDERS`].
+///
+/// [`sys::IMAPIFolder::CopyFolder`] needs to be called on the current parent folder, so this
+/// opens it from the [`sys::PR_PARENT_ENTRYID`] of `folder`.
+pub fn move_folder(
+    store: &sys::IMsgStore,
+    folder: &sys::IMAPIFolder,
+    new_parent: &sys::IMAPIFolder,
+    options: MoveFolderOptions,
+) -> Result<()> {
+    let entry_id = folder.get_binary(PropTag(sys::PR_ENTRYID))?;
+    let parent_entry_id = folder.get_binary(PropTag(sys::PR_PARENT_ENTRYID))?;
+    let parent: sys::IMAPIFolder = open_store_entry(store, &parent_entry_id, OpenFlags::modify())?;
+
+    let name = match options.new_name {
+        Some(name) => name,
+        None => folder
+            .get_string_props(&[PropTag(sys::PR_DISPLAY_NAME_W)], Default::default())?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::from(E_UNEXPECTED))??,
+    };
+    let name = match options.collision {
+        NameCollision::Fail => name,
+        NameCollision::Rename => unique_name(new_parent, name)?,
+    };
+    let mut name: Vec<_> = name.encode_utf16().chain(iter::once(0)).collect();
+
+    let mut flags = sys::FOLDER_MOVE | sys::COPY_SUBFOLDERS | sys::MAPI_UNICODE;
+    let ui_param = options.ui_param.filter(|_| !NoUi::is_enabled());
+    if options.progress.is_some() && ui_param.is_some() {
+        flags |= sys::FOLDER_DIALOG;
+    }
+
+    unsafe {
+        parent.CopyFolder(
+            entry_id.len() as u32,
+            entry_id.as_ptr() as *mut _,
+            InterfaceId::MAPI_FOLDER.as_mut_ptr(),
+            new_parent.as_raw(),
+            name.as_mut_ptr() as *mut _,
+            ui_param.map(|hwnd| hwnd.0 as usize).unwrap_or_default(),
+            options.progress.as_ref(),
+            flags,
+        )
+    }
+}
+
+/// Find a name which is not used by any of the subfolders in `parent`, starting with `name`.
+fn unique_name(parent: &sys::IMAPIFolder, name: String) -> Result<String> {
+    SizedSPropTagArray! { PropTagArray[1] }
+    let mut prop_tag_array = PropTagArray {
+        aulPropTag: [sys::PR_DISPLAY_NAME_W],
+        ..Default::default()
+    };
+    let mut rows: RowSet = Default::default();
+    unsafe {
+        let table = parent.GetHierarchyTable(sys::MAPI_UNICODE)?;
+        sys::HrQueryAllRows(
+            &table,
+            prop_tag_array.as_mut_ptr(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            0,
+            rows.as_mut_ptr(),
+        )?;
+    }
+    let existing: Vec<String> = rows
+        .into_iter()
+        .filter_map(|row| {
+            row.iter().find_map(|value| match value {
+                PropValue {
+                    value: PropValueData::Unicode(value),
+                    ..
+                } => unsafe { value.to_string() }.ok(),
+                _ => None,
+            })
+        })
+        .map(|name| name.to_lowercase())
+        .collect();
+
+    let is_taken = |candidate: &str| existing.contains(&candidate.to_lowercase());
+    if !is_taken(&name) {
+        return Ok(name);
+    }
+    (2..)
+        .map(|idx| format!("{name} ({idx})"))
+        .find(|candidate| !is_taken(candidate))
+        .ok_or_else(|| Error::from(sys::MAPI_E_COLLISION))
+}
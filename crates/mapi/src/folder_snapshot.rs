@@ -0,0 +1,167 @@
+//! Define [`FolderSnapshot`], [`SnapshotFolder`], and [`SnapshotItem`].
+
+use crate::{
+    get_props, query_all_rows, sys, Folder, OwnedPropValue, OwnedPropValueData, PropTag, TableFlags,
+};
+use std::collections::HashMap;
+use windows_core::*;
+
+/// Columns read from the hierarchy table by [`FolderSnapshot::read`].
+const FOLDER_COLUMNS: [PropTag; 4] = [
+    PropTag(sys::PR_ENTRYID),
+    PropTag(sys::PR_SOURCE_KEY),
+    PropTag(sys::PR_DISPLAY_NAME_W),
+    PropTag(sys::PR_DEPTH),
+];
+
+/// Columns read from the contents table by [`FolderSnapshot::read`], in addition to the ones the
+/// caller asks for, to find the folder each item is in.
+const PARENT_COLUMNS: [PropTag; 2] = [
+    PropTag(sys::PR_PARENT_SOURCE_KEY),
+    PropTag(sys::PR_PARENT_ENTRYID),
+];
+
+/// Folder in a [`FolderSnapshot`], from one row of the hierarchy table.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SnapshotFolder {
+    /// [`sys::PR_ENTRYID`]
+    pub entry_id: Vec<u8>,
+
+    /// [`sys::PR_SOURCE_KEY`], which is empty if the store does not support it.
+    pub source_key: Vec<u8>,
+
+    /// [`sys::PR_DISPLAY_NAME_W`]
+    pub display_name: String,
+
+    /// [`sys::PR_DEPTH`] from the hierarchy table, which is 1 for the immediate subfolders of the
+    /// root, or 0 for the root itself.
+    pub depth: u32,
+}
+
+/// Item in a [`FolderSnapshot`], from one row of the contents table.
+#[derive(Clone, Debug)]
+pub struct SnapshotItem {
+    /// Index of the folder the item is in, in [`FolderSnapshot::folders`], or `None` if it is not
+    /// in any of them.
+    pub folder: Option<usize>,
+
+    /// The columns which were passed to [`FolderSnapshot::read`], in the same order. Missing
+    /// values are returned as [`OwnedPropValueData::Error`].
+    pub values: Vec<OwnedPropValue>,
+}
+
+/// A folder tree and a list of items, read with one query on each table and correlated in
+/// memory, so that a tree-plus-items view does not need to open every parent folder to show its
+/// name.
+///
+/// MAPI has no way to read two tables at the same instant, so the hierarchy is read first and the
+/// items second. An item which is moved into a new folder in between is reported with
+/// [`SnapshotItem::folder`] set to `None`, rather than with a folder from a different point in
+/// time.
+#[derive(Clone, Debug, Default)]
+pub struct FolderSnapshot {
+    /// `root` and all of its subfolders, in hierarchy table order.
+    pub folders: Vec<SnapshotFolder>,
+
+    /// Every item in the contents table of `items`.
+    pub items: Vec<SnapshotItem>,
+}
+
+impl FolderSnapshot {
+    /// Read every subfolder of `root` with [`TableFlags::convenient_depth`], then read `columns`
+    /// for every item in `items`, and match each item to its folder by
+    /// [`sys::PR_PARENT_SOURCE_KEY`], or by [`sys::PR_PARENT_ENTRYID`] if the store does not
+    /// support source keys.
+    ///
+    /// `items` is usually `root` itself, or a search folder scoped to the subtree under `root`.
+    pub fn read(root: &Folder, items: &Folder, columns: &[PropTag]) -> Result<Self> {
+        let mut folders = vec![Self::root_folder(root)?];
+        let hierarchy = root.hierarchy_table_with(TableFlags {
+            convenient_depth: true,
+            ..Default::default()
+        })?;
+        let rows = query_all_rows(&hierarchy, &FOLDER_COLUMNS, None, None, 0)?;
+        folders.extend(
+            rows.into_iter()
+                .map(|row| Self::folder_from_props(row.iter().map(|value| value.to_owned()))),
+        );
+
+        let by_source_key: HashMap<&[u8], usize> = folders
+            .iter()
+            .enumerate()
+            .filter(|(_, folder)| !folder.source_key.is_empty())
+            .map(|(index, folder)| (folder.source_key.as_slice(), index))
+            .collect();
+        let by_entry_id: HashMap<&[u8], usize> = folders
+            .iter()
+            .enumerate()
+            .map(|(index, folder)| (folder.entry_id.as_slice(), index))
+            .collect();
+
+        let contents = items.contents_table()?;
+        let all_columns: Vec<_> = columns
+            .iter()
+            .chain(PARENT_COLUMNS.iter())
+            .copied()
+            .collect();
+        let rows = query_all_rows(&contents, &all_columns, None, None, 0)?;
+        let items = rows
+            .into_iter()
+            .map(|row| {
+                let mut values: Vec<_> = row.iter().map(|value| value.to_owned()).collect();
+                let parent = values.split_off(columns.len().min(values.len()));
+                let folder = parent
+                    .iter()
+                    .find_map(|value| match (u32::from(value.tag), &value.value) {
+                        (sys::PR_PARENT_SOURCE_KEY, OwnedPropValueData::Binary(key)) => {
+                            by_source_key.get(key.as_slice()).copied()
+                        }
+                        _ => None,
+                    })
+                    .or_else(|| {
+                        parent
+                            .iter()
+                            .find_map(|value| match (u32::from(value.tag), &value.value) {
+                                (sys::PR_PARENT_ENTRYID, OwnedPropValueData::Binary(entry_id)) => {
+                                    by_entry_id.get(entry_id.as_slice()).copied()
+                                }
+                                _ => None,
+                            })
+                    });
+                SnapshotItem { folder, values }
+            })
+            .collect();
+
+        Ok(Self { folders, items })
+    }
+
+    /// Get the folder an item is in.
+    pub fn folder_of(&self, item: &SnapshotItem) -> Option<&SnapshotFolder> {
+        item.folder.and_then(|index| self.folders.get(index))
+    }
+
+    fn root_folder(root: &Folder) -> Result<SnapshotFolder> {
+        let values = get_props(root.folder(), &FOLDER_COLUMNS[..3])?;
+        Ok(Self::folder_from_props(
+            values.into_iter().filter_map(|(_, value)| value.ok()),
+        ))
+    }
+
+    fn folder_from_props(values: impl IntoIterator<Item = OwnedPropValue>) -> SnapshotFolder {
+        let mut folder = SnapshotFolder::default();
+        for OwnedPropValue { tag, value } in values {
+            match (u32::from(tag), value) {
+                (sys::PR_ENTRYID, OwnedPropValueData::Binary(value)) => folder.entry_id = value,
+                (sys::PR_SOURCE_KEY, OwnedPropValueData::Binary(value)) => {
+                    folder.source_key = value
+                }
+                (sys::PR_DISPLAY_NAME_W, OwnedPropValueData::Unicode(value)) => {
+                    folder.display_name = value
+                }
+                (sys::PR_DEPTH, OwnedPropValueData::Long(value)) => folder.depth = value as u32,
+                _ => {}
+            }
+        }
+        folder
+    }
+}
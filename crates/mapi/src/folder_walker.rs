@@ -0,0 +1,147 @@
+//! Define [`FolderWalker`], [`WalkEvent`], [`DeletedItemPolicy`], and [`is_object_deleted`].
+
+use crate::{
+    mapi_prop::open_store_entry, sys, OpenFlags, PropTag, PropValue, PropValueData, TableQuery,
+};
+use windows_core::*;
+
+/// What [`FolderWalker`] and [`crate::rows_to_writer_with`] do when an item disappears in the
+/// middle of a crawl, i.e. when it fails with an error that [`is_object_deleted`] accepts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DeletedItemPolicy {
+    /// Return the error and let the caller decide whether to keep going.
+    #[default]
+    Abort,
+
+    /// Report the item as skipped and move on to the next one.
+    Skip,
+
+    /// Try to open the item again up to `attempts` more times, e.g. if it was being moved by
+    /// another client, and then skip it like [`DeletedItemPolicy::Skip`].
+    Retry {
+        /// Number of extra attempts.
+        attempts: u32,
+    },
+}
+
+/// Test if an error means the object was deleted (or moved) by someone else while it was being
+/// read: [`sys::MAPI_E_OBJECT_DELETED`] on an object which is already open, or
+/// [`sys::MAPI_E_NOT_FOUND`] when opening it by entry ID.
+pub fn is_object_deleted(err: &Error) -> bool {
+    err.code() == sys::MAPI_E_OBJECT_DELETED || err.code() == sys::MAPI_E_NOT_FOUND
+}
+
+/// Item returned by [`FolderWalker`].
+pub enum WalkEvent {
+    /// A folder which was opened successfully.
+    Folder {
+        /// [`sys::PR_ENTRYID`] of the folder, or empty for the root of the walk if it was opened
+        /// with an empty entry ID.
+        entry_id: Vec<u8>,
+
+        /// Number of levels below the root of the walk.
+        depth: usize,
+
+        /// The open folder.
+        folder: sys::IMAPIFolder,
+    },
+
+    /// A folder which disappeared before it could be read, with the error it failed with.
+    Skipped {
+        /// [`sys::PR_ENTRYID`] of the folder.
+        entry_id: Vec<u8>,
+
+        /// Number of levels below the root of the walk.
+        depth: usize,
+
+        /// The error which [`is_object_deleted`] accepted.
+        error: Error,
+    },
+}
+
+/// Depth-first, pre-order [`Iterator`] over a folder and all of its subfolders.
+///
+/// Each folder is opened with [`sys::MAPI_BEST_ACCESS`], and its subfolders are read from its
+/// hierarchy table before it is returned. If a folder is deleted or moved by another client in the
+/// middle of the walk, the [`DeletedItemPolicy`] decides whether that ends the walk with an error
+/// or yields a [`WalkEvent::Skipped`] and continues with its siblings.
+pub struct FolderWalker {
+    store: sys::IMsgStore,
+    policy: DeletedItemPolicy,
+    pending: Vec<(Vec<u8>, usize)>,
+}
+
+impl FolderWalker {
+    /// Walk the folder with `entry_id` in `store`. An empty `entry_id` walks the whole store from
+    /// the root folder.
+    pub fn new(store: &sys::IMsgStore, entry_id: &[u8], policy: DeletedItemPolicy) -> Self {
+        Self {
+            store: store.clone(),
+            policy,
+            pending: vec![(entry_id.to_vec(), 0)],
+        }
+    }
+
+    fn open(&self, entry_id: &[u8]) -> Result<(sys::IMAPIFolder, Vec<Vec<u8>>)> {
+        let folder: sys::IMAPIFolder =
+            open_store_entry(&self.store, entry_id, OpenFlags::default())?;
+        let subfolders = TableQuery::hierarchy(&folder)?
+            .columns(&[PropTag(sys::PR_ENTRYID)])
+            .execute()?
+            .filter_map(|row| match row {
+                Ok(row) => {
+                    row.iter()
+                        .find_map(|PropValue { tag, value }| match (u32::from(tag), value) {
+                            (sys::PR_ENTRYID, PropValueData::Binary(value)) => {
+                                Some(Ok(value.to_vec()))
+                            }
+                            _ => None,
+                        })
+                }
+                Err(err) => Some(Err(err)),
+            })
+            .collect::<Result<_>>()?;
+        Ok((folder, subfolders))
+    }
+}
+
+impl Iterator for FolderWalker {
+    type Item = Result<WalkEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (entry_id, depth) = self.pending.pop()?;
+        let retries = match self.policy {
+            DeletedItemPolicy::Retry { attempts } => attempts,
+            _ => 0,
+        };
+
+        let mut attempt = 0;
+        let result = loop {
+            match self.open(&entry_id) {
+                Err(err) if is_object_deleted(&err) && attempt < retries => attempt += 1,
+                result => break result,
+            }
+        };
+
+        Some(match result {
+            Ok((folder, subfolders)) => {
+                // Push in reverse so the subfolders are visited in table order.
+                self.pending
+                    .extend(subfolders.into_iter().rev().map(|child| (child, depth + 1)));
+                Ok(WalkEvent::Folder {
+                    entry_id,
+                    depth,
+                    folder,
+                })
+            }
+            Err(error) if is_object_deleted(&error) && self.policy != DeletedItemPolicy::Abort => {
+                Ok(WalkEvent::Skipped {
+                    entry_id,
+                    depth,
+                    error,
+                })
+            }
+            Err(error) => Err(error),
+        })
+    }
+}
@@ -0,0 +1,227 @@
+//! Define [`FollowUpFlag`] and [`FlagStatus`].
+
+use crate::{
+    mapi_prop::{delete_props, get_named_prop_tags, get_one_prop, set_props, SetPropValue},
+    sys, PropTag, PropValueData, LID_REMINDER_SET, LID_REMINDER_SIGNAL_TIME, LID_REMINDER_TIME,
+};
+use windows::Win32::Foundation::{E_UNEXPECTED, FILETIME};
+use windows_core::*;
+
+/// `PR_FOLLOWUP_ICON` is missing from the generated bindings.
+pub const PR_FOLLOWUP_ICON: u32 = 0x1095_0003;
+
+/// `PR_FLAG_COMPLETE_TIME` is missing from the generated bindings.
+pub const PR_FLAG_COMPLETE_TIME: u32 = 0x1091_0040;
+
+/// `PidLidFlagRequest` in [`sys::PSETID_Common`].
+pub const LID_FLAG_REQUEST: i32 = 0x8530;
+
+/// `PidLidTaskStartDate` in [`sys::PSETID_Task`].
+pub const LID_TASK_START_DATE: i32 = 0x8104;
+
+/// `PidLidTaskDueDate` in [`sys::PSETID_Task`].
+pub const LID_TASK_DUE_DATE: i32 = 0x8105;
+
+/// Value of [`sys::PR_FLAG_STATUS`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FlagStatus {
+    /// The property is missing, or has an unknown value.
+    #[default]
+    NotFlagged,
+
+    /// `followupComplete`
+    Complete,
+
+    /// `followupFlagged`
+    Flagged,
+}
+
+impl From<u32> for FlagStatus {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => Self::Complete,
+            2 => Self::Flagged,
+            _ => Self::NotFlagged,
+        }
+    }
+}
+
+/// Typed view of the follow-up flag on a message. Any property which is missing from the message
+/// is `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FollowUpFlag {
+    /// [`sys::PR_FLAG_STATUS`]
+    pub status: FlagStatus,
+
+    /// [`PR_FOLLOWUP_ICON`], which is the color of the flag in older versions of Outlook.
+    pub icon: Option<i32>,
+
+    /// `PidLidFlagRequest`, e.g. `Follow up`.
+    pub request: Option<String>,
+
+    /// `PidLidTaskStartDate`
+    pub start_date: Option<FILETIME>,
+
+    /// `PidLidTaskDueDate`
+    pub due_date: Option<FILETIME>,
+
+    /// [`PR_FLAG_COMPLETE_TIME`]
+    pub complete_time: Option<FILETIME>,
+
+    /// `PidLidReminderTime`, if `PidLidReminderSet` is `true`.
+    pub reminder_time: Option<FILETIME>,
+}
+
+impl FollowUpFlag {
+    /// Create a flag with [`FlagStatus::Flagged`] and a `PidLidFlagRequest`.
+    pub fn flagged(request: impl Into<String>) -> Self {
+        Self {
+            status: FlagStatus::Flagged,
+            request: Some(request.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Read the follow-up flag from a message. The named properties are looked up without creating
+    /// new mappings, so reading a message in a store which has never used them does not consume
+    /// any named property IDs.
+    pub fn read(message: &sys::IMessage) -> Result<Self> {
+        let [request, reminder_set, reminder_time, _, start_date, due_date] =
+            named_tags(message, 0)?;
+        let long = |tag| {
+            get_one_prop(message, tag, |value| match value {
+                PropValueData::Long(value) => Some(value),
+                _ => None,
+            })
+        };
+        let filetime = |tag: Option<PropTag>| match tag {
+            Some(tag) => get_one_prop(message, u32::from(tag), |value| match value {
+                PropValueData::FileTime(value) => Some(value),
+                _ => None,
+            }),
+            None => Ok(None),
+        };
+
+        let reminder_set = match reminder_set {
+            Some(tag) => get_one_prop(message, u32::from(tag), |value| match value {
+                PropValueData::Boolean(value) => Some(value != 0),
+                _ => None,
+            })?
+            .unwrap_or_default(),
+            None => false,
+        };
+        let request = match request {
+            Some(tag) => get_one_prop(message, u32::from(tag), |value| match value {
+                PropValueData::Unicode(value) => unsafe { value.to_string() }.ok(),
+                _ => None,
+            })?,
+            None => None,
+        };
+
+        Ok(Self {
+            status: long(sys::PR_FLAG_STATUS)?
+                .map(|value| FlagStatus::from(value as u32))
+                .unwrap_or_default(),
+            icon: long(PR_FOLLOWUP_ICON)?,
+            request,
+            start_date: filetime(start_date)?,
+            due_date: filetime(due_date)?,
+            complete_time: filetime(Some(PropTag(PR_FLAG_COMPLETE_TIME)))?,
+            reminder_time: if reminder_set {
+                filetime(reminder_time)?
+            } else {
+                None
+            },
+        })
+    }
+
+    /// Write the follow-up flag to a message. Properties which are `None` (or
+    /// [`FlagStatus::NotFlagged`]) are deleted, so writing [`FollowUpFlag::default`] clears the
+    /// flag. The caller still needs to call [`sys::IMAPIProp::SaveChanges`].
+    pub fn write(&self, message: &sys::IMessage) -> Result<()> {
+        let tags: Vec<u32> = named_tags(message, sys::MAPI_CREATE)?
+            .into_iter()
+            .map(|tag| tag.map(u32::from))
+            .collect::<Option<_>>()
+            .ok_or_else(|| Error::from(sys::MAPI_E_NOT_FOUND))?;
+        let &[request, reminder_set, reminder_time, signal_time, start_date, due_date] =
+            tags.as_slice()
+        else {
+            return Err(Error::from(E_UNEXPECTED));
+        };
+
+        let mut values = vec![(
+            reminder_set,
+            SetPropValue::Boolean(self.reminder_time.is_some()),
+        )];
+        let mut deleted = Vec::new();
+        let status = match self.status {
+            FlagStatus::NotFlagged => None,
+            FlagStatus::Complete => Some(1),
+            FlagStatus::Flagged => Some(2),
+        };
+        match status {
+            Some(status) => values.push((sys::PR_FLAG_STATUS, SetPropValue::Long(status))),
+            None => deleted.push(sys::PR_FLAG_STATUS),
+        }
+        match self.icon {
+            Some(icon) => values.push((PR_FOLLOWUP_ICON, SetPropValue::Long(icon))),
+            None => deleted.push(PR_FOLLOWUP_ICON),
+        }
+        match self.request.as_deref() {
+            Some(value) => values.push((request, SetPropValue::Unicode(value))),
+            None => deleted.push(request),
+        }
+        for (tag, value) in [
+            (start_date, self.start_date),
+            (due_date, self.due_date),
+            (PR_FLAG_COMPLETE_TIME, self.complete_time),
+            (reminder_time, self.reminder_time),
+            (signal_time, self.reminder_time),
+        ] {
+            match value {
+                Some(value) => values.push((tag, SetPropValue::FileTime(value))),
+                None => deleted.push(tag),
+            }
+        }
+
+        set_props(message, &values)?;
+        delete_props(message, &deleted)
+    }
+}
+
+/// Look up `PidLidFlagRequest`, `PidLidReminderSet`, `PidLidReminderTime`,
+/// `PidLidReminderSignalTime`, `PidLidTaskStartDate`, and `PidLidTaskDueDate`.
+fn named_tags(message: &sys::IMessage, flags: u32) -> Result<[Option<PropTag>; 6]> {
+    let common = get_named_prop_tags(
+        message,
+        &sys::PSETID_Common,
+        &[
+            (LID_FLAG_REQUEST, sys::PT_UNICODE),
+            (LID_REMINDER_SET, sys::PT_BOOLEAN),
+            (LID_REMINDER_TIME, sys::PT_SYSTIME),
+            (LID_REMINDER_SIGNAL_TIME, sys::PT_SYSTIME),
+        ],
+        flags,
+    )?;
+    let task = get_named_prop_tags(
+        message,
+        &sys::PSETID_Task,
+        &[
+            (LID_TASK_START_DATE, sys::PT_SYSTIME),
+            (LID_TASK_DUE_DATE, sys::PT_SYSTIME),
+        ],
+        flags,
+    )?;
+    match (common.as_slice(), task.as_slice()) {
+        (&[request, reminder_set, reminder_time, signal_time], &[start_date, due_date]) => Ok([
+            request,
+            reminder_set,
+            reminder_time,
+            signal_time,
+            start_date,
+            due_date,
+        ]),
+        _ => Err(Error::from(E_UNEXPECTED)),
+    }
+}
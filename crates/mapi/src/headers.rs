@@ -0,0 +1,135 @@
+//! Define [`MessageHeader`] and [`MessageHeaders`].
+
+use crate::{
+    from_file_time, sys, Folder, PropTag, PropValue, PropValueData, Row, SortDirection, TableQuery,
+    TableRows,
+};
+use std::time::SystemTime;
+use windows_core::*;
+
+/// Columns requested by [`Folder::headers`].
+const HEADER_COLUMNS: [u32; 7] = [
+    sys::PR_ENTRYID,
+    sys::PR_SUBJECT_W,
+    sys::PR_SENDER_NAME_W,
+    sys::PR_SENDER_EMAIL_ADDRESS_W,
+    sys::PR_MESSAGE_DELIVERY_TIME,
+    sys::PR_MESSAGE_SIZE,
+    sys::PR_MESSAGE_FLAGS,
+];
+
+/// Lightweight summary of a message for listing UIs, returned by [`Folder::headers`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MessageHeader {
+    /// [`sys::PR_ENTRYID`] of the message, which can be passed to [`Folder::open_message`].
+    pub entry_id: Vec<u8>,
+
+    /// [`sys::PR_SUBJECT_W`]
+    pub subject: Option<String>,
+
+    /// [`sys::PR_SENDER_NAME_W`]
+    pub sender_name: Option<String>,
+
+    /// [`sys::PR_SENDER_EMAIL_ADDRESS_W`]
+    pub sender_email_address: Option<String>,
+
+    /// [`sys::PR_MESSAGE_DELIVERY_TIME`]
+    pub received_time: Option<SystemTime>,
+
+    /// [`sys::PR_MESSAGE_SIZE`]
+    pub size: Option<u32>,
+
+    /// [`sys::PR_MESSAGE_FLAGS`], a combination of `MSGFLAG_*` values such as
+    /// [`sys::MSGFLAG_READ`].
+    pub message_flags: u32,
+}
+
+impl MessageHeader {
+    /// Read a [`MessageHeader`] from a row with the [`Folder::headers`] columns. Returns `None` if
+    /// the row does not have a [`sys::PR_ENTRYID`].
+    pub fn from_row(row: &Row) -> Option<Self> {
+        let mut header = Self::default();
+        let mut entry_id = None;
+        for PropValue { tag, value } in row.iter() {
+            match (u32::from(tag), value) {
+                (sys::PR_ENTRYID, PropValueData::Binary(value)) => entry_id = Some(value.to_vec()),
+                (sys::PR_SUBJECT_W, PropValueData::Unicode(value)) => {
+                    header.subject = unsafe { value.to_string() }.ok()
+                }
+                (sys::PR_SENDER_NAME_W, PropValueData::Unicode(value)) => {
+                    header.sender_name = unsafe { value.to_string() }.ok()
+                }
+                (sys::PR_SENDER_EMAIL_ADDRESS_W, PropValueData::Unicode(value)) => {
+                    header.sender_email_address = unsafe { value.to_string() }.ok()
+                }
+                (sys::PR_MESSAGE_DELIVERY_TIME, PropValueData::FileTime(value)) => {
+                    header.received_time = from_file_time(value)
+                }
+                (sys::PR_MESSAGE_SIZE, PropValueData::Long(value)) => {
+                    header.size = Some(value as u32)
+                }
+                (sys::PR_MESSAGE_FLAGS, PropValueData::Long(value)) => {
+                    header.message_flags = value as u32
+                }
+                _ => {}
+            }
+        }
+        header.entry_id = entry_id?;
+        Some(header)
+    }
+
+    /// Test if [`sys::MSGFLAG_READ`] is set.
+    pub fn is_read(&self) -> bool {
+        self.message_flags & sys::MSGFLAG_READ != 0
+    }
+
+    /// Test if [`sys::MSGFLAG_HASATTACH`] is set.
+    pub fn has_attachments(&self) -> bool {
+        self.message_flags & sys::MSGFLAG_HASATTACH != 0
+    }
+}
+
+/// Lazy [`Iterator`] over the [`MessageHeader`] of each message in a folder, returned by
+/// [`Folder::headers`].
+pub struct MessageHeaders {
+    rows: TableRows,
+}
+
+impl Iterator for MessageHeaders {
+    type Item = Result<MessageHeader>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.rows.next()? {
+                Ok(row) => {
+                    if let Some(header) = MessageHeader::from_row(&row) {
+                        return Some(Ok(header));
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+impl Folder {
+    /// List the messages in this folder with a fixed, lightweight set of columns, newest first,
+    /// reading `batch` rows at a time from the contents table.
+    ///
+    /// Every column is served from the contents table itself, and the messages are never opened,
+    /// so this does not download message bodies or attachments even on a store opened in online
+    /// mode. Use [`Folder::open_message`] with
+    /// [`MessageHeader::entry_id`] to read the rest of a message on demand.
+    pub fn headers(&self, batch: usize) -> Result<MessageHeaders> {
+        let columns = HEADER_COLUMNS.map(PropTag);
+        let rows = TableQuery::new(self.contents_table()?)
+            .columns(&columns)
+            .sort(
+                PropTag(sys::PR_MESSAGE_DELIVERY_TIME),
+                SortDirection::Descending,
+            )
+            .batch(batch)
+            .execute()?;
+        Ok(MessageHeaders { rows })
+    }
+}
@@ -0,0 +1,261 @@
+//! Define [`HierarchySync`], [`HierarchySyncFlags`], [`HierarchyImporter`], and [`FolderChange`].
+
+use crate::{
+    ics::{
+        configure, copy_props, copy_source_keys, export_state, is_done, push_change, synchronize,
+        take_changes, Queue,
+    },
+    sys, Folder, MapiProp, OpenFlags, OwnedPropValue, OwnedPropValueData, PropTag, SyncState,
+};
+use windows::Win32::System::Com::IStream;
+use windows_core::*;
+use windows_implement::implement;
+
+/// Set of flags that can be passed to [`HierarchySync::new`], in addition to
+/// [`sys::SYNC_UNICODE`].
+#[derive(Default)]
+pub struct HierarchySyncFlags {
+    /// Pass [`sys::SYNC_NO_DELETIONS`], to skip [`HierarchyImporter::folder_deleted`] calls.
+    pub no_deletions: bool,
+
+    /// Pass [`sys::SYNC_NO_SOFT_DELETIONS`], to skip folders which were soft deleted.
+    pub no_soft_deletions: bool,
+
+    /// Pass [`sys::SYNC_CATCHUP`], to advance the [`SyncState`] to the current hierarchy without
+    /// reporting any changes.
+    pub catchup: bool,
+}
+
+impl From<HierarchySyncFlags> for u32 {
+    fn from(value: HierarchySyncFlags) -> Self {
+        let no_deletions = if value.no_deletions {
+            sys::SYNC_NO_DELETIONS
+        } else {
+            0
+        };
+        let no_soft_deletions = if value.no_soft_deletions {
+            sys::SYNC_NO_SOFT_DELETIONS
+        } else {
+            0
+        };
+        let catchup = if value.catchup { sys::SYNC_CATCHUP } else { 0 };
+
+        sys::SYNC_UNICODE | no_deletions | no_soft_deletions | catchup
+    }
+}
+
+/// Properties of a folder which was added or changed, from
+/// [`sys::IExchangeImportHierarchyChanges::ImportFolderChange`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct FolderChange {
+    /// Property values the provider passed for the folder, which include
+    /// [`sys::PR_SOURCE_KEY`], [`sys::PR_PARENT_SOURCE_KEY`], and [`sys::PR_DISPLAY_NAME_W`].
+    pub props: Vec<OwnedPropValue>,
+}
+
+impl FolderChange {
+    fn find(&self, tag: u32) -> Option<&OwnedPropValueData> {
+        self.props
+            .iter()
+            .find(|value| u32::from(value.tag) == tag)
+            .map(|value| &value.value)
+    }
+
+    fn find_binary(&self, tag: u32) -> Option<&[u8]> {
+        match self.find(tag)? {
+            OwnedPropValueData::Binary(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the [`sys::PR_SOURCE_KEY`] of the folder.
+    pub fn source_key(&self) -> Option<&[u8]> {
+        self.find_binary(sys::PR_SOURCE_KEY)
+    }
+
+    /// Get the [`sys::PR_SOURCE_KEY`] of the parent folder. The hierarchy is reported parents
+    /// first, so the parent is either the synchronized folder or one which was already reported.
+    pub fn parent_source_key(&self) -> Option<&[u8]> {
+        self.find_binary(sys::PR_PARENT_SOURCE_KEY)
+    }
+
+    /// Get the [`sys::PR_DISPLAY_NAME_W`] of the folder.
+    pub fn display_name(&self) -> Option<&str> {
+        match self.find(sys::PR_DISPLAY_NAME_W)? {
+            OwnedPropValueData::Unicode(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Receive the folder events from a [`HierarchySync`].
+///
+/// ICS reports a new folder the same way as a rename or a move of an existing one, as the
+/// current properties of the folder, so an importer which needs to tell them apart should look
+/// up the [`FolderChange::source_key`] in its own mirror. Both methods do nothing by default.
+///
+/// Returning an error stops the synchronization before the [`SyncState`] is updated, so the same
+/// changes are reported again on the next run.
+pub trait HierarchyImporter {
+    /// A folder was added, or renamed, moved, or otherwise changed.
+    fn folder_changed(&mut self, folder: FolderChange) -> Result<()> {
+        let _ = folder;
+        Ok(())
+    }
+
+    /// A folder was deleted. `soft` is set if [`sys::SYNC_SOFT_DELETE`] was passed, i.e. the
+    /// folder is still recoverable.
+    fn folder_deleted(&mut self, source_key: Vec<u8>, soft: bool) -> Result<()> {
+        let _ = (source_key, soft);
+        Ok(())
+    }
+}
+
+enum HierarchyEvent {
+    Changed(FolderChange),
+    Deleted {
+        source_keys: Vec<Vec<u8>>,
+        soft: bool,
+    },
+}
+
+/// Implementation of [`sys::IExchangeImportHierarchyChanges`] which queues each change for
+/// [`HierarchySync::step`], like the contents importer behind [`crate::ContentsSync`].
+#[implement(sys::IExchangeImportHierarchyChanges)]
+struct HierarchyCollector {
+    events: Queue<HierarchyEvent>,
+}
+
+impl sys::IExchangeImportHierarchyChanges_Impl for HierarchyCollector_Impl {
+    fn GetLastError(
+        &self,
+        _hresult: HRESULT,
+        _ulflags: u32,
+        _lppmapierror: *mut *mut sys::MAPIERROR,
+    ) -> Result<()> {
+        Err(Error::from(sys::MAPI_E_NO_SUPPORT))
+    }
+
+    fn Config(&self, _lpstream: Option<&IStream>, _ulflags: u32) -> Result<()> {
+        Ok(())
+    }
+
+    fn UpdateState(&self, _lpstream: Option<&IStream>) -> Result<()> {
+        Ok(())
+    }
+
+    fn ImportFolderChange(
+        &self,
+        cpvalchanges: u32,
+        ppvalchanges: *mut sys::SPropValue,
+    ) -> Result<()> {
+        push_change(
+            &self.events,
+            HierarchyEvent::Changed(FolderChange {
+                props: copy_props(cpvalchanges, ppvalchanges),
+            }),
+        );
+        Ok(())
+    }
+
+    fn ImportFolderDeletion(
+        &self,
+        ulflags: u32,
+        lpsrcentrylist: *mut sys::SBinaryArray,
+    ) -> Result<()> {
+        push_change(
+            &self.events,
+            HierarchyEvent::Deleted {
+                source_keys: copy_source_keys(lpsrcentrylist),
+                soft: ulflags & sys::SYNC_SOFT_DELETE != 0,
+            },
+        );
+        Ok(())
+    }
+}
+
+/// Incremental Change Synchronization (ICS) of the folders under a [`Folder`], with the
+/// [`sys::IExchangeExportChanges`] from [`sys::PR_HIERARCHY_SYNCHRONIZER`], so a tool can track
+/// folder creation, deletion, and moves with a persisted [`SyncState`].
+pub struct HierarchySync {
+    exporter: sys::IExchangeExportChanges,
+    events: Queue<HierarchyEvent>,
+    steps: u32,
+    progress: u32,
+    done: bool,
+}
+
+impl HierarchySync {
+    /// Open the [`sys::PR_HIERARCHY_SYNCHRONIZER`] on `folder` and configure it to start from
+    /// `state`.
+    pub fn new(folder: &Folder, state: &SyncState, flags: HierarchySyncFlags) -> Result<Self> {
+        let exporter: sys::IExchangeExportChanges = folder.folder().open_property(
+            PropTag(sys::PR_HIERARCHY_SYNCHRONIZER),
+            OpenFlags::read_only(),
+        )?;
+        let events = Queue::default();
+        let importer: sys::IExchangeImportHierarchyChanges = HierarchyCollector {
+            events: events.clone(),
+        }
+        .into();
+        configure(&exporter, state, flags.into(), &importer.cast()?)?;
+        Ok(Self {
+            exporter,
+            events,
+            steps: 0,
+            progress: 0,
+            done: false,
+        })
+    }
+
+    /// Run one step of [`sys::IExchangeExportChanges::Synchronize`], and pass the changes it
+    /// reported to `importer`. Returns `true` while there are more steps to run.
+    pub fn step(&mut self, importer: &mut dyn HierarchyImporter) -> Result<bool> {
+        if self.done {
+            return Ok(false);
+        }
+        let hr = synchronize(&self.exporter, &mut self.steps, &mut self.progress);
+        for event in take_changes(&self.events) {
+            match event {
+                HierarchyEvent::Changed(folder) => importer.folder_changed(folder)?,
+                HierarchyEvent::Deleted { source_keys, soft } => {
+                    for source_key in source_keys {
+                        importer.folder_deleted(source_key, soft)?;
+                    }
+                }
+            }
+        }
+        self.done = is_done(hr)?;
+        Ok(!self.done)
+    }
+
+    /// Get the `(progress, steps)` reported by the last call to [`HierarchySync::step`].
+    pub fn progress(&self) -> (u32, u32) {
+        (self.progress, self.steps)
+    }
+
+    /// Get the [`SyncState`] which includes every change reported so far, with
+    /// [`sys::IExchangeExportChanges::UpdateState`].
+    pub fn state(&self) -> Result<SyncState> {
+        export_state(&self.exporter)
+    }
+
+    /// Run [`HierarchySync::step`] until the synchronization is complete, and return the new
+    /// [`SyncState`].
+    pub fn run(mut self, importer: &mut dyn HierarchyImporter) -> Result<SyncState> {
+        while self.step(importer)? {}
+        self.state()
+    }
+}
+
+impl Folder {
+    /// Synchronize the folders under this folder with [`HierarchySync`] and the default
+    /// [`HierarchySyncFlags`], starting from `state`, and return the new [`SyncState`].
+    pub fn sync_hierarchy(
+        &self,
+        state: &SyncState,
+        importer: &mut dyn HierarchyImporter,
+    ) -> Result<SyncState> {
+        HierarchySync::new(self, state, Default::default())?.run(importer)
+    }
+}
@@ -0,0 +1,535 @@
+//! Define [`SyncState`], [`SyncFlags`], [`ContentChange`], [`ContentsImporter`], and
+//! [`ContentsSync`].
+
+use crate::{
+    mapi_prop::{memory_stream, read_whole_stream},
+    sys, Folder, MapiProp, OpenFlags, OwnedPropValue, PropTag, PropValue,
+};
+use core::{mem, ptr, slice};
+use std::sync::{Arc, Mutex};
+use windows::Win32::System::Com::IStream;
+use windows_core::*;
+use windows_implement::implement;
+
+/// `SYNC_W_PROGRESS` is missing from the generated bindings.
+pub const SYNC_W_PROGRESS: HRESULT = HRESULT(0x0004_0820);
+
+/// `SYNC_W_CLIENT_CHANGE_NEWER` is missing from the generated bindings.
+pub const SYNC_W_CLIENT_CHANGE_NEWER: HRESULT = HRESULT(0x0004_0821);
+
+/// `SYNC_E_OBJECT_DELETED` is missing from the generated bindings.
+pub const SYNC_E_OBJECT_DELETED: HRESULT = HRESULT(0x8004_0800_u32 as _);
+
+/// `SYNC_E_IGNORE` is missing from the generated bindings.
+pub const SYNC_E_IGNORE: HRESULT = HRESULT(0x8004_0801_u32 as _);
+
+/// `SYNC_E_CONFLICT` is missing from the generated bindings.
+pub const SYNC_E_CONFLICT: HRESULT = HRESULT(0x8004_0802_u32 as _);
+
+/// `SYNC_E_NO_PARENT` is missing from the generated bindings.
+pub const SYNC_E_NO_PARENT: HRESULT = HRESULT(0x8004_0803_u32 as _);
+
+/// Opaque synchronization state from [`sys::IExchangeExportChanges::UpdateState`].
+///
+/// Persist the bytes between runs and pass them back to the next [`ContentsSync::new`], so the
+/// provider only reports what changed since then. [`SyncState::default`] is an empty state, which
+/// starts a full synchronization.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SyncState(Vec<u8>);
+
+impl SyncState {
+    /// Wrap state which was saved by a previous run.
+    pub fn new(state: Vec<u8>) -> Self {
+        Self(state)
+    }
+
+    /// Test if this is an empty state, i.e. nothing has been synchronized yet.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Borrow the bytes, e.g. to persist them.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Take the bytes, e.g. to persist them.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl From<Vec<u8>> for SyncState {
+    fn from(value: Vec<u8>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<SyncState> for Vec<u8> {
+    fn from(value: SyncState) -> Self {
+        value.0
+    }
+}
+
+/// Set of flags that can be passed to [`ContentsSync::new`], in addition to
+/// [`sys::SYNC_UNICODE`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SyncFlags {
+    /// Pass [`sys::SYNC_NORMAL`], to report changes to the normal messages in the folder.
+    pub normal: bool,
+
+    /// Pass [`sys::SYNC_ASSOCIATED`], to report changes to the folder associated information
+    /// (FAI) messages in the folder.
+    pub associated: bool,
+
+    /// Pass [`sys::SYNC_READ_STATE`], to report [`ContentChange::ReadState`] changes.
+    pub read_state: bool,
+
+    /// Pass [`sys::SYNC_NO_DELETIONS`], to skip [`ContentChange::Deleted`] changes.
+    pub no_deletions: bool,
+
+    /// Pass [`sys::SYNC_NO_SOFT_DELETIONS`], to skip soft deletions, e.g. messages which were
+    /// moved to the recoverable items by the retention policy.
+    pub no_soft_deletions: bool,
+
+    /// Pass [`sys::SYNC_CATCHUP`], to advance the [`SyncState`] to the current contents of the
+    /// folder without reporting any changes, e.g. after copying the folder some other way.
+    pub catchup: bool,
+}
+
+impl Default for SyncFlags {
+    fn default() -> Self {
+        Self {
+            normal: true,
+            associated: false,
+            read_state: true,
+            no_deletions: false,
+            no_soft_deletions: false,
+            catchup: false,
+        }
+    }
+}
+
+impl From<SyncFlags> for u32 {
+    fn from(value: SyncFlags) -> Self {
+        let normal = if value.normal { sys::SYNC_NORMAL } else { 0 };
+        let associated = if value.associated {
+            sys::SYNC_ASSOCIATED
+        } else {
+            0
+        };
+        let read_state = if value.read_state {
+            sys::SYNC_READ_STATE
+        } else {
+            0
+        };
+        let no_deletions = if value.no_deletions {
+            sys::SYNC_NO_DELETIONS
+        } else {
+            0
+        };
+        let no_soft_deletions = if value.no_soft_deletions {
+            sys::SYNC_NO_SOFT_DELETIONS
+        } else {
+            0
+        };
+        let catchup = if value.catchup { sys::SYNC_CATCHUP } else { 0 };
+
+        sys::SYNC_UNICODE
+            | normal
+            | associated
+            | read_state
+            | no_deletions
+            | no_soft_deletions
+            | catchup
+    }
+}
+
+/// Change to the read flag of a message, reported with [`ContentChange::ReadState`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReadStateChange {
+    /// [`sys::PR_SOURCE_KEY`] of the message.
+    pub source_key: Vec<u8>,
+
+    /// Set if [`sys::SYNC_READ`] was passed, i.e. the message was marked as read.
+    pub read: bool,
+}
+
+/// One change reported by [`sys::IExchangeExportChanges::Synchronize`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContentChange {
+    /// A message was created or modified, from
+    /// [`sys::IExchangeImportContentsChanges::ImportMessageChange`].
+    ///
+    /// The properties are the ones the provider passes to identify the change, which include
+    /// [`sys::PR_SOURCE_KEY`], [`sys::PR_CHANGE_KEY`], and [`sys::PR_LAST_MODIFICATION_TIME`].
+    /// The importer answers with [`SYNC_E_IGNORE`], so the provider does not copy the rest of the
+    /// message; open it by [`sys::PR_ENTRYID`] to read the content.
+    Changed {
+        /// Property values the provider passed for the message.
+        props: Vec<OwnedPropValue>,
+
+        /// Set if [`sys::SYNC_NEW_MESSAGE`] was passed, i.e. the message did not exist in the
+        /// previous [`SyncState`].
+        new: bool,
+
+        /// Set if [`sys::SYNC_ASSOCIATED`] was passed, i.e. this is an FAI message.
+        associated: bool,
+    },
+
+    /// Messages were deleted, from [`sys::IExchangeImportContentsChanges::ImportMessageDeletion`].
+    Deleted {
+        /// [`sys::PR_SOURCE_KEY`] of each message.
+        source_keys: Vec<Vec<u8>>,
+
+        /// Set if [`sys::SYNC_SOFT_DELETE`] was passed, i.e. the messages are still recoverable.
+        soft: bool,
+
+        /// Set if [`sys::SYNC_EXPIRY`] was passed, i.e. the messages were removed by a retention
+        /// policy rather than by a user.
+        expired: bool,
+    },
+
+    /// Read flags changed, from
+    /// [`sys::IExchangeImportContentsChanges::ImportPerUserReadStateChange`].
+    ReadState(Vec<ReadStateChange>),
+
+    /// A message was moved in from another folder, from
+    /// [`sys::IExchangeImportContentsChanges::ImportMessageMove`].
+    Moved {
+        /// [`sys::PR_SOURCE_KEY`] of the folder the message was moved from.
+        source_folder: Vec<u8>,
+
+        /// [`sys::PR_SOURCE_KEY`] of the message in the source folder.
+        source_message: Vec<u8>,
+
+        /// [`sys::PR_PREDECESSOR_CHANGE_LIST`] of the message in the source folder.
+        predecessor_change_list: Vec<u8>,
+
+        /// [`sys::PR_SOURCE_KEY`] of the message in this folder.
+        destination_message: Vec<u8>,
+
+        /// [`sys::PR_CHANGE_KEY`] of the message in this folder.
+        destination_change_key: Vec<u8>,
+    },
+}
+
+/// Receive each [`ContentChange`] from a [`ContentsSync`].
+///
+/// Any `FnMut(ContentChange) -> Result<()>` closure is also an importer. Returning an error stops
+/// the synchronization before the [`SyncState`] is updated, so the same changes are reported again
+/// on the next run.
+pub trait ContentsImporter {
+    /// Apply one change to the mirror.
+    fn import(&mut self, change: ContentChange) -> Result<()>;
+}
+
+impl<F> ContentsImporter for F
+where
+    F: FnMut(ContentChange) -> Result<()>,
+{
+    fn import(&mut self, change: ContentChange) -> Result<()> {
+        self(change)
+    }
+}
+
+/// Queue of changes which an importer collects during [`sys::IExchangeExportChanges::Synchronize`].
+pub(crate) type Queue<T> = Arc<Mutex<Vec<T>>>;
+
+type Changes = Queue<ContentChange>;
+
+/// Take the changes which were queued by the last step.
+pub(crate) fn take_changes<T>(queue: &Queue<T>) -> Vec<T> {
+    queue
+        .lock()
+        .map(|mut changes| mem::take(&mut *changes))
+        .unwrap_or_default()
+}
+
+/// Add a change to the queue.
+pub(crate) fn push_change<T>(queue: &Queue<T>, change: T) {
+    if let Ok(mut changes) = queue.lock() {
+        changes.push(change);
+    }
+}
+
+/// Call [`sys::IExchangeExportChanges::Config`] with the `state` and an importer.
+pub(crate) fn configure(
+    exporter: &sys::IExchangeExportChanges,
+    state: &SyncState,
+    flags: u32,
+    importer: &IUnknown,
+) -> Result<()> {
+    let stream = memory_stream(state.as_bytes())?;
+    unsafe {
+        exporter.Config(
+            &stream,
+            flags,
+            importer,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            0,
+        )
+    }
+}
+
+/// Run one step of [`sys::IExchangeExportChanges::Synchronize`], and return the raw [`HRESULT`].
+pub(crate) fn synchronize(
+    exporter: &sys::IExchangeExportChanges,
+    steps: &mut u32,
+    progress: &mut u32,
+) -> HRESULT {
+    // The generated wrapper turns SYNC_W_PROGRESS into S_OK, so call through the vtable to tell
+    // the two apart.
+    unsafe {
+        (Interface::vtable(exporter).Synchronize)(Interface::as_raw(exporter), steps, progress)
+    }
+}
+
+/// Test if the [`HRESULT`] from [`synchronize`] means the synchronization is complete.
+pub(crate) fn is_done(hr: HRESULT) -> Result<bool> {
+    if hr == SYNC_W_PROGRESS {
+        return Ok(false);
+    }
+    hr.ok()?;
+    Ok(true)
+}
+
+/// Read the current state of an exporter with [`sys::IExchangeExportChanges::UpdateState`].
+pub(crate) fn export_state(exporter: &sys::IExchangeExportChanges) -> Result<SyncState> {
+    let stream = memory_stream(&[])?;
+    unsafe {
+        exporter.UpdateState(&stream)?;
+    }
+    Ok(SyncState(read_whole_stream(&stream)?))
+}
+
+/// Implementation of [`sys::IExchangeImportContentsChanges`] which queues each change, so
+/// [`ContentsSync::step`] can hand them to the [`ContentsImporter`] after
+/// [`sys::IExchangeExportChanges::Synchronize`] returns, instead of calling back into Rust code
+/// across the COM boundary.
+#[implement(sys::IExchangeImportContentsChanges)]
+struct ContentsCollector {
+    changes: Changes,
+}
+
+impl ContentsCollector_Impl {
+    fn push(&self, change: ContentChange) {
+        push_change(&self.changes, change);
+    }
+}
+
+fn copy_bytes(cb: u32, pb: *const u8) -> Vec<u8> {
+    if cb == 0 || pb.is_null() {
+        Vec::new()
+    } else {
+        unsafe { slice::from_raw_parts(pb, cb as usize) }.to_vec()
+    }
+}
+
+/// Copy the property values an exporter passes to one of the `Import*Change` methods.
+pub(crate) fn copy_props(count: u32, values: *const sys::SPropValue) -> Vec<OwnedPropValue> {
+    if count == 0 || values.is_null() {
+        Vec::new()
+    } else {
+        unsafe { slice::from_raw_parts(values, count as usize) }
+            .iter()
+            .map(|value| OwnedPropValue::from(PropValue::from(value)))
+            .collect()
+    }
+}
+
+/// Copy the source keys an exporter passes to one of the `Import*Deletion` methods.
+pub(crate) fn copy_source_keys(list: *const sys::SBinaryArray) -> Vec<Vec<u8>> {
+    match unsafe { list.as_ref() } {
+        Some(list) if list.cValues > 0 && !list.lpbin.is_null() => {
+            unsafe { slice::from_raw_parts(list.lpbin, list.cValues as usize) }
+                .iter()
+                .map(|value| copy_bytes(value.cb, value.lpb))
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+impl sys::IExchangeImportContentsChanges_Impl for ContentsCollector_Impl {
+    fn GetLastError(
+        &self,
+        _hresult: HRESULT,
+        _ulflags: u32,
+        _lppmapierror: *mut *mut sys::MAPIERROR,
+    ) -> Result<()> {
+        Err(Error::from(sys::MAPI_E_NO_SUPPORT))
+    }
+
+    fn Config(&self, _lpstream: Option<&IStream>, _ulflags: u32) -> Result<()> {
+        Ok(())
+    }
+
+    fn UpdateState(&self, _lpstream: Option<&IStream>) -> Result<()> {
+        Ok(())
+    }
+
+    fn ImportMessageChange(
+        &self,
+        cpvalchanges: u32,
+        ppvalchanges: *mut sys::SPropValue,
+        ulflags: u32,
+        _lppmessage: *mut Option<sys::IMessage>,
+    ) -> Result<()> {
+        self.push(ContentChange::Changed {
+            props: copy_props(cpvalchanges, ppvalchanges),
+            new: ulflags & sys::SYNC_NEW_MESSAGE != 0,
+            associated: ulflags & sys::SYNC_ASSOCIATED != 0,
+        });
+        Err(Error::from(SYNC_E_IGNORE))
+    }
+
+    fn ImportMessageDeletion(
+        &self,
+        ulflags: u32,
+        lpsrcentrylist: *mut sys::SBinaryArray,
+    ) -> Result<()> {
+        self.push(ContentChange::Deleted {
+            source_keys: copy_source_keys(lpsrcentrylist),
+            soft: ulflags & sys::SYNC_SOFT_DELETE != 0,
+            expired: ulflags & sys::SYNC_EXPIRY != 0,
+        });
+        Ok(())
+    }
+
+    fn ImportPerUserReadStateChange(
+        &self,
+        celements: u32,
+        lpreadstate: *mut sys::READSTATE,
+    ) -> Result<()> {
+        if celements == 0 || lpreadstate.is_null() {
+            return Ok(());
+        }
+        let changes = unsafe { slice::from_raw_parts(lpreadstate, celements as usize) }
+            .iter()
+            .map(|value| ReadStateChange {
+                source_key: copy_bytes(value.cbSourceKey, value.pbSourceKey),
+                read: value.ulFlags & sys::SYNC_READ != 0,
+            })
+            .collect();
+        self.push(ContentChange::ReadState(changes));
+        Ok(())
+    }
+
+    fn ImportMessageMove(
+        &self,
+        cbsourcekeysrcfolder: u32,
+        pbsourcekeysrcfolder: *mut u8,
+        cbsourcekeysrcmessage: u32,
+        pbsourcekeysrcmessage: *mut u8,
+        cbpclmessage: u32,
+        pbpclmessage: *mut u8,
+        cbsourcekeydestmessage: u32,
+        pbsourcekeydestmessage: *mut u8,
+        cbchangenumdestmessage: u32,
+        pbchangenumdestmessage: *mut u8,
+    ) -> Result<()> {
+        self.push(ContentChange::Moved {
+            source_folder: copy_bytes(cbsourcekeysrcfolder, pbsourcekeysrcfolder),
+            source_message: copy_bytes(cbsourcekeysrcmessage, pbsourcekeysrcmessage),
+            predecessor_change_list: copy_bytes(cbpclmessage, pbpclmessage),
+            destination_message: copy_bytes(cbsourcekeydestmessage, pbsourcekeydestmessage),
+            destination_change_key: copy_bytes(cbchangenumdestmessage, pbchangenumdestmessage),
+        });
+        Ok(())
+    }
+}
+
+/// Incremental Change Synchronization (ICS) of the messages in a [`Folder`], with the
+/// [`sys::IExchangeExportChanges`] from [`sys::PR_CONTENTS_SYNCHRONIZER`].
+///
+/// Each run reports only what changed since the [`SyncState`] it started from, which is the only
+/// way to mirror a large mailbox without re-reading every contents table.
+///
+/// ```no_run
+/// # use outlook_mapi::*;
+/// # fn example(folder: &Folder, saved: Vec<u8>) -> windows_core::Result<Vec<u8>> {
+/// let state = folder.sync_contents(&SyncState::new(saved), |change: ContentChange| {
+///     println!("{change:?}");
+///     Ok(())
+/// })?;
+/// Ok(state.into_bytes())
+/// # }
+/// ```
+pub struct ContentsSync {
+    exporter: sys::IExchangeExportChanges,
+    changes: Changes,
+    steps: u32,
+    progress: u32,
+    done: bool,
+}
+
+impl ContentsSync {
+    /// Open the [`sys::PR_CONTENTS_SYNCHRONIZER`] on `folder` and configure it to start from
+    /// `state`.
+    pub fn new(folder: &Folder, state: &SyncState, flags: SyncFlags) -> Result<Self> {
+        let exporter: sys::IExchangeExportChanges = folder.folder().open_property(
+            PropTag(sys::PR_CONTENTS_SYNCHRONIZER),
+            OpenFlags::read_only(),
+        )?;
+        let changes = Changes::default();
+        let importer: sys::IExchangeImportContentsChanges = ContentsCollector {
+            changes: changes.clone(),
+        }
+        .into();
+        configure(&exporter, state, flags.into(), &importer.cast()?)?;
+        Ok(Self {
+            exporter,
+            changes,
+            steps: 0,
+            progress: 0,
+            done: false,
+        })
+    }
+
+    /// Run one step of [`sys::IExchangeExportChanges::Synchronize`], and pass the changes it
+    /// reported to `importer`. Returns `true` while there are more steps to run.
+    pub fn step(&mut self, importer: &mut dyn ContentsImporter) -> Result<bool> {
+        if self.done {
+            return Ok(false);
+        }
+
+        let hr = synchronize(&self.exporter, &mut self.steps, &mut self.progress);
+        for change in take_changes(&self.changes) {
+            importer.import(change)?;
+        }
+        self.done = is_done(hr)?;
+        Ok(!self.done)
+    }
+
+    /// Get the `(progress, steps)` reported by the last call to [`ContentsSync::step`].
+    pub fn progress(&self) -> (u32, u32) {
+        (self.progress, self.steps)
+    }
+
+    /// Get the [`SyncState`] which includes every change reported so far, with
+    /// [`sys::IExchangeExportChanges::UpdateState`].
+    pub fn state(&self) -> Result<SyncState> {
+        export_state(&self.exporter)
+    }
+
+    /// Run [`ContentsSync::step`] until the synchronization is complete, and return the new
+    /// [`SyncState`].
+    pub fn run(mut self, importer: &mut dyn ContentsImporter) -> Result<SyncState> {
+        while self.step(importer)? {}
+        self.state()
+    }
+}
+
+impl Folder {
+    /// Synchronize the normal messages in this folder with [`ContentsSync`] and the default
+    /// [`SyncFlags`], starting from `state`, and return the new [`SyncState`].
+    pub fn sync_contents<I>(&self, state: &SyncState, mut importer: I) -> Result<SyncState>
+    where
+        I: ContentsImporter,
+    {
+        ContentsSync::new(self, state, Default::default())?.run(&mut importer)
+    }
+}
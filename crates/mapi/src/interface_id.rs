@@ -0,0 +1,96 @@
+//! Define [`InterfaceId`].
+
+use crate::sys;
+use core::ptr;
+use windows::Win32::System::Com::IStream;
+use windows_core::*;
+
+/// Typed interface ID, for the MAPI methods which take an `LPIID` parameter and return the
+/// requested interface, such as [`sys::IMAPIProp::OpenProperty`] or
+/// [`sys::IMAPIContainer::OpenEntry`].
+///
+/// The wrappers in this crate infer the interface from the type they return whenever they can,
+/// e.g. [`crate::MapiProp::open_property`]. The constants here cover the cases where the caller
+/// needs to choose one at runtime, e.g. with [`crate::MapiProp::open_property_as`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InterfaceId(GUID);
+
+impl InterfaceId {
+    /// [`sys::IABContainer`]
+    pub const AB_CONTAINER: Self = Self::of::<sys::IABContainer>();
+
+    /// [`sys::IAddrBook`]
+    pub const ADDR_BOOK: Self = Self::of::<sys::IAddrBook>();
+
+    /// [`sys::IAttach`]
+    pub const ATTACH: Self = Self::of::<sys::IAttach>();
+
+    /// [`sys::IDistList`]
+    pub const DIST_LIST: Self = Self::of::<sys::IDistList>();
+
+    /// [`sys::IExchangeExportChanges`]
+    pub const EXCHANGE_EXPORT_CHANGES: Self = Self::of::<sys::IExchangeExportChanges>();
+
+    /// [`sys::IExchangeImportContentsChanges`]
+    pub const EXCHANGE_IMPORT_CONTENTS_CHANGES: Self =
+        Self::of::<sys::IExchangeImportContentsChanges>();
+
+    /// [`sys::IExchangeImportHierarchyChanges`]
+    pub const EXCHANGE_IMPORT_HIERARCHY_CHANGES: Self =
+        Self::of::<sys::IExchangeImportHierarchyChanges>();
+
+    /// [`sys::IExchangeModifyTable`]
+    pub const EXCHANGE_MODIFY_TABLE: Self = Self::of::<sys::IExchangeModifyTable>();
+
+    /// [`sys::IMailUser`]
+    pub const MAIL_USER: Self = Self::of::<sys::IMailUser>();
+
+    /// [`sys::IMAPIContainer`]
+    pub const MAPI_CONTAINER: Self = Self::of::<sys::IMAPIContainer>();
+
+    /// [`sys::IMAPIFolder`]
+    pub const MAPI_FOLDER: Self = Self::of::<sys::IMAPIFolder>();
+
+    /// [`sys::IMAPIProp`]
+    pub const MAPI_PROP: Self = Self::of::<sys::IMAPIProp>();
+
+    /// [`sys::IMAPITable`]
+    pub const MAPI_TABLE: Self = Self::of::<sys::IMAPITable>();
+
+    /// [`sys::IMessage`]
+    pub const MESSAGE: Self = Self::of::<sys::IMessage>();
+
+    /// [`sys::IMsgStore`]
+    pub const MSG_STORE: Self = Self::of::<sys::IMsgStore>();
+
+    /// [`sys::IPropData`]
+    pub const PROP_DATA: Self = Self::of::<sys::IPropData>();
+
+    /// [`IStream`]
+    pub const STREAM: Self = Self::of::<IStream>();
+
+    /// Get the [`InterfaceId`] of any COM interface type.
+    pub const fn of<T>() -> Self
+    where
+        T: Interface,
+    {
+        Self(T::IID)
+    }
+
+    /// Get the interface ID as a [`GUID`].
+    pub const fn guid(&self) -> &GUID {
+        &self.0
+    }
+
+    /// Get a `LPIID` pointer for the MAPI methods which take one. MAPI never writes through it,
+    /// even though the parameter is not `const`.
+    pub(crate) fn as_mut_ptr(&self) -> *mut GUID {
+        ptr::from_ref(&self.0) as *mut _
+    }
+}
+
+impl From<InterfaceId> for GUID {
+    fn from(value: InterfaceId) -> Self {
+        value.0
+    }
+}
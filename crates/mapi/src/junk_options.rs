@@ -0,0 +1,196 @@
+//! Define [`JunkOptions`].
+//!
+//! Outlook stores the junk e-mail settings on an associated message in the Inbox, with
+//! [`JUNK_RULE_MESSAGE_CLASS`] and a [`sys::PR_RULE_MSG_PROVIDER_W`] of [`JUNK_RULE_PROVIDER`].
+//! The properties are documented in
+//! [MS-OXCSPAM](https://learn.microsoft.com/en-us/openspecs/exchange_server_protocols/ms-oxcspam).
+
+use crate::{
+    mapi_prop::{get_one_prop, open_inbox, open_store_entry, set_props, SetPropValue},
+    sys, MapiProp, OpenFlags, PropTag, PropValue, PropValueData, RowSet, SizedSPropTagArray,
+};
+use core::ptr;
+use windows_core::*;
+
+/// Message class of the junk e-mail rule message.
+pub const JUNK_RULE_MESSAGE_CLASS: &str = "IPM.ExtendedRule.Message";
+
+/// [`sys::PR_RULE_MSG_PROVIDER_W`] of the junk e-mail rule message.
+pub const JUNK_RULE_PROVIDER: &str = "JunkEmailRule";
+
+/// `PidTagJunkIncludeContacts` is missing from the generated bindings.
+pub const PR_JUNK_INCLUDE_CONTACTS: u32 = 0x6100_0003;
+
+/// `PidTagJunkThreshold` is missing from the generated bindings.
+pub const PR_JUNK_THRESHOLD: u32 = 0x6101_0003;
+
+/// `PidTagJunkPermanentlyDelete` is missing from the generated bindings.
+pub const PR_JUNK_PERMANENTLY_DELETE: u32 = 0x6102_0003;
+
+/// `PidTagJunkAddRecipientsToSafeSendersList` is missing from the generated bindings.
+pub const PR_JUNK_ADD_RECIPS_TO_SSL: u32 = 0x6103_0003;
+
+/// `PidTagJunkPhishingEnableLinks` is missing from the generated bindings.
+pub const PR_JUNK_PHISHING_ENABLE_LINKS: u32 = 0x6107_000B;
+
+/// [`JunkOptions::threshold`] value which turns off junk filtering.
+pub const JUNK_THRESHOLD_NONE: u32 = 0xFFFF_FFFF;
+
+/// [`JunkOptions::threshold`] value for the low protection level.
+pub const JUNK_THRESHOLD_LOW: u32 = 0x0000_0006;
+
+/// [`JunkOptions::threshold`] value for the high protection level.
+pub const JUNK_THRESHOLD_HIGH: u32 = 0x0000_0003;
+
+/// [`JunkOptions::threshold`] value which only accepts mail from the safe lists.
+pub const JUNK_THRESHOLD_TRUSTED_ONLY: u32 = 0x8000_0000;
+
+/// Typed view of the junk e-mail rule message. Any property which is `None` is missing from the
+/// message, and will not be written by [`JunkOptions::write`].
+#[derive(Clone, Debug, Default)]
+pub struct JunkOptions {
+    /// `PidTagJunkIncludeContacts`: treat senders in the Contacts folder as safe senders.
+    pub include_contacts: Option<bool>,
+
+    /// `PidTagJunkThreshold`: one of the `JUNK_THRESHOLD_*` values.
+    pub threshold: Option<u32>,
+
+    /// `PidTagJunkPermanentlyDelete`: delete junk mail instead of moving it to the Junk E-mail
+    /// folder.
+    pub permanently_delete: Option<bool>,
+
+    /// `PidTagJunkAddRecipientsToSafeSendersList`: add recipients of outgoing mail to the safe
+    /// senders list.
+    pub add_recipients_to_safe_senders: Option<bool>,
+
+    /// `PidTagJunkPhishingEnableLinks`: leave links enabled in messages flagged as phishing.
+    pub phishing_enable_links: Option<bool>,
+
+    /// [`sys::PR_EXTENDED_RULE_MSG_CONDITION`], which holds the safe senders, safe recipients,
+    /// and blocked senders lists.
+    ///
+    /// The lists are serialized as a restriction in the extended rule format from
+    /// [MS-OXORULE](https://learn.microsoft.com/en-us/openspecs/exchange_server_protocols/ms-oxorule),
+    /// which this crate does not decode yet. The value is exposed as-is so it can be copied
+    /// between mailboxes, e.g. by a migration tool.
+    pub lists: Option<Vec<u8>>,
+}
+
+impl JunkOptions {
+    /// Read the junk e-mail settings from the Inbox of a store. Fails with
+    /// [`sys::MAPI_E_NOT_FOUND`] if the rule message has never been created.
+    pub fn read(store: &sys::IMsgStore) -> Result<Self> {
+        let message = open_junk_rule_message(store, OpenFlags::default())?;
+        let get_bool = |tag| {
+            get_one_prop(&message, tag, |value| match value {
+                PropValueData::Long(value) => Some(value != 0),
+                PropValueData::Boolean(value) => Some(value != 0),
+                _ => None,
+            })
+        };
+        Ok(Self {
+            include_contacts: get_bool(PR_JUNK_INCLUDE_CONTACTS)?,
+            threshold: get_one_prop(&message, PR_JUNK_THRESHOLD, |value| match value {
+                PropValueData::Long(value) => Some(value as u32),
+                _ => None,
+            })?,
+            permanently_delete: get_bool(PR_JUNK_PERMANENTLY_DELETE)?,
+            add_recipients_to_safe_senders: get_bool(PR_JUNK_ADD_RECIPS_TO_SSL)?,
+            phishing_enable_links: get_bool(PR_JUNK_PHISHING_ENABLE_LINKS)?,
+            lists: match message.get_binary(PropTag(sys::PR_EXTENDED_RULE_MSG_CONDITION)) {
+                Err(err) if err.code() == sys::MAPI_E_NOT_FOUND => None,
+                result => Some(result?),
+            },
+        })
+    }
+
+    /// Write the settings which are not `None` back to the junk e-mail rule message in the Inbox
+    /// of a store, and save it. Outlook reloads the rule the next time it syncs the Inbox.
+    pub fn write(&self, store: &sys::IMsgStore) -> Result<()> {
+        let message = open_junk_rule_message(store, OpenFlags::modify())?;
+        let longs = [
+            (
+                PR_JUNK_INCLUDE_CONTACTS,
+                self.include_contacts.map(i32::from),
+            ),
+            (PR_JUNK_THRESHOLD, self.threshold.map(|value| value as i32)),
+            (
+                PR_JUNK_PERMANENTLY_DELETE,
+                self.permanently_delete.map(i32::from),
+            ),
+            (
+                PR_JUNK_ADD_RECIPS_TO_SSL,
+                self.add_recipients_to_safe_senders.map(i32::from),
+            ),
+        ];
+        let mut values: Vec<_> = longs
+            .into_iter()
+            .filter_map(|(tag, value)| value.map(|value| (tag, SetPropValue::Long(value))))
+            .collect();
+        if let Some(value) = self.phishing_enable_links {
+            values.push((PR_JUNK_PHISHING_ENABLE_LINKS, SetPropValue::Boolean(value)));
+        }
+        if let Some(value) = self.lists.as_ref() {
+            values.push((
+                sys::PR_EXTENDED_RULE_MSG_CONDITION,
+                SetPropValue::Binary(value),
+            ));
+        }
+
+        set_props(&message, &values)?;
+        unsafe { message.SaveChanges(0) }
+    }
+}
+
+fn open_junk_rule_message(store: &sys::IMsgStore, flags: OpenFlags) -> Result<sys::IMessage> {
+    SizedSPropTagArray! { PropTagArray[3] }
+    let mut prop_tag_array = PropTagArray {
+        aulPropTag: [
+            sys::PR_ENTRYID,
+            sys::PR_MESSAGE_CLASS_W,
+            sys::PR_RULE_MSG_PROVIDER_W,
+        ],
+        ..Default::default()
+    };
+    let inbox = open_inbox(store)?;
+    let mut rows: RowSet = Default::default();
+    unsafe {
+        let table = inbox.GetContentsTable(sys::MAPI_ASSOCIATED | sys::MAPI_UNICODE)?;
+        sys::HrQueryAllRows(
+            &table,
+            prop_tag_array.as_mut_ptr(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            0,
+            rows.as_mut_ptr(),
+        )?;
+    }
+
+    let entry_id = rows
+        .into_iter()
+        .find_map(|row| {
+            let mut entry_id = None;
+            let mut message_class = false;
+            let mut provider = false;
+            for PropValue { tag, value } in row.iter() {
+                match (u32::from(tag), value) {
+                    (sys::PR_ENTRYID, PropValueData::Binary(value)) => {
+                        entry_id = Some(value.to_vec())
+                    }
+                    (sys::PR_MESSAGE_CLASS_W, PropValueData::Unicode(value)) => {
+                        message_class = unsafe { value.to_string() }
+                            .is_ok_and(|value| value.eq_ignore_ascii_case(JUNK_RULE_MESSAGE_CLASS))
+                    }
+                    (sys::PR_RULE_MSG_PROVIDER_W, PropValueData::Unicode(value)) => {
+                        provider = unsafe { value.to_string() }
+                            .is_ok_and(|value| value == JUNK_RULE_PROVIDER)
+                    }
+                    _ => {}
+                }
+            }
+            entry_id.filter(|_| message_class && provider)
+        })
+        .ok_or_else(|| Error::from(sys::MAPI_E_NOT_FOUND))?;
+
+    open_store_entry(store, &entry_id, flags)
+}
@@ -4,6 +4,13 @@
 //! All of the nested, unsafe types from
 //! [outlook-mapi-sys](https://crates.io/crates/outlook-mapi-sys) are re-exported as the `sys`
 //! module in this crate.
+//!
+//! The handful of types from the [Windows](https://github.com/microsoft/windows-rs) crates which
+//! appear in the public API of this crate are re-exported as the `types` module.
+//!
+//! The platform independent parsers from
+//! [outlook-mapi-core](https://crates.io/crates/outlook-mapi-core) are re-exported from the root
+//! module of this crate as well.
 
 /// Re-export all of the unsafe bindings from the
 /// [outlook-mapi-sys](https://crates.io/crates/outlook-mapi-sys) crate.
@@ -11,23 +18,178 @@ pub mod sys {
     pub use outlook_mapi_sys::Microsoft::Office::Outlook::MAPI::Win32::*;
 }
 
+/// Re-export the foreign types from the [Windows](https://github.com/microsoft/windows-rs) crates
+/// which appear in the public signatures of this crate, so callers do not need to add (and
+/// version-match) their own dependencies on `windows` and `windows-core`.
+pub mod types {
+    pub use windows::Win32::{
+        Foundation::{FILETIME, HWND},
+        System::Com::CY,
+    };
+    pub use windows_core::{Error, Result, GUID, HRESULT, PCSTR, PCWSTR};
+}
+
+pub use outlook_mapi_core::*;
+
+pub mod address_book;
+pub mod adr_list;
+pub mod archive;
+pub mod attachments;
+pub mod categories;
+pub mod copy_props;
+pub mod crawl;
+pub mod delegates;
+pub mod diagnostics;
+pub mod entry_list;
+pub mod error_messages;
+pub mod event_log;
+pub mod export;
+pub mod favorites;
+pub mod file_time;
+pub mod folder;
+pub mod folder_monitor;
+pub mod folder_move;
+pub mod folder_snapshot;
+pub mod folder_walker;
+pub mod follow_up;
+pub mod headers;
+pub mod hierarchy_sync;
+pub mod ics;
+pub mod interface_id;
+pub mod junk_options;
+pub mod limits;
+pub mod mapi_error;
 pub mod mapi_initialize;
 pub mod mapi_logon;
+pub mod mapi_prop;
 pub mod mapi_ptr;
-pub mod prop_tag;
+pub mod mapi_table;
+pub mod memory_prop;
+pub mod message;
+pub mod message_builder;
+pub mod msg_file;
+pub mod named_props;
+pub mod no_ui;
+pub mod notification_stream;
+pub mod notifications;
+pub mod open_flags;
+pub mod owned_prop_value;
+pub mod profiles;
+pub mod progress;
+pub mod prop_data;
+pub mod prop_problems;
+pub mod prop_tag_names;
 pub mod prop_value;
+pub mod prop_value_builder;
+pub mod property_stream;
+pub mod pst_store;
+pub mod receipts;
+pub mod recipients;
+pub mod redact;
+pub mod reminders;
+pub mod render;
+pub mod restriction;
 pub mod row;
+pub mod row_guard;
 pub mod row_set;
+pub mod search_folder;
+pub mod send_later;
+pub mod session_pool;
+pub mod size_estimate;
+pub mod sized_buffers;
 pub mod sized_types;
+pub mod sort_locale;
+pub mod special_folders;
+pub mod store;
+pub mod store_capabilities;
+pub mod table_query;
+pub mod tnef;
+pub mod transport_headers;
+pub mod typed_view;
+pub mod ui_param;
+pub mod voting;
+pub mod watchdog;
+pub mod weak_refs;
 
+pub use address_book::*;
+pub use adr_list::*;
+pub use archive::*;
+pub use attachments::*;
+pub use categories::*;
+pub use copy_props::*;
+pub use crawl::*;
+pub use delegates::*;
+pub use diagnostics::*;
+pub use entry_list::*;
+pub use error_messages::*;
+pub use event_log::*;
+pub use export::*;
+pub use favorites::*;
+pub use file_time::*;
+pub use folder::*;
+pub use folder_monitor::*;
+pub use folder_move::*;
+pub use folder_snapshot::*;
+pub use folder_walker::*;
+pub use follow_up::*;
+pub use headers::*;
+pub use hierarchy_sync::*;
+pub use ics::*;
+pub use interface_id::*;
+pub use junk_options::*;
+pub use limits::*;
+pub use mapi_error::*;
 pub use mapi_initialize::*;
 pub use mapi_logon::*;
+pub use mapi_prop::*;
 pub use mapi_ptr::*;
-pub use prop_tag::*;
+pub use mapi_table::*;
+pub use memory_prop::*;
+pub use message::*;
+pub use message_builder::*;
+pub use msg_file::*;
+pub use named_props::*;
+pub use no_ui::*;
+pub use notification_stream::*;
+pub use notifications::*;
+pub use open_flags::*;
+pub use owned_prop_value::*;
+pub use profiles::*;
+pub use progress::*;
+pub use prop_data::*;
+pub use prop_problems::*;
+pub use prop_tag_names::*;
 pub use prop_value::*;
+pub use prop_value_builder::*;
+pub use property_stream::*;
+pub use pst_store::*;
+pub use receipts::*;
+pub use recipients::*;
+pub use redact::*;
+pub use reminders::*;
+pub use render::*;
+pub use restriction::*;
 pub use row::*;
+pub use row_guard::*;
 pub use row_set::*;
+pub use search_folder::*;
+pub use send_later::*;
+pub use session_pool::*;
+pub use size_estimate::*;
+pub use sized_buffers::*;
 pub use sized_types::*;
+pub use sort_locale::*;
+pub use special_folders::*;
+pub use store::*;
+pub use store_capabilities::*;
+pub use table_query::*;
+pub use tnef::*;
+pub use transport_headers::*;
+pub use typed_view::*;
+pub use ui_param::*;
+pub use voting::*;
+pub use watchdog::*;
+pub use weak_refs::*;
 
 pub fn is_outlook_mapi_installed() -> bool {
     outlook_mapi_sys::ensure_olmapi32().is_ok()
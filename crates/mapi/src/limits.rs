@@ -0,0 +1,191 @@
+//! Define [`Limits`] and [`LimitError`].
+
+use crate::{sys, DEFAULT_MAX_PROP_VALUES};
+use core::{
+    fmt,
+    sync::atomic::{AtomicU32, Ordering},
+};
+use windows_core::*;
+
+static MAX_BINARY_SIZE: AtomicU32 = AtomicU32::new(u32::MAX);
+static MAX_VALUES: AtomicU32 = AtomicU32::new(DEFAULT_MAX_PROP_VALUES);
+static MAX_ROWS: AtomicU32 = AtomicU32::new(u32::MAX);
+
+/// Error returned when data from a provider exceeds one of the [`Limits`]. It converts to an
+/// [`Error`] with [`sys::MAPI_E_TOO_BIG`] and the [`fmt::Display`] text as the message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimitError {
+    /// A [`sys::PT_BINARY`] value, or one of the values in a [`sys::PT_MV_BINARY`] property, is
+    /// larger than [`Limits::max_binary_size`].
+    BinaryTooBig {
+        /// Size of the value in bytes.
+        size: u32,
+
+        /// The [`Limits::max_binary_size`] in effect.
+        max: u32,
+    },
+
+    /// A multi-valued property has more values than [`Limits::max_values`].
+    TooManyValues {
+        /// Number of values in the property.
+        count: u32,
+
+        /// The [`Limits::max_values`] in effect.
+        max: u32,
+    },
+
+    /// A table query returned more rows than [`Limits::max_rows`].
+    TooManyRows {
+        /// The [`Limits::max_rows`] in effect.
+        max: u32,
+    },
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BinaryTooBig { size, max } => {
+                write!(f, "binary value of {size} bytes exceeds the limit of {max}")
+            }
+            Self::TooManyValues { count, max } => {
+                write!(f, "{count} property values exceed the limit of {max}")
+            }
+            Self::TooManyRows { max } => write!(f, "table query exceeds the limit of {max} rows"),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl From<LimitError> for Error {
+    fn from(value: LimitError) -> Self {
+        Error::new(sys::MAPI_E_TOO_BIG, value.to_string())
+    }
+}
+
+/// Crate-wide limits on the size of the data this crate will parse, for services which process
+/// untrusted mailbox content and would rather fail on a hostile or corrupt value than allocate or
+/// copy an unbounded amount of memory.
+///
+/// [`crate::PropValue::from`] checks [`Limits::max_binary_size`] and [`Limits::max_values`], and
+/// [`crate::TableRows`] checks [`Limits::max_rows`] for each query. The [`Default`] limits only
+/// cap the number of values at [`DEFAULT_MAX_PROP_VALUES`], which no well-behaved provider should
+/// reach.
+///
+/// ```no_run
+/// # use outlook_mapi::*;
+/// Limits {
+///     max_binary_size: 16 * 1024 * 1024,
+///     max_rows: 100_000,
+///     ..Default::default()
+/// }
+/// .apply();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// Largest [`sys::PT_BINARY`] value, in bytes.
+    pub max_binary_size: u32,
+
+    /// Largest number of values in a multi-valued property.
+    pub max_values: u32,
+
+    /// Largest number of rows a single [`crate::TableQuery`] or [`crate::MapiTable::iter_rows`]
+    /// will return.
+    pub max_rows: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_binary_size: u32::MAX,
+            max_values: DEFAULT_MAX_PROP_VALUES,
+            max_rows: u32::MAX,
+        }
+    }
+}
+
+impl Limits {
+    /// Get the limits which are currently in effect.
+    pub fn current() -> Self {
+        Self {
+            max_binary_size: MAX_BINARY_SIZE.load(Ordering::Relaxed),
+            max_values: MAX_VALUES.load(Ordering::Relaxed),
+            max_rows: MAX_ROWS.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Make these the limits for the whole process. Values and queries which were already
+    /// checked are not affected.
+    pub fn apply(self) {
+        MAX_BINARY_SIZE.store(self.max_binary_size, Ordering::Relaxed);
+        MAX_VALUES.store(self.max_values, Ordering::Relaxed);
+        MAX_ROWS.store(self.max_rows, Ordering::Relaxed);
+    }
+
+    /// Check the size of a binary value against [`Limits::max_binary_size`].
+    pub fn check_binary_size(&self, size: u32) -> core::result::Result<(), LimitError> {
+        if size > self.max_binary_size {
+            Err(LimitError::BinaryTooBig {
+                size,
+                max: self.max_binary_size,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check the number of values in a multi-valued property against [`Limits::max_values`].
+    pub fn check_values(&self, count: u32) -> core::result::Result<(), LimitError> {
+        if count > self.max_values {
+            Err(LimitError::TooManyValues {
+                count,
+                max: self.max_values,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check the number of rows a query has returned so far against [`Limits::max_rows`].
+    pub fn check_rows(&self, count: usize) -> core::result::Result<(), LimitError> {
+        if count > self.max_rows as usize {
+            Err(LimitError::TooManyRows { max: self.max_rows })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_limits() {
+        let limits = Limits {
+            max_binary_size: 4,
+            max_values: 2,
+            max_rows: 10,
+        };
+        assert_eq!(limits.check_binary_size(4), Ok(()));
+        assert_eq!(
+            limits.check_binary_size(5),
+            Err(LimitError::BinaryTooBig { size: 5, max: 4 })
+        );
+        assert_eq!(limits.check_values(2), Ok(()));
+        assert_eq!(
+            limits.check_values(3),
+            Err(LimitError::TooManyValues { count: 3, max: 2 })
+        );
+        assert_eq!(limits.check_rows(10), Ok(()));
+        assert_eq!(
+            limits.check_rows(11),
+            Err(LimitError::TooManyRows { max: 10 })
+        );
+
+        let err = Error::from(LimitError::TooManyValues { count: 3, max: 2 });
+        assert_eq!(err.code(), sys::MAPI_E_TOO_BIG);
+        assert_eq!(err.message(), "3 property values exceed the limit of 2");
+        assert_eq!(Limits::default().max_values, DEFAULT_MAX_PROP_VALUES);
+    }
+}
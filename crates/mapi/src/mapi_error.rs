@@ -0,0 +1,326 @@
+//! Define [`MapiError`], [`MapiErrorExt`], and [`Facility`].
+
+use crate::sys;
+use core::fmt;
+use windows_core::*;
+
+/// Facility code of an [`HRESULT`], which identifies the subsystem that defined the error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Facility {
+    /// `FACILITY_NULL`, used by generic errors like `E_FAIL` and `E_INVALIDARG`.
+    Null,
+
+    /// `FACILITY_RPC`, for errors from the RPC runtime or COM marshaling.
+    Rpc,
+
+    /// `FACILITY_DISPATCH`
+    Dispatch,
+
+    /// `FACILITY_STORAGE`, for errors from structured storage and `IStream`.
+    Storage,
+
+    /// `FACILITY_ITF`, which is interface specific. All of the `MAPI_E_*` and `MAPI_W_*` codes use
+    /// this facility.
+    Interface,
+
+    /// `FACILITY_WIN32`, for Win32 error codes wrapped with `HRESULT_FROM_WIN32`.
+    Win32,
+
+    /// `FACILITY_WINDOWS`
+    Windows,
+
+    /// Any other value.
+    Other(u16),
+}
+
+impl From<u16> for Facility {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => Self::Null,
+            1 => Self::Rpc,
+            2 => Self::Dispatch,
+            3 => Self::Storage,
+            4 => Self::Interface,
+            7 => Self::Win32,
+            8 => Self::Windows,
+            value => Self::Other(value),
+        }
+    }
+}
+
+/// `RPC_E_CALL_REJECTED`
+const RPC_E_CALL_REJECTED: HRESULT = HRESULT(0x8001_0001_u32 as _);
+
+/// `RPC_E_DISCONNECTED`
+const RPC_E_DISCONNECTED: HRESULT = HRESULT(0x8001_0108_u32 as _);
+
+/// `RPC_E_SERVERCALL_RETRYLATER`
+const RPC_E_SERVERCALL_RETRYLATER: HRESULT = HRESULT(0x8001_010A_u32 as _);
+
+/// Win32 error codes which [`MapiErrorExt::is_transient`] accepts when they are wrapped in an
+/// [`HRESULT`]: `ERROR_SEM_TIMEOUT`, `WAIT_TIMEOUT`, `ERROR_TIMEOUT`, `RPC_S_SERVER_UNAVAILABLE`,
+/// `RPC_S_SERVER_TOO_BUSY`, and `RPC_S_CALL_FAILED`.
+const TRANSIENT_WIN32_CODES: [u32; 6] = [121, 258, 1460, 1722, 1723, 1726];
+
+/// Classify the [`HRESULT`] behind an error, so retry layers can branch on the kind of error
+/// rather than comparing raw codes.
+///
+/// This is implemented for both [`Error`] and [`HRESULT`].
+///
+/// ```no_run
+/// # use outlook_mapi::*;
+/// # fn example(table: &MapiTable) -> windows_core::Result<RowSet> {
+/// loop {
+///     match table.query_rows(100) {
+///         Err(err) if err.is_transient() => std::thread::sleep(std::time::Duration::from_secs(1)),
+///         result => return result,
+///     }
+/// }
+/// # }
+/// ```
+pub trait MapiErrorExt {
+    /// Get the [`HRESULT`].
+    fn hresult(&self) -> HRESULT;
+
+    /// Get the [`Facility`] of the [`HRESULT`].
+    fn facility(&self) -> Facility {
+        Facility::from(((self.hresult().0 as u32 >> 16) & 0x1FFF) as u16)
+    }
+
+    /// Get the Win32 error code, if the [`HRESULT`] is a failure in [`Facility::Win32`].
+    fn win32_code(&self) -> Option<u32> {
+        let hresult = self.hresult();
+        (hresult.is_err() && self.facility() == Facility::Win32)
+            .then_some(hresult.0 as u32 & 0xFFFF)
+    }
+
+    /// Test if the error is likely to go away if the call is retried later, e.g. because the
+    /// server was busy, the network connection dropped, or the call timed out:
+    /// [`sys::MAPI_E_NETWORK_ERROR`], [`sys::MAPI_E_BUSY`], [`sys::MAPI_E_TIMEOUT`], and the
+    /// equivalent RPC and Win32 errors.
+    fn is_transient(&self) -> bool {
+        let hresult = self.hresult();
+        if [
+            sys::MAPI_E_NETWORK_ERROR,
+            sys::MAPI_E_BUSY,
+            sys::MAPI_E_TIMEOUT,
+            RPC_E_CALL_REJECTED,
+            RPC_E_DISCONNECTED,
+            RPC_E_SERVERCALL_RETRYLATER,
+        ]
+        .contains(&hresult)
+        {
+            return true;
+        }
+        self.win32_code()
+            .is_some_and(|code| TRANSIENT_WIN32_CODES.contains(&code))
+    }
+}
+
+impl MapiErrorExt for HRESULT {
+    fn hresult(&self) -> HRESULT {
+        *self
+    }
+}
+
+impl MapiErrorExt for Error {
+    fn hresult(&self) -> HRESULT {
+        self.code()
+    }
+}
+
+macro_rules! mapi_errors {
+    ($($variant:ident => $code:ident, $text:literal;)*) => {
+        /// Well-known MAPI [`HRESULT`] codes, so callers can match on the kind of error instead of
+        /// comparing raw codes. Anything else is kept in [`MapiError::Other`].
+        ///
+        /// Convert from an [`Error`] with [`MapiError::from`], and back with [`Error::from`].
+        ///
+        /// ```no_run
+        /// # use outlook_mapi::*;
+        /// # fn example(table: &MapiTable) -> windows_core::Result<()> {
+        /// match table.query_rows(100).map_err(MapiError::from) {
+        ///     Ok(_rows) => {}
+        ///     Err(MapiError::TableTooBig) => {}
+        ///     Err(err) => return Err(err.into()),
+        /// }
+        /// # Ok(())
+        /// # }
+        /// ```
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub enum MapiError {
+            $(
+                #[doc = concat!("[`sys::", stringify!($code), "`]")]
+                $variant,
+            )*
+
+            /// Any other [`HRESULT`].
+            Other(HRESULT),
+        }
+
+        impl From<HRESULT> for MapiError {
+            fn from(value: HRESULT) -> Self {
+                $(
+                    if value == sys::$code {
+                        return Self::$variant;
+                    }
+                )*
+                Self::Other(value)
+            }
+        }
+
+        impl MapiErrorExt for MapiError {
+            fn hresult(&self) -> HRESULT {
+                match self {
+                    $(Self::$variant => sys::$code,)*
+                    Self::Other(hresult) => *hresult,
+                }
+            }
+        }
+
+        impl fmt::Display for MapiError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $(Self::$variant => write!(f, "{} ({})", $text, stringify!($code)),)*
+                    Self::Other(hresult) => write!(f, "MAPI call failed (0x{:08X})", hresult.0),
+                }
+            }
+        }
+    };
+}
+
+mapi_errors! {
+    CallFailed => MAPI_E_CALL_FAILED, "The call failed";
+    NotEnoughMemory => MAPI_E_NOT_ENOUGH_MEMORY, "Not enough memory";
+    InvalidParameter => MAPI_E_INVALID_PARAMETER, "Invalid parameter";
+    InterfaceNotSupported => MAPI_E_INTERFACE_NOT_SUPPORTED, "Interface not supported";
+    NoAccess => MAPI_E_NO_ACCESS, "Access denied";
+    NoSupport => MAPI_E_NO_SUPPORT, "Not supported by the provider";
+    BadCharWidth => MAPI_E_BAD_CHARWIDTH, "Unicode strings are not supported";
+    StringTooLong => MAPI_E_STRING_TOO_LONG, "String is too long";
+    UnknownFlags => MAPI_E_UNKNOWN_FLAGS, "Unknown flags";
+    InvalidEntryId => MAPI_E_INVALID_ENTRYID, "Invalid entry ID";
+    InvalidObject => MAPI_E_INVALID_OBJECT, "Invalid object";
+    ObjectChanged => MAPI_E_OBJECT_CHANGED, "The object was changed by someone else";
+    ObjectDeleted => MAPI_E_OBJECT_DELETED, "The object was deleted";
+    Busy => MAPI_E_BUSY, "The server is busy";
+    NotEnoughDisk => MAPI_E_NOT_ENOUGH_DISK, "Not enough disk space";
+    NotEnoughResources => MAPI_E_NOT_ENOUGH_RESOURCES, "Not enough resources";
+    NotFound => MAPI_E_NOT_FOUND, "Not found";
+    VersionMismatch => MAPI_E_VERSION, "Version mismatch";
+    LogonFailed => MAPI_E_LOGON_FAILED, "Logon failed";
+    SessionLimit => MAPI_E_SESSION_LIMIT, "Too many sessions";
+    UserCancel => MAPI_E_USER_CANCEL, "Cancelled by the user";
+    UnableToAbort => MAPI_E_UNABLE_TO_ABORT, "Unable to abort the operation";
+    NetworkError => MAPI_E_NETWORK_ERROR, "Network error";
+    DiskError => MAPI_E_DISK_ERROR, "Disk error";
+    TooComplex => MAPI_E_TOO_COMPLEX, "The operation is too complex";
+    BadColumn => MAPI_E_BAD_COLUMN, "Bad column";
+    Computed => MAPI_E_COMPUTED, "The property is computed and read-only";
+    CorruptData => MAPI_E_CORRUPT_DATA, "Corrupt data";
+    Unconfigured => MAPI_E_UNCONFIGURED, "The provider is not configured";
+    FailOneProvider => MAPI_E_FAILONEPROVIDER, "One of the providers failed";
+    UnknownCodePage => MAPI_E_UNKNOWN_CPID, "Unknown code page";
+    UnknownLocale => MAPI_E_UNKNOWN_LCID, "Unknown locale";
+    PasswordChangeRequired => MAPI_E_PASSWORD_CHANGE_REQUIRED, "Password change required";
+    PasswordExpired => MAPI_E_PASSWORD_EXPIRED, "Password expired";
+    EndOfSession => MAPI_E_END_OF_SESSION, "The session has ended";
+    UnknownEntryId => MAPI_E_UNKNOWN_ENTRYID, "Unknown entry ID";
+    MissingRequiredColumn => MAPI_E_MISSING_REQUIRED_COLUMN, "Missing required column";
+    CorruptStore => MAPI_E_CORRUPT_STORE, "The store is corrupt";
+    NotInitialized => MAPI_E_NOT_INITIALIZED, "MAPI is not initialized";
+    Timeout => MAPI_E_TIMEOUT, "The operation timed out";
+    TableEmpty => MAPI_E_TABLE_EMPTY, "The table is empty";
+    TableTooBig => MAPI_E_TABLE_TOO_BIG, "The table is too big";
+    InvalidBookmark => MAPI_E_INVALID_BOOKMARK, "Invalid bookmark";
+    Wait => MAPI_E_WAIT, "Wait for the operation to finish";
+    Cancel => MAPI_E_CANCEL, "The operation was cancelled";
+    NotMe => MAPI_E_NOT_ME, "Not handled by this provider";
+    Collision => MAPI_E_COLLISION, "An object with the same name already exists";
+    NotInQueue => MAPI_E_NOT_IN_QUEUE, "The message is not in the outgoing queue";
+    Submitted => MAPI_E_SUBMITTED, "The message has already been submitted";
+    HasFolders => MAPI_E_HAS_FOLDERS, "The folder has subfolders";
+    HasMessages => MAPI_E_HAS_MESSAGES, "The folder has messages";
+    FolderCycle => MAPI_E_FOLDER_CYCLE, "A folder cannot be moved into its own subfolder";
+    AmbiguousRecipient => MAPI_E_AMBIGUOUS_RECIP, "The recipient name is ambiguous";
+    NoRecipients => MAPI_E_NO_RECIPIENTS, "The message has no recipients";
+    PartialCompletion => MAPI_W_PARTIAL_COMPLETION, "The operation only partially completed";
+    ErrorsReturned => MAPI_W_ERRORS_RETURNED, "Some properties could not be returned";
+    PositionChanged => MAPI_W_POSITION_CHANGED, "The table position changed";
+    ApproxCount => MAPI_W_APPROX_COUNT, "The count is approximate";
+    CancelMessage => MAPI_W_CANCEL_MESSAGE, "The message was cancelled";
+}
+
+impl From<Error> for MapiError {
+    fn from(value: Error) -> Self {
+        Self::from(value.code())
+    }
+}
+
+impl From<MapiError> for Error {
+    fn from(value: MapiError) -> Self {
+        Error::from(value.hresult())
+    }
+}
+
+impl std::error::Error for MapiError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, E_FAIL};
+
+    #[test]
+    fn facility() {
+        assert_eq!(E_FAIL.facility(), Facility::Null);
+        assert_eq!(sys::MAPI_E_NOT_FOUND.facility(), Facility::Interface);
+        assert_eq!(RPC_E_DISCONNECTED.facility(), Facility::Rpc);
+        assert_eq!(
+            HRESULT::from_win32(ERROR_ACCESS_DENIED.0).facility(),
+            Facility::Win32
+        );
+    }
+
+    #[test]
+    fn win32_code() {
+        assert_eq!(
+            HRESULT::from_win32(ERROR_ACCESS_DENIED.0).win32_code(),
+            Some(ERROR_ACCESS_DENIED.0)
+        );
+        assert_eq!(sys::MAPI_E_NOT_FOUND.win32_code(), None);
+        assert_eq!(Error::from(E_FAIL).win32_code(), None);
+    }
+
+    #[test]
+    fn is_transient() {
+        assert!(sys::MAPI_E_NETWORK_ERROR.is_transient());
+        assert!(Error::from(sys::MAPI_E_BUSY).is_transient());
+        assert!(RPC_E_SERVERCALL_RETRYLATER.is_transient());
+        assert!(HRESULT::from_win32(1722).is_transient());
+        assert!(!sys::MAPI_E_NOT_FOUND.is_transient());
+        assert!(!HRESULT::from_win32(ERROR_ACCESS_DENIED.0).is_transient());
+    }
+
+    #[test]
+    fn mapi_error() {
+        assert_eq!(MapiError::from(sys::MAPI_E_NOT_FOUND), MapiError::NotFound);
+        assert_eq!(
+            MapiError::from(Error::from(sys::MAPI_E_TABLE_TOO_BIG)),
+            MapiError::TableTooBig
+        );
+        assert_eq!(
+            MapiError::from(sys::MAPI_W_PARTIAL_COMPLETION).hresult(),
+            sys::MAPI_W_PARTIAL_COMPLETION
+        );
+        assert_eq!(MapiError::from(E_FAIL), MapiError::Other(E_FAIL));
+        assert_eq!(
+            Error::from(MapiError::NoAccess).code(),
+            sys::MAPI_E_NO_ACCESS
+        );
+        assert!(MapiError::Busy.is_transient());
+        assert_eq!(
+            MapiError::UserCancel.to_string(),
+            "Cancelled by the user (MAPI_E_USER_CANCEL)"
+        );
+    }
+}
@@ -2,7 +2,10 @@
 
 use crate::sys;
 use core::ptr;
-use std::sync::Arc;
+use std::{
+    sync::{Arc, Mutex, Weak},
+    thread::{self, ThreadId},
+};
 use windows_core::*;
 
 /// Set of flags that can be passed to [`sys::MAPIInitialize`] through the
@@ -41,27 +44,95 @@ impl From<InitializeFlags> for u32 {
     }
 }
 
+/// The shared [`Initialize`] handed out by [`Initialize::acquire`]. The lock is also held while
+/// calling [`sys::MAPIInitialize`] and [`sys::MAPIUninitialize`], so they never overlap.
+static SHARED: Mutex<Weak<Initialize>> = Mutex::new(Weak::new());
+
 /// Call [`sys::MAPIInitialize`] in the constructor, and balance it with a call to
 /// [`sys::MAPIUninitialize`] in the destructor.
-pub struct Initialize();
+///
+/// Prefer [`Initialize::acquire`] over [`Initialize::new`] in a library or anywhere else which
+/// might not own the whole process: it shares one [`Initialize`] across the process, so MAPI is
+/// only initialized by the first reference and uninitialized when the last one is dropped.
+///
+/// [`sys::MAPIUninitialize`] must run on the same thread as [`sys::MAPIInitialize`], which also
+/// initialized COM on that thread unless the flags included [`sys::MAPI_NO_COINIT`]. References
+/// may be shared with other threads, but the last one must be dropped on [`Initialize::thread`].
+/// If it is dropped on any other thread, the destructor does not call [`sys::MAPIUninitialize`],
+/// and MAPI stays initialized on the original thread rather than leaving COM unbalanced.
+pub struct Initialize {
+    multithread_notifications: bool,
+    flags: u32,
+    thread: ThreadId,
+}
 
 impl Initialize {
-    /// Call [`sys::MAPIInitialize`] with the specified flags in [`InitializeFlags`].
+    /// Call [`sys::MAPIInitialize`] with the specified flags in [`InitializeFlags`]. Each call
+    /// initializes MAPI again, e.g. once per worker thread in [`crate::SessionPool`].
     pub fn new(flags: InitializeFlags) -> Result<Arc<Self>> {
+        let _lock = SHARED.lock().unwrap_or_else(|err| err.into_inner());
+        Self::initialize(flags)
+    }
+
+    /// Get the process-wide [`Initialize`], calling [`sys::MAPIInitialize`] with `flags` only if
+    /// there is no live reference to it yet. Otherwise, `flags` are ignored and the existing one is
+    /// returned, so check [`Initialize::flags`] if the caller depends on a particular flag.
+    pub fn acquire(flags: InitializeFlags) -> Result<Arc<Self>> {
+        let mut shared = SHARED.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(initialized) = shared.upgrade() {
+            return Ok(initialized);
+        }
+
+        let initialized = Self::initialize(flags)?;
+        *shared = Arc::downgrade(&initialized);
+        Ok(initialized)
+    }
+
+    /// Call [`sys::MAPIInitialize`] while holding the lock on [`SHARED`].
+    fn initialize(flags: InitializeFlags) -> Result<Arc<Self>> {
+        let multithread_notifications = flags.multithread_notifications;
+        let flags = flags.into();
         unsafe {
             sys::MAPIInitialize(ptr::from_mut(&mut sys::MAPIINIT {
                 ulVersion: sys::MAPI_INIT_VERSION,
-                ulFlags: flags.into(),
+                ulFlags: flags,
             }) as *mut _)?;
         }
 
-        Ok(Arc::new(Self()))
+        Ok(Arc::new(Self {
+            multithread_notifications,
+            flags,
+            thread: thread::current().id(),
+        }))
+    }
+
+    /// Get the [`sys::MAPIINIT::ulFlags`] which were passed to [`sys::MAPIInitialize`].
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Get the thread which called [`sys::MAPIInitialize`]. Unless the flags included
+    /// [`sys::MAPI_NO_COINIT`], that is the thread which COM was initialized on.
+    pub fn thread(&self) -> ThreadId {
+        self.thread
+    }
+
+    /// Test if MAPI was initialized with [`InitializeFlags::multithread_notifications`], in which
+    /// case notifications are delivered on a separate MAPI thread instead of through window
+    /// messages on the thread which registered for them.
+    pub fn multithread_notifications(&self) -> bool {
+        self.multithread_notifications
     }
 }
 
 impl Drop for Initialize {
-    /// Call [`sys::MAPIUninitialize`].
+    /// Call [`sys::MAPIUninitialize`], if this is the thread which called
+    /// [`sys::MAPIInitialize`].
     fn drop(&mut self) {
+        let _lock = SHARED.lock().unwrap_or_else(|err| err.into_inner());
+        if thread::current().id() != self.thread {
+            return;
+        }
         unsafe {
             sys::MAPIUninitialize();
         }
@@ -1,7 +1,10 @@
-//! Define [`Logon`] and [`LogonFlags`].
+//! Define [`Logon`], [`LogonFlags`], and [`MapiUriTarget`].
 
-use crate::{sys, Initialize};
-use std::{iter, ptr, sync::Arc};
+use crate::{
+    mapi_prop::open_store_entry, sys, EventLog, Initialize, InterfaceId, MapiUri, NoUi, OpenFlags,
+    OpenStoreFlags, PropValue, PropValueData, RowSet, SizedSPropTagArray, Store, UiParam,
+};
+use std::{iter, marker::PhantomData, ptr, sync::Arc};
 use windows::Win32::Foundation::*;
 use windows_core::*;
 
@@ -129,21 +132,49 @@ impl From<LogonFlags> for u32 {
 /// This helper also holds onto an `Arc<Initialize>`, which ensures that there are balanced calls
 /// to [`sys::MAPIInitialize`] and [`sys::MAPIUninitialize`] around every [`Logon`] object that
 /// shares a reference to that instance of [`Initialize`].
+///
+/// # Concurrency
+///
+/// A [`Logon`] is neither [`Send`] nor [`Sync`]. The [`sys::IMAPISession`] and every object opened
+/// through it belong to the thread which called [`sys::MAPILogonEx`], and the wrappers in this
+/// crate assume they are never called concurrently. To share a session between several owners on
+/// the same thread, wrap it in an [`std::rc::Rc`]. To use the same profile from another thread,
+/// call [`Logon::new`] on that thread; passing [`LogonFlags::allow_others`] on the first logon
+/// lets later logons share the underlying MAPI session instead of creating a new one.
+///
+/// ```compile_fail
+/// fn assert_send<T: Send>() {}
+/// assert_send::<outlook_mapi::Logon>();
+/// ```
+///
+/// ```compile_fail
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<outlook_mapi::Logon>();
+/// ```
 pub struct Logon {
-    /// Access the [`sys::IMAPISession`].
-    pub session: sys::IMAPISession,
+    session: sys::IMAPISession,
+    initialized: Arc<Initialize>,
 
-    _initialized: Arc<Initialize>,
+    /// Keep [`Logon`] `!Send` and `!Sync` even if the bindings ever mark the interfaces as agile.
+    _not_send_sync: PhantomData<*const ()>,
 }
 
 impl Logon {
     pub fn new(
         initialized: Arc<Initialize>,
-        ui_param: HWND,
+        ui_param: impl Into<UiParam>,
         profile_name: Option<&str>,
         password: Option<&str>,
-        flags: LogonFlags,
+        mut flags: LogonFlags,
     ) -> Result<Self> {
+        NoUi::check_flag("MAPILogonEx", "MAPI_LOGON_UI", flags.logon_ui)?;
+        NoUi::check_flag(
+            "MAPILogonEx",
+            "MAPI_SERVICE_UI_ALWAYS",
+            flags.service_ui_always,
+        )?;
+        flags.no_mail |= NoUi::is_enabled();
+        let ui_param = NoUi::ui_param(ui_param.into());
         let mut profile_name: Option<Vec<_>> =
             profile_name.map(|value| value.bytes().chain(iter::once(0)).collect());
         let profile_name = profile_name
@@ -158,19 +189,163 @@ impl Logon {
             .unwrap_or(ptr::null_mut());
 
         Ok(Self {
-            _initialized: initialized,
+            initialized,
+            _not_send_sync: PhantomData,
             session: unsafe {
                 let mut session = None;
-                sys::MAPILogonEx(
-                    ui_param.0 as usize,
-                    profile_name as *mut _,
-                    password as *mut _,
-                    flags.into(),
-                    ptr::from_mut(&mut session),
-                )?;
+                EventLog::record_result(
+                    "MAPILogonEx",
+                    sys::MAPILogonEx(
+                        ui_param.hwnd().0 as usize,
+                        profile_name as *mut _,
+                        password as *mut _,
+                        flags.into(),
+                        ptr::from_mut(&mut session),
+                    ),
+                )
+                .map_err(|err| NoUi::prompt_error("MAPILogonEx", err))?;
                 session
             }
             .ok_or_else(|| Error::from(E_FAIL))?,
         })
     }
+
+    /// Borrow the [`sys::IMAPISession`], e.g. to call methods which do not have a wrapper yet.
+    /// Cloning the interface adds a reference which keeps the session alive, but not MAPI itself,
+    /// so the clone must not outlive the [`Logon`].
+    pub fn session(&self) -> &sys::IMAPISession {
+        &self.session
+    }
+
+    /// Borrow the [`Initialize`] which this session keeps alive, e.g. to check
+    /// [`Initialize::multithread_notifications`].
+    pub fn initialized(&self) -> &Initialize {
+        &self.initialized
+    }
+
+    /// Open a store with [`sys::IMAPISession::OpenMsgStore`], e.g. with the [`sys::PR_ENTRYID`]
+    /// from a row in the [`sys::IMAPISession::GetMsgStoresTable`].
+    pub fn open_store(&self, entry_id: &[u8], mut flags: OpenStoreFlags) -> Result<Store> {
+        flags.no_dialog |= NoUi::is_enabled();
+        unsafe {
+            let mut store = None;
+            EventLog::record_result(
+                "IMAPISession::OpenMsgStore",
+                self.session.OpenMsgStore(
+                    0,
+                    entry_id.len() as u32,
+                    entry_id.as_ptr() as *mut _,
+                    InterfaceId::MSG_STORE.as_mut_ptr(),
+                    flags.into(),
+                    &mut store,
+                ),
+            )?;
+            store.map(Store::from).ok_or_else(|| Error::from(E_FAIL))
+        }
+    }
+
+    /// Test if two entry IDs refer to the same object with [`sys::IMAPISession::CompareEntryIDs`].
+    /// This is more reliable than comparing the bytes, because an object can have several entry
+    /// IDs, e.g. a short-term and a long-term one. Either argument can be an [`crate::EntryId`].
+    pub fn compare_entry_ids(&self, entry_id1: &[u8], entry_id2: &[u8]) -> Result<bool> {
+        let mut result = 0;
+        unsafe {
+            self.session.CompareEntryIDs(
+                entry_id1.len() as u32,
+                entry_id1.as_ptr() as *mut _,
+                entry_id2.len() as u32,
+                entry_id2.as_ptr() as *mut _,
+                0,
+                &mut result,
+            )?;
+        }
+        Ok(result != 0)
+    }
+
+    /// Open the object identified by a [`MapiUri`].
+    ///
+    /// The store is located by matching [`MapiUri::store`] against the [`sys::PR_RECORD_KEY`]
+    /// column of the [`sys::IMAPISession::GetMsgStoresTable`], so the URI still works after the
+    /// profile is recreated. Fails with [`sys::MAPI_E_NOT_FOUND`] if no store in the profile
+    /// matches.
+    pub fn open_uri(&self, uri: &MapiUri) -> Result<MapiUriTarget> {
+        let store = self.open_store_by_record_key(&uri.store)?;
+        match (uri.folder.as_ref(), uri.message.as_ref()) {
+            (None, None) => Ok(MapiUriTarget::Store(store)),
+            (Some(folder), None) => Ok(MapiUriTarget::Folder(open_store_entry(
+                &store,
+                folder,
+                OpenFlags::default(),
+            )?)),
+            (_, Some(message)) => Ok(MapiUriTarget::Message(open_store_entry(
+                &store,
+                message,
+                OpenFlags::default(),
+            )?)),
+        }
+    }
+
+    fn open_store_by_record_key(&self, record_key: &[u8]) -> Result<sys::IMsgStore> {
+        SizedSPropTagArray! { PropTagArray[2] }
+        let mut prop_tag_array = PropTagArray {
+            aulPropTag: [sys::PR_ENTRYID, sys::PR_RECORD_KEY],
+            ..Default::default()
+        };
+        let mut rows: RowSet = Default::default();
+        unsafe {
+            let stores_table = self.session.GetMsgStoresTable(0)?;
+            sys::HrQueryAllRows(
+                &stores_table,
+                prop_tag_array.as_mut_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                0,
+                rows.as_mut_ptr(),
+            )?;
+        }
+
+        let entry_id = rows
+            .into_iter()
+            .find_map(|row| {
+                let mut entry_id = None;
+                let mut matched = false;
+                for value in row.iter() {
+                    match value {
+                        PropValue {
+                            tag,
+                            value: PropValueData::Binary(value),
+                        } if u32::from(tag) == sys::PR_ENTRYID => entry_id = Some(value.to_vec()),
+                        PropValue {
+                            tag,
+                            value: PropValueData::Binary(value),
+                        } if u32::from(tag) == sys::PR_RECORD_KEY => matched = value == record_key,
+                        _ => {}
+                    }
+                }
+                entry_id.filter(|_| matched)
+            })
+            .ok_or_else(|| Error::from(sys::MAPI_E_NOT_FOUND))?;
+
+        self.open_store(
+            &entry_id,
+            OpenStoreFlags {
+                best_access: true,
+                no_dialog: true,
+                ..Default::default()
+            },
+        )
+        .map(sys::IMsgStore::from)
+    }
+}
+
+/// Object returned from [`Logon::open_uri`], depending on how many segments the [`MapiUri`] has.
+pub enum MapiUriTarget {
+    /// The [`MapiUri`] only identified a store.
+    Store(sys::IMsgStore),
+
+    /// The [`MapiUri`] identified a folder.
+    Folder(sys::IMAPIFolder),
+
+    /// The [`MapiUri`] identified a message.
+    Message(sys::IMessage),
 }
@@ -0,0 +1,620 @@
+//! Define [`MapiProp`], [`get_props`], [`get_string_props`], and [`StringPropOptions`].
+
+use crate::{
+    sys, InterfaceId, MAPIOutParam, OpenFlags, OwnedPropValue, PredecessorChangeList, PropProblems,
+    PropTag, PropType, PropValue, PropValueData, Xid,
+};
+use core::{iter, ptr, slice};
+use windows::Win32::{
+    Foundation::{E_FAIL, E_INVALIDARG, E_UNEXPECTED, FILETIME, HGLOBAL},
+    Globalization::{MultiByteToWideChar, CP_ACP, MB_ERR_INVALID_CHARS},
+    System::Com::{
+        IStream, StructuredStorage::CreateStreamOnHGlobal, STATFLAG_NONAME, STATSTG,
+        STREAM_SEEK_SET,
+    },
+};
+use windows_core::*;
+
+/// Common helpers for any object which exposes an [`sys::IMAPIProp`] interface, including all of
+/// the [`sys`] interfaces which inherit from [`sys::IMAPIProp`].
+pub trait MapiProp {
+    /// Get the [`sys::IMAPIProp`] interface for this object.
+    fn as_mapi_prop(&self) -> &sys::IMAPIProp;
+
+    /// Read a [`sys::PT_BINARY`] property into a [`Vec<u8>`].
+    ///
+    /// This first tries to read the value inline with [`sys::HrGetOneProp`]. Providers refuse to
+    /// return large values that way, and fail with [`sys::MAPI_E_NOT_ENOUGH_MEMORY`] instead, in
+    /// which case this switches to reading the value through an [`IStream`] from
+    /// [`sys::IMAPIProp::OpenProperty`]. Either way, the caller gets the whole value.
+    fn get_binary(&self, tag: PropTag) -> Result<Vec<u8>> {
+        let prop = self.as_mapi_prop();
+        let tag = tag.change_prop_type(PropType::new(sys::PT_BINARY as u16));
+        let mut value: MAPIOutParam<sys::SPropValue> = Default::default();
+        unsafe {
+            match sys::HrGetOneProp(prop, tag.into(), value.as_mut_ptr()) {
+                Ok(()) => match value.as_mut().map(|value| PropValue::from(&*value).value) {
+                    Some(PropValueData::Binary(value)) => Ok(value.to_vec()),
+                    Some(PropValueData::Error(err)) => Err(Error::from(err)),
+                    _ => Err(Error::from(E_UNEXPECTED)),
+                },
+                Err(err) if err.code() == sys::MAPI_E_NOT_ENOUGH_MEMORY => {
+                    let stream: IStream = self
+                        .open_property_as(tag, InterfaceId::STREAM, OpenFlags::read_only())?
+                        .cast()?;
+                    read_stream(&stream)
+                }
+                Err(err) => Err(err),
+            }
+        }
+    }
+
+    /// Open a property as an interface with [`sys::IMAPIProp::OpenProperty`], e.g. a large
+    /// [`sys::PT_BINARY`] property as an [`IStream`], or [`sys::PR_ACL_TABLE`] as an
+    /// [`sys::IExchangeModifyTable`]. The interface ID is inferred from `T`.
+    fn open_property<T>(&self, tag: PropTag, flags: OpenFlags) -> Result<T>
+    where
+        T: Interface,
+        Self: Sized,
+    {
+        self.open_property_as(tag, InterfaceId::of::<T>(), flags)?
+            .cast()
+    }
+
+    /// Same as [`MapiProp::open_property`], but with an [`InterfaceId`] chosen at runtime.
+    fn open_property_as(
+        &self,
+        tag: PropTag,
+        interface: InterfaceId,
+        flags: OpenFlags,
+    ) -> Result<IUnknown> {
+        let mut unknown = None;
+        unsafe {
+            self.as_mapi_prop().OpenProperty(
+                tag.into(),
+                interface.as_mut_ptr(),
+                0,
+                flags.into(),
+                &mut unknown,
+            )?;
+        }
+        unknown.ok_or_else(|| Error::from(E_UNEXPECTED))
+    }
+
+    /// Call [`get_string_props`] on this object.
+    fn get_string_props(
+        &self,
+        tags: &[PropTag],
+        options: StringPropOptions,
+    ) -> Result<Vec<Result<String>>> {
+        get_string_props(self.as_mapi_prop(), tags, options)
+    }
+
+    /// Read and parse the [`sys::PR_CHANGE_KEY`] property. Fails with [`sys::MAPI_E_CORRUPT_DATA`]
+    /// if the value is not a valid [`Xid`].
+    fn change_key(&self) -> Result<Xid> {
+        let value = self.get_binary(PropTag(sys::PR_CHANGE_KEY))?;
+        Xid::parse(&value).map_err(|_| Error::from(sys::MAPI_E_CORRUPT_DATA))
+    }
+
+    /// Read and parse the [`sys::PR_PREDECESSOR_CHANGE_LIST`] property. Fails with
+    /// [`sys::MAPI_E_CORRUPT_DATA`] if the value is not a valid [`PredecessorChangeList`].
+    fn predecessor_change_list(&self) -> Result<PredecessorChangeList> {
+        let value = self.get_binary(PropTag(sys::PR_PREDECESSOR_CHANGE_LIST))?;
+        PredecessorChangeList::parse(&value).map_err(|_| Error::from(sys::MAPI_E_CORRUPT_DATA))
+    }
+
+    /// Test if the object has been modified since a previous read of its [`sys::PR_CHANGE_KEY`].
+    ///
+    /// Every save assigns a new [`sys::PR_CHANGE_KEY`], so this is true whenever the current value
+    /// differs from `change_key`. Use [`MapiProp::predecessor_change_list`] and
+    /// [`PredecessorChangeList::includes`] to tell a later version apart from a conflicting one.
+    fn has_changed_since(&self, change_key: &Xid) -> Result<bool> {
+        Ok(self.change_key()? != *change_key)
+    }
+}
+
+macro_rules! impl_mapi_prop {
+    ($($interface:ty),+ $(,)?) => {
+        $(
+            impl MapiProp for $interface {
+                fn as_mapi_prop(&self) -> &sys::IMAPIProp {
+                    self
+                }
+            }
+        )+
+    };
+}
+
+impl_mapi_prop!(
+    sys::IMAPIProp,
+    sys::IMAPIContainer,
+    sys::IMAPIFolder,
+    sys::IMsgStore,
+    sys::IMessage,
+    sys::IAttach,
+    sys::IABContainer,
+    sys::IMailUser,
+    sys::IDistList,
+    sys::IMAPIStatus,
+    sys::IProfSect,
+);
+
+/// Read the remainder of an [`IStream`] into a [`Vec<u8>`].
+pub(crate) fn read_stream(stream: &IStream) -> Result<Vec<u8>> {
+    const CHUNK_SIZE: usize = 0x10000;
+
+    let mut stat = STATSTG::default();
+    let capacity = unsafe { stream.Stat(&mut stat, STATFLAG_NONAME) }
+        .map(|_| stat.cbSize as usize)
+        .unwrap_or_default();
+    let mut buffer = Vec::with_capacity(capacity);
+    let mut chunk = vec![0_u8; CHUNK_SIZE];
+    loop {
+        let mut read = 0;
+        unsafe {
+            stream
+                .Read(
+                    chunk.as_mut_ptr() as *mut _,
+                    chunk.len() as u32,
+                    Some(&mut read),
+                )
+                .ok()?;
+        }
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read as usize]);
+    }
+    Ok(buffer)
+}
+
+/// Copy `data` to a new in-memory [`IStream`], and rewind it so it can be read from the start.
+pub(crate) fn memory_stream(data: &[u8]) -> Result<IStream> {
+    let stream = unsafe { CreateStreamOnHGlobal(HGLOBAL::default(), true)? };
+    let mut offset = 0;
+    while offset < data.len() {
+        let mut written = 0;
+        unsafe {
+            stream
+                .Write(
+                    data[offset..].as_ptr() as *const _,
+                    (data.len() - offset) as u32,
+                    Some(&mut written),
+                )
+                .ok()?;
+        }
+        if written == 0 {
+            return Err(Error::from(sys::MAPI_E_CALL_FAILED));
+        }
+        offset += written as usize;
+    }
+    unsafe {
+        stream.Seek(0, STREAM_SEEK_SET, None)?;
+    }
+    Ok(stream)
+}
+
+/// Rewind an [`IStream`], e.g. one from [`memory_stream`] which has been written to, and read the
+/// whole thing into a [`Vec<u8>`].
+pub(crate) fn read_whole_stream(stream: &IStream) -> Result<Vec<u8>> {
+    unsafe {
+        stream.Seek(0, STREAM_SEEK_SET, None)?;
+    }
+    read_stream(stream)
+}
+
+/// Read a set of properties from a [`sys::IMAPIProp`] with [`sys::IMAPIProp::GetProps`].
+///
+/// The result has one entry for each of the `tags`, in the same order, pairing the requested tag
+/// with either the value or the error code from the [`sys::PT_ERROR`] value the provider returned
+/// in its place, e.g. [`sys::MAPI_E_NOT_FOUND`] if the property is missing or
+/// [`sys::MAPI_E_NOT_ENOUGH_MEMORY`] if it is too large to read inline. A partial result with
+/// [`sys::MAPI_W_ERRORS_RETURNED`] is not an error.
+pub fn get_props(
+    prop: &sys::IMAPIProp,
+    tags: &[PropTag],
+) -> Result<Vec<(PropTag, core::result::Result<OwnedPropValue, HRESULT>)>> {
+    let mut tag_array = prop_tag_array(tags)?;
+    let mut count = 0;
+    let mut values: MAPIOutParam<sys::SPropValue> = Default::default();
+    unsafe {
+        prop.GetProps(
+            tag_array.as_mut_ptr() as *mut _,
+            sys::MAPI_UNICODE,
+            &mut count,
+            values.as_mut_ptr(),
+        )?;
+        let values = values
+            .as_mut_slice(count as usize)
+            .ok_or_else(|| Error::from(E_UNEXPECTED))?;
+        if values.len() != tags.len() {
+            return Err(Error::from(E_UNEXPECTED));
+        }
+        Ok(tags
+            .iter()
+            .zip(values.iter())
+            .map(|(tag, value)| {
+                let value = PropValue::from(value);
+                let result = match value.value {
+                    PropValueData::Error(err) => Err(err),
+                    _ => Ok(value.to_owned()),
+                };
+                (*tag, result)
+            })
+            .collect())
+    }
+}
+
+/// Options for [`get_string_props`].
+#[derive(Default)]
+pub struct StringPropOptions {
+    /// If the provider does not support [`sys::PT_UNICODE`] properties, either because
+    /// [`sys::IMAPIProp::GetProps`] fails with [`sys::MAPI_E_BAD_CHARWIDTH`] or because it returns
+    /// a [`sys::PT_ERROR`] value for the [`sys::PT_UNICODE`] tag, try again with the
+    /// [`sys::PT_STRING8`] tag.
+    pub ansi_fallback: bool,
+
+    /// Code page used to decode [`sys::PT_STRING8`] values, if [`StringPropOptions::ansi_fallback`]
+    /// is set. Defaults to `CP_ACP`.
+    pub code_page: Option<u32>,
+}
+
+/// Read a set of string properties from a [`sys::IMAPIProp`], requesting each of them as
+/// [`sys::PT_UNICODE`] regardless of the `PROP_TYPE` in `tags`.
+///
+/// The result has one entry for each of the `tags`, in the same order. Properties which are
+/// missing (or which cannot be read even with [`StringPropOptions::ansi_fallback`]) are returned
+/// as an [`Error`] in that position, rather than failing the whole call.
+pub fn get_string_props(
+    prop: &sys::IMAPIProp,
+    tags: &[PropTag],
+    options: StringPropOptions,
+) -> Result<Vec<Result<String>>> {
+    let unicode_tags: Vec<_> = tags
+        .iter()
+        .map(|tag| tag.change_prop_type(PropType::new(sys::PT_UNICODE as u16)))
+        .collect();
+    let code_page = options.code_page.unwrap_or(CP_ACP);
+    let mut results = match get_props_as(prop, &unicode_tags, sys::MAPI_UNICODE, code_page) {
+        Err(err) if options.ansi_fallback && err.code() == sys::MAPI_E_BAD_CHARWIDTH => {
+            tags.iter().map(|_| Err(Error::from(err.code()))).collect()
+        }
+        result => result?,
+    };
+
+    if !options.ansi_fallback {
+        return Ok(results);
+    }
+
+    let retry: Vec<_> = results
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, result)| result.is_err().then_some(idx))
+        .collect();
+    if retry.is_empty() {
+        return Ok(results);
+    }
+
+    let ansi_tags: Vec<_> = retry
+        .iter()
+        .map(|idx| tags[*idx].change_prop_type(PropType::new(sys::PT_STRING8 as u16)))
+        .collect();
+    let ansi_results = get_props_as(prop, &ansi_tags, 0, code_page)?;
+    for (idx, result) in retry.into_iter().zip(ansi_results) {
+        results[idx] = result;
+    }
+
+    Ok(results)
+}
+
+/// Build a `SPropTagArray` on the heap. A `u32` count followed by the `u32` tags has the same
+/// layout as [`sys::SPropTagArray`].
+fn prop_tag_array(tags: &[PropTag]) -> Result<Vec<u32>> {
+    let count = u32::try_from(tags.len()).map_err(|_| Error::from(E_INVALIDARG))?;
+    Ok(iter::once(count)
+        .chain(tags.iter().map(|tag| u32::from(*tag)))
+        .collect())
+}
+
+fn get_props_as(
+    prop: &sys::IMAPIProp,
+    tags: &[PropTag],
+    flags: u32,
+    code_page: u32,
+) -> Result<Vec<Result<String>>> {
+    let mut tag_array = prop_tag_array(tags)?;
+    let mut count = 0;
+    let mut values: MAPIOutParam<sys::SPropValue> = Default::default();
+    unsafe {
+        prop.GetProps(
+            tag_array.as_mut_ptr() as *mut _,
+            flags,
+            &mut count,
+            values.as_mut_ptr(),
+        )?;
+        let values = values
+            .as_mut_slice(count as usize)
+            .ok_or_else(|| Error::from(E_UNEXPECTED))?;
+        Ok(values
+            .iter()
+            .map(|value| match PropValue::from(value).value {
+                PropValueData::Unicode(value) => Ok(value.to_string()?),
+                PropValueData::AnsiString(value) => decode_ansi(value.as_bytes(), code_page),
+                PropValueData::Error(err) => Err(Error::from(err)),
+                _ => Err(Error::from(E_UNEXPECTED)),
+            })
+            .collect())
+    }
+}
+
+pub(crate) fn decode_ansi(value: &[u8], code_page: u32) -> Result<String> {
+    if value.is_empty() {
+        return Ok(String::new());
+    }
+
+    unsafe {
+        let size = MultiByteToWideChar(code_page, MB_ERR_INVALID_CHARS, value, None);
+        if size <= 0 {
+            return Err(Error::from_win32());
+        }
+        let mut buffer = vec![0_u16; size as usize];
+        let size = MultiByteToWideChar(
+            code_page,
+            MB_ERR_INVALID_CHARS,
+            value,
+            Some(buffer.as_mut_slice()),
+        );
+        if size <= 0 {
+            return Err(Error::from_win32());
+        }
+        buffer.truncate(size as usize);
+        Ok(String::from_utf16(&buffer)?)
+    }
+}
+
+/// Borrowed property value for [`set_props`]. The buffers only need to live until the call to
+/// [`sys::IMAPIProp::SetProps`] returns.
+pub(crate) enum SetPropValue<'a> {
+    Long(i32),
+    Boolean(bool),
+    FileTime(FILETIME),
+    Binary(&'a [u8]),
+    Unicode(&'a str),
+}
+
+/// Call [`sys::IMAPIProp::SetProps`] with a set of simple values, and turn the first entry in the
+/// [`sys::SPropProblemArray`] (if any) into an [`Error`].
+pub(crate) fn set_props(prop: &sys::IMAPIProp, values: &[(u32, SetPropValue)]) -> Result<()> {
+    let strings: Vec<Vec<u16>> = values
+        .iter()
+        .map(|(_, value)| match value {
+            SetPropValue::Unicode(value) => value.encode_utf16().chain(iter::once(0)).collect(),
+            _ => Vec::new(),
+        })
+        .collect();
+    let mut props: Vec<_> = values
+        .iter()
+        .zip(strings.iter())
+        .map(|((tag, value), string)| {
+            let mut prop = sys::SPropValue {
+                ulPropTag: *tag,
+                ..Default::default()
+            };
+            match value {
+                SetPropValue::Long(value) => prop.Value.l = *value,
+                SetPropValue::Boolean(value) => prop.Value.b = u16::from(*value),
+                SetPropValue::FileTime(value) => prop.Value.ft = *value,
+                SetPropValue::Binary(value) => {
+                    prop.Value.bin = sys::SBinary {
+                        cb: value.len() as u32,
+                        lpb: value.as_ptr() as *mut _,
+                    }
+                }
+                SetPropValue::Unicode(_) => prop.Value.lpszW = PWSTR(string.as_ptr() as *mut _),
+            }
+            prop
+        })
+        .collect();
+    set_sprop_values(prop, &mut props)
+}
+
+/// Call [`sys::IMAPIProp::SetProps`] with values which have already been converted to
+/// [`sys::SPropValue`], and turn the first entry in the [`sys::SPropProblemArray`] (if any) into
+/// an [`Error`].
+pub(crate) fn set_sprop_values(prop: &sys::IMAPIProp, props: &mut [sys::SPropValue]) -> Result<()> {
+    set_sprop_problems(prop, props)?.into_result()
+}
+
+/// Call [`sys::IMAPIProp::SetProps`] with values which have already been converted to
+/// [`sys::SPropValue`], and return the [`sys::SPropProblemArray`] as [`PropProblems`].
+pub(crate) fn set_sprop_problems(
+    prop: &sys::IMAPIProp,
+    props: &mut [sys::SPropValue],
+) -> Result<PropProblems> {
+    let mut problems: MAPIOutParam<sys::SPropProblemArray> = Default::default();
+    unsafe {
+        prop.SetProps(
+            props.len() as u32,
+            props.as_mut_ptr(),
+            problems.as_mut_ptr(),
+        )?;
+        Ok(PropProblems::from_raw(
+            problems
+                .as_mut()
+                .map_or(ptr::null(), |problems| problems as *const _),
+        ))
+    }
+}
+
+/// Call [`sys::IMAPIProp::DeleteProps`], and turn the first entry in the
+/// [`sys::SPropProblemArray`] (if any) into an [`Error`]. Properties which are already missing are
+/// not treated as a problem.
+pub(crate) fn delete_props(prop: &sys::IMAPIProp, tags: &[u32]) -> Result<()> {
+    delete_prop_problems(prop, tags)?.into_result()
+}
+
+/// Call [`sys::IMAPIProp::DeleteProps`], and return the [`sys::SPropProblemArray`] as
+/// [`PropProblems`], leaving out properties which are already missing.
+pub(crate) fn delete_prop_problems(prop: &sys::IMAPIProp, tags: &[u32]) -> Result<PropProblems> {
+    let mut prop_tag_array: Vec<u32> = iter::once(tags.len() as u32)
+        .chain(tags.iter().copied())
+        .collect();
+    let mut problems: MAPIOutParam<sys::SPropProblemArray> = Default::default();
+    unsafe {
+        prop.DeleteProps(prop_tag_array.as_mut_ptr() as *mut _, problems.as_mut_ptr())?;
+        Ok(PropProblems::from_raw(
+            problems
+                .as_mut()
+                .map_or(ptr::null(), |problems| problems as *const _),
+        )
+        .into_iter()
+        .filter(|problem| problem.hresult != sys::MAPI_E_NOT_FOUND)
+        .collect())
+    }
+}
+
+/// Call [`sys::IMsgStore::OpenEntry`] and cast the result to the requested interface. An empty
+/// `entry_id` opens the root folder of the store.
+pub(crate) fn open_store_entry<T>(
+    store: &sys::IMsgStore,
+    entry_id: &[u8],
+    flags: OpenFlags,
+) -> Result<T>
+where
+    T: Interface,
+{
+    unsafe {
+        let mut obj_type = 0;
+        let mut unknown = None;
+        store.OpenEntry(
+            entry_id.len() as u32,
+            if entry_id.is_empty() {
+                ptr::null_mut()
+            } else {
+                entry_id.as_ptr() as *mut _
+            },
+            InterfaceId::of::<T>().as_mut_ptr(),
+            flags.into(),
+            &mut obj_type,
+            &mut unknown,
+        )?;
+        unknown.ok_or_else(|| Error::from(E_FAIL))?.cast()
+    }
+}
+
+/// Call [`sys::IMAPIContainer::OpenEntry`] and cast the result to the requested interface.
+pub(crate) fn open_container_entry<T>(
+    container: &sys::IMAPIContainer,
+    entry_id: &[u8],
+    flags: OpenFlags,
+) -> Result<T>
+where
+    T: Interface,
+{
+    unsafe {
+        let mut obj_type = 0;
+        let mut unknown = None;
+        container.OpenEntry(
+            entry_id.len() as u32,
+            entry_id.as_ptr() as *mut _,
+            InterfaceId::of::<T>().as_mut_ptr(),
+            flags.into(),
+            &mut obj_type,
+            &mut unknown,
+        )?;
+        unknown.ok_or_else(|| Error::from(E_FAIL))?.cast()
+    }
+}
+
+/// Look up a set of numeric named properties in the same property set with
+/// [`sys::IMAPIProp::GetIDsFromNames`]. Each entry in the result has the `PROP_TYPE` from `props`,
+/// or `None` if that name has never been mapped on this store. Pass [`sys::MAPI_CREATE`] in
+/// `flags` to create any missing mappings.
+pub(crate) fn get_named_prop_tags(
+    prop: &sys::IMAPIProp,
+    guid: &GUID,
+    props: &[(i32, u32)],
+    flags: u32,
+) -> Result<Vec<Option<PropTag>>> {
+    let mut names: Vec<_> = props
+        .iter()
+        .map(|(id, _)| sys::MAPINAMEID {
+            lpguid: ptr::from_ref(guid) as *mut _,
+            ulKind: sys::MNID_ID,
+            Kind: sys::MAPINAMEID_0 { lID: *id },
+        })
+        .collect();
+    let mut name_ptrs: Vec<_> = names.iter_mut().map(ptr::from_mut).collect();
+    let mut tags: MAPIOutParam<sys::SPropTagArray> = Default::default();
+    unsafe {
+        prop.GetIDsFromNames(
+            name_ptrs.len() as u32,
+            name_ptrs.as_mut_ptr(),
+            flags,
+            tags.as_mut_ptr(),
+        )?;
+        let tags = tags.as_mut().ok_or_else(|| Error::from(E_UNEXPECTED))?;
+        let tags = slice::from_raw_parts(tags.aulPropTag.as_ptr(), tags.cValues as usize);
+        Ok(tags
+            .iter()
+            .zip(props.iter())
+            .map(|(tag, (_, prop_type))| {
+                let tag = PropTag(*tag);
+                (u32::from(tag.prop_type()) != sys::PT_ERROR)
+                    .then(|| tag.change_prop_type(PropType::new(*prop_type as u16)))
+            })
+            .collect())
+    }
+}
+
+/// Call [`sys::HrGetOneProp`] and convert the [`PropValueData`] with `map`. Returns `None` if the
+/// property is missing, or [`E_UNEXPECTED`] if `map` does not accept the value.
+pub(crate) fn get_one_prop<T>(
+    prop: &sys::IMAPIProp,
+    tag: u32,
+    map: impl FnOnce(PropValueData<'_>) -> Option<T>,
+) -> Result<Option<T>> {
+    let mut value: MAPIOutParam<sys::SPropValue> = Default::default();
+    unsafe {
+        match sys::HrGetOneProp(prop, tag, value.as_mut_ptr()) {
+            Ok(()) => match value.as_mut().map(|value| PropValue::from(&*value).value) {
+                Some(PropValueData::Error(err)) if err == sys::MAPI_E_NOT_FOUND => Ok(None),
+                Some(PropValueData::Error(err)) => Err(Error::from(err)),
+                Some(value) => map(value)
+                    .map(Some)
+                    .ok_or_else(|| Error::from(E_UNEXPECTED)),
+                None => Err(Error::from(E_UNEXPECTED)),
+            },
+            Err(err) if err.code() == sys::MAPI_E_NOT_FOUND => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Open the default receive folder for the `IPM` message class with
+/// [`sys::IMsgStore::GetReceiveFolder`], which is the Inbox.
+pub(crate) fn open_inbox(store: &sys::IMsgStore) -> Result<sys::IMAPIFolder> {
+    let entry_id = inbox_entry_id(store)?;
+    open_store_entry(store, &entry_id, OpenFlags::default())
+}
+
+/// Get the entry ID of the default receive folder for the `IPM` message class with
+/// [`sys::IMsgStore::GetReceiveFolder`].
+pub(crate) fn inbox_entry_id(store: &sys::IMsgStore) -> Result<Vec<u8>> {
+    let mut message_class = *b"IPM\0";
+    let mut count = 0;
+    let mut entry_id: MAPIOutParam<sys::ENTRYID> = Default::default();
+    let mut explicit_class: MAPIOutParam<i8> = Default::default();
+    unsafe {
+        store.GetReceiveFolder(
+            message_class.as_mut_ptr() as *mut _,
+            0,
+            &mut count,
+            entry_id.as_mut_ptr(),
+            explicit_class.as_mut_ptr(),
+        )?;
+        let entry_id = entry_id.as_mut().ok_or_else(|| Error::from(E_UNEXPECTED))?;
+        Ok(slice::from_raw_parts(ptr::from_mut(entry_id) as *const u8, count as usize).to_vec())
+    }
+}
@@ -0,0 +1,194 @@
+//! Define [`MapiTable`], [`Bookmark`], and [`query_all_rows`].
+
+use crate::{
+    sys, PropTag, PropTagArrayBuf, Restriction, Row, RowSet, SortDirection, SortOrderSetBuf,
+    TableRows,
+};
+use core::ptr;
+use windows_core::*;
+
+/// Predefined bookmark for [`MapiTable::seek_row`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Bookmark {
+    /// [`sys::BOOKMARK_BEGINNING`]
+    #[default]
+    Beginning,
+
+    /// [`sys::BOOKMARK_CURRENT`]
+    Current,
+
+    /// [`sys::BOOKMARK_END`]
+    End,
+}
+
+impl From<Bookmark> for usize {
+    fn from(value: Bookmark) -> Self {
+        (match value {
+            Bookmark::Beginning => sys::BOOKMARK_BEGINNING,
+            Bookmark::Current => sys::BOOKMARK_CURRENT,
+            Bookmark::End => sys::BOOKMARK_END,
+        }) as usize
+    }
+}
+
+/// Safe wrapper for [`sys::HrQueryAllRows`], which sets the `columns`, applies the optional
+/// `restriction` and `sort` keys, and reads every matching row into a single [`RowSet`]. The
+/// column and sort order arrays are built at runtime with [`PropTagArrayBuf`] and
+/// [`SortOrderSetBuf`], so unlike [`crate::SizedSPropTagArray`], the number of columns does not
+/// need to be known at compile time.
+///
+/// `limit` is the maximum number of rows to read, or `0` for no limit. [`sys::HrQueryAllRows`]
+/// fails with [`sys::MAPI_E_TABLE_TOO_BIG`] if there are more rows than that, so prefer
+/// [`MapiTable::iter_rows`] for tables which might be large.
+pub fn query_all_rows(
+    table: &sys::IMAPITable,
+    columns: &[PropTag],
+    restriction: Option<&Restriction>,
+    sort: Option<&[(PropTag, SortDirection)]>,
+    limit: u32,
+) -> Result<RowSet> {
+    let mut columns = PropTagArrayBuf::from(columns);
+    let mut restriction = restriction.map(Restriction::build);
+    let mut sort = sort.map(SortOrderSetBuf::from);
+    let mut rows: RowSet = Default::default();
+    unsafe {
+        sys::HrQueryAllRows(
+            table,
+            columns.as_mut_ptr(),
+            restriction
+                .as_mut()
+                .map_or(ptr::null_mut(), |restriction| restriction.as_mut_ptr()),
+            sort.as_mut()
+                .map_or(ptr::null_mut(), SortOrderSetBuf::as_mut_ptr),
+            limit.min(i32::MAX as u32) as i32,
+            rows.as_mut_ptr(),
+        )?;
+    }
+    Ok(rows)
+}
+
+/// Wrapper for a [`sys::IMAPITable`] with safe methods for the common operations.
+///
+/// To page through a large table, call [`MapiTable::query_rows`] repeatedly until it returns an
+/// empty [`RowSet`], or use [`MapiTable::iter_rows`] to do that with an [`Iterator`]. Each call
+/// only fetches the next batch from the provider, so unlike [`sys::HrQueryAllRows`], the whole
+/// table is never held in memory at once.
+///
+/// Cloning a [`MapiTable`] calls `AddRef` on the same [`sys::IMAPITable`], so the clones share
+/// one cursor, column set, sort order, and restriction. Reading rows or calling
+/// [`MapiTable::set_columns`] through one clone moves or changes the table for all of them, so
+/// open a separate table if the reads need to be independent.
+#[derive(Clone)]
+pub struct MapiTable {
+    table: sys::IMAPITable,
+}
+
+impl MapiTable {
+    /// Borrow the [`sys::IMAPITable`], e.g. to call methods which do not have a wrapper yet.
+    pub fn table(&self) -> &sys::IMAPITable {
+        &self.table
+    }
+
+    /// Replace the column set with [`sys::IMAPITable::SetColumns`].
+    pub fn set_columns(&self, columns: &[PropTag]) -> Result<()> {
+        let mut columns = PropTagArrayBuf::from(columns);
+        unsafe { self.table.SetColumns(columns.as_mut_ptr(), 0) }
+    }
+
+    /// Replace the sort order with [`sys::IMAPITable::SortTable`]. The first key is the primary
+    /// sort key.
+    pub fn sort(&self, keys: &[(PropTag, SortDirection)]) -> Result<()> {
+        let mut sort_order_set = SortOrderSetBuf::from(keys);
+        unsafe { self.table.SortTable(sort_order_set.as_mut_ptr(), 0) }
+    }
+
+    /// Replace the [`Restriction`] with [`sys::IMAPITable::Restrict`], or remove it with `None`.
+    pub fn restrict(&self, restriction: Option<&Restriction>) -> Result<()> {
+        match restriction {
+            Some(restriction) => {
+                let mut restriction = restriction.build();
+                unsafe { self.table.Restrict(restriction.as_mut_ptr(), 0) }
+            }
+            None => unsafe { self.table.Restrict(ptr::null_mut(), 0) },
+        }
+    }
+
+    /// Move the cursor `rows` rows from `origin` with [`sys::IMAPITable::SeekRow`], and return
+    /// the number of rows it actually moved, which is smaller if it reached either end of the
+    /// table. A negative `rows` moves backwards.
+    pub fn seek_row(&self, origin: Bookmark, rows: i32) -> Result<i32> {
+        let mut sought = 0;
+        unsafe {
+            self.table.SeekRow(origin.into(), rows, &mut sought)?;
+        }
+        Ok(sought)
+    }
+
+    /// Move the cursor to the first row from `origin` which matches `restriction` with
+    /// [`sys::IMAPITable::FindRow`], and read that row with [`MapiTable::query_rows`]. Returns
+    /// `None` if no row matches.
+    pub fn find_row(&self, restriction: &Restriction, origin: Bookmark) -> Result<Option<Row>> {
+        let mut restriction = restriction.build();
+        match unsafe {
+            self.table
+                .FindRow(restriction.as_mut_ptr(), origin.into(), 0)
+        } {
+            Ok(()) => Ok(self.query_rows(1)?.into_iter().next()),
+            Err(err) if err.code() == sys::MAPI_E_NOT_FOUND => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Get the number of rows with [`sys::IMAPITable::GetRowCount`].
+    pub fn row_count(&self) -> Result<u32> {
+        let mut count = 0;
+        unsafe {
+            self.table.GetRowCount(0, &mut count)?;
+        }
+        Ok(count)
+    }
+
+    /// Read up to `count` rows from the cursor with [`sys::IMAPITable::QueryRows`], and move the
+    /// cursor past them. Returns an empty [`RowSet`] at the end of the table.
+    pub fn query_rows(&self, count: usize) -> Result<RowSet> {
+        let mut rows: RowSet = Default::default();
+        unsafe {
+            self.table.QueryRows(
+                count.clamp(1, i32::MAX as usize) as i32,
+                0,
+                rows.as_mut_ptr(),
+            )?;
+        }
+        Ok(rows)
+    }
+
+    /// Read every row at once with [`query_all_rows`].
+    pub fn query_all_rows(
+        &self,
+        columns: &[PropTag],
+        restriction: Option<&Restriction>,
+        sort: Option<&[(PropTag, SortDirection)]>,
+        limit: u32,
+    ) -> Result<RowSet> {
+        query_all_rows(&self.table, columns, restriction, sort, limit)
+    }
+
+    /// Iterate over the rows from the current cursor position to the end of the table, reading
+    /// `batch_size` rows at a time with [`MapiTable::query_rows`]. Call
+    /// [`MapiTable::seek_row`] with [`Bookmark::Beginning`] first to start from the top.
+    pub fn iter_rows(&self, batch_size: usize) -> TableRows {
+        TableRows::new(self.table.clone().into(), batch_size)
+    }
+}
+
+impl From<sys::IMAPITable> for MapiTable {
+    fn from(table: sys::IMAPITable) -> Self {
+        Self { table }
+    }
+}
+
+impl From<MapiTable> for sys::IMAPITable {
+    fn from(value: MapiTable) -> Self {
+        value.table
+    }
+}
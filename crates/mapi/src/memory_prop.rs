@@ -0,0 +1,426 @@
+//! Define [`MemoryProp`].
+
+use crate::{
+    sys, MAPIOutParam, MAPIUninit, MapiProp, OwnedPropValue, OwnedPropValueData, PropTag, PropType,
+    PropValue, PropValueBuilder,
+};
+use core::{iter, mem, ptr, slice};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+use windows_core::*;
+use windows_implement::implement;
+
+type Values = Arc<Mutex<HashMap<u16, OwnedPropValue>>>;
+
+fn lock(values: &Values) -> MutexGuard<'_, HashMap<u16, OwnedPropValue>> {
+    values.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Standalone property object which keeps its values in a [`HashMap`], and implements
+/// [`sys::IMAPIProp`] over them without a provider or an [`sys::IMAPISupport`] object, so code
+/// written against [`MapiProp`] can run against synthetic objects in tests and offline
+/// transformations.
+///
+/// Like a provider, it stores one value per property ID, so setting a property replaces any value
+/// with the same ID and a different type. [`sys::IMAPIProp::GetProps`] only returns a value if the
+/// requested type matches or is [`sys::PT_UNSPECIFIED`], and never converts between
+/// [`sys::PT_STRING8`] and [`sys::PT_UNICODE`]. [`sys::IMAPIProp::SaveChanges`] succeeds without
+/// doing anything, and the methods which need a provider (e.g. [`sys::IMAPIProp::OpenProperty`],
+/// [`sys::IMAPIProp::CopyTo`], or named properties) fail with [`sys::MAPI_E_NO_SUPPORT`].
+///
+/// Clones share the same values.
+///
+/// ```no_run
+/// # use outlook_mapi::{sys, *};
+/// let prop = MemoryProp::new();
+/// prop.set(OwnedPropValue {
+///     tag: PropTag(sys::PR_SUBJECT_W),
+///     value: OwnedPropValueData::Unicode("Hello".to_string()),
+/// });
+/// let subject = prop.get_string_props(&[PropTag(sys::PR_SUBJECT_W)], Default::default());
+/// ```
+#[derive(Clone)]
+pub struct MemoryProp {
+    prop: sys::IMAPIProp,
+    values: Values,
+}
+
+impl Default for MemoryProp {
+    fn default() -> Self {
+        let values = Values::default();
+        let prop = MemoryPropImpl {
+            values: values.clone(),
+        }
+        .into();
+        Self { prop, values }
+    }
+}
+
+impl MemoryProp {
+    /// Create an empty property object.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Copy every property which `prop` returns from [`sys::IMAPIProp::GetProps`] with a `NULL`
+    /// tag array, e.g. to transform a snapshot of a message after the session is gone. Values
+    /// which the provider returns as [`sys::PT_ERROR`] are skipped.
+    pub fn copy_from(prop: &impl MapiProp) -> Result<Self> {
+        let mut count = 0;
+        let mut values: MAPIOutParam<sys::SPropValue> = Default::default();
+        unsafe {
+            prop.as_mapi_prop().GetProps(
+                ptr::null_mut(),
+                sys::MAPI_UNICODE,
+                &mut count,
+                values.as_mut_ptr(),
+            )?;
+            Ok(values
+                .as_mut_slice(count as usize)
+                .unwrap_or_default()
+                .iter()
+                .map(PropValue::from)
+                .filter(|value| u32::from(value.tag.prop_type()) != sys::PT_ERROR)
+                .map(OwnedPropValue::from)
+                .collect())
+        }
+    }
+
+    /// Get the value of a property, if it is set with the same type as `tag`, or with any type if
+    /// `tag` is [`sys::PT_UNSPECIFIED`].
+    pub fn get(&self, tag: PropTag) -> Option<OwnedPropValue> {
+        find_value(&lock(&self.values), tag).cloned()
+    }
+
+    /// Set the value of a property, and return the value it replaced, if any.
+    pub fn set(&self, value: OwnedPropValue) -> Option<OwnedPropValue> {
+        lock(&self.values).insert(value.tag.prop_id(), value)
+    }
+
+    /// Remove a property by ID, regardless of the type in `tag`, and return the value it had.
+    pub fn remove(&self, tag: PropTag) -> Option<OwnedPropValue> {
+        lock(&self.values).remove(&tag.prop_id())
+    }
+
+    /// Get a copy of every value, sorted by property ID.
+    pub fn values(&self) -> Vec<OwnedPropValue> {
+        let mut values: Vec<_> = lock(&self.values).values().cloned().collect();
+        values.sort_by_key(|value| value.tag.prop_id());
+        values
+    }
+
+    /// Number of properties which are set.
+    pub fn len(&self) -> usize {
+        lock(&self.values).len()
+    }
+
+    /// Test if no properties are set.
+    pub fn is_empty(&self) -> bool {
+        lock(&self.values).is_empty()
+    }
+}
+
+impl FromIterator<OwnedPropValue> for MemoryProp {
+    fn from_iter<T: IntoIterator<Item = OwnedPropValue>>(iter: T) -> Self {
+        let prop = Self::new();
+        for value in iter {
+            prop.set(value);
+        }
+        prop
+    }
+}
+
+impl MapiProp for MemoryProp {
+    fn as_mapi_prop(&self) -> &sys::IMAPIProp {
+        &self.prop
+    }
+}
+
+fn find_value(values: &HashMap<u16, OwnedPropValue>, tag: PropTag) -> Option<&OwnedPropValue> {
+    values.get(&tag.prop_id()).filter(|value| {
+        let prop_type = u32::from(tag.prop_type());
+        prop_type == sys::PT_UNSPECIFIED || prop_type == u32::from(value.tag.prop_type())
+    })
+}
+
+/// Implementation of [`sys::IMAPIProp`] for [`MemoryProp`].
+#[implement(sys::IMAPIProp)]
+struct MemoryPropImpl {
+    values: Values,
+}
+
+fn out_of_memory<T>(_: T) -> Error {
+    Error::from(sys::MAPI_E_NOT_ENOUGH_MEMORY)
+}
+
+/// Allocate a [`sys::SPropTagArray`] with [`sys::MAPIAllocateBuffer`], which the caller must free
+/// with [`sys::MAPIFreeBuffer`].
+fn alloc_prop_tag_array(tags: &[u32]) -> Result<*mut sys::SPropTagArray> {
+    let mut buffer = MAPIUninit::<u32>::new(tags.len() + 1).map_err(out_of_memory)?;
+    for (tag, mut target) in iter::once(tags.len() as u32)
+        .chain(tags.iter().copied())
+        .zip(buffer.iter())
+    {
+        target.uninit().map_err(out_of_memory)?.write(tag);
+    }
+    let result = buffer.uninit().map_err(out_of_memory)?.as_mut_ptr() as *mut _;
+    mem::forget(unsafe { buffer.assume_init() });
+    Ok(result)
+}
+
+impl sys::IMAPIProp_Impl for MemoryPropImpl_Impl {
+    fn GetLastError(
+        &self,
+        _hresult: HRESULT,
+        _ulflags: u32,
+        _lppmapierror: *mut *mut sys::MAPIERROR,
+    ) -> Result<()> {
+        Err(Error::from(sys::MAPI_E_NO_SUPPORT))
+    }
+
+    fn SaveChanges(&self, _ulflags: u32) -> Result<()> {
+        Ok(())
+    }
+
+    fn GetProps(
+        &self,
+        lpproptagarray: *mut sys::SPropTagArray,
+        _ulflags: u32,
+        lpcvalues: *mut u32,
+        lppproparray: *mut *mut sys::SPropValue,
+    ) -> Result<()> {
+        if lpcvalues.is_null() || lppproparray.is_null() {
+            return Err(Error::from(sys::MAPI_E_INVALID_PARAMETER));
+        }
+        let values = lock(&self.values);
+        let mut errors = false;
+        let values: Vec<_> = match unsafe { lpproptagarray.as_ref() } {
+            Some(tags) => {
+                let tags = unsafe {
+                    slice::from_raw_parts(tags.aulPropTag.as_ptr(), tags.cValues as usize)
+                };
+                tags.iter()
+                    .map(|tag| {
+                        let tag = PropTag(*tag);
+                        find_value(&values, tag).cloned().unwrap_or_else(|| {
+                            errors = true;
+                            OwnedPropValue {
+                                tag: tag.change_prop_type(PropType::new(sys::PT_ERROR as u16)),
+                                value: OwnedPropValueData::Error(sys::MAPI_E_NOT_FOUND),
+                            }
+                        })
+                    })
+                    .collect()
+            }
+            None => {
+                let mut values: Vec<_> = values.values().cloned().collect();
+                values.sort_by_key(|value| value.tag.prop_id());
+                values
+            }
+        };
+        let count = values.len() as u32;
+        let buffer = PropValueBuilder::from_iter(values)
+            .build()
+            .map_err(out_of_memory)?;
+        unsafe {
+            *lpcvalues = count;
+            *lppproparray = buffer.into_raw();
+        }
+        if errors {
+            Err(Error::from(sys::MAPI_W_ERRORS_RETURNED))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn GetPropList(
+        &self,
+        _ulflags: u32,
+        lppproptagarray: *mut *mut sys::SPropTagArray,
+    ) -> Result<()> {
+        if lppproptagarray.is_null() {
+            return Err(Error::from(sys::MAPI_E_INVALID_PARAMETER));
+        }
+        let mut tags: Vec<_> = lock(&self.values)
+            .values()
+            .map(|value| u32::from(value.tag))
+            .collect();
+        tags.sort_by_key(|tag| PropTag(*tag).prop_id());
+        unsafe {
+            *lppproptagarray = alloc_prop_tag_array(&tags)?;
+        }
+        Ok(())
+    }
+
+    fn OpenProperty(
+        &self,
+        _ulproptag: u32,
+        _lpiid: *mut GUID,
+        _ulinterfaceoptions: u32,
+        _ulflags: u32,
+        _lppunk: *mut Option<IUnknown>,
+    ) -> Result<()> {
+        Err(Error::from(sys::MAPI_E_NO_SUPPORT))
+    }
+
+    fn SetProps(
+        &self,
+        cvalues: u32,
+        lpproparray: *mut sys::SPropValue,
+        lppproblems: *mut *mut sys::SPropProblemArray,
+    ) -> Result<()> {
+        if cvalues > 0 && lpproparray.is_null() {
+            return Err(Error::from(sys::MAPI_E_INVALID_PARAMETER));
+        }
+        if !lppproblems.is_null() {
+            unsafe {
+                *lppproblems = ptr::null_mut();
+            }
+        }
+        if cvalues == 0 {
+            return Ok(());
+        }
+        let mut values = lock(&self.values);
+        for value in unsafe { slice::from_raw_parts(lpproparray, cvalues as usize) } {
+            let value = PropValue::from(value);
+            match u32::from(value.tag.prop_type()) {
+                sys::PT_ERROR | sys::PT_NULL => continue,
+                _ => {
+                    values.insert(value.tag.prop_id(), OwnedPropValue::from(value));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn DeleteProps(
+        &self,
+        lpproptagarray: *mut sys::SPropTagArray,
+        lppproblems: *mut *mut sys::SPropProblemArray,
+    ) -> Result<()> {
+        let Some(tags) = (unsafe { lpproptagarray.as_ref() }) else {
+            return Err(Error::from(sys::MAPI_E_INVALID_PARAMETER));
+        };
+        if !lppproblems.is_null() {
+            unsafe {
+                *lppproblems = ptr::null_mut();
+            }
+        }
+        let tags =
+            unsafe { slice::from_raw_parts(tags.aulPropTag.as_ptr(), tags.cValues as usize) };
+        let mut values = lock(&self.values);
+        for tag in tags {
+            values.remove(&PropTag(*tag).prop_id());
+        }
+        Ok(())
+    }
+
+    fn CopyTo(
+        &self,
+        _ciidexclude: u32,
+        _rgiidexclude: *mut GUID,
+        _lpexcludeprops: *mut sys::SPropTagArray,
+        _uluiparam: usize,
+        _lpprogress: Option<&sys::IMAPIProgress>,
+        _lpinterface: *mut GUID,
+        _lpdestobj: *mut core::ffi::c_void,
+        _ulflags: u32,
+        _lppproblems: *mut *mut sys::SPropProblemArray,
+    ) -> Result<()> {
+        Err(Error::from(sys::MAPI_E_NO_SUPPORT))
+    }
+
+    fn CopyProps(
+        &self,
+        _lpincludeprops: *mut sys::SPropTagArray,
+        _uluiparam: usize,
+        _lpprogress: Option<&sys::IMAPIProgress>,
+        _lpinterface: *mut GUID,
+        _lpdestobj: *mut core::ffi::c_void,
+        _ulflags: u32,
+        _lppproblems: *mut *mut sys::SPropProblemArray,
+    ) -> Result<()> {
+        Err(Error::from(sys::MAPI_E_NO_SUPPORT))
+    }
+
+    fn GetNamesFromIDs(
+        &self,
+        _lppproptags: *mut *mut sys::SPropTagArray,
+        _lppropsetguid: *mut GUID,
+        _ulflags: u32,
+        _lpcpropnames: *mut u32,
+        _lppppropnames: *mut *mut *mut sys::MAPINAMEID,
+    ) -> Result<()> {
+        Err(Error::from(sys::MAPI_E_NO_SUPPORT))
+    }
+
+    fn GetIDsFromNames(
+        &self,
+        _cpropnames: u32,
+        _lpppropnames: *mut *mut sys::MAPINAMEID,
+        _ulflags: u32,
+        _lppproptags: *mut *mut sys::SPropTagArray,
+    ) -> Result<()> {
+        Err(Error::from(sys::MAPI_E_NO_SUPPORT))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subject(value: &str) -> OwnedPropValue {
+        OwnedPropValue {
+            tag: PropTag(sys::PR_SUBJECT_W),
+            value: OwnedPropValueData::Unicode(value.to_string()),
+        }
+    }
+
+    #[test]
+    fn set_and_get() {
+        let prop = MemoryProp::new();
+        assert!(prop.is_empty());
+        assert_eq!(prop.set(subject("first")), None);
+        assert_eq!(prop.set(subject("second")), Some(subject("first")));
+        assert_eq!(
+            prop.get(PropTag(sys::PR_SUBJECT_W)),
+            Some(subject("second"))
+        );
+        assert_eq!(
+            prop.get(
+                PropTag(sys::PR_SUBJECT_W)
+                    .change_prop_type(PropType::new(sys::PT_UNSPECIFIED as u16))
+            ),
+            Some(subject("second"))
+        );
+        assert_eq!(prop.get(PropTag(sys::PR_SUBJECT_A)), None);
+
+        let mut tags = [1, sys::PR_SUBJECT_A];
+        unsafe {
+            prop.as_mapi_prop()
+                .DeleteProps(tags.as_mut_ptr() as *mut _, ptr::null_mut())
+                .expect("DeleteProps failed");
+        }
+        assert!(prop.is_empty());
+
+        let mut value = sys::SPropValue {
+            ulPropTag: sys::PR_MESSAGE_FLAGS,
+            ..Default::default()
+        };
+        value.Value.l = sys::MSGFLAG_READ as i32;
+        unsafe {
+            prop.as_mapi_prop()
+                .SetProps(1, &mut value, ptr::null_mut())
+                .expect("SetProps failed");
+        }
+        assert_eq!(
+            prop.values(),
+            vec![OwnedPropValue {
+                tag: PropTag(sys::PR_MESSAGE_FLAGS),
+                value: OwnedPropValueData::Long(sys::MSGFLAG_READ as i32),
+            }]
+        );
+    }
+}
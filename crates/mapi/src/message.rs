@@ -0,0 +1,157 @@
+//! Define [`Message`] and [`SaveChangesFlags`].
+
+use crate::{
+    mapi_prop::{delete_prop_problems, open_container_entry, open_store_entry, set_sprop_problems},
+    sys, Folder, OpenFlags, PropProblems, PropTag, PropValue, Row, Store,
+};
+use core::ptr;
+use std::iter;
+use windows_core::*;
+
+/// Set of flags that can be passed to [`sys::IMAPIProp::SaveChanges`] by
+/// [`Message::save_changes`].
+#[derive(Default)]
+pub struct SaveChangesFlags {
+    /// Pass [`sys::FORCE_SAVE`].
+    pub force_save: bool,
+
+    /// Pass [`sys::KEEP_OPEN_READONLY`].
+    pub keep_open_read_only: bool,
+
+    /// Pass [`sys::KEEP_OPEN_READWRITE`].
+    pub keep_open_read_write: bool,
+}
+
+impl From<SaveChangesFlags> for u32 {
+    fn from(value: SaveChangesFlags) -> Self {
+        let force_save = if value.force_save { sys::FORCE_SAVE } else { 0 };
+        let keep_open_read_only = if value.keep_open_read_only {
+            sys::KEEP_OPEN_READONLY
+        } else {
+            0
+        };
+        let keep_open_read_write = if value.keep_open_read_write {
+            sys::KEEP_OPEN_READWRITE
+        } else {
+            0
+        };
+
+        force_save | keep_open_read_only | keep_open_read_write
+    }
+}
+
+/// Wrapper for a [`sys::IMessage`], opened with [`Folder::open_message`] or
+/// [`Store::open_message`].
+///
+/// Cloning a [`Message`] calls `AddRef` on the same [`sys::IMessage`], it does not copy the
+/// message. Unsaved property, recipient, and attachment changes are shared by every clone, and
+/// whichever clone calls [`sys::IMAPIProp::SaveChanges`] first saves them for all of them. Use
+/// [`sys::IMAPIProp::CopyTo`] for an independent copy.
+#[derive(Clone)]
+pub struct Message {
+    message: sys::IMessage,
+}
+
+impl Message {
+    /// Borrow the [`sys::IMessage`], e.g. to pass it to the functions in this crate which take a
+    /// `&sys::IMessage`, or to call methods which do not have a wrapper yet.
+    pub fn message(&self) -> &sys::IMessage {
+        &self.message
+    }
+
+    /// Read a set of properties with [`sys::IMAPIProp::GetProps`], requesting
+    /// [`sys::MAPI_UNICODE`] strings. The [`Row`] has one value per tag in the same order, and
+    /// properties which are missing come back as [`sys::PT_ERROR`] values.
+    pub fn get_props(&self, tags: &[PropTag]) -> Result<Row> {
+        let mut prop_tag_array: Vec<u32> = iter::once(tags.len() as u32)
+            .chain(tags.iter().copied().map(u32::from))
+            .collect();
+        let mut count = 0;
+        let mut props = ptr::null_mut();
+        unsafe {
+            self.message.GetProps(
+                prop_tag_array.as_mut_ptr() as *mut _,
+                sys::MAPI_UNICODE,
+                &mut count,
+                &mut props,
+            )?;
+        }
+        Ok(Row::new(&mut sys::SRow {
+            ulAdrEntryPad: 0,
+            cValues: count,
+            lpProps: props,
+        }))
+    }
+
+    /// Set a set of properties with [`sys::IMAPIProp::SetProps`]. The changes are not persisted
+    /// until [`Message::save_changes`] is called. Returns the [`PropProblems`] for any properties
+    /// which could not be set; use [`PropProblems::into_result`] to treat them as an error.
+    pub fn set_props(&self, values: &[PropValue]) -> Result<PropProblems> {
+        let mut props: Vec<_> = values.iter().map(PropValue::to_sprop_value).collect();
+        set_sprop_problems(&self.message, &mut props)
+    }
+
+    /// Delete a set of properties with [`sys::IMAPIProp::DeleteProps`]. Returns the
+    /// [`PropProblems`] for any properties which could not be deleted. Properties which are
+    /// already missing are ignored.
+    pub fn delete_props(&self, tags: &[PropTag]) -> Result<PropProblems> {
+        let tags: Vec<_> = tags.iter().copied().map(u32::from).collect();
+        delete_prop_problems(&self.message, &tags)
+    }
+
+    /// Persist the changes with [`sys::IMAPIProp::SaveChanges`].
+    pub fn save_changes(&self, flags: SaveChangesFlags) -> Result<()> {
+        unsafe { self.message.SaveChanges(flags.into()) }
+    }
+
+    /// Get the [`sys::IMessage::GetAttachmentTable`], with [`sys::MAPI_UNICODE`] string columns.
+    pub fn attachment_table(&self) -> Result<sys::IMAPITable> {
+        unsafe { self.message.GetAttachmentTable(sys::MAPI_UNICODE) }
+    }
+
+    /// Get the [`sys::IMessage::GetRecipientTable`], with [`sys::MAPI_UNICODE`] string columns.
+    pub fn recipient_table(&self) -> Result<sys::IMAPITable> {
+        unsafe { self.message.GetRecipientTable(sys::MAPI_UNICODE) }
+    }
+}
+
+impl From<sys::IMessage> for Message {
+    fn from(message: sys::IMessage) -> Self {
+        Self { message }
+    }
+}
+
+impl From<Message> for sys::IMessage {
+    fn from(value: Message) -> Self {
+        value.message
+    }
+}
+
+impl Folder {
+    /// Open a message in this folder with [`sys::IMAPIContainer::OpenEntry`], e.g. with the
+    /// [`sys::PR_ENTRYID`] from a row in the [`Folder::contents_table`].
+    pub fn open_message(&self, entry_id: &[u8], flags: OpenFlags) -> Result<Message> {
+        open_container_entry::<sys::IMessage>(self.folder(), entry_id, flags).map(Message::from)
+    }
+
+    /// Create a new message in this folder with [`sys::IMAPIFolder::CreateMessage`]. The message
+    /// is not persisted until [`Message::save_changes`] is called.
+    pub fn create_message(&self) -> Result<Message> {
+        let mut message = None;
+        unsafe {
+            self.folder()
+                .CreateMessage(ptr::null_mut(), 0, &mut message)?;
+        }
+        message
+            .map(Message::from)
+            .ok_or_else(|| Error::from(sys::MAPI_E_CALL_FAILED))
+    }
+}
+
+impl Store {
+    /// Open a message in this store with [`sys::IMsgStore::OpenEntry`], without opening the
+    /// folder it is in.
+    pub fn open_message(&self, entry_id: &[u8], flags: OpenFlags) -> Result<Message> {
+        open_store_entry::<sys::IMessage>(self.store(), entry_id, flags).map(Message::from)
+    }
+}
@@ -0,0 +1,257 @@
+//! Define [`MessageBuilder`] and [`NewAttachment`].
+
+use crate::{
+    mapi_prop::{set_props, SetPropValue},
+    sys, MapiProp, Message, OpenFlags, PropTag, ReceiptRequest, Recipient, RecipientType,
+    SaveChangesFlags, SpecialFolder, Store,
+};
+use core::ptr;
+use windows::Win32::System::Com::{IStream, STGC_DEFAULT};
+use windows_core::*;
+
+/// Largest chunk [`NewAttachment`] data is written in, with [`IStream`].
+const WRITE_CHUNK_SIZE: usize = 0x10000;
+
+/// File attachment for [`MessageBuilder::attachment`], attached by value with
+/// [`sys::ATTACH_BY_VALUE`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NewAttachment {
+    /// [`sys::PR_ATTACH_LONG_FILENAME_W`] and [`sys::PR_DISPLAY_NAME_W`]
+    pub file_name: String,
+
+    /// [`sys::PR_ATTACH_DATA_BIN`], which is streamed to the attachment, so it can be larger than
+    /// a provider would accept inline.
+    pub data: Vec<u8>,
+}
+
+/// Compose a message and send it through the transports in the current profile, with
+/// [`sys::IMessage::SubmitMessage`].
+///
+/// The message is created in the [`SpecialFolder::Outbox`] of the store, and unless
+/// [`MessageBuilder::keep_copy`] is turned off, the transport moves it to the
+/// [`SpecialFolder::SentItems`] after it is sent.
+///
+/// ```no_run
+/// # use outlook_mapi::*;
+/// # fn example(store: &Store) -> windows_core::Result<()> {
+/// MessageBuilder::new()
+///     .subject("Quarterly report")
+///     .body("The report is attached.")
+///     .to("Jane Doe", "jane@example.com")
+///     .attachment("report.pdf", std::fs::read("report.pdf").unwrap())
+///     .send(store)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MessageBuilder {
+    message_class: String,
+    subject: String,
+    body: String,
+    recipients: Vec<Recipient>,
+    attachments: Vec<NewAttachment>,
+    receipts: Option<ReceiptRequest>,
+    keep_copy: bool,
+}
+
+impl Default for MessageBuilder {
+    fn default() -> Self {
+        Self {
+            message_class: "IPM.Note".to_string(),
+            subject: Default::default(),
+            body: Default::default(),
+            recipients: Default::default(),
+            attachments: Default::default(),
+            receipts: None,
+            keep_copy: true,
+        }
+    }
+}
+
+impl MessageBuilder {
+    /// Start an empty `IPM.Note`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Replace the [`sys::PR_MESSAGE_CLASS_W`], which defaults to `IPM.Note`.
+    pub fn message_class(mut self, message_class: impl Into<String>) -> Self {
+        self.message_class = message_class.into();
+        self
+    }
+
+    /// Set the [`sys::PR_SUBJECT_W`].
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = subject.into();
+        self
+    }
+
+    /// Set the plain text [`sys::PR_BODY_W`].
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Add a [`Recipient`], e.g. one which was resolved with
+    /// [`crate::AddressBook::resolve_name`].
+    pub fn recipient(mut self, recipient: Recipient) -> Self {
+        self.recipients.push(recipient);
+        self
+    }
+
+    /// Add a one-off [`RecipientType::To`] recipient with [`Recipient::smtp`].
+    pub fn to(self, display_name: impl Into<String>, email_address: impl Into<String>) -> Self {
+        self.recipient(Recipient::smtp(
+            display_name,
+            email_address,
+            RecipientType::To,
+        ))
+    }
+
+    /// Add a one-off [`RecipientType::Cc`] recipient with [`Recipient::smtp`].
+    pub fn cc(self, display_name: impl Into<String>, email_address: impl Into<String>) -> Self {
+        self.recipient(Recipient::smtp(
+            display_name,
+            email_address,
+            RecipientType::Cc,
+        ))
+    }
+
+    /// Add a one-off [`RecipientType::Bcc`] recipient with [`Recipient::smtp`].
+    pub fn bcc(self, display_name: impl Into<String>, email_address: impl Into<String>) -> Self {
+        self.recipient(Recipient::smtp(
+            display_name,
+            email_address,
+            RecipientType::Bcc,
+        ))
+    }
+
+    /// Attach a file by value.
+    pub fn attachment(mut self, file_name: impl Into<String>, data: Vec<u8>) -> Self {
+        self.attachments.push(NewAttachment {
+            file_name: file_name.into(),
+            data,
+        });
+        self
+    }
+
+    /// Request receipts and reports with [`ReceiptRequest::apply`].
+    pub fn receipts(mut self, receipts: ReceiptRequest) -> Self {
+        self.receipts = Some(receipts);
+        self
+    }
+
+    /// Keep a copy in the [`SpecialFolder::SentItems`] (the default), or set
+    /// [`sys::PR_DELETE_AFTER_SUBMIT`] so the transport deletes the message once it is sent.
+    pub fn keep_copy(mut self, keep_copy: bool) -> Self {
+        self.keep_copy = keep_copy;
+        self
+    }
+
+    /// Create and save the message in the Outbox of `store` without submitting it, e.g. so the
+    /// caller can make further changes before calling [`sys::IMessage::SubmitMessage`] itself.
+    pub fn create(&self, store: &Store) -> Result<Message> {
+        if self.recipients.is_empty() {
+            return Err(Error::from(sys::MAPI_E_INVALID_PARAMETER));
+        }
+        let message = store.outbox()?.create_message()?;
+        let sent_items = if self.keep_copy {
+            SpecialFolder::SentItems.entry_id(store.store()).ok()
+        } else {
+            None
+        };
+        let mut props = vec![
+            (
+                sys::PR_MESSAGE_CLASS_W,
+                SetPropValue::Unicode(&self.message_class),
+            ),
+            (sys::PR_SUBJECT_W, SetPropValue::Unicode(&self.subject)),
+            (sys::PR_BODY_W, SetPropValue::Unicode(&self.body)),
+        ];
+        match sent_items.as_deref() {
+            Some(entry_id) => {
+                props.push((sys::PR_SENTMAIL_ENTRYID, SetPropValue::Binary(entry_id)))
+            }
+            None => props.push((sys::PR_DELETE_AFTER_SUBMIT, SetPropValue::Boolean(true))),
+        }
+        set_props(message.message(), &props)?;
+        if let Some(receipts) = self.receipts.as_ref() {
+            receipts.apply(message.message())?;
+        }
+        message.add_recipients(&self.recipients)?;
+        for attachment in self.attachments.iter() {
+            add_attachment(message.message(), attachment)?;
+        }
+        message.save_changes(SaveChangesFlags {
+            keep_open_read_write: true,
+            ..Default::default()
+        })?;
+        Ok(message)
+    }
+
+    /// Create the message with [`MessageBuilder::create`], and submit it with
+    /// [`sys::IMessage::SubmitMessage`].
+    pub fn send(&self, store: &Store) -> Result<()> {
+        let message = self.create(store)?;
+        unsafe { message.message().SubmitMessage(0) }
+    }
+}
+
+/// Create an attachment with [`sys::IMessage::CreateAttach`], stream the data to it, and save it.
+fn add_attachment(message: &sys::IMessage, attachment: &NewAttachment) -> Result<()> {
+    let mut attach_num = 0;
+    let mut attach = None;
+    let attach = unsafe {
+        message.CreateAttach(ptr::null_mut(), 0, &mut attach_num, &mut attach)?;
+        attach.ok_or_else(|| Error::from(sys::MAPI_E_CALL_FAILED))?
+    };
+    set_props(
+        &attach,
+        &[
+            (
+                sys::PR_ATTACH_METHOD,
+                SetPropValue::Long(sys::ATTACH_BY_VALUE as i32),
+            ),
+            (
+                sys::PR_ATTACH_LONG_FILENAME_W,
+                SetPropValue::Unicode(&attachment.file_name),
+            ),
+            (
+                sys::PR_DISPLAY_NAME_W,
+                SetPropValue::Unicode(&attachment.file_name),
+            ),
+        ],
+    )?;
+    let stream: IStream = attach.open_property(
+        PropTag(sys::PR_ATTACH_DATA_BIN),
+        OpenFlags {
+            best_access: false,
+            create: true,
+            modify: true,
+            ..Default::default()
+        },
+    )?;
+    for chunk in attachment.data.chunks(WRITE_CHUNK_SIZE) {
+        let mut offset = 0;
+        while offset < chunk.len() {
+            let mut written = 0;
+            unsafe {
+                stream
+                    .Write(
+                        chunk[offset..].as_ptr() as *const _,
+                        (chunk.len() - offset) as u32,
+                        Some(&mut written),
+                    )
+                    .ok()?;
+            }
+            if written == 0 {
+                return Err(Error::from(sys::MAPI_E_CALL_FAILED));
+            }
+            offset += written as usize;
+        }
+    }
+    unsafe {
+        stream.Commit(STGC_DEFAULT)?;
+        attach.SaveChanges(0)
+    }
+}
@@ -0,0 +1,162 @@
+//! Define [`MsgFile`] and [`CLSID_MAIL_MESSAGE`], for reading and writing standalone `.msg`
+//! files.
+
+use crate::{
+    prop_data::{allocate_buffer, allocate_more, free_buffer},
+    sys, InterfaceId, Message,
+};
+use core::ptr;
+use std::path::Path;
+use windows::Win32::{
+    Foundation::E_UNEXPECTED,
+    Storage::Imapi::LPMSGSESS,
+    System::Com::{
+        StructuredStorage::{IStorage, StgCreateDocfile, StgOpenStorage, WriteClassStg},
+        STGC_DEFAULT, STGM, STGM_CREATE, STGM_READ, STGM_READWRITE, STGM_SHARE_DENY_WRITE,
+        STGM_SHARE_EXCLUSIVE, STGM_TRANSACTED,
+    },
+};
+use windows_core::*;
+
+/// `CLSID_MailMessage` is missing from the generated bindings. Outlook expects it as the class of
+/// the root storage in a `.msg` file.
+pub const CLSID_MAIL_MESSAGE: GUID = GUID::from_u128(0x00020D0B_0000_0000_C000_000000000046);
+
+/// Close the [`LPMSGSESS`] from [`sys::OpenIMsgSession`] with [`sys::CloseIMsgSession`] when
+/// dropped.
+struct MsgSession(LPMSGSESS);
+
+impl MsgSession {
+    fn new() -> Result<Self> {
+        let mut session = LPMSGSESS::default();
+        unsafe {
+            let malloc = sys::MAPIGetDefaultMalloc();
+            HRESULT(sys::OpenIMsgSession(malloc.as_ref(), 0, &mut session)).ok()?;
+        }
+        Ok(Self(session))
+    }
+}
+
+impl Drop for MsgSession {
+    fn drop(&mut self) {
+        unsafe {
+            sys::CloseIMsgSession(self.0);
+        }
+    }
+}
+
+/// A [`Message`] backed by a `.msg` file on disk, opened with [`sys::OpenIMsgOnIStg`].
+///
+/// The file is a compound file opened in transacted mode, so nothing is written to disk until
+/// [`MsgFile::commit`] is called. The [`MsgFile`] owns the message session and the storage, so
+/// the [`Message`] (and any clones of it) must not outlive it. MAPI must be initialized.
+pub struct MsgFile {
+    message: Message,
+    storage: IStorage,
+    _session: MsgSession,
+}
+
+impl MsgFile {
+    /// Create a new, empty `.msg` file, replacing the file if it already exists. String properties
+    /// are stored as [`sys::PT_UNICODE`].
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = HSTRING::from(path.as_ref());
+        let storage = unsafe {
+            StgCreateDocfile(
+                &path,
+                STGM_CREATE | STGM_READWRITE | STGM_SHARE_EXCLUSIVE | STGM_TRANSACTED,
+                0,
+            )?
+        };
+        unsafe {
+            WriteClassStg(&storage, &CLSID_MAIL_MESSAGE)?;
+        }
+        Self::open_storage(storage, sys::MAPI_UNICODE)
+    }
+
+    /// Open an existing `.msg` file. Unless `writable` is set, other processes can still read the
+    /// file, and [`MsgFile::commit`] fails.
+    pub fn open(path: impl AsRef<Path>, writable: bool) -> Result<Self> {
+        let path = HSTRING::from(path.as_ref());
+        let mode: STGM = if writable {
+            STGM_READWRITE | STGM_SHARE_EXCLUSIVE
+        } else {
+            STGM_READ | STGM_SHARE_DENY_WRITE
+        };
+        let storage =
+            unsafe { StgOpenStorage(&path, None::<&IStorage>, mode | STGM_TRANSACTED, None, 0)? };
+        Self::open_storage(storage, 0)
+    }
+
+    /// Open the [`sys::IMessage`] on an [`IStorage`] with [`sys::OpenIMsgOnIStg`].
+    fn open_storage(storage: IStorage, flags: u32) -> Result<Self> {
+        let session = MsgSession::new()?;
+        let mut message = None;
+        unsafe {
+            let malloc = sys::MAPIGetDefaultMalloc();
+            HRESULT(sys::OpenIMsgOnIStg(
+                session.0,
+                Some(allocate_buffer),
+                Some(allocate_more),
+                Some(free_buffer),
+                malloc.as_ref(),
+                ptr::null_mut(),
+                &storage,
+                ptr::null_mut(),
+                0,
+                flags,
+                &mut message,
+            ))
+            .ok()?;
+        }
+        let message = message.ok_or_else(|| Error::from(E_UNEXPECTED))?;
+        Ok(Self {
+            message: Message::from(message),
+            storage,
+            _session: session,
+        })
+    }
+
+    /// Borrow the [`Message`] stored in the file.
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+
+    /// Save the changes to the [`Message`] with [`sys::IMAPIProp::SaveChanges`], and commit the
+    /// storage to disk.
+    pub fn commit(&self) -> Result<()> {
+        unsafe {
+            self.message
+                .message()
+                .SaveChanges(sys::KEEP_OPEN_READWRITE)?;
+            self.storage.Commit(STGC_DEFAULT.0 as u32)
+        }
+    }
+}
+
+impl Message {
+    /// Copy this message, with its recipients and attachments, to a new `.msg` file at `path` with
+    /// [`sys::IMAPIProp::CopyTo`].
+    pub fn save_to_msg(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = MsgFile::create(path)?;
+        unsafe {
+            self.message().CopyTo(
+                0,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                0,
+                None::<&sys::IMAPIProgress>,
+                InterfaceId::MESSAGE.as_mut_ptr(),
+                file.message().message().as_raw(),
+                0,
+                ptr::null_mut(),
+            )?;
+        }
+        file.commit()
+    }
+
+    /// Open a `.msg` file read-only. The [`Message`] is only valid as long as the [`MsgFile`].
+    pub fn load_from_msg(path: impl AsRef<Path>) -> Result<MsgFile> {
+        MsgFile::open(path, false)
+    }
+}
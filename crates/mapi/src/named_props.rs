@@ -0,0 +1,216 @@
+//! Define [`NamedId`] and [`NamedPropertyMap`].
+
+use crate::{sys, MAPIOutParam, NamedPropName, PropTag, PropType};
+use core::{iter, ptr, slice};
+use std::collections::HashMap;
+use windows::Win32::Foundation::E_UNEXPECTED;
+use windows_core::*;
+
+/// A named property, identified by its property set and a numeric or string name, as in a
+/// [`sys::MAPINAMEID`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NamedId {
+    /// Property set GUID, e.g. [`sys::PS_PUBLIC_STRINGS`].
+    pub property_set: GUID,
+
+    /// Numeric ([`sys::MNID_ID`]) or string ([`sys::MNID_STRING`]) name within the
+    /// [`NamedId::property_set`].
+    pub name: NamedPropName,
+}
+
+impl NamedId {
+    /// Create a [`sys::MNID_ID`] name.
+    pub fn id(property_set: GUID, id: i32) -> Self {
+        Self {
+            property_set,
+            name: NamedPropName::Id(id),
+        }
+    }
+
+    /// Create a [`sys::MNID_STRING`] name.
+    pub fn string(property_set: GUID, name: &str) -> Self {
+        Self {
+            property_set,
+            name: NamedPropName::String(name.to_string()),
+        }
+    }
+}
+
+/// Cache of the named property mappings for one store, which wraps
+/// [`sys::IMAPIProp::GetIDsFromNames`] and [`sys::IMAPIProp::GetNamesFromIDs`].
+///
+/// The `PROP_ID` assigned to a name never changes once a store maps it, so every successful lookup
+/// is cached, and only names or IDs which have not been seen before go to the store. Misses are not
+/// cached, since another client may create the mapping later.
+///
+/// The tags returned by [`NamedPropertyMap::resolve`] and [`NamedPropertyMap::lookup`] have a
+/// `PROP_TYPE` of [`sys::PT_UNSPECIFIED`]; use [`PropTag::change_prop_type`] to add the type before
+/// reading or writing the property.
+///
+/// ```no_run
+/// # use outlook_mapi::{sys, *};
+/// # fn example(store: &sys::IMsgStore) -> windows_core::Result<()> {
+/// let mut named_props = NamedPropertyMap::new(store);
+/// let names = [NamedId::string(sys::PS_PUBLIC_STRINGS, "Keywords")];
+/// let keywords = named_props.resolve(&names)?[0]
+///     .change_prop_type(PropType::new(sys::PT_MV_UNICODE as u16));
+/// # Ok(())
+/// # }
+/// ```
+pub struct NamedPropertyMap {
+    prop: sys::IMAPIProp,
+    by_name: HashMap<NamedId, u16>,
+    by_id: HashMap<u16, NamedId>,
+}
+
+impl NamedPropertyMap {
+    /// Create an empty cache for the named property mappings in `store`.
+    pub fn new(store: &sys::IMsgStore) -> Self {
+        Self {
+            prop: (**store).clone(),
+            by_name: Default::default(),
+            by_id: Default::default(),
+        }
+    }
+
+    /// Get the [`PropTag`] for each name, passing [`sys::MAPI_CREATE`] to
+    /// [`sys::IMAPIProp::GetIDsFromNames`] so that missing mappings are added to the store. Fails
+    /// if the store cannot map any of the names, e.g. because it has run out of named property
+    /// IDs.
+    pub fn resolve(&mut self, names: &[NamedId]) -> Result<Vec<PropTag>> {
+        self.get_ids(names, sys::MAPI_CREATE)?
+            .into_iter()
+            .map(|tag| tag.ok_or_else(|| Error::from(sys::MAPI_E_NOT_ENOUGH_MEMORY)))
+            .collect()
+    }
+
+    /// Get the [`PropTag`] for each name without creating any mappings. Each entry is `None` if
+    /// that name has never been mapped on this store, which also means no item in the store has
+    /// that property.
+    pub fn lookup(&mut self, names: &[NamedId]) -> Result<Vec<Option<PropTag>>> {
+        self.get_ids(names, 0)
+    }
+
+    /// Reverse lookup of the [`NamedId`] for each tag with [`sys::IMAPIProp::GetNamesFromIDs`].
+    /// Each entry is `None` if the tag is not in the named property range, or if the store has no
+    /// mapping for it.
+    pub fn names(&mut self, tags: &[PropTag]) -> Result<Vec<Option<NamedId>>> {
+        let missing: Vec<_> = tags
+            .iter()
+            .map(PropTag::prop_id)
+            .filter(|prop_id| !self.by_id.contains_key(prop_id))
+            .collect();
+        if !missing.is_empty() {
+            let mut prop_tag_array: Vec<u32> = iter::once(missing.len() as u32)
+                .chain(missing.iter().map(|prop_id| {
+                    PropTag::new(PropType::new(sys::PT_UNSPECIFIED as u16), *prop_id).into()
+                }))
+                .collect();
+            let mut prop_tags = prop_tag_array.as_mut_ptr() as *mut sys::SPropTagArray;
+            let mut count = 0;
+            let mut names: MAPIOutParam<*mut sys::MAPINAMEID> = Default::default();
+            unsafe {
+                self.prop.GetNamesFromIDs(
+                    &mut prop_tags,
+                    ptr::null_mut(),
+                    0,
+                    &mut count,
+                    names.as_mut_ptr(),
+                )?;
+                let names = names
+                    .as_mut_slice(count as usize)
+                    .ok_or_else(|| Error::from(E_UNEXPECTED))?;
+                for (prop_id, name) in missing.into_iter().zip(names.iter()) {
+                    if let Some(name) = name.as_ref().and_then(|name| read_name(name)) {
+                        self.add(name, prop_id);
+                    }
+                }
+            }
+        }
+
+        Ok(tags
+            .iter()
+            .map(|tag| self.by_id.get(&tag.prop_id()).cloned())
+            .collect())
+    }
+
+    fn get_ids(&mut self, names: &[NamedId], flags: u32) -> Result<Vec<Option<PropTag>>> {
+        let missing: Vec<_> = names
+            .iter()
+            .filter(|name| !self.by_name.contains_key(name))
+            .collect();
+        if !missing.is_empty() {
+            let strings: Vec<_> = missing
+                .iter()
+                .map(|name| match &name.name {
+                    NamedPropName::Id(_) => Vec::new(),
+                    NamedPropName::String(value) => {
+                        value.encode_utf16().chain(iter::once(0)).collect()
+                    }
+                })
+                .collect();
+            let mut mapi_names: Vec<_> = missing
+                .iter()
+                .zip(strings.iter())
+                .map(|(name, string)| sys::MAPINAMEID {
+                    lpguid: ptr::from_ref(&name.property_set) as *mut _,
+                    ulKind: match name.name {
+                        NamedPropName::Id(_) => sys::MNID_ID,
+                        NamedPropName::String(_) => sys::MNID_STRING,
+                    },
+                    Kind: match name.name {
+                        NamedPropName::Id(id) => sys::MAPINAMEID_0 { lID: id },
+                        NamedPropName::String(_) => sys::MAPINAMEID_0 {
+                            lpwstrName: PWSTR(string.as_ptr() as *mut _),
+                        },
+                    },
+                })
+                .collect();
+            let mut name_ptrs: Vec<_> = mapi_names.iter_mut().map(ptr::from_mut).collect();
+            let mut tags: MAPIOutParam<sys::SPropTagArray> = Default::default();
+            unsafe {
+                self.prop.GetIDsFromNames(
+                    name_ptrs.len() as u32,
+                    name_ptrs.as_mut_ptr(),
+                    flags,
+                    tags.as_mut_ptr(),
+                )?;
+                let tags = tags.as_mut().ok_or_else(|| Error::from(E_UNEXPECTED))?;
+                let tags = slice::from_raw_parts(tags.aulPropTag.as_ptr(), tags.cValues as usize);
+                let mapped: Vec<_> = missing
+                    .into_iter()
+                    .zip(tags.iter().map(|tag| PropTag(*tag)))
+                    .filter(|(_, tag)| u32::from(tag.prop_type()) != sys::PT_ERROR)
+                    .map(|(name, tag)| (name.clone(), tag.prop_id()))
+                    .collect();
+                for (name, prop_id) in mapped {
+                    self.add(name, prop_id);
+                }
+            }
+        }
+
+        Ok(names
+            .iter()
+            .map(|name| {
+                self.by_name.get(name).map(|prop_id| {
+                    PropTag::new(PropType::new(sys::PT_UNSPECIFIED as u16), *prop_id)
+                })
+            })
+            .collect())
+    }
+
+    fn add(&mut self, name: NamedId, prop_id: u16) {
+        self.by_id.insert(prop_id, name.clone());
+        self.by_name.insert(name, prop_id);
+    }
+}
+
+unsafe fn read_name(name: &sys::MAPINAMEID) -> Option<NamedId> {
+    let property_set = *name.lpguid.as_ref()?;
+    let name = match name.ulKind {
+        sys::MNID_ID => NamedPropName::Id(name.Kind.lID),
+        sys::MNID_STRING => NamedPropName::String(name.Kind.lpwstrName.to_string().ok()?),
+        _ => return None,
+    };
+    Some(NamedId { property_set, name })
+}
@@ -0,0 +1,156 @@
+//! Define [`NoUi`] and [`NoUiError`].
+
+use crate::{sys, UiParam};
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use windows_core::*;
+
+static NO_UI: AtomicBool = AtomicBool::new(false);
+
+/// Error returned by the wrappers in this crate when [`NoUi`] is enabled and a call could show a
+/// dialog. It converts to an [`Error`] with the [`fmt::Display`] text as the message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoUiError {
+    /// The caller asked for a flag which shows a dialog, e.g. [`sys::MAPI_LOGON_UI`]. Converts to
+    /// [`sys::MAPI_E_NO_SUPPORT`].
+    DialogFlag {
+        /// Name of the MAPI function which the wrapper would have called.
+        api: &'static str,
+
+        /// Name of the flag which shows a dialog.
+        flag: &'static str,
+    },
+
+    /// MAPI failed with an error which it would have prompted the user to fix if UI were allowed,
+    /// e.g. [`sys::MAPI_E_LOGON_FAILED`] for a missing profile or password. Converts to the
+    /// original `code`.
+    PromptRequired {
+        /// Name of the MAPI function which failed.
+        api: &'static str,
+
+        /// The original error code returned by `api`.
+        code: HRESULT,
+    },
+}
+
+impl fmt::Display for NoUiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DialogFlag { api, flag } => {
+                write!(f, "{api} with {flag} is not allowed in no-UI mode")
+            }
+            Self::PromptRequired { api, code } => write!(
+                f,
+                "{api} failed with 0x{:08X} and would have prompted the user",
+                code.0
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NoUiError {}
+
+impl From<NoUiError> for Error {
+    fn from(value: NoUiError) -> Self {
+        let code = match value {
+            NoUiError::DialogFlag { .. } => sys::MAPI_E_NO_SUPPORT,
+            NoUiError::PromptRequired { code, .. } => code,
+        };
+        Error::new(code, value.to_string())
+    }
+}
+
+/// Crate-wide switch for services which must never block on a dialog box, e.g. one which nobody
+/// can see because the process runs in a non-interactive session.
+///
+/// While it is enabled:
+/// - [`crate::Logon::new`] rejects [`crate::LogonFlags::logon_ui`] and
+///   [`crate::LogonFlags::service_ui_always`] with [`NoUiError::DialogFlag`], always passes
+///   [`sys::MAPI_NO_MAIL`] so the spooler does not start and prompt for transport credentials,
+///   ignores the [`UiParam`], and reports a logon failure as [`NoUiError::PromptRequired`].
+/// - [`crate::Logon::open_store`] always passes [`sys::MDB_NO_DIALOG`].
+/// - [`crate::move_folder`] never passes [`sys::FOLDER_DIALOG`] or a parent window.
+///
+/// ```no_run
+/// # use outlook_mapi::*;
+/// NoUi::enable();
+/// ```
+pub struct NoUi;
+
+impl NoUi {
+    /// Turn on no-UI mode for the whole process. Calls which are already in progress are not
+    /// affected.
+    pub fn enable() {
+        NO_UI.store(true, Ordering::Relaxed);
+    }
+
+    /// Turn off no-UI mode for the whole process.
+    pub fn disable() {
+        NO_UI.store(false, Ordering::Relaxed);
+    }
+
+    /// Test if no-UI mode is enabled.
+    pub fn is_enabled() -> bool {
+        NO_UI.load(Ordering::Relaxed)
+    }
+
+    /// Fail with [`NoUiError::DialogFlag`] if no-UI mode is enabled and `flag` is set.
+    pub fn check_flag(
+        api: &'static str,
+        flag: &'static str,
+        set: bool,
+    ) -> core::result::Result<(), NoUiError> {
+        if set && Self::is_enabled() {
+            Err(NoUiError::DialogFlag { api, flag })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Replace the parent window with none if no-UI mode is enabled.
+    pub fn ui_param(ui_param: UiParam) -> UiParam {
+        if Self::is_enabled() {
+            UiParam::default()
+        } else {
+            ui_param
+        }
+    }
+
+    /// Convert an error which MAPI would have prompted the user to fix into
+    /// [`NoUiError::PromptRequired`] if no-UI mode is enabled.
+    pub(crate) fn prompt_error(api: &'static str, err: Error) -> Error {
+        let code = err.code();
+        if Self::is_enabled() && [sys::MAPI_E_LOGON_FAILED, sys::MAPI_E_USER_CANCEL].contains(&code)
+        {
+            NoUiError::PromptRequired { api, code }.into()
+        } else {
+            err
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_ui_errors() {
+        let err = Error::from(NoUiError::DialogFlag {
+            api: "MAPILogonEx",
+            flag: "MAPI_LOGON_UI",
+        });
+        assert_eq!(err.code(), sys::MAPI_E_NO_SUPPORT);
+        assert_eq!(
+            err.message(),
+            "MAPILogonEx with MAPI_LOGON_UI is not allowed in no-UI mode"
+        );
+
+        let err = Error::from(NoUiError::PromptRequired {
+            api: "MAPILogonEx",
+            code: sys::MAPI_E_LOGON_FAILED,
+        });
+        assert_eq!(err.code(), sys::MAPI_E_LOGON_FAILED);
+    }
+}
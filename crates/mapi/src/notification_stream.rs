@@ -0,0 +1,119 @@
+//! Define [`NotificationStream`].
+
+use crate::{AdviseConnection, Logon, Notification, NotificationEvents, Store};
+use std::{
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+use windows::Win32::Foundation::E_ILLEGAL_METHOD_CALL;
+use windows_core::*;
+
+/// Channel which receives the notifications for an [`AdviseConnection`], so a service can block
+/// in [`NotificationStream::recv`] instead of polling or handling each [`Notification`] inside a
+/// callback.
+///
+/// Blocking only works if the notifications arrive on another thread, so the constructors fail
+/// with [`E_ILLEGAL_METHOD_CALL`] unless MAPI was initialized with
+/// [`crate::InitializeFlags::multithread_notifications`]. Without that flag, MAPI delivers the
+/// notifications through window messages on the thread which registered for them, and a blocked
+/// [`NotificationStream::recv`] on that thread would never see them. Use [`Logon::advise`] or
+/// [`Store::advise`] with a closure in that case, and keep pumping messages.
+///
+/// ```no_run
+/// # use outlook_mapi::*;
+/// # fn example(logon: &Logon, store: &Store) -> windows_core::Result<()> {
+/// let events = NotificationEvents {
+///     new_mail: true,
+///     ..Default::default()
+/// };
+/// let stream = NotificationStream::store(logon, store, &[], events)?;
+/// while let Some(notification) = stream.recv() {
+///     if let Notification::NewMail(new_mail) = notification {
+///         println!("new {} message", new_mail.message_class);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct NotificationStream {
+    receiver: Receiver<Notification>,
+    connection: AdviseConnection,
+}
+
+impl NotificationStream {
+    /// Register for `events` on the session with [`Logon::advise`].
+    pub fn session(logon: &Logon, entry_id: &[u8], events: NotificationEvents) -> Result<Self> {
+        Self::check_multithread_notifications(logon)?;
+        let (sender, receiver) = mpsc::channel();
+        let connection = logon.advise(entry_id, events, move |notification| {
+            let _ = sender.send(notification);
+        })?;
+        Ok(Self {
+            receiver,
+            connection,
+        })
+    }
+
+    /// Register for `events` on a store which was opened with `logon`, with [`Store::advise`].
+    pub fn store(
+        logon: &Logon,
+        store: &Store,
+        entry_id: &[u8],
+        events: NotificationEvents,
+    ) -> Result<Self> {
+        Self::check_multithread_notifications(logon)?;
+        let (sender, receiver) = mpsc::channel();
+        let connection = store.advise(entry_id, events, move |notification| {
+            let _ = sender.send(notification);
+        })?;
+        Ok(Self {
+            receiver,
+            connection,
+        })
+    }
+
+    fn check_multithread_notifications(logon: &Logon) -> Result<()> {
+        if logon.initialized().multithread_notifications() {
+            Ok(())
+        } else {
+            Err(Error::from(E_ILLEGAL_METHOD_CALL))
+        }
+    }
+
+    /// Borrow the [`AdviseConnection`]. The registration is removed when the
+    /// [`NotificationStream`] is dropped.
+    pub fn connection(&self) -> &AdviseConnection {
+        &self.connection
+    }
+
+    /// Block until the next notification arrives. Returns `None` if the provider released the
+    /// advise sink, e.g. because the store was closed.
+    pub fn recv(&self) -> Option<Notification> {
+        self.receiver.recv().ok()
+    }
+
+    /// Same as [`NotificationStream::recv`], but give up and return `None` after `timeout`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Notification> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
+
+    /// Return the next notification if one has already arrived, without blocking.
+    pub fn try_recv(&self) -> Option<Notification> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Iterate over the notifications which have already arrived, without blocking.
+    pub fn try_iter(&self) -> impl Iterator<Item = Notification> + '_ {
+        self.receiver.try_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a NotificationStream {
+    type Item = Notification;
+    type IntoIter = mpsc::Iter<'a, Notification>;
+
+    /// Block on each notification in turn, like [`NotificationStream::recv`].
+    fn into_iter(self) -> Self::IntoIter {
+        self.receiver.iter()
+    }
+}
@@ -0,0 +1,546 @@
+//! Define [`Notification`], [`NotificationEvents`], and [`AdviseConnection`].
+
+use crate::{
+    mapi_prop::decode_ansi, sys, EventLog, LogEventKind, Logon, OwnedPropValue, PropTag, PropValue,
+    Store, StoreCapabilities, PROP_TYPE_MASK,
+};
+use core::{ffi::CStr, ptr, slice};
+use std::panic::{self, AssertUnwindSafe};
+use windows::Win32::{Foundation::E_UNEXPECTED, Globalization::CP_ACP};
+use windows_core::*;
+use windows_implement::implement;
+
+/// Set of events which can be passed to [`Logon::advise`] or [`Store::advise`] as the
+/// `ulEventMask`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NotificationEvents {
+    /// Pass [`sys::fnevCriticalError`].
+    pub critical_error: bool,
+
+    /// Pass [`sys::fnevNewMail`].
+    pub new_mail: bool,
+
+    /// Pass [`sys::fnevObjectCreated`].
+    pub object_created: bool,
+
+    /// Pass [`sys::fnevObjectDeleted`].
+    pub object_deleted: bool,
+
+    /// Pass [`sys::fnevObjectModified`].
+    pub object_modified: bool,
+
+    /// Pass [`sys::fnevObjectMoved`].
+    pub object_moved: bool,
+
+    /// Pass [`sys::fnevObjectCopied`].
+    pub object_copied: bool,
+
+    /// Pass [`sys::fnevSearchComplete`].
+    pub search_complete: bool,
+
+    /// Pass [`sys::fnevTableModified`].
+    pub table_modified: bool,
+
+    /// Pass [`sys::fnevStatusObjectModified`].
+    pub status_object_modified: bool,
+
+    /// Pass [`sys::fnevExtended`].
+    pub extended: bool,
+}
+
+impl NotificationEvents {
+    /// Every object event: created, deleted, modified, moved, and copied.
+    pub fn objects() -> Self {
+        Self {
+            object_created: true,
+            object_deleted: true,
+            object_modified: true,
+            object_moved: true,
+            object_copied: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<NotificationEvents> for u32 {
+    fn from(value: NotificationEvents) -> Self {
+        [
+            (value.critical_error, sys::fnevCriticalError),
+            (value.new_mail, sys::fnevNewMail),
+            (value.object_created, sys::fnevObjectCreated),
+            (value.object_deleted, sys::fnevObjectDeleted),
+            (value.object_modified, sys::fnevObjectModified),
+            (value.object_moved, sys::fnevObjectMoved),
+            (value.object_copied, sys::fnevObjectCopied),
+            (value.search_complete, sys::fnevSearchComplete),
+            (value.table_modified, sys::fnevTableModified),
+            (value.status_object_modified, sys::fnevStatusObjectModified),
+            (value.extended, sys::fnevExtended),
+        ]
+        .into_iter()
+        .filter_map(|(set, flag)| set.then_some(flag))
+        .fold(0, |mask, flag| mask | flag)
+    }
+}
+
+/// Kind of change reported in an [`ObjectNotification`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectEvent {
+    /// [`sys::fnevObjectCreated`]
+    Created,
+
+    /// [`sys::fnevObjectDeleted`]
+    Deleted,
+
+    /// [`sys::fnevObjectModified`]
+    Modified,
+
+    /// [`sys::fnevObjectMoved`]
+    Moved,
+
+    /// [`sys::fnevObjectCopied`]
+    Copied,
+
+    /// [`sys::fnevSearchComplete`]
+    SearchComplete,
+}
+
+/// Owned copy of a [`sys::NEWMAIL_NOTIFICATION`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NewMailNotification {
+    /// Entry ID of the new message.
+    pub entry_id: Option<Vec<u8>>,
+
+    /// Entry ID of the folder which received the message.
+    pub parent_id: Option<Vec<u8>>,
+
+    /// [`sys::PR_MESSAGE_CLASS_W`] of the new message, e.g. `"IPM.Note"`.
+    pub message_class: String,
+
+    /// [`sys::PR_MESSAGE_FLAGS`] of the new message.
+    pub message_flags: u32,
+}
+
+/// Owned copy of an [`sys::OBJECT_NOTIFICATION`].
+#[derive(Clone, Debug)]
+pub struct ObjectNotification {
+    /// Which event this is.
+    pub event: ObjectEvent,
+
+    /// Entry ID of the object which changed.
+    pub entry_id: Option<Vec<u8>>,
+
+    /// [`sys::PR_OBJECT_TYPE`] of the object, e.g. [`sys::MAPI_FOLDER`] or [`sys::MAPI_MESSAGE`].
+    pub object_type: u32,
+
+    /// Entry ID of the parent folder.
+    pub parent_id: Option<Vec<u8>>,
+
+    /// Previous entry ID, for [`ObjectEvent::Moved`] and [`ObjectEvent::Copied`].
+    pub old_id: Option<Vec<u8>>,
+
+    /// Previous parent folder, for [`ObjectEvent::Moved`] and [`ObjectEvent::Copied`].
+    pub old_parent_id: Option<Vec<u8>>,
+
+    /// Properties which changed, if the provider reports them.
+    pub prop_tags: Vec<PropTag>,
+}
+
+/// Owned copy of a [`sys::TABLE_NOTIFICATION`].
+#[derive(Clone, Debug)]
+pub struct TableNotification {
+    /// [`sys::TABLE_NOTIFICATION::ulTableEvent`], e.g. [`sys::TABLE_ROW_ADDED`] or
+    /// [`sys::TABLE_RELOAD`].
+    pub event: u32,
+
+    /// Error code for [`sys::TABLE_ERROR`].
+    pub hresult: HRESULT,
+
+    /// Index column of the row which changed, if any.
+    pub index: Option<OwnedPropValue>,
+
+    /// Index column of the row before the one which changed, if any.
+    pub prior: Option<OwnedPropValue>,
+
+    /// Columns of the row which was added or modified.
+    pub row: Vec<OwnedPropValue>,
+}
+
+/// Safe, owned translation of a [`sys::NOTIFICATION`]. The data is copied out of the
+/// notification before the callback is invoked, so it can be kept or sent to another thread.
+#[derive(Clone, Debug)]
+pub enum Notification {
+    /// [`sys::fnevCriticalError`]
+    CriticalError {
+        /// Entry ID of the object which failed.
+        entry_id: Option<Vec<u8>>,
+
+        /// Error reported by the provider.
+        hresult: HRESULT,
+    },
+
+    /// [`sys::fnevNewMail`]
+    NewMail(NewMailNotification),
+
+    /// [`sys::fnevObjectCreated`], [`sys::fnevObjectDeleted`], [`sys::fnevObjectModified`],
+    /// [`sys::fnevObjectMoved`], [`sys::fnevObjectCopied`], or [`sys::fnevSearchComplete`].
+    Object(ObjectNotification),
+
+    /// [`sys::fnevTableModified`]
+    Table(TableNotification),
+
+    /// [`sys::fnevStatusObjectModified`]
+    StatusObjectModified {
+        /// Entry ID of the status object.
+        entry_id: Option<Vec<u8>>,
+
+        /// Properties of the status object which changed.
+        values: Vec<OwnedPropValue>,
+    },
+
+    /// [`sys::fnevExtended`]
+    Extended {
+        /// Provider specific event.
+        event: u32,
+
+        /// Provider specific parameters.
+        parameters: Vec<u8>,
+    },
+
+    /// Any other value of [`sys::NOTIFICATION::ulEventType`].
+    Other(u32),
+}
+
+impl From<&sys::NOTIFICATION> for Notification {
+    fn from(value: &sys::NOTIFICATION) -> Self {
+        unsafe {
+            match value.ulEventType {
+                sys::fnevCriticalError => {
+                    let err = &value.info.err;
+                    Self::CriticalError {
+                        entry_id: copy_entry_id(err.cbEntryID, err.lpEntryID),
+                        hresult: HRESULT(err.scode),
+                    }
+                }
+                sys::fnevNewMail => {
+                    let newmail = &value.info.newmail;
+                    Self::NewMail(NewMailNotification {
+                        entry_id: copy_entry_id(newmail.cbEntryID, newmail.lpEntryID),
+                        parent_id: copy_entry_id(newmail.cbParentID, newmail.lpParentID),
+                        message_class: copy_message_class(newmail),
+                        message_flags: newmail.ulMessageFlags,
+                    })
+                }
+                sys::fnevObjectCreated
+                | sys::fnevObjectDeleted
+                | sys::fnevObjectModified
+                | sys::fnevObjectMoved
+                | sys::fnevObjectCopied
+                | sys::fnevSearchComplete => {
+                    let obj = &value.info.obj;
+                    let event = match value.ulEventType {
+                        sys::fnevObjectCreated => ObjectEvent::Created,
+                        sys::fnevObjectDeleted => ObjectEvent::Deleted,
+                        sys::fnevObjectModified => ObjectEvent::Modified,
+                        sys::fnevObjectMoved => ObjectEvent::Moved,
+                        sys::fnevObjectCopied => ObjectEvent::Copied,
+                        _ => ObjectEvent::SearchComplete,
+                    };
+                    let prop_tags = if obj.lpPropTagArray.is_null() {
+                        Vec::new()
+                    } else {
+                        let tags = &*obj.lpPropTagArray;
+                        slice::from_raw_parts(tags.aulPropTag.as_ptr(), tags.cValues as usize)
+                            .iter()
+                            .copied()
+                            .map(PropTag)
+                            .collect()
+                    };
+                    Self::Object(ObjectNotification {
+                        event,
+                        entry_id: copy_entry_id(obj.cbEntryID, obj.lpEntryID),
+                        object_type: obj.ulObjType,
+                        parent_id: copy_entry_id(obj.cbParentID, obj.lpParentID),
+                        old_id: copy_entry_id(obj.cbOldID, obj.lpOldID),
+                        old_parent_id: copy_entry_id(obj.cbOldParentID, obj.lpOldParentID),
+                        prop_tags,
+                    })
+                }
+                sys::fnevTableModified => {
+                    let tab = &value.info.tab;
+                    Self::Table(TableNotification {
+                        event: tab.ulTableEvent,
+                        hresult: tab.hResult,
+                        index: copy_optional_prop(&tab.propIndex),
+                        prior: copy_optional_prop(&tab.propPrior),
+                        row: copy_props(tab.row.cValues, tab.row.lpProps),
+                    })
+                }
+                sys::fnevStatusObjectModified => {
+                    let statobj = &value.info.statobj;
+                    Self::StatusObjectModified {
+                        entry_id: copy_entry_id(statobj.cbEntryID, statobj.lpEntryID),
+                        values: copy_props(statobj.cValues, statobj.lpPropVals),
+                    }
+                }
+                sys::fnevExtended => {
+                    let ext = &value.info.ext;
+                    Self::Extended {
+                        event: ext.ulEvent,
+                        parameters: if ext.pbEventParameters.is_null() {
+                            Vec::new()
+                        } else {
+                            slice::from_raw_parts(ext.pbEventParameters, ext.cb as usize).to_vec()
+                        },
+                    }
+                }
+                event => Self::Other(event),
+            }
+        }
+    }
+}
+
+unsafe fn copy_entry_id(size: u32, entry_id: *const sys::ENTRYID) -> Option<Vec<u8>> {
+    if size == 0 || entry_id.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(entry_id as *const u8, size as usize).to_vec())
+    }
+}
+
+unsafe fn copy_message_class(newmail: &sys::NEWMAIL_NOTIFICATION) -> String {
+    if newmail.lpszMessageClass.is_null() {
+        String::new()
+    } else if newmail.ulFlags & sys::MAPI_UNICODE != 0 {
+        PCWSTR(newmail.lpszMessageClass as *const u16)
+            .to_string()
+            .unwrap_or_default()
+    } else {
+        let value = CStr::from_ptr(newmail.lpszMessageClass as *const _).to_bytes();
+        decode_ansi(value, CP_ACP).unwrap_or_else(|_| String::from_utf8_lossy(value).into())
+    }
+}
+
+unsafe fn copy_props(count: u32, values: *const sys::SPropValue) -> Vec<OwnedPropValue> {
+    if values.is_null() {
+        Vec::new()
+    } else {
+        slice::from_raw_parts(values, count as usize)
+            .iter()
+            .map(|value| PropValue::from(value).to_owned())
+            .collect()
+    }
+}
+
+fn copy_optional_prop(value: &sys::SPropValue) -> Option<OwnedPropValue> {
+    match value.ulPropTag & PROP_TYPE_MASK {
+        0 | sys::PT_NULL => None,
+        _ => Some(PropValue::from(value).to_owned()),
+    }
+}
+
+type Callback = dyn Fn(Notification) + Send + Sync;
+
+/// Implementation of [`sys::IMAPIAdviseSink`] which translates each [`sys::NOTIFICATION`] to a
+/// [`Notification`] and passes it to a closure.
+#[implement(sys::IMAPIAdviseSink)]
+struct AdviseSink {
+    callback: Box<Callback>,
+}
+
+impl sys::IMAPIAdviseSink_Impl for AdviseSink_Impl {
+    fn OnNotify(&self, cnotif: u32, lpnotifications: *mut sys::NOTIFICATION) -> u32 {
+        if lpnotifications.is_null() {
+            return 0;
+        }
+        let notifications = unsafe { slice::from_raw_parts(lpnotifications, cnotif as usize) };
+        for notification in notifications {
+            if EventLog::is_enabled() {
+                EventLog::record(
+                    LogEventKind::Notification,
+                    "IMAPIAdviseSink::OnNotify",
+                    format!("event 0x{:X}", notification.ulEventType),
+                );
+            }
+            let notification = Notification::from(notification);
+
+            // Unwinding across the COM boundary would abort the process, so drop any panic from
+            // the callback on the floor.
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| (self.callback)(notification)));
+        }
+        0
+    }
+}
+
+fn advise_sink<F>(callback: F) -> sys::IMAPIAdviseSink
+where
+    F: Fn(Notification) + Send + Sync + 'static,
+{
+    AdviseSink {
+        callback: Box::new(callback),
+    }
+    .into()
+}
+
+fn entry_id_ptr(entry_id: &[u8]) -> *mut sys::ENTRYID {
+    if entry_id.is_empty() {
+        ptr::null_mut()
+    } else {
+        entry_id.as_ptr() as *mut _
+    }
+}
+
+enum AdviseSource {
+    Session(sys::IMAPISession),
+    Store(sys::IMsgStore),
+}
+
+/// Registration returned by [`Logon::advise`] or [`Store::advise`]. The closure keeps receiving
+/// notifications until this is dropped, which calls `Unadvise`.
+///
+/// Unless MAPI was initialized with [`crate::InitializeFlags::multithread_notifications`],
+/// notifications are delivered through window messages on the thread which called `Advise`, so
+/// that thread must keep pumping messages. With it, they arrive on a separate MAPI thread, which
+/// is why the closure must be [`Send`] and [`Sync`].
+pub struct AdviseConnection {
+    source: AdviseSource,
+    connection: usize,
+}
+
+impl AdviseConnection {
+    /// Get the connection number returned by `Advise`.
+    pub fn connection(&self) -> usize {
+        self.connection
+    }
+}
+
+impl Drop for AdviseConnection {
+    /// Call [`sys::IMAPISession::Unadvise`] or [`sys::IMsgStore::Unadvise`].
+    fn drop(&mut self) {
+        unsafe {
+            let _ = match &self.source {
+                AdviseSource::Session(session) => session.Unadvise(self.connection),
+                AdviseSource::Store(store) => store.Unadvise(self.connection),
+            };
+        }
+    }
+}
+
+impl Logon {
+    /// Register `callback` for `events` on the store, address book, or status object identified
+    /// by `entry_id`, with [`sys::IMAPISession::Advise`]. An empty `entry_id` registers for
+    /// session-wide events like [`NotificationEvents::critical_error`].
+    pub fn advise<F>(
+        &self,
+        entry_id: &[u8],
+        events: NotificationEvents,
+        callback: F,
+    ) -> Result<AdviseConnection>
+    where
+        F: Fn(Notification) + Send + Sync + 'static,
+    {
+        let sink = advise_sink(callback);
+        let mut connection = 0;
+        unsafe {
+            self.session().Advise(
+                entry_id.len() as u32,
+                entry_id_ptr(entry_id),
+                events.into(),
+                &sink,
+                &mut connection,
+            )?;
+        }
+        if connection == 0 {
+            return Err(Error::from(E_UNEXPECTED));
+        }
+        Ok(AdviseConnection {
+            source: AdviseSource::Session(self.session().clone()),
+            connection,
+        })
+    }
+}
+
+impl Store {
+    /// Register `callback` for `events` on the folder or message identified by `entry_id`, with
+    /// [`sys::IMsgStore::Advise`]. An empty `entry_id` registers for events anywhere in the
+    /// store, e.g. [`NotificationEvents::new_mail`].
+    ///
+    /// Fails with [`sys::MAPI_E_NO_SUPPORT`] if the [`Store::capabilities`] do not include
+    /// [`StoreCapabilities::notifications`].
+    pub fn advise<F>(
+        &self,
+        entry_id: &[u8],
+        events: NotificationEvents,
+        callback: F,
+    ) -> Result<AdviseConnection>
+    where
+        F: Fn(Notification) + Send + Sync + 'static,
+    {
+        StoreCapabilities::check(self.store(), |capabilities| capabilities.notifications)?;
+        let sink = advise_sink(callback);
+        let mut connection = 0;
+        unsafe {
+            self.store().Advise(
+                entry_id.len() as u32,
+                entry_id_ptr(entry_id),
+                events.into(),
+                &sink,
+                &mut connection,
+            )?;
+        }
+        if connection == 0 {
+            return Err(Error::from(E_UNEXPECTED));
+        }
+        Ok(AdviseConnection {
+            source: AdviseSource::Store(self.store().clone()),
+            connection,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_mask() {
+        assert_eq!(u32::from(NotificationEvents::default()), 0);
+        assert_eq!(
+            u32::from(NotificationEvents {
+                new_mail: true,
+                ..NotificationEvents::objects()
+            }),
+            sys::fnevNewMail
+                | sys::fnevObjectCreated
+                | sys::fnevObjectDeleted
+                | sys::fnevObjectModified
+                | sys::fnevObjectMoved
+                | sys::fnevObjectCopied
+        );
+    }
+
+    #[test]
+    fn new_mail() {
+        let mut entry_id = [0_u8, 0, 0, 0, 1, 2];
+        let mut message_class = *b"IPM.Note\0";
+        let mut notification = sys::NOTIFICATION {
+            ulEventType: sys::fnevNewMail,
+            ..Default::default()
+        };
+        notification.info.newmail = sys::NEWMAIL_NOTIFICATION {
+            cbEntryID: entry_id.len() as u32,
+            lpEntryID: entry_id.as_mut_ptr() as *mut _,
+            lpszMessageClass: message_class.as_mut_ptr() as *mut _,
+            ulMessageFlags: sys::MSGFLAG_UNMODIFIED,
+            ..Default::default()
+        };
+
+        let Notification::NewMail(newmail) = Notification::from(&notification) else {
+            panic!("expected fnevNewMail");
+        };
+        assert_eq!(newmail.entry_id.as_deref(), Some(entry_id.as_slice()));
+        assert_eq!(newmail.parent_id, None);
+        assert_eq!(newmail.message_class, "IPM.Note");
+        assert_eq!(newmail.message_flags, sys::MSGFLAG_UNMODIFIED);
+    }
+}
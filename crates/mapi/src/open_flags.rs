@@ -0,0 +1,149 @@
+//! Define [`OpenFlags`] and [`DeferredOpenError`].
+
+use crate::{mapi_prop::get_one_prop, sys};
+use core::fmt;
+use windows_core::*;
+
+/// Set of flags that can be passed to [`sys::IMAPIContainer::OpenEntry`],
+/// [`sys::IMsgStore::OpenEntry`], and [`sys::IMAPIProp::OpenProperty`] by the open methods in this
+/// crate, such as [`crate::Store::open_folder_with`].
+///
+/// The [`Default`] is [`OpenFlags::best_access`] alone, which opens the object for writing if the
+/// user has permission to modify it, and read-only otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpenFlags {
+    /// Pass [`sys::MAPI_BEST_ACCESS`].
+    pub best_access: bool,
+
+    /// Pass [`sys::MAPI_CACHE_ONLY`].
+    pub cache_only: bool,
+
+    /// Pass [`sys::MAPI_CREATE`], which is only supported by [`crate::MapiProp::open_property`],
+    /// to create the property if it does not exist yet.
+    pub create: bool,
+
+    /// Pass [`sys::MAPI_DEFERRED_ERRORS`]. The open call returns immediately, and any error opening
+    /// the object is reported by the first call which uses it. Call [`OpenFlags::resolve`] to
+    /// find out whether the open actually succeeded.
+    pub deferred_errors: bool,
+
+    /// Pass [`sys::MAPI_MODIFY`].
+    pub modify: bool,
+
+    /// Pass [`sys::MAPI_NO_CACHE`].
+    pub no_cache: bool,
+
+    /// Pass [`sys::SHOW_SOFT_DELETES`], to open an item which was soft deleted.
+    pub show_soft_deletes: bool,
+}
+
+impl Default for OpenFlags {
+    fn default() -> Self {
+        Self {
+            best_access: true,
+            cache_only: false,
+            create: false,
+            deferred_errors: false,
+            modify: false,
+            no_cache: false,
+            show_soft_deletes: false,
+        }
+    }
+}
+
+impl OpenFlags {
+    /// Open the object read-only.
+    pub fn read_only() -> Self {
+        Self {
+            best_access: false,
+            ..Default::default()
+        }
+    }
+
+    /// Open the object for writing, and fail with [`sys::MAPI_E_NO_ACCESS`] if the user does not
+    /// have permission to modify it.
+    pub fn modify() -> Self {
+        Self {
+            best_access: false,
+            modify: true,
+            ..Default::default()
+        }
+    }
+
+    /// Toggle [`OpenFlags::deferred_errors`] on a set of flags, e.g.
+    /// `OpenFlags::modify().deferred_errors(true)`.
+    pub fn deferred_errors(self, deferred_errors: bool) -> Self {
+        Self {
+            deferred_errors,
+            ..self
+        }
+    }
+
+    /// Force the provider to finish opening an object which was opened with
+    /// [`OpenFlags::deferred_errors`], by reading [`sys::PR_OBJECT_TYPE`]. An error from the open
+    /// is returned as a [`DeferredOpenError`], so it can be told apart from a failure of the
+    /// operation the caller was about to perform.
+    pub fn resolve(prop: &sys::IMAPIProp) -> core::result::Result<(), DeferredOpenError> {
+        match get_one_prop(prop, sys::PR_OBJECT_TYPE, |_| Some(())) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DeferredOpenError(err)),
+        }
+    }
+}
+
+impl From<OpenFlags> for u32 {
+    fn from(value: OpenFlags) -> Self {
+        let best_access = if value.best_access {
+            sys::MAPI_BEST_ACCESS
+        } else {
+            0
+        };
+        let cache_only = if value.cache_only {
+            sys::MAPI_CACHE_ONLY
+        } else {
+            0
+        };
+        let create = if value.create { sys::MAPI_CREATE } else { 0 };
+        let deferred_errors = if value.deferred_errors {
+            sys::MAPI_DEFERRED_ERRORS
+        } else {
+            0
+        };
+        let modify = if value.modify { sys::MAPI_MODIFY } else { 0 };
+        let no_cache = if value.no_cache {
+            sys::MAPI_NO_CACHE
+        } else {
+            0
+        };
+        let show_soft_deletes = if value.show_soft_deletes {
+            sys::SHOW_SOFT_DELETES
+        } else {
+            0
+        };
+
+        best_access | cache_only | create | deferred_errors | modify | no_cache | show_soft_deletes
+    }
+}
+
+/// Error returned by [`OpenFlags::resolve`] when opening an object with
+/// [`OpenFlags::deferred_errors`] failed after the open call itself had already succeeded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeferredOpenError(pub Error);
+
+impl fmt::Display for DeferredOpenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "deferred open failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for DeferredOpenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl From<DeferredOpenError> for Error {
+    fn from(value: DeferredOpenError) -> Self {
+        value.0
+    }
+}
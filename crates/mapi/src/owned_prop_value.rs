@@ -0,0 +1,218 @@
+//! Define [`OwnedPropValue`] and [`OwnedPropValueData`].
+
+use crate::{mapi_prop::decode_ansi, sys, PropTag, PropValue, PropValueData};
+use core::{fmt, slice};
+use windows::Win32::{Foundation::FILETIME, Globalization::CP_ACP};
+use windows_core::*;
+
+/// Owned counterpart of [`PropValue`], which does not borrow from the [`sys::SPropValue`] it was
+/// read from, so it can outlive the [`crate::Row`] or [`crate::RowSet`] it came from.
+#[derive(Clone)]
+pub struct OwnedPropValue {
+    pub tag: PropTag,
+    pub value: OwnedPropValueData,
+}
+
+impl fmt::Debug for OwnedPropValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OwnedPropValue")
+            .field("tag", &format_args!("0x{:08X}", u32::from(self.tag)))
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl PartialEq for OwnedPropValue {
+    fn eq(&self, other: &Self) -> bool {
+        u32::from(self.tag) == u32::from(other.tag) && self.value == other.value
+    }
+}
+
+/// Owned counterpart of [`PropValueData`]. Strings are decoded to [`String`] (replacing anything
+/// which is not valid), and arrays are copied to [`Vec`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum OwnedPropValueData {
+    /// [`sys::PT_NULL`]
+    Null,
+
+    /// [`sys::PT_I2`] or [`sys::PT_SHORT`]
+    Short(i16),
+
+    /// [`sys::PT_I4`] or [`sys::PT_LONG`]
+    Long(i32),
+
+    /// [`sys::PT_PTR`] or [`sys::PT_FILE_HANDLE`], as an address.
+    Pointer(usize),
+
+    /// [`sys::PT_R4`] or [`sys::PT_FLOAT`]
+    Float(f32),
+
+    /// [`sys::PT_R8`] or [`sys::PT_DOUBLE`]
+    Double(f64),
+
+    /// [`sys::PT_BOOLEAN`]
+    Boolean(bool),
+
+    /// [`sys::PT_CURRENCY`]
+    Currency(i64),
+
+    /// [`sys::PT_APPTIME`]
+    AppTime(f64),
+
+    /// [`sys::PT_SYSTIME`]
+    FileTime(FILETIME),
+
+    /// [`sys::PT_STRING8`], decoded with the ANSI code page.
+    AnsiString(String),
+
+    /// [`sys::PT_BINARY`]
+    Binary(Vec<u8>),
+
+    /// [`sys::PT_UNICODE`]
+    Unicode(String),
+
+    /// [`sys::PT_CLSID`]
+    Guid(GUID),
+
+    /// [`sys::PT_I8`] or [`sys::PT_LONGLONG`]
+    LargeInteger(i64),
+
+    /// [`sys::PT_MV_SHORT`]
+    ShortArray(Vec<i16>),
+
+    /// [`sys::PT_MV_LONG`]
+    LongArray(Vec<i32>),
+
+    /// [`sys::PT_MV_FLOAT`]
+    FloatArray(Vec<f32>),
+
+    /// [`sys::PT_MV_DOUBLE`]
+    DoubleArray(Vec<f64>),
+
+    /// [`sys::PT_MV_CURRENCY`]
+    CurrencyArray(Vec<i64>),
+
+    /// [`sys::PT_MV_APPTIME`]
+    AppTimeArray(Vec<f64>),
+
+    /// [`sys::PT_MV_SYSTIME`]
+    FileTimeArray(Vec<FILETIME>),
+
+    /// [`sys::PT_MV_BINARY`]
+    BinaryArray(Vec<Vec<u8>>),
+
+    /// [`sys::PT_MV_STRING8`], decoded with the ANSI code page.
+    AnsiStringArray(Vec<String>),
+
+    /// [`sys::PT_MV_UNICODE`]
+    UnicodeArray(Vec<String>),
+
+    /// [`sys::PT_MV_CLSID`]
+    GuidArray(Vec<GUID>),
+
+    /// [`sys::PT_MV_LONGLONG`]
+    LargeIntegerArray(Vec<i64>),
+
+    /// [`sys::PT_ERROR`]
+    Error(HRESULT),
+
+    /// [`sys::PT_OBJECT`]
+    Object(i32),
+}
+
+impl PropValue<'_> {
+    /// Copy this value into an [`OwnedPropValue`].
+    pub fn to_owned(&self) -> OwnedPropValue {
+        OwnedPropValue {
+            tag: self.tag,
+            value: OwnedPropValueData::from(&self.value),
+        }
+    }
+}
+
+impl From<PropValue<'_>> for OwnedPropValue {
+    fn from(value: PropValue<'_>) -> Self {
+        value.to_owned()
+    }
+}
+
+impl From<&PropValueData<'_>> for OwnedPropValueData {
+    fn from(value: &PropValueData<'_>) -> Self {
+        let ansi = |value: &PCSTR| {
+            let value = unsafe { value.as_bytes() };
+            decode_ansi(value, CP_ACP).unwrap_or_else(|_| String::from_utf8_lossy(value).into())
+        };
+        let unicode = |value: &PCWSTR| String::from_utf16_lossy(unsafe { value.as_wide() });
+        let binary = |value: &sys::SBinary| {
+            if value.lpb.is_null() {
+                Vec::new()
+            } else {
+                unsafe { slice::from_raw_parts(value.lpb, value.cb as usize) }.to_vec()
+            }
+        };
+
+        match value {
+            PropValueData::Null => Self::Null,
+            PropValueData::Short(value) => Self::Short(*value),
+            PropValueData::Long(value) => Self::Long(*value),
+            PropValueData::Pointer(value) => Self::Pointer(*value as usize),
+            PropValueData::Float(value) => Self::Float(*value),
+            PropValueData::Double(value) => Self::Double(*value),
+            PropValueData::Boolean(value) => Self::Boolean(*value != 0),
+            PropValueData::Currency(value) => Self::Currency(*value),
+            PropValueData::AppTime(value) => Self::AppTime(*value),
+            PropValueData::FileTime(value) => Self::FileTime(*value),
+            PropValueData::AnsiString(value) => Self::AnsiString(ansi(value)),
+            PropValueData::Binary(value) => Self::Binary(value.to_vec()),
+            PropValueData::Unicode(value) => Self::Unicode(unicode(value)),
+            PropValueData::Guid(value) => Self::Guid(*value),
+            PropValueData::LargeInteger(value) => Self::LargeInteger(*value),
+            PropValueData::ShortArray(values) => Self::ShortArray(values.to_vec()),
+            PropValueData::LongArray(values) => Self::LongArray(values.to_vec()),
+            PropValueData::FloatArray(values) => Self::FloatArray(values.to_vec()),
+            PropValueData::DoubleArray(values) => Self::DoubleArray(values.clone()),
+            PropValueData::CurrencyArray(values) => {
+                Self::CurrencyArray(values.iter().map(|value| unsafe { value.int64 }).collect())
+            }
+            PropValueData::AppTimeArray(values) => Self::AppTimeArray(values.clone()),
+            PropValueData::FileTimeArray(values) => Self::FileTimeArray(values.clone()),
+            PropValueData::BinaryArray(values) => {
+                Self::BinaryArray(values.iter().map(binary).collect())
+            }
+            PropValueData::AnsiStringArray(values) => {
+                Self::AnsiStringArray(values.iter().map(ansi).collect())
+            }
+            PropValueData::UnicodeArray(values) => {
+                Self::UnicodeArray(values.iter().map(unicode).collect())
+            }
+            PropValueData::GuidArray(values) => Self::GuidArray(values.clone()),
+            PropValueData::LargeIntegerArray(values) => Self::LargeIntegerArray(values.clone()),
+            PropValueData::Error(err) => Self::Error(*err),
+            PropValueData::Object(value) => Self::Object(*value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn owned_unicode() {
+        let expected: Vec<u16> = "subject".encode_utf16().chain([0]).collect();
+        let mut value = sys::SPropValue {
+            ulPropTag: sys::PR_SUBJECT_W,
+            ..Default::default()
+        };
+        value.Value.lpszW = PWSTR(expected.as_ptr() as *mut _);
+        let value = PropValue::from(&value).to_owned();
+        drop(expected);
+        assert_eq!(
+            value,
+            OwnedPropValue {
+                tag: PropTag(sys::PR_SUBJECT_W),
+                value: OwnedPropValueData::Unicode("subject".to_string()),
+            }
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Define [`validate`], [`ServiceStatus`], and [`ServiceProblem`].
+//!
+//! These helpers use [`sys::MAPIAdminProfiles`], so the caller must keep MAPI initialized (e.g.
+//! with an [`crate::Initialize`]) while calling them.
+
+use crate::{
+    sys, Logon, MapiProp, OpenStoreFlags, PropTag, PropValue, PropValueData, RowSet,
+    SizedSPropTagArray, StringPropOptions,
+};
+use core::ptr;
+use std::{iter, path::PathBuf};
+use windows::Win32::Foundation::E_FAIL;
+use windows_core::*;
+
+/// Problem detected by [`validate`] for one of the services in a profile.
+#[derive(Debug)]
+pub enum ServiceProblem {
+    /// The [`sys::PR_PST_PATH`] of the service (or one of its providers) does not exist.
+    MissingPstFile(PathBuf),
+
+    /// A store which belongs to the service could not be opened. This is only checked if
+    /// [`validate`] is given a [`Logon`] for the profile, and it typically means the service
+    /// points at an Exchange mailbox which no longer exists.
+    StoreOpenFailed(Error),
+}
+
+/// Result of [`validate`] for one of the services in a profile.
+#[derive(Debug)]
+pub struct ServiceStatus {
+    /// [`sys::PR_SERVICE_UID`], which can be passed to [`remove_service`].
+    pub service_uid: sys::MAPIUID,
+
+    /// [`sys::PR_SERVICE_NAME_W`], e.g. `MSUPST MS` or `MSEMS`.
+    pub service_name: String,
+
+    /// [`sys::PR_DISPLAY_NAME_W`]
+    pub display_name: String,
+
+    /// Everything that looks broken about this service. If this is empty, the service passed
+    /// validation.
+    pub problems: Vec<ServiceProblem>,
+}
+
+impl ServiceStatus {
+    /// Test if [`validate`] found any problems with this service.
+    pub fn is_broken(&self) -> bool {
+        !self.problems.is_empty()
+    }
+}
+
+/// Check the services in a profile for orphaned stores.
+///
+/// Every service is checked for a [`sys::PR_PST_PATH`] which points at a missing file. If `logon`
+/// is a session for the same profile, this also attempts to open every store in the session's
+/// [`sys::IMAPISession::GetMsgStoresTable`], without any UI, and attributes failures to the
+/// service which owns the store.
+pub fn validate(profile_name: &str, logon: Option<&Logon>) -> Result<Vec<ServiceStatus>> {
+    let service_admin = admin_services(profile_name)?;
+
+    SizedSPropTagArray! { ServiceColumns[3] }
+    let mut columns = ServiceColumns {
+        aulPropTag: [
+            sys::PR_SERVICE_UID,
+            sys::PR_SERVICE_NAME_W,
+            sys::PR_DISPLAY_NAME_W,
+        ],
+        ..Default::default()
+    };
+    let mut rows: RowSet = Default::default();
+    unsafe {
+        let table = service_admin.GetMsgServiceTable(sys::MAPI_UNICODE)?;
+        sys::HrQueryAllRows(
+            &table,
+            columns.as_mut_ptr(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            0,
+            rows.as_mut_ptr(),
+        )?;
+    }
+
+    let mut statuses: Vec<_> = rows
+        .into_iter()
+        .filter_map(|row| {
+            let mut service_uid = None;
+            let mut service_name = String::new();
+            let mut display_name = String::new();
+            for PropValue { tag, value } in row.iter() {
+                match (u32::from(tag), value) {
+                    (sys::PR_SERVICE_UID, PropValueData::Binary(value)) => {
+                        service_uid = to_mapi_uid(value)
+                    }
+                    (sys::PR_SERVICE_NAME_W, PropValueData::Unicode(value)) => {
+                        service_name = unsafe { value.to_string() }.unwrap_or_default()
+                    }
+                    (sys::PR_DISPLAY_NAME_W, PropValueData::Unicode(value)) => {
+                        display_name = unsafe { value.to_string() }.unwrap_or_default()
+                    }
+                    _ => {}
+                }
+            }
+            Some(ServiceStatus {
+                service_uid: service_uid?,
+                service_name,
+                display_name,
+                problems: Vec::new(),
+            })
+        })
+        .collect();
+
+    for status in statuses.iter_mut() {
+        for path in pst_paths(&service_admin, status.service_uid)? {
+            if !path.exists() {
+                status.problems.push(ServiceProblem::MissingPstFile(path));
+            }
+        }
+    }
+
+    if let Some(logon) = logon {
+        for (service_uid, err) in open_failures(logon)? {
+            if let Some(status) = statuses
+                .iter_mut()
+                .find(|status| status.service_uid == service_uid)
+            {
+                status.problems.push(ServiceProblem::StoreOpenFailed(err));
+            }
+        }
+    }
+
+    Ok(statuses)
+}
+
+/// Delete a service from a profile with [`sys::IMsgServiceAdmin::DeleteMsgService`].
+pub fn remove_service(profile_name: &str, service_uid: sys::MAPIUID) -> Result<()> {
+    let service_admin = admin_services(profile_name)?;
+    let mut service_uid = service_uid;
+    unsafe { service_admin.DeleteMsgService(&mut service_uid) }
+}
+
+/// Delete every service which [`validate`] flagged with [`ServiceStatus::is_broken`], and return
+/// the number of services which were removed.
+pub fn remove_broken_services(profile_name: &str, statuses: &[ServiceStatus]) -> Result<usize> {
+    let mut count = 0;
+    for status in statuses.iter().filter(|status| status.is_broken()) {
+        remove_service(profile_name, status.service_uid)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn admin_services(profile_name: &str) -> Result<sys::IMsgServiceAdmin> {
+    let mut profile_name: Vec<_> = profile_name.bytes().chain(iter::once(0)).collect();
+    unsafe {
+        let profile_admin = sys::MAPIAdminProfiles(0)?;
+        let mut service_admin = None;
+        profile_admin.AdminServices(
+            profile_name.as_mut_ptr() as *mut _,
+            ptr::null_mut(),
+            0,
+            0,
+            &mut service_admin,
+        )?;
+        service_admin.ok_or_else(|| Error::from(E_FAIL))
+    }
+}
+
+fn to_mapi_uid(value: &[u8]) -> Option<sys::MAPIUID> {
+    Some(sys::MAPIUID {
+        ab: value.try_into().ok()?,
+    })
+}
+
+fn read_pst_path(section: &sys::IProfSect) -> Option<PathBuf> {
+    section
+        .get_string_props(
+            &[PropTag(sys::PR_PST_PATH)],
+            StringPropOptions {
+                ansi_fallback: true,
+                ..Default::default()
+            },
+        )
+        .ok()?
+        .into_iter()
+        .next()?
+        .ok()
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Collect the [`sys::PR_PST_PATH`] values from the profile section of the service and each of
+/// its providers. Most services which are not backed by a PST do not have any.
+fn pst_paths(
+    service_admin: &sys::IMsgServiceAdmin,
+    service_uid: sys::MAPIUID,
+) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let mut service_uid = service_uid;
+    unsafe {
+        let mut section = None;
+        if service_admin
+            .OpenProfileSection(&mut service_uid, ptr::null_mut(), 0, &mut section)
+            .is_ok()
+        {
+            paths.extend(section.as_ref().and_then(read_pst_path));
+        }
+
+        let mut provider_admin = None;
+        service_admin.AdminProviders(&mut service_uid, 0, &mut provider_admin)?;
+        let provider_admin = provider_admin.ok_or_else(|| Error::from(E_FAIL))?;
+
+        SizedSPropTagArray! { ProviderColumns[1] }
+        let mut columns = ProviderColumns {
+            aulPropTag: [sys::PR_PROVIDER_UID],
+            ..Default::default()
+        };
+        let mut rows: RowSet = Default::default();
+        let table = provider_admin.GetProviderTable(0)?;
+        sys::HrQueryAllRows(
+            &table,
+            columns.as_mut_ptr(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            0,
+            rows.as_mut_ptr(),
+        )?;
+
+        for row in rows {
+            let Some(mut provider_uid) = row.iter().find_map(|value| match value {
+                PropValue {
+                    tag,
+                    value: PropValueData::Binary(value),
+                } if u32::from(tag) == sys::PR_PROVIDER_UID => to_mapi_uid(value),
+                _ => None,
+            }) else {
+                continue;
+            };
+            let mut section = None;
+            if provider_admin
+                .OpenProfileSection(&mut provider_uid, ptr::null_mut(), 0, &mut section)
+                .is_ok()
+            {
+                paths.extend(section.as_ref().and_then(read_pst_path));
+            }
+        }
+    }
+
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
+}
+
+/// Try to open every store in the session, and return the [`sys::PR_SERVICE_UID`] of each store
+/// which failed along with the error.
+fn open_failures(logon: &Logon) -> Result<Vec<(sys::MAPIUID, Error)>> {
+    SizedSPropTagArray! { StoreColumns[2] }
+    let mut columns = StoreColumns {
+        aulPropTag: [sys::PR_ENTRYID, sys::PR_SERVICE_UID],
+        ..Default::default()
+    };
+    let mut rows: RowSet = Default::default();
+    unsafe {
+        let table = logon.session().GetMsgStoresTable(0)?;
+        sys::HrQueryAllRows(
+            &table,
+            columns.as_mut_ptr(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            0,
+            rows.as_mut_ptr(),
+        )?;
+    }
+
+    let mut failures = Vec::new();
+    for row in rows {
+        let mut entry_id = None;
+        let mut service_uid = None;
+        for PropValue { tag, value } in row.iter() {
+            match (u32::from(tag), value) {
+                (sys::PR_ENTRYID, PropValueData::Binary(value)) => entry_id = Some(value.to_vec()),
+                (sys::PR_SERVICE_UID, PropValueData::Binary(value)) => {
+                    service_uid = to_mapi_uid(value)
+                }
+                _ => {}
+            }
+        }
+        let (Some(entry_id), Some(service_uid)) = (entry_id, service_uid) else {
+            continue;
+        };
+
+        let result = logon
+            .open_store(
+                &entry_id,
+                OpenStoreFlags {
+                    best_access: true,
+                    no_dialog: true,
+                    no_mail: true,
+                    ..Default::default()
+                },
+            )
+            // Opening the store may be deferred, so read a property to make sure the provider
+            // actually connected to it.
+            .and_then(|store| store.store().get_binary(PropTag(sys::PR_RECORD_KEY)));
+        if let Err(err) = result {
+            failures.push((service_uid, err));
+        }
+    }
+
+    Ok(failures)
+}
@@ -0,0 +1,297 @@
+//! Define [`ProgressSink`], [`ProgressUpdate`], and [`EmptyFolderFlags`].
+
+use crate::{sys, EntryList, Folder, InterfaceId};
+use core::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use windows::Win32::Foundation::E_INVALIDARG;
+use windows_core::*;
+use windows_implement::implement;
+
+/// Default range for [`sys::IMAPIProgress::GetMin`] and [`sys::IMAPIProgress::GetMax`], which
+/// providers usually replace with [`sys::IMAPIProgress::SetLimits`].
+const DEFAULT_LIMITS: (u32, u32) = (1, 1000);
+
+/// One call to [`sys::IMAPIProgress::Progress`], passed to the [`ProgressSink`] callback.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProgressUpdate {
+    /// Position in the range between `min` and `max`.
+    pub value: u32,
+
+    /// Number of items which have been processed so far, if the provider reports it.
+    pub count: u32,
+
+    /// Total number of items, if the provider reports it.
+    pub total: u32,
+
+    /// Start of the range, from the last [`sys::IMAPIProgress::SetLimits`].
+    pub min: u32,
+
+    /// End of the range, from the last [`sys::IMAPIProgress::SetLimits`].
+    pub max: u32,
+}
+
+impl ProgressUpdate {
+    /// Get the position as a percentage of the range, clamped to `0..=100`.
+    pub fn percent(&self) -> u32 {
+        if self.max <= self.min {
+            return 0;
+        }
+        let done = u64::from(self.value.clamp(self.min, self.max) - self.min);
+        (done * 100 / u64::from(self.max - self.min)) as u32
+    }
+}
+
+type Callback = dyn Fn(ProgressUpdate);
+
+/// Implementation of [`sys::IMAPIProgress`] which keeps track of the limits set by the provider,
+/// and passes each [`ProgressUpdate`] to a closure.
+#[implement(sys::IMAPIProgress)]
+struct ProgressImpl {
+    callback: Box<Callback>,
+    min: Cell<u32>,
+    max: Cell<u32>,
+    flags: Cell<u32>,
+}
+
+impl sys::IMAPIProgress_Impl for ProgressImpl_Impl {
+    fn Progress(&self, ulvalue: u32, ulcount: u32, ultotal: u32) -> Result<()> {
+        let update = ProgressUpdate {
+            value: ulvalue,
+            count: ulcount,
+            total: ultotal,
+            min: self.min.get(),
+            max: self.max.get(),
+        };
+
+        // Unwinding across the COM boundary would abort the process, so drop any panic from
+        // the callback on the floor.
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| (self.callback)(update)));
+        Ok(())
+    }
+
+    fn GetFlags(&self, lpulflags: *mut u32) -> Result<()> {
+        write_out(lpulflags, self.flags.get())
+    }
+
+    fn GetMax(&self, lpulmax: *mut u32) -> Result<()> {
+        write_out(lpulmax, self.max.get())
+    }
+
+    fn GetMin(&self, lpulmin: *mut u32) -> Result<()> {
+        write_out(lpulmin, self.min.get())
+    }
+
+    fn SetLimits(&self, lpulmin: *mut u32, lpulmax: *mut u32, lpulflags: *mut u32) -> Result<()> {
+        unsafe {
+            if let Some(min) = lpulmin.as_ref() {
+                self.min.set(*min);
+            }
+            if let Some(max) = lpulmax.as_ref() {
+                self.max.set(*max);
+            }
+            if let Some(flags) = lpulflags.as_ref() {
+                self.flags.set(*flags);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_out(target: *mut u32, value: u32) -> Result<()> {
+    match unsafe { target.as_mut() } {
+        Some(target) => {
+            *target = value;
+            Ok(())
+        }
+        None => Err(Error::from(E_INVALIDARG)),
+    }
+}
+
+/// Rust implementation of [`sys::IMAPIProgress`], which calls a closure with each
+/// [`ProgressUpdate`], so callers can show their own progress for long running operations such as
+/// [`Folder::copy_messages_with`], [`Folder::empty_folder`], or [`crate::copy_props`].
+///
+/// Providers call [`sys::IMAPIProgress::Progress`] synchronously, on the thread which started
+/// the operation, so the closure does not need to be [`Send`].
+///
+/// ```no_run
+/// # use outlook_mapi::*;
+/// # fn example(
+/// #     folder: &Folder,
+/// #     entry_ids: &EntryList,
+/// #     dest: &Folder,
+/// # ) -> windows_core::Result<()> {
+/// let progress = ProgressSink::new(|update| println!("{}%", update.percent()));
+/// folder.copy_messages_with(entry_ids, dest, false, Some(&progress))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ProgressSink {
+    progress: sys::IMAPIProgress,
+}
+
+impl ProgressSink {
+    /// Wrap `callback` in a new [`sys::IMAPIProgress`] object, which reports
+    /// [`sys::MAPI_TOP_LEVEL`] progress until the provider changes it.
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(ProgressUpdate) + 'static,
+    {
+        let (min, max) = DEFAULT_LIMITS;
+        let progress = ProgressImpl {
+            callback: Box::new(callback),
+            min: Cell::new(min),
+            max: Cell::new(max),
+            flags: Cell::new(sys::MAPI_TOP_LEVEL),
+        }
+        .into();
+        Self { progress }
+    }
+
+    /// Borrow the [`sys::IMAPIProgress`], e.g. to pass it to [`crate::copy_props`] or to methods
+    /// which do not have a wrapper yet.
+    pub fn progress(&self) -> &sys::IMAPIProgress {
+        &self.progress
+    }
+}
+
+impl From<ProgressSink> for sys::IMAPIProgress {
+    fn from(value: ProgressSink) -> Self {
+        value.progress
+    }
+}
+
+/// Set of flags that can be passed to [`Folder::empty_folder`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EmptyFolderFlags {
+    /// Pass [`sys::DEL_ASSOCIATED`], which also deletes the folder associated information (FAI)
+    /// messages, e.g. views and rules.
+    pub associated: bool,
+
+    /// Pass [`sys::DELETE_HARD_DELETE`], which skips the retention period in stores which keep
+    /// soft deleted items.
+    pub hard_delete: bool,
+}
+
+impl From<EmptyFolderFlags> for u32 {
+    fn from(value: EmptyFolderFlags) -> Self {
+        let associated = if value.associated {
+            sys::DEL_ASSOCIATED
+        } else {
+            0
+        };
+        let hard_delete = if value.hard_delete {
+            sys::DELETE_HARD_DELETE
+        } else {
+            0
+        };
+
+        associated | hard_delete
+    }
+}
+
+impl Folder {
+    /// Same as [`Folder::copy_messages`], but report progress to a [`ProgressSink`].
+    pub fn copy_messages_with(
+        &self,
+        entry_ids: &EntryList,
+        dest: &Folder,
+        move_messages: bool,
+        progress: Option<&ProgressSink>,
+    ) -> Result<()> {
+        let mut entry_list = entry_ids
+            .build()
+            .map_err(|_| Error::from(sys::MAPI_E_NOT_ENOUGH_MEMORY))?;
+        let flags = if move_messages { sys::MAPI_MOVE } else { 0 };
+        unsafe {
+            self.folder().CopyMessages(
+                entry_list.as_mut_ptr(),
+                InterfaceId::MAPI_FOLDER.as_mut_ptr(),
+                dest.folder().as_raw(),
+                0,
+                progress.map(ProgressSink::progress),
+                flags,
+            )
+        }
+    }
+
+    /// Same as [`Folder::delete_messages`], but report progress to a [`ProgressSink`].
+    pub fn delete_messages_with(
+        &self,
+        entry_ids: &EntryList,
+        progress: Option<&ProgressSink>,
+    ) -> Result<()> {
+        let mut entry_list = entry_ids
+            .build()
+            .map_err(|_| Error::from(sys::MAPI_E_NOT_ENOUGH_MEMORY))?;
+        unsafe {
+            self.folder().DeleteMessages(
+                entry_list.as_mut_ptr(),
+                0,
+                progress.map(ProgressSink::progress),
+                0,
+            )
+        }
+    }
+
+    /// Delete every message and subfolder in this folder with [`sys::IMAPIFolder::EmptyFolder`],
+    /// leaving the folder itself in place, and report progress to a [`ProgressSink`].
+    pub fn empty_folder(
+        &self,
+        flags: EmptyFolderFlags,
+        progress: Option<&ProgressSink>,
+    ) -> Result<()> {
+        unsafe {
+            self.folder()
+                .EmptyFolder(0, progress.map(ProgressSink::progress), u32::from(flags))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn percent() {
+        let update = ProgressUpdate {
+            value: 500,
+            min: 0,
+            max: 1000,
+            ..Default::default()
+        };
+        assert_eq!(update.percent(), 50);
+        assert_eq!(
+            ProgressUpdate {
+                value: 2000,
+                ..update
+            }
+            .percent(),
+            100
+        );
+        assert_eq!(ProgressUpdate::default().percent(), 0);
+    }
+
+    #[test]
+    fn progress_sink() {
+        let updates = Rc::new(Cell::new(None));
+        let sink = ProgressSink::new({
+            let updates = updates.clone();
+            move |update| updates.set(Some(update))
+        });
+        let mut min = 0;
+        let mut max = 10;
+        let mut flags = 0;
+        unsafe {
+            sink.progress()
+                .SetLimits(&mut min, &mut max, &mut flags)
+                .unwrap();
+            sink.progress().Progress(5, 1, 2).unwrap();
+        }
+        let update = updates.get().unwrap();
+        assert_eq!(update.count, 1);
+        assert_eq!(update.percent(), 50);
+    }
+}
@@ -0,0 +1,216 @@
+//! Define [`PropData`] and [`PropAccess`].
+
+use crate::{
+    mapi_prop::{delete_prop_problems, set_sprop_problems},
+    sys, InterfaceId, MAPIOutParam, MapiProp, MemoryProp, PropProblems, PropTag, PropValue,
+    PropValueBuilder,
+};
+use core::{ffi::c_void, iter, slice};
+use windows::Win32::Foundation::E_UNEXPECTED;
+use windows_core::*;
+
+/// Access flags for a single property in a [`PropData`], with
+/// [`sys::IPropData::HrSetPropAccess`] and [`sys::IPropData::HrGetPropAccess`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PropAccess {
+    /// Pass [`sys::IPROP_READWRITE`] instead of [`sys::IPROP_READONLY`].
+    pub read_write: bool,
+
+    /// Pass [`sys::IPROP_DIRTY`] instead of [`sys::IPROP_CLEAN`], i.e. the property has been
+    /// modified.
+    pub dirty: bool,
+}
+
+impl From<PropAccess> for u32 {
+    fn from(value: PropAccess) -> Self {
+        let read_write = if value.read_write {
+            sys::IPROP_READWRITE
+        } else {
+            sys::IPROP_READONLY
+        };
+        let dirty = if value.dirty {
+            sys::IPROP_DIRTY
+        } else {
+            sys::IPROP_CLEAN
+        };
+
+        read_write | dirty
+    }
+}
+
+impl From<u32> for PropAccess {
+    fn from(value: u32) -> Self {
+        Self {
+            read_write: value & sys::IPROP_READWRITE != 0,
+            dirty: value & sys::IPROP_DIRTY != 0,
+        }
+    }
+}
+
+/// `CreateIProp` and `OpenIMsgOnIStg` take `extern "system"` allocators, so forward them to the
+/// delay-loaded MAPI functions.
+pub(crate) unsafe extern "system" fn allocate_buffer(size: u32, buffer: *mut *mut c_void) -> i32 {
+    sys::MAPIAllocateBuffer(size, buffer)
+}
+
+pub(crate) unsafe extern "system" fn allocate_more(
+    size: u32,
+    object: *mut c_void,
+    buffer: *mut *mut c_void,
+) -> i32 {
+    sys::MAPIAllocateMore(size, object, buffer)
+}
+
+pub(crate) unsafe extern "system" fn free_buffer(buffer: *mut c_void) -> u32 {
+    sys::MAPIFreeBuffer(buffer)
+}
+
+/// Wrapper for the [`sys::IPropData`] implementation which MAPI creates with
+/// [`sys::CreateIProp`], e.g. to back a display table or a form, or to hand property values to
+/// code which expects a real provider object.
+///
+/// Unlike [`MemoryProp`], access to the object and to each property can be restricted, and
+/// [`sys::IMAPIProp::SetProps`] reports a [`sys::MAPI_E_NO_ACCESS`] problem for a property which
+/// is read-only.
+pub struct PropData {
+    prop_data: sys::IPropData,
+}
+
+impl PropData {
+    /// Create an empty, writable object with [`sys::CreateIProp`]. MAPI must be initialized.
+    pub fn new() -> Result<Self> {
+        let mut prop_data = None;
+        unsafe {
+            HRESULT(sys::CreateIProp(
+                InterfaceId::PROP_DATA.as_mut_ptr(),
+                Some(allocate_buffer),
+                Some(allocate_more),
+                Some(free_buffer),
+                core::ptr::null_mut(),
+                &mut prop_data,
+            ))
+            .ok()?;
+        }
+        let prop_data = prop_data.ok_or_else(|| Error::from(E_UNEXPECTED))?;
+        Ok(Self { prop_data })
+    }
+
+    /// Borrow the [`sys::IPropData`], e.g. to call methods which do not have a wrapper yet.
+    pub fn prop_data(&self) -> &sys::IPropData {
+        &self.prop_data
+    }
+
+    /// Set a set of properties with [`sys::IMAPIProp::SetProps`]. Returns the [`PropProblems`]
+    /// for any properties which could not be set, e.g. because they are read-only.
+    pub fn set_props(&self, values: &[PropValue]) -> Result<PropProblems> {
+        let mut props: Vec<_> = values.iter().map(PropValue::to_sprop_value).collect();
+        set_sprop_problems(&self.prop_data, &mut props)
+    }
+
+    /// Delete a set of properties with [`sys::IMAPIProp::DeleteProps`]. Returns the
+    /// [`PropProblems`] for any properties which could not be deleted. Properties which are
+    /// already missing are ignored.
+    pub fn delete_props(&self, tags: &[PropTag]) -> Result<PropProblems> {
+        let tags: Vec<_> = tags.iter().copied().map(u32::from).collect();
+        delete_prop_problems(&self.prop_data, &tags)
+    }
+
+    /// List the tags of every property with [`sys::IMAPIProp::GetPropList`].
+    pub fn prop_list(&self) -> Result<Vec<PropTag>> {
+        let mut tags: MAPIOutParam<sys::SPropTagArray> = Default::default();
+        unsafe {
+            self.prop_data
+                .GetPropList(sys::MAPI_UNICODE, tags.as_mut_ptr())?;
+            let tags = tags.as_mut().ok_or_else(|| Error::from(E_UNEXPECTED))?;
+            Ok(
+                slice::from_raw_parts(tags.aulPropTag.as_ptr(), tags.cValues as usize)
+                    .iter()
+                    .copied()
+                    .map(PropTag)
+                    .collect(),
+            )
+        }
+    }
+
+    /// Make the whole object read-only (or writable again) with
+    /// [`sys::IPropData::HrSetObjAccess`].
+    pub fn set_read_only(&self, read_only: bool) -> Result<()> {
+        let access = if read_only {
+            sys::IPROP_READONLY
+        } else {
+            sys::IPROP_READWRITE
+        };
+        unsafe { self.prop_data.HrSetObjAccess(access) }
+    }
+
+    /// Set the [`PropAccess`] of individual properties with
+    /// [`sys::IPropData::HrSetPropAccess`].
+    pub fn set_prop_access(&self, access: &[(PropTag, PropAccess)]) -> Result<()> {
+        let mut tags: Vec<u32> = iter::once(access.len() as u32)
+            .chain(access.iter().map(|(tag, _)| u32::from(*tag)))
+            .collect();
+        let mut flags: Vec<u32> = access
+            .iter()
+            .map(|(_, access)| u32::from(*access))
+            .collect();
+        unsafe {
+            self.prop_data
+                .HrSetPropAccess(tags.as_mut_ptr() as *mut _, flags.as_mut_ptr())
+        }
+    }
+
+    /// Get the [`PropAccess`] of every property with [`sys::IPropData::HrGetPropAccess`].
+    pub fn prop_access(&self) -> Result<Vec<(PropTag, PropAccess)>> {
+        let mut tags: MAPIOutParam<sys::SPropTagArray> = Default::default();
+        let mut flags: MAPIOutParam<u32> = Default::default();
+        unsafe {
+            self.prop_data
+                .HrGetPropAccess(tags.as_mut_ptr(), flags.as_mut_ptr())?;
+            let tags = tags.as_mut().ok_or_else(|| Error::from(E_UNEXPECTED))?;
+            let count = tags.cValues as usize;
+            let tags = slice::from_raw_parts(tags.aulPropTag.as_ptr(), count);
+            let flags = flags
+                .as_mut_slice(count)
+                .ok_or_else(|| Error::from(E_UNEXPECTED))?;
+            Ok(tags
+                .iter()
+                .zip(flags.iter())
+                .map(|(tag, flags)| (PropTag(*tag), PropAccess::from(*flags)))
+                .collect())
+        }
+    }
+}
+
+impl MapiProp for PropData {
+    fn as_mapi_prop(&self) -> &sys::IMAPIProp {
+        &self.prop_data
+    }
+}
+
+impl From<sys::IPropData> for PropData {
+    fn from(value: sys::IPropData) -> Self {
+        Self { prop_data: value }
+    }
+}
+
+impl From<PropData> for sys::IPropData {
+    fn from(value: PropData) -> Self {
+        value.prop_data
+    }
+}
+
+impl MemoryProp {
+    /// Copy every value to a new [`PropData`], e.g. to hand them to an API which needs a real
+    /// [`sys::IPropData`]. Use [`MemoryProp::copy_from`] to go the other way.
+    pub fn to_prop_data(&self) -> Result<PropData> {
+        let prop_data = PropData::new()?;
+        let mut values = PropValueBuilder::from_iter(self.values())
+            .build()
+            .map_err(|_| Error::from(sys::MAPI_E_NOT_ENOUGH_MEMORY))?;
+        if !values.is_empty() {
+            let props = unsafe { slice::from_raw_parts_mut(values.as_mut_ptr(), values.len()) };
+            set_sprop_problems(&prop_data.prop_data, props)?.into_result()?;
+        }
+        Ok(prop_data)
+    }
+}
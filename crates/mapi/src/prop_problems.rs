@@ -0,0 +1,174 @@
+//! Define [`PropProblems`] and [`PropProblem`].
+
+use crate::{sys, MapiError, PropTag};
+use core::{fmt, slice};
+use windows_core::*;
+
+/// Owned copy of a [`sys::SPropProblem`], which reports a property that could not be set or
+/// deleted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PropProblem {
+    /// Index of the property in the array passed to [`sys::IMAPIProp::SetProps`] or
+    /// [`sys::IMAPIProp::DeleteProps`].
+    pub index: u32,
+
+    /// Tag of the property.
+    pub tag: PropTag,
+
+    /// Error for this property, e.g. [`sys::MAPI_E_NO_ACCESS`] or [`sys::MAPI_E_COMPUTED`].
+    pub hresult: HRESULT,
+}
+
+impl fmt::Display for PropProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "property 0x{:08X} at index {}: {}",
+            u32::from(self.tag),
+            self.index,
+            MapiError::from(self.hresult)
+        )
+    }
+}
+
+/// Problems reported in the [`sys::SPropProblemArray`] returned by [`sys::IMAPIProp::SetProps`]
+/// or [`sys::IMAPIProp::DeleteProps`], e.g. by [`crate::Message::set_props`]. The call itself
+/// succeeded, but some of the properties were not changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PropProblems {
+    problems: Vec<PropProblem>,
+}
+
+impl PropProblems {
+    /// Copy the entries from a [`sys::SPropProblemArray`], which may be `null` if there were no
+    /// problems. The caller still owns the array.
+    ///
+    /// # Safety
+    ///
+    /// `problems` must either be `null` or point to a valid [`sys::SPropProblemArray`] with
+    /// [`sys::SPropProblemArray::cProblem`] entries.
+    pub unsafe fn from_raw(problems: *const sys::SPropProblemArray) -> Self {
+        let Some(problems) = problems.as_ref() else {
+            return Default::default();
+        };
+        let problems =
+            slice::from_raw_parts(problems.aProblem.as_ptr(), problems.cProblem as usize);
+        problems
+            .iter()
+            .map(|problem| PropProblem {
+                index: problem.ulIndex,
+                tag: PropTag(problem.ulPropTag),
+                hresult: HRESULT(problem.scode),
+            })
+            .collect()
+    }
+
+    /// Test if every property was changed.
+    pub fn is_empty(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Number of properties which were not changed.
+    pub fn len(&self) -> usize {
+        self.problems.len()
+    }
+
+    /// Iterate over the problems.
+    pub fn iter(&self) -> impl Iterator<Item = &PropProblem> {
+        self.problems.iter()
+    }
+
+    /// Turn the first problem (if any) into an [`Error`], for callers which need every property
+    /// to be changed.
+    pub fn into_result(self) -> Result<()> {
+        match self.problems.first() {
+            Some(problem) => Err(Error::from(problem.hresult)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromIterator<PropProblem> for PropProblems {
+    fn from_iter<T: IntoIterator<Item = PropProblem>>(iter: T) -> Self {
+        Self {
+            problems: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for PropProblems {
+    type Item = PropProblem;
+    type IntoIter = std::vec::IntoIter<PropProblem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.problems.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PropProblems {
+    type Item = &'a PropProblem;
+    type IntoIter = slice::Iter<'a, PropProblem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.problems.iter()
+    }
+}
+
+impl fmt::Display for PropProblems {
+    /// Print each problem on a separate line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, problem) in self.problems.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{problem}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SizedSPropProblemArray;
+
+    #[test]
+    fn from_raw() {
+        SizedSPropProblemArray! { Problems[2] }
+        let problems = Problems {
+            cProblem: 2,
+            aProblem: [
+                sys::SPropProblem {
+                    ulIndex: 0,
+                    ulPropTag: sys::PR_SUBJECT_W,
+                    scode: sys::MAPI_E_NO_ACCESS.0,
+                },
+                sys::SPropProblem {
+                    ulIndex: 1,
+                    ulPropTag: sys::PR_MESSAGE_SIZE,
+                    scode: sys::MAPI_E_COMPUTED.0,
+                },
+            ],
+        };
+        let problems = unsafe { PropProblems::from_raw(problems.as_ptr()) };
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems.iter().nth(1).map(|problem| problem.index), Some(1));
+        assert_eq!(
+            problems.to_string(),
+            format!(
+                "property 0x{:08X} at index 0: Access denied (MAPI_E_NO_ACCESS)\n\
+                 property 0x{:08X} at index 1: The property is computed and read-only \
+                 (MAPI_E_COMPUTED)",
+                sys::PR_SUBJECT_W,
+                sys::PR_MESSAGE_SIZE
+            )
+        );
+        assert_eq!(
+            problems.into_result().map_err(|err| err.code()),
+            Err(sys::MAPI_E_NO_ACCESS)
+        );
+        assert!(unsafe { PropProblems::from_raw(core::ptr::null()) }
+            .into_result()
+            .is_ok());
+    }
+}
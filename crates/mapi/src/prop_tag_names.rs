@@ -0,0 +1,37 @@
+//! Define [`prop_tag_name`] and [`format_prop_tag`].
+
+use crate::{KnownProps, PropTag};
+
+/// Look up the name of a well-known property in [`KnownProps`], without the `_W` or `_A` suffix.
+/// Returns `None` for named properties and properties which are not in the table.
+pub fn prop_tag_name(tag: PropTag) -> Option<&'static str> {
+    KnownProps::base_name(tag)
+}
+
+/// Format a [`PropTag`] for diagnostic output, using [`prop_tag_name`] if the name is known, or
+/// the hex value of the tag otherwise.
+pub fn format_prop_tag(tag: PropTag) -> String {
+    match prop_tag_name(tag) {
+        Some(name) => name.to_string(),
+        None => format!("0x{:08X}", u32::from(tag)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sys;
+
+    #[test]
+    fn lookup_ignores_prop_type() {
+        assert_eq!(
+            prop_tag_name(PropTag(sys::PR_SUBJECT_W)),
+            Some("PR_SUBJECT")
+        );
+        assert_eq!(
+            prop_tag_name(PropTag(sys::PR_SUBJECT_A)),
+            Some("PR_SUBJECT")
+        );
+        assert_eq!(format_prop_tag(PropTag(0x8001_001F)), "0x8001001F");
+    }
+}
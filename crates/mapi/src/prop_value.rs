@@ -1,7 +1,7 @@
 //! Define [`PropValue`] and [`PropValueData`].
 
-use crate::{sys, PropTag};
-use core::{ffi, ptr, slice};
+use crate::{sys, Limits, PropTag, PropType};
+use core::{ffi, mem, ptr, slice};
 use windows::Win32::{
     Foundation::{E_INVALIDARG, E_POINTER, FILETIME},
     System::Com::CY,
@@ -104,209 +104,343 @@ pub enum PropValueData<'a> {
     Object(i32),
 }
 
+/// Default for [`Limits::max_values`]. No provider should return a multi-valued property with more
+/// values than this.
+pub const DEFAULT_MAX_PROP_VALUES: u32 = 0x10_0000;
+
 impl<'a> From<&'a sys::SPropValue> for PropValue<'a> {
     /// Convert a [`sys::SPropValue`] reference into a friendlier [`PropValue`] type, which often
     /// supports safe access to the [`sys::SPropValue::Value`] union.
+    ///
+    /// The value is checked with [`PropValue::validate`] and [`Limits::current`] first, and if it
+    /// fails, the result is a [`PropValueData::Error`] with the same [`HRESULT`], and the tag is
+    /// changed to [`sys::PT_ERROR`] like any other error value.
     fn from(value: &sys::SPropValue) -> Self {
         let tag = PropTag(value.ulPropTag);
-        let prop_type = tag.prop_type().remove_flags(sys::MV_INSTANCE).into();
+        if let Err(err) = PropValue::validate(value, &Limits::current()) {
+            return PropValue {
+                tag: tag.change_prop_type(PropType::new(sys::PT_ERROR as u16)),
+                value: PropValueData::Error(err.code()),
+            };
+        }
+        // A column from an instanced table has a single value of the base type in each row.
+        let prop_type = if tag.is_mv_instance() {
+            tag.base_type()
+        } else {
+            tag.prop_type()
+        }
+        .into();
         let data = unsafe {
+            let value = &value.Value;
             match prop_type {
                 sys::PT_NULL => PropValueData::Null,
-                sys::PT_SHORT => PropValueData::Short(value.Value.i),
-                sys::PT_LONG => PropValueData::Long(value.Value.l),
-                sys::PT_PTR => PropValueData::Pointer(value.Value.lpv),
-                sys::PT_FLOAT => PropValueData::Float(value.Value.flt),
-                sys::PT_DOUBLE => PropValueData::Double(value.Value.dbl),
-                sys::PT_BOOLEAN => PropValueData::Boolean(value.Value.b),
-                sys::PT_CURRENCY => PropValueData::Currency(value.Value.cur.int64),
-                sys::PT_APPTIME => PropValueData::AppTime(value.Value.at),
-                sys::PT_SYSTIME => PropValueData::FileTime(value.Value.ft),
-                sys::PT_STRING8 => {
-                    if value.Value.lpszA.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        PropValueData::AnsiString(PCSTR::from_raw(value.Value.lpszA.as_ptr()))
-                    }
-                }
-                sys::PT_BINARY => {
-                    if value.Value.bin.lpb.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        PropValueData::Binary(slice::from_raw_parts(
-                            value.Value.bin.lpb,
-                            value.Value.bin.cb as usize,
-                        ))
-                    }
-                }
-                sys::PT_UNICODE => {
-                    if value.Value.lpszW.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        PropValueData::Unicode(PCWSTR::from_raw(value.Value.lpszW.as_ptr()))
-                    }
-                }
-                sys::PT_CLSID => {
-                    if value.Value.lpguid.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        PropValueData::Guid(ptr::read_unaligned(value.Value.lpguid))
-                    }
-                }
-                sys::PT_LONGLONG => PropValueData::LargeInteger(value.Value.li),
+                sys::PT_SHORT => PropValueData::Short(value.i),
+                sys::PT_LONG => PropValueData::Long(value.l),
+                sys::PT_PTR => PropValueData::Pointer(value.lpv),
+                sys::PT_FLOAT => PropValueData::Float(value.flt),
+                sys::PT_DOUBLE => PropValueData::Double(value.dbl),
+                sys::PT_BOOLEAN => PropValueData::Boolean(value.b),
+                sys::PT_CURRENCY => PropValueData::Currency(value.cur.int64),
+                sys::PT_APPTIME => PropValueData::AppTime(value.at),
+                sys::PT_SYSTIME => PropValueData::FileTime(value.ft),
+                sys::PT_STRING8 => PropValueData::AnsiString(PCSTR::from_raw(value.lpszA.as_ptr())),
+                sys::PT_BINARY => PropValueData::Binary(borrow_values(value.bin.lpb, value.bin.cb)),
+                sys::PT_UNICODE => PropValueData::Unicode(PCWSTR::from_raw(value.lpszW.as_ptr())),
+                sys::PT_CLSID => PropValueData::Guid(ptr::read_unaligned(value.lpguid)),
+                sys::PT_LONGLONG => PropValueData::LargeInteger(value.li),
                 sys::PT_MV_SHORT => {
-                    if value.Value.MVi.lpi.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        PropValueData::ShortArray(slice::from_raw_parts(
-                            value.Value.MVi.lpi,
-                            value.Value.MVi.cValues as usize,
-                        ))
-                    }
+                    PropValueData::ShortArray(borrow_values(value.MVi.lpi, value.MVi.cValues))
                 }
                 sys::PT_MV_LONG => {
-                    if value.Value.MVl.lpl.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        PropValueData::LongArray(slice::from_raw_parts(
-                            value.Value.MVl.lpl,
-                            value.Value.MVl.cValues as usize,
-                        ))
-                    }
+                    PropValueData::LongArray(borrow_values(value.MVl.lpl, value.MVl.cValues))
                 }
                 sys::PT_MV_FLOAT => {
-                    if value.Value.MVflt.lpflt.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        PropValueData::FloatArray(slice::from_raw_parts(
-                            value.Value.MVflt.lpflt,
-                            value.Value.MVflt.cValues as usize,
-                        ))
-                    }
+                    PropValueData::FloatArray(borrow_values(value.MVflt.lpflt, value.MVflt.cValues))
                 }
                 sys::PT_MV_DOUBLE => {
-                    if value.Value.MVdbl.lpdbl.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        let count = value.Value.MVdbl.cValues as usize;
-                        let first = value.Value.MVdbl.lpdbl;
-                        let mut values = Vec::with_capacity(count);
-                        for idx in 0..count {
-                            values.push(ptr::read_unaligned(first.add(idx)))
-                        }
-                        PropValueData::DoubleArray(values)
-                    }
-                }
-                sys::PT_MV_CURRENCY => {
-                    if value.Value.MVcur.lpcur.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        let count = value.Value.MVcur.cValues as usize;
-                        let first = value.Value.MVcur.lpcur;
-                        let mut values = Vec::with_capacity(count);
-                        for idx in 0..count {
-                            values.push(ptr::read_unaligned(first.add(idx)))
-                        }
-                        PropValueData::CurrencyArray(values)
-                    }
+                    PropValueData::DoubleArray(copy_values(value.MVdbl.lpdbl, value.MVdbl.cValues))
                 }
+                sys::PT_MV_CURRENCY => PropValueData::CurrencyArray(copy_values(
+                    value.MVcur.lpcur,
+                    value.MVcur.cValues,
+                )),
                 sys::PT_MV_APPTIME => {
-                    if value.Value.MVat.lpat.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        let count = value.Value.MVat.cValues as usize;
-                        let first = value.Value.MVat.lpat;
-                        let mut values = Vec::with_capacity(count);
-                        for idx in 0..count {
-                            values.push(ptr::read_unaligned(first.add(idx)))
-                        }
-                        PropValueData::AppTimeArray(values)
-                    }
+                    PropValueData::AppTimeArray(copy_values(value.MVat.lpat, value.MVat.cValues))
                 }
                 sys::PT_MV_SYSTIME => {
-                    if value.Value.MVft.lpft.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        let count = value.Value.MVft.cValues as usize;
-                        let first = value.Value.MVft.lpft;
-                        let mut values = Vec::with_capacity(count);
-                        for idx in 0..count {
-                            values.push(ptr::read_unaligned(first.add(idx)))
-                        }
-                        PropValueData::FileTimeArray(values)
-                    }
+                    PropValueData::FileTimeArray(copy_values(value.MVft.lpft, value.MVft.cValues))
                 }
                 sys::PT_MV_BINARY => {
-                    if value.Value.MVbin.lpbin.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        let count = value.Value.MVbin.cValues as usize;
-                        let first = value.Value.MVbin.lpbin;
-                        let mut values = Vec::with_capacity(count);
-                        for idx in 0..count {
-                            values.push(ptr::read_unaligned(first.add(idx)))
-                        }
-                        PropValueData::BinaryArray(values)
-                    }
+                    PropValueData::BinaryArray(copy_values(value.MVbin.lpbin, value.MVbin.cValues))
+                }
+                sys::PT_MV_STRING8 => PropValueData::AnsiStringArray(
+                    copy_values(value.MVszA.lppszA, value.MVszA.cValues)
+                        .into_iter()
+                        .map(|value| PCSTR(value.0))
+                        .collect(),
+                ),
+                sys::PT_MV_UNICODE => PropValueData::UnicodeArray(
+                    copy_values(value.MVszW.lppszW, value.MVszW.cValues)
+                        .into_iter()
+                        .map(|value| PCWSTR(value.0))
+                        .collect(),
+                ),
+                sys::PT_MV_CLSID => {
+                    PropValueData::GuidArray(copy_values(value.MVguid.lpguid, value.MVguid.cValues))
+                }
+                sys::PT_MV_LONGLONG => PropValueData::LargeIntegerArray(copy_values(
+                    value.MVli.lpli,
+                    value.MVli.cValues,
+                )),
+                sys::PT_ERROR => PropValueData::Error(HRESULT(value.err)),
+                sys::PT_OBJECT => PropValueData::Object(value.x),
+                _ => PropValueData::Error(E_INVALIDARG),
+            }
+        };
+        PropValue { tag, value: data }
+    }
+}
+
+/// Borrow `count` values starting at `first`, which [`PropValue::validate`] has already checked,
+/// without forming a slice over a `null` pointer when `count` is 0.
+unsafe fn borrow_values<'a, T>(first: *const T, count: u32) -> &'a [T] {
+    if count == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(first, count as usize)
+    }
+}
+
+/// Copy `count` values starting at `first`, which might not be aligned.
+unsafe fn copy_values<T>(first: *const T, count: u32) -> Vec<T> {
+    (0..count as usize)
+        .map(|idx| ptr::read_unaligned(first.add(idx)))
+        .collect()
+}
+
+fn check_pointer<T>(first: *const T, count: u32) -> Result<()> {
+    if count > 0 && first.is_null() {
+        Err(Error::from(E_POINTER))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_alignment<T>(first: *const T, count: u32) -> Result<()> {
+    if count > 0 && first as usize % mem::align_of::<T>() != 0 {
+        Err(Error::from(sys::MAPI_E_CORRUPT_DATA))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_values<T>(first: *const T, count: u32, limits: &Limits) -> Result<()> {
+    limits.check_values(count)?;
+    check_pointer(first, count)
+}
+
+impl PropValue<'_> {
+    /// Check the invariants of a [`sys::SPropValue`] which [`PropValue::from`] relies on, before
+    /// it forms any slices over the [`sys::SPropValue::Value`] union:
+    ///
+    /// - A string or [`sys::PT_CLSID`] pointer must not be `null`, nor may any of the strings in
+    ///   a [`sys::PT_MV_STRING8`] or [`sys::PT_MV_UNICODE`] value. Fails with [`E_POINTER`].
+    /// - A binary or multi-valued pointer may only be `null` if the count is 0. Fails with
+    ///   [`E_POINTER`].
+    /// - A binary value may not be larger than [`Limits::max_binary_size`], and a multi-valued
+    ///   property may not have more than [`Limits::max_values`] values. Fails with the
+    ///   [`crate::LimitError`], which has [`sys::MAPI_E_TOO_BIG`].
+    /// - An array which [`PropValue::from`] borrows instead of copying must be aligned. Fails with
+    ///   [`sys::MAPI_E_CORRUPT_DATA`].
+    /// - The property type must be one [`PropValueData`] supports. Fails with [`E_INVALIDARG`].
+    ///
+    /// This reads the elements of [`sys::PT_MV_BINARY`] and multi-valued string arrays, but it
+    /// does not check that the pointers refer to readable memory. Use [`crate::Row::check_memory`]
+    /// for that.
+    pub fn validate(value: &sys::SPropValue, limits: &Limits) -> Result<()> {
+        let tag = PropTag(value.ulPropTag);
+        let prop_type = if tag.is_mv_instance() {
+            tag.base_type()
+        } else {
+            tag.prop_type()
+        };
+        unsafe {
+            let value = &value.Value;
+            match u32::from(prop_type) {
+                sys::PT_NULL
+                | sys::PT_SHORT
+                | sys::PT_LONG
+                | sys::PT_PTR
+                | sys::PT_FLOAT
+                | sys::PT_DOUBLE
+                | sys::PT_BOOLEAN
+                | sys::PT_CURRENCY
+                | sys::PT_APPTIME
+                | sys::PT_SYSTIME
+                | sys::PT_LONGLONG
+                | sys::PT_ERROR
+                | sys::PT_OBJECT => Ok(()),
+                sys::PT_STRING8 => check_pointer(value.lpszA.0, 1),
+                sys::PT_UNICODE => check_pointer(value.lpszW.0, 1),
+                sys::PT_CLSID => check_pointer(value.lpguid, 1),
+                sys::PT_BINARY => {
+                    limits.check_binary_size(value.bin.cb)?;
+                    check_pointer(value.bin.lpb, value.bin.cb)
+                }
+                sys::PT_MV_SHORT => {
+                    check_values(value.MVi.lpi, value.MVi.cValues, limits)?;
+                    check_alignment(value.MVi.lpi, value.MVi.cValues)
+                }
+                sys::PT_MV_LONG => {
+                    check_values(value.MVl.lpl, value.MVl.cValues, limits)?;
+                    check_alignment(value.MVl.lpl, value.MVl.cValues)
+                }
+                sys::PT_MV_FLOAT => {
+                    check_values(value.MVflt.lpflt, value.MVflt.cValues, limits)?;
+                    check_alignment(value.MVflt.lpflt, value.MVflt.cValues)
+                }
+                sys::PT_MV_DOUBLE => check_values(value.MVdbl.lpdbl, value.MVdbl.cValues, limits),
+                sys::PT_MV_CURRENCY => check_values(value.MVcur.lpcur, value.MVcur.cValues, limits),
+                sys::PT_MV_APPTIME => check_values(value.MVat.lpat, value.MVat.cValues, limits),
+                sys::PT_MV_SYSTIME => check_values(value.MVft.lpft, value.MVft.cValues, limits),
+                sys::PT_MV_BINARY => {
+                    let values = value.MVbin;
+                    check_values(values.lpbin, values.cValues, limits)?;
+                    copy_values(values.lpbin, values.cValues)
+                        .into_iter()
+                        .try_for_each(|value| {
+                            limits.check_binary_size(value.cb)?;
+                            check_pointer(value.lpb, value.cb)
+                        })
                 }
                 sys::PT_MV_STRING8 => {
-                    if value.Value.MVszA.lppszA.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        let count = value.Value.MVszA.cValues as usize;
-                        let first = value.Value.MVszA.lppszA;
-                        let mut values = Vec::with_capacity(count);
-                        for idx in 0..count {
-                            values.push(PCSTR(ptr::read_unaligned(first.add(idx)).0))
-                        }
-                        PropValueData::AnsiStringArray(values)
-                    }
+                    let values = value.MVszA;
+                    check_values(values.lppszA, values.cValues, limits)?;
+                    copy_values(values.lppszA, values.cValues)
+                        .into_iter()
+                        .try_for_each(|value| check_pointer(value.0, 1))
                 }
                 sys::PT_MV_UNICODE => {
-                    if value.Value.MVszW.lppszW.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        let count = value.Value.MVszW.cValues as usize;
-                        let first = value.Value.MVszW.lppszW;
-                        let mut values = Vec::with_capacity(count);
-                        for idx in 0..count {
-                            values.push(PCWSTR(ptr::read_unaligned(first.add(idx)).0))
-                        }
-                        PropValueData::UnicodeArray(values)
-                    }
+                    let values = value.MVszW;
+                    check_values(values.lppszW, values.cValues, limits)?;
+                    copy_values(values.lppszW, values.cValues)
+                        .into_iter()
+                        .try_for_each(|value| check_pointer(value.0, 1))
                 }
-                sys::PT_MV_CLSID => {
-                    if value.Value.MVguid.lpguid.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        let count = value.Value.MVguid.cValues as usize;
-                        let first = value.Value.MVguid.lpguid;
-                        let mut values = Vec::with_capacity(count);
-                        for idx in 0..count {
-                            values.push(ptr::read_unaligned(first.add(idx)))
-                        }
-                        PropValueData::GuidArray(values)
-                    }
+                sys::PT_MV_CLSID => check_values(value.MVguid.lpguid, value.MVguid.cValues, limits),
+                sys::PT_MV_LONGLONG => check_values(value.MVli.lpli, value.MVli.cValues, limits),
+                _ => Err(Error::from(E_INVALIDARG)),
+            }
+        }
+    }
+
+    /// Build a [`sys::SPropValue`] which points at the data in this [`PropValue`], e.g. to pass to
+    /// [`sys::IMAPIProp::SetProps`]. The result borrows from `self` without a lifetime, so it must
+    /// not be used after `self` is dropped.
+    pub(crate) fn to_sprop_value(&self) -> sys::SPropValue {
+        let mut prop = sys::SPropValue {
+            ulPropTag: self.tag.into(),
+            ..Default::default()
+        };
+        match &self.value {
+            PropValueData::Null => {}
+            PropValueData::Short(value) => prop.Value.i = *value,
+            PropValueData::Long(value) => prop.Value.l = *value,
+            PropValueData::Pointer(value) => prop.Value.lpv = *value,
+            PropValueData::Float(value) => prop.Value.flt = *value,
+            PropValueData::Double(value) => prop.Value.dbl = *value,
+            PropValueData::Boolean(value) => prop.Value.b = *value,
+            PropValueData::Currency(value) => prop.Value.cur = CY { int64: *value },
+            PropValueData::AppTime(value) => prop.Value.at = *value,
+            PropValueData::FileTime(value) => prop.Value.ft = *value,
+            PropValueData::AnsiString(value) => prop.Value.lpszA = PSTR(value.0 as *mut _),
+            PropValueData::Binary(value) => {
+                prop.Value.bin = sys::SBinary {
+                    cb: value.len() as u32,
+                    lpb: value.as_ptr() as *mut _,
                 }
-                sys::PT_MV_LONGLONG => {
-                    if value.Value.MVli.lpli.is_null() {
-                        PropValueData::Error(E_POINTER)
-                    } else {
-                        let count = value.Value.MVli.cValues as usize;
-                        let first = value.Value.MVli.lpli;
-                        let mut values = Vec::with_capacity(count);
-                        for idx in 0..count {
-                            values.push(ptr::read_unaligned(first.add(idx)))
-                        }
-                        PropValueData::LargeIntegerArray(values)
-                    }
+            }
+            PropValueData::Unicode(value) => prop.Value.lpszW = PWSTR(value.0 as *mut _),
+            PropValueData::Guid(value) => prop.Value.lpguid = ptr::from_ref(value) as *mut _,
+            PropValueData::LargeInteger(value) => prop.Value.li = *value,
+            PropValueData::ShortArray(values) => {
+                prop.Value.MVi = sys::SShortArray {
+                    cValues: values.len() as u32,
+                    lpi: values.as_ptr() as *mut _,
                 }
-                sys::PT_ERROR => PropValueData::Error(HRESULT(value.Value.err)),
-                sys::PT_OBJECT => PropValueData::Object(value.Value.x),
-                _ => PropValueData::Error(E_INVALIDARG),
             }
-        };
-        PropValue { tag, value: data }
+            PropValueData::LongArray(values) => {
+                prop.Value.MVl = sys::SLongArray {
+                    cValues: values.len() as u32,
+                    lpl: values.as_ptr() as *mut _,
+                }
+            }
+            PropValueData::FloatArray(values) => {
+                prop.Value.MVflt = sys::SRealArray {
+                    cValues: values.len() as u32,
+                    lpflt: values.as_ptr() as *mut _,
+                }
+            }
+            PropValueData::DoubleArray(values) => {
+                prop.Value.MVdbl = sys::SDoubleArray {
+                    cValues: values.len() as u32,
+                    lpdbl: values.as_ptr() as *mut _,
+                }
+            }
+            PropValueData::CurrencyArray(values) => {
+                prop.Value.MVcur = sys::SCurrencyArray {
+                    cValues: values.len() as u32,
+                    lpcur: values.as_ptr() as *mut _,
+                }
+            }
+            PropValueData::AppTimeArray(values) => {
+                prop.Value.MVat = sys::SAppTimeArray {
+                    cValues: values.len() as u32,
+                    lpat: values.as_ptr() as *mut _,
+                }
+            }
+            PropValueData::FileTimeArray(values) => {
+                prop.Value.MVft = sys::SDateTimeArray {
+                    cValues: values.len() as u32,
+                    lpft: values.as_ptr() as *mut _,
+                }
+            }
+            PropValueData::BinaryArray(values) => {
+                prop.Value.MVbin = sys::SBinaryArray {
+                    cValues: values.len() as u32,
+                    lpbin: values.as_ptr() as *mut _,
+                }
+            }
+            PropValueData::AnsiStringArray(values) => {
+                // PCSTR and PSTR are both transparent wrappers for a pointer.
+                prop.Value.MVszA = sys::SLPSTRArray {
+                    cValues: values.len() as u32,
+                    lppszA: values.as_ptr() as *mut _,
+                }
+            }
+            PropValueData::UnicodeArray(values) => {
+                // PCWSTR and PWSTR are both transparent wrappers for a pointer.
+                prop.Value.MVszW = sys::SWStringArray {
+                    cValues: values.len() as u32,
+                    lppszW: values.as_ptr() as *mut _,
+                }
+            }
+            PropValueData::GuidArray(values) => {
+                prop.Value.MVguid = sys::SGuidArray {
+                    cValues: values.len() as u32,
+                    lpguid: values.as_ptr() as *mut _,
+                }
+            }
+            PropValueData::LargeIntegerArray(values) => {
+                prop.Value.MVli = sys::SLargeIntegerArray {
+                    cValues: values.len() as u32,
+                    lpli: values.as_ptr() as *mut _,
+                }
+            }
+            PropValueData::Error(value) => prop.Value.err = value.0,
+            PropValueData::Object(value) => prop.Value.x = *value,
+        }
+        prop
     }
 }
 
@@ -881,4 +1015,105 @@ mod tests {
         assert_eq!(u32::from(value.tag.prop_type()), sys::PT_OBJECT);
         assert!(matches!(value.value, PropValueData::Object(39)));
     }
+
+    #[test]
+    fn test_to_sprop_value() {
+        let expected = [15_i32, 16];
+        let value = PropValue {
+            tag: PropTag(sys::PR_NULL).change_prop_type(PropType::new(sys::PT_MV_LONG as u16)),
+            value: PropValueData::LongArray(&expected),
+        };
+        let value = value.to_sprop_value();
+        let value = PropValue::from(&value);
+        assert_eq!(u32::from(value.tag.prop_type()), sys::PT_MV_LONG);
+        assert!(matches!(value.value, PropValueData::LongArray([15, 16])));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut value = sys::SPropValue {
+            ulPropTag: u32::from(
+                PropTag(sys::PR_NULL).change_prop_type(PropType::new(sys::PT_MV_LONG as u16)),
+            ),
+            ..Default::default()
+        };
+        assert!(PropValue::validate(&value, &Limits::default()).is_ok());
+        assert!(matches!(
+            PropValue::from(&value).value,
+            PropValueData::LongArray([])
+        ));
+
+        value.Value.MVl.cValues = 2;
+        assert_eq!(
+            PropValue::validate(&value, &Limits::default()).map_err(|err| err.code()),
+            Err(E_POINTER)
+        );
+        assert!(matches!(
+            PropValue::from(&value).value,
+            PropValueData::Error(E_POINTER)
+        ));
+        let invalid = PropValue::from(&value);
+        assert_eq!(invalid.tag.prop_id(), PropTag(sys::PR_NULL).prop_id());
+        assert_eq!(u32::from(invalid.tag.prop_type()), sys::PT_ERROR);
+        let invalid = invalid.to_sprop_value();
+        assert_eq!(
+            u32::from(PropTag(invalid.ulPropTag).prop_type()),
+            sys::PT_ERROR
+        );
+        assert_eq!(unsafe { invalid.Value.err }, E_POINTER.0);
+
+        let expected = [15_i32, 16];
+        value.Value.MVl.lpl = expected.as_ptr() as *mut _;
+        let limits = Limits {
+            max_values: 2,
+            ..Default::default()
+        };
+        assert!(PropValue::validate(&value, &limits).is_ok());
+        let limits = Limits {
+            max_values: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            PropValue::validate(&value, &limits).map_err(|err| err.code()),
+            Err(sys::MAPI_E_TOO_BIG)
+        );
+
+        value.Value.MVl.lpl = (expected.as_ptr() as *const u8).wrapping_add(1) as *mut _;
+        assert_eq!(
+            PropValue::validate(&value, &Limits::default()).map_err(|err| err.code()),
+            Err(sys::MAPI_E_CORRUPT_DATA)
+        );
+
+        let expected = [s!("forty"), PCSTR::null()];
+        let mut value = sys::SPropValue {
+            ulPropTag: u32::from(
+                PropTag(sys::PR_NULL).change_prop_type(PropType::new(sys::PT_MV_STRING8 as u16)),
+            ),
+            ..Default::default()
+        };
+        value.Value.MVszA.cValues = expected.len() as u32;
+        value.Value.MVszA.lppszA = expected.as_ptr() as *mut _;
+        assert_eq!(
+            PropValue::validate(&value, &Limits::default()).map_err(|err| err.code()),
+            Err(E_POINTER)
+        );
+
+        let expected = [41_u8; 8];
+        let mut value = sys::SPropValue {
+            ulPropTag: u32::from(
+                PropTag(sys::PR_NULL).change_prop_type(PropType::new(sys::PT_BINARY as u16)),
+            ),
+            ..Default::default()
+        };
+        value.Value.bin.cb = expected.len() as u32;
+        value.Value.bin.lpb = expected.as_ptr() as *mut _;
+        let limits = Limits {
+            max_binary_size: 4,
+            ..Default::default()
+        };
+        assert_eq!(
+            PropValue::validate(&value, &limits).map_err(|err| err.message()),
+            Err("binary value of 8 bytes exceeds the limit of 4".into())
+        );
+    }
 }
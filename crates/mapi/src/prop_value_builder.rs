@@ -0,0 +1,297 @@
+//! Define [`PropValueBuilder`] and [`PropValueBuffer`].
+
+use crate::{
+    sys, MAPIAllocError, MAPIBuffer, MAPIUninit, OwnedPropValue, OwnedPropValueData, PropValue,
+};
+use core::{ffi, iter, mem, ptr};
+use windows::Win32::{
+    Globalization::{WideCharToMultiByte, CP_ACP},
+    System::Com::CY,
+};
+use windows_core::{PCSTR, PSTR, PWSTR};
+
+/// Builder which converts a set of property values into an array of [`sys::SPropValue`] in a
+/// single chain of MAPI allocations, e.g. to pass to [`sys::IMAPIProp::SetProps`] or to return
+/// from a provider.
+///
+/// The array itself is allocated with [`sys::MAPIAllocateBuffer`], and every string, binary, and
+/// multi-valued payload is chained to it with [`sys::MAPIAllocateMore`], so the whole set is
+/// freed with a single call to [`sys::MAPIFreeBuffer`] when the [`PropValueBuffer`] is dropped.
+///
+/// ```no_run
+/// # use outlook_mapi::{sys, *};
+/// # fn example(message: &sys::IMessage) -> windows_core::Result<()> {
+/// let mut values = PropValueBuilder::new()
+///     .push(OwnedPropValue {
+///         tag: PropTag(sys::PR_SUBJECT_W),
+///         value: OwnedPropValueData::Unicode("Hello".to_string()),
+///     })
+///     .build()
+///     .map_err(|_| windows_core::Error::from(sys::MAPI_E_NOT_ENOUGH_MEMORY))?;
+/// unsafe {
+///     message.SetProps(values.len() as u32, values.as_mut_ptr(), std::ptr::null_mut())?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PropValueBuilder {
+    values: Vec<OwnedPropValue>,
+}
+
+impl PropValueBuilder {
+    /// Start with an empty set of values.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Append an owned value.
+    pub fn push(mut self, value: OwnedPropValue) -> Self {
+        self.values.push(value);
+        self
+    }
+
+    /// Append a copy of a borrowed value, e.g. from a [`crate::Row`].
+    pub fn push_value(self, value: &PropValue) -> Self {
+        self.push(value.to_owned())
+    }
+
+    /// Allocate and fill in the [`sys::SPropValue`] array.
+    pub fn build(&self) -> Result<PropValueBuffer, MAPIAllocError> {
+        let mut root = MAPIUninit::<sys::SPropValue>::new(self.values.len().max(1))?;
+        if self.values.is_empty() {
+            root.uninit()?.write(Default::default());
+        }
+        for (value, mut target) in self.values.iter().zip(root.iter()) {
+            let prop = build_prop_value(&root, value)?;
+            target.uninit()?.write(prop);
+        }
+        let values = root.uninit()?.as_mut_ptr();
+        Ok(PropValueBuffer {
+            values,
+            count: self.values.len(),
+            _buffer: unsafe { root.assume_init() },
+        })
+    }
+}
+
+impl FromIterator<OwnedPropValue> for PropValueBuilder {
+    fn from_iter<T: IntoIterator<Item = OwnedPropValue>>(iter: T) -> Self {
+        Self {
+            values: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Array of [`sys::SPropValue`] built by [`PropValueBuilder::build`] in a chain of MAPI
+/// allocations.
+pub struct PropValueBuffer {
+    values: *mut sys::SPropValue,
+    count: usize,
+    _buffer: MAPIBuffer<'static, sys::SPropValue>,
+}
+
+impl PropValueBuffer {
+    /// Number of values in the array.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Test if the array is empty.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Get a pointer to the first [`sys::SPropValue`], which stays valid until the
+    /// [`PropValueBuffer`] is dropped.
+    pub fn as_mut_ptr(&mut self) -> *mut sys::SPropValue {
+        self.values
+    }
+
+    /// Give up ownership of the array, e.g. to store it in a [`sys::ADRENTRY`] which the provider
+    /// may free and replace. The caller must free the result with [`sys::MAPIFreeBuffer`].
+    pub fn into_raw(self) -> *mut sys::SPropValue {
+        let values = self.values;
+        mem::forget(self);
+        values
+    }
+}
+
+fn build_prop_value(
+    root: &MAPIUninit<'_, sys::SPropValue>,
+    value: &OwnedPropValue,
+) -> Result<sys::SPropValue, MAPIAllocError> {
+    let mut prop = sys::SPropValue {
+        ulPropTag: value.tag.into(),
+        ..Default::default()
+    };
+    let count = |len: usize| len as u32;
+    match &value.value {
+        OwnedPropValueData::Null => {}
+        OwnedPropValueData::Short(value) => prop.Value.i = *value,
+        OwnedPropValueData::Long(value) => prop.Value.l = *value,
+        OwnedPropValueData::Pointer(value) => prop.Value.lpv = *value as *mut ffi::c_void,
+        OwnedPropValueData::Float(value) => prop.Value.flt = *value,
+        OwnedPropValueData::Double(value) => prop.Value.dbl = *value,
+        OwnedPropValueData::Boolean(value) => prop.Value.b = u16::from(*value),
+        OwnedPropValueData::Currency(value) => prop.Value.cur = CY { int64: *value },
+        OwnedPropValueData::AppTime(value) => prop.Value.at = *value,
+        OwnedPropValueData::FileTime(value) => prop.Value.ft = *value,
+        OwnedPropValueData::AnsiString(value) => {
+            prop.Value.lpszA = PSTR(chain_copy(root, &encode_ansi(value))?)
+        }
+        OwnedPropValueData::Binary(value) => {
+            prop.Value.bin = sys::SBinary {
+                cb: count(value.len()),
+                lpb: chain_copy(root, value)?,
+            }
+        }
+        OwnedPropValueData::Unicode(value) => {
+            prop.Value.lpszW = PWSTR(chain_copy(root, &encode_unicode(value))?)
+        }
+        OwnedPropValueData::Guid(value) => prop.Value.lpguid = chain_copy(root, &[*value])?,
+        OwnedPropValueData::LargeInteger(value) => prop.Value.li = *value,
+        OwnedPropValueData::ShortArray(values) => {
+            prop.Value.MVi = sys::SShortArray {
+                cValues: count(values.len()),
+                lpi: chain_copy(root, values)?,
+            }
+        }
+        OwnedPropValueData::LongArray(values) => {
+            prop.Value.MVl = sys::SLongArray {
+                cValues: count(values.len()),
+                lpl: chain_copy(root, values)?,
+            }
+        }
+        OwnedPropValueData::FloatArray(values) => {
+            prop.Value.MVflt = sys::SRealArray {
+                cValues: count(values.len()),
+                lpflt: chain_copy(root, values)?,
+            }
+        }
+        OwnedPropValueData::DoubleArray(values) => {
+            prop.Value.MVdbl = sys::SDoubleArray {
+                cValues: count(values.len()),
+                lpdbl: chain_copy(root, values)?,
+            }
+        }
+        OwnedPropValueData::CurrencyArray(values) => {
+            let values: Vec<_> = values.iter().map(|&int64| CY { int64 }).collect();
+            prop.Value.MVcur = sys::SCurrencyArray {
+                cValues: count(values.len()),
+                lpcur: chain_copy(root, &values)?,
+            }
+        }
+        OwnedPropValueData::AppTimeArray(values) => {
+            prop.Value.MVat = sys::SAppTimeArray {
+                cValues: count(values.len()),
+                lpat: chain_copy(root, values)?,
+            }
+        }
+        OwnedPropValueData::FileTimeArray(values) => {
+            prop.Value.MVft = sys::SDateTimeArray {
+                cValues: count(values.len()),
+                lpft: chain_copy(root, values)?,
+            }
+        }
+        OwnedPropValueData::BinaryArray(values) => {
+            let values = values
+                .iter()
+                .map(|value| {
+                    Ok(sys::SBinary {
+                        cb: count(value.len()),
+                        lpb: chain_copy(root, value)?,
+                    })
+                })
+                .collect::<Result<Vec<_>, MAPIAllocError>>()?;
+            prop.Value.MVbin = sys::SBinaryArray {
+                cValues: count(values.len()),
+                lpbin: chain_copy(root, &values)?,
+            }
+        }
+        OwnedPropValueData::AnsiStringArray(values) => {
+            let values = values
+                .iter()
+                .map(|value| Ok(PSTR(chain_copy(root, &encode_ansi(value))?)))
+                .collect::<Result<Vec<_>, MAPIAllocError>>()?;
+            prop.Value.MVszA = sys::SLPSTRArray {
+                cValues: count(values.len()),
+                lppszA: chain_copy(root, &values)?,
+            }
+        }
+        OwnedPropValueData::UnicodeArray(values) => {
+            let values = values
+                .iter()
+                .map(|value| Ok(PWSTR(chain_copy(root, &encode_unicode(value))?)))
+                .collect::<Result<Vec<_>, MAPIAllocError>>()?;
+            prop.Value.MVszW = sys::SWStringArray {
+                cValues: count(values.len()),
+                lppszW: chain_copy(root, &values)?,
+            }
+        }
+        OwnedPropValueData::GuidArray(values) => {
+            prop.Value.MVguid = sys::SGuidArray {
+                cValues: count(values.len()),
+                lpguid: chain_copy(root, values)?,
+            }
+        }
+        OwnedPropValueData::LargeIntegerArray(values) => {
+            prop.Value.MVli = sys::SLargeIntegerArray {
+                cValues: count(values.len()),
+                lpli: chain_copy(root, values)?,
+            }
+        }
+        OwnedPropValueData::Error(err) => prop.Value.err = err.0,
+        OwnedPropValueData::Object(value) => prop.Value.x = *value,
+    }
+    Ok(prop)
+}
+
+/// Copy `values` into a new allocation chained to `root`, or return `null` if it is empty.
+fn chain_copy<T>(
+    root: &MAPIUninit<'_, sys::SPropValue>,
+    values: &[T],
+) -> Result<*mut T, MAPIAllocError>
+where
+    T: Copy,
+{
+    if values.is_empty() {
+        return Ok(ptr::null_mut());
+    }
+    let mut buffer = root.chain::<T>(values.len())?;
+    let first = buffer.uninit()?.as_mut_ptr();
+    unsafe {
+        ptr::copy_nonoverlapping(values.as_ptr(), first, values.len());
+    }
+    Ok(first)
+}
+
+/// Encode a null-terminated UTF-16 string.
+fn encode_unicode(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(iter::once(0)).collect()
+}
+
+/// Encode a null-terminated string in the ANSI code page, replacing characters which do not fit.
+fn encode_ansi(value: &str) -> Vec<u8> {
+    let wide: Vec<u16> = value.encode_utf16().collect();
+    let mut result = Vec::new();
+    if !wide.is_empty() {
+        unsafe {
+            let size = WideCharToMultiByte(CP_ACP, 0, &wide, None, PCSTR::null(), None);
+            if size > 0 {
+                result.resize(size as usize, 0);
+                let size = WideCharToMultiByte(
+                    CP_ACP,
+                    0,
+                    &wide,
+                    Some(result.as_mut_slice()),
+                    PCSTR::null(),
+                    None,
+                );
+                result.truncate(size.max(0) as usize);
+            }
+        }
+    }
+    result.push(0);
+    result
+}
@@ -0,0 +1,141 @@
+//! Define [`PropertyStream`].
+
+use crate::{InterfaceId, MapiProp, OpenFlags, PropTag};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use windows::Win32::System::Com::{
+    IStream, STATFLAG_NONAME, STATSTG, STGC_DEFAULT, STREAM_SEEK_CUR, STREAM_SEEK_END,
+    STREAM_SEEK_SET,
+};
+use windows_core::*;
+
+/// Adapter which implements [`Read`], [`Write`], and [`Seek`] over an [`IStream`] from
+/// [`crate::sys::IMAPIProp::OpenProperty`], e.g. for [`crate::sys::PR_BODY_W`] or
+/// [`crate::sys::PR_ATTACH_DATA_BIN`].
+///
+/// This makes it possible to copy a large body or attachment to or from a file with
+/// [`io::copy`], without reading the whole value into memory first.
+///
+/// Writes are not visible to other readers until the stream is committed, which
+/// [`Write::flush`] does with [`IStream::Commit`]. The object the property belongs to still needs
+/// to be saved afterwards, e.g. with [`crate::Message::save_changes`].
+pub struct PropertyStream {
+    stream: IStream,
+}
+
+impl PropertyStream {
+    /// Open an existing property for reading with [`OpenFlags::read_only`].
+    pub fn open(prop: &impl MapiProp, tag: PropTag) -> Result<Self> {
+        Self::open_with(prop, tag, OpenFlags::read_only())
+    }
+
+    /// Open a property for writing with [`OpenFlags::modify`] and [`OpenFlags::create`], which
+    /// creates the property if it does not exist yet, and replaces the current value if it does.
+    pub fn create(prop: &impl MapiProp, tag: PropTag) -> Result<Self> {
+        Self::open_with(
+            prop,
+            tag,
+            OpenFlags {
+                create: true,
+                ..OpenFlags::modify()
+            },
+        )
+    }
+
+    /// Open a property with [`crate::sys::IMAPIProp::OpenProperty`] and a custom set of
+    /// [`OpenFlags`].
+    pub fn open_with(prop: &impl MapiProp, tag: PropTag, flags: OpenFlags) -> Result<Self> {
+        let stream: IStream = prop
+            .open_property_as(tag, InterfaceId::STREAM, flags)?
+            .cast()?;
+        Ok(Self { stream })
+    }
+
+    /// Borrow the [`IStream`], e.g. to call methods which do not have a wrapper yet.
+    pub fn stream(&self) -> &IStream {
+        &self.stream
+    }
+
+    /// Get the size of the stream in bytes with [`IStream::Stat`].
+    pub fn len(&self) -> Result<u64> {
+        let mut stat = STATSTG::default();
+        unsafe { self.stream.Stat(&mut stat, STATFLAG_NONAME) }?;
+        Ok(stat.cbSize)
+    }
+
+    /// Test if the stream is empty.
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Truncate or extend the stream with [`IStream::SetSize`].
+    pub fn set_len(&self, size: u64) -> Result<()> {
+        unsafe { self.stream.SetSize(size) }
+    }
+}
+
+impl From<IStream> for PropertyStream {
+    fn from(stream: IStream) -> Self {
+        Self { stream }
+    }
+}
+
+impl From<PropertyStream> for IStream {
+    fn from(value: PropertyStream) -> Self {
+        value.stream
+    }
+}
+
+/// Clamp a buffer length to the `ULONG` byte count accepted by [`IStream`].
+fn chunk_len(len: usize) -> u32 {
+    u32::try_from(len).unwrap_or(u32::MAX)
+}
+
+impl Read for PropertyStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        unsafe {
+            self.stream
+                .Read(
+                    buf.as_mut_ptr() as *mut _,
+                    chunk_len(buf.len()),
+                    Some(&mut read),
+                )
+                .ok()?;
+        }
+        Ok(read as usize)
+    }
+}
+
+impl Write for PropertyStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut written = 0;
+        unsafe {
+            self.stream
+                .Write(
+                    buf.as_ptr() as *const _,
+                    chunk_len(buf.len()),
+                    Some(&mut written),
+                )
+                .ok()?;
+        }
+        Ok(written as usize)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        unsafe { self.stream.Commit(STGC_DEFAULT) }?;
+        Ok(())
+    }
+}
+
+impl Seek for PropertyStream {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (offset, origin) = match pos {
+            SeekFrom::Start(offset) => (offset as i64, STREAM_SEEK_SET),
+            SeekFrom::Current(offset) => (offset, STREAM_SEEK_CUR),
+            SeekFrom::End(offset) => (offset, STREAM_SEEK_END),
+        };
+        let mut position = 0;
+        unsafe { self.stream.Seek(offset, origin, Some(&mut position)) }?;
+        Ok(position)
+    }
+}
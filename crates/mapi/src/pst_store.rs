@@ -0,0 +1,92 @@
+//! Define [`PstStoreProps`], [`Store::rename`], and [`Store::set_comment`].
+
+use crate::{
+    mapi_prop::{get_one_prop, set_props, SetPropValue},
+    sys, MapiProp, PropTag, PropValueData, Store, StringPropOptions,
+};
+use windows_core::*;
+
+/// Typed view of the store properties which are specific to (or mostly interesting for) PST
+/// stores. Any property which is missing from the store is `None`.
+#[derive(Clone, Debug, Default)]
+pub struct PstStoreProps {
+    /// [`sys::PR_PST_PATH`], read as `PT_UNICODE`.
+    pub path: Option<String>,
+
+    /// [`sys::PR_DISPLAY_NAME_W`]
+    pub display_name: Option<String>,
+
+    /// [`sys::PR_COMMENT_W`]
+    pub comment: Option<String>,
+
+    /// [`sys::PR_PST_ENCRYPTION`], one of [`sys::PSTF_NO_ENCRYPTION`],
+    /// [`sys::PSTF_COMPRESSABLE_ENCRYPTION`], or [`sys::PSTF_BEST_ENCRYPTION`].
+    pub encryption: Option<u32>,
+}
+
+impl PstStoreProps {
+    /// Read the properties from an open store.
+    pub fn read(store: &sys::IMsgStore) -> Result<Self> {
+        let mut strings = store
+            .get_string_props(
+                &[
+                    PropTag(sys::PR_PST_PATH),
+                    PropTag(sys::PR_DISPLAY_NAME_W),
+                    PropTag(sys::PR_COMMENT_W),
+                ],
+                StringPropOptions {
+                    ansi_fallback: true,
+                    ..Default::default()
+                },
+            )?
+            .into_iter()
+            .map(Result::ok);
+        Ok(Self {
+            path: strings.next().flatten(),
+            display_name: strings.next().flatten(),
+            comment: strings.next().flatten(),
+            encryption: get_one_prop(store, sys::PR_PST_ENCRYPTION, |value| match value {
+                PropValueData::Long(value) => Some(value as u32),
+                _ => None,
+            })?,
+        })
+    }
+}
+
+impl Store {
+    /// Change the [`sys::PR_DISPLAY_NAME_W`] of this store, which is the name Outlook shows at the
+    /// top of the folder list.
+    ///
+    /// Unlike folders and messages, most store providers commit [`sys::IMAPIProp::SetProps`] on
+    /// the store object immediately, and some of them fail [`sys::IMAPIProp::SaveChanges`] with
+    /// [`sys::MAPI_E_NO_SUPPORT`] or [`sys::MAPI_E_NO_ACCESS`]. This still calls
+    /// [`sys::IMAPIProp::SaveChanges`] for the providers that need it, but ignores those errors.
+    ///
+    /// Outlook caches the store name in the profile, so a running instance of Outlook may not show
+    /// the new name until it restarts.
+    pub fn rename(&self, display_name: &str) -> Result<()> {
+        set_store_props(
+            self.store(),
+            &[(sys::PR_DISPLAY_NAME_W, SetPropValue::Unicode(display_name))],
+        )
+    }
+
+    /// Change the [`sys::PR_COMMENT_W`] of this store. See [`Store::rename`] for the details of
+    /// how the change is saved.
+    pub fn set_comment(&self, comment: &str) -> Result<()> {
+        set_store_props(
+            self.store(),
+            &[(sys::PR_COMMENT_W, SetPropValue::Unicode(comment))],
+        )
+    }
+}
+
+fn set_store_props(store: &sys::IMsgStore, values: &[(u32, SetPropValue)]) -> Result<()> {
+    set_props(store, values)?;
+    match unsafe { store.SaveChanges(sys::KEEP_OPEN_READWRITE) } {
+        Err(err) if err.code() == sys::MAPI_E_NO_SUPPORT || err.code() == sys::MAPI_E_NO_ACCESS => {
+            Ok(())
+        }
+        result => result,
+    }
+}
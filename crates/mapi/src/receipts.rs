@@ -0,0 +1,316 @@
+//! Define [`ReceiptRequest`], [`ReceiptHandling`], [`ReceiptStatus`], [`ReadFlags`],
+//! [`mark_read`], and [`mark_unread`].
+
+use crate::{
+    mapi_prop::{get_one_prop, set_props, SetPropValue},
+    sys, EntryList, Folder, Message, PropValueData,
+};
+use windows_core::*;
+
+/// Set of receipts and reports which the sender of a message can request. Set these on a new
+/// message before calling [`sys::IMessage::SubmitMessage`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReceiptRequest {
+    /// Set [`sys::PR_READ_RECEIPT_REQUESTED`].
+    pub read_receipt: bool,
+
+    /// Set [`sys::PR_NON_RECEIPT_NOTIFICATION_REQUESTED`], which asks for a receipt if the message
+    /// is deleted or expires without being read.
+    pub non_read_receipt: bool,
+
+    /// Set [`sys::PR_ORIGINATOR_DELIVERY_REPORT_REQUESTED`].
+    pub delivery_report: bool,
+
+    /// Set [`sys::PR_ORIGINATOR_NON_DELIVERY_REPORT_REQUESTED`].
+    pub non_delivery_report: bool,
+}
+
+impl ReceiptRequest {
+    /// Read the requested receipts from a message. Missing properties are treated as `false`.
+    pub fn read(message: &sys::IMessage) -> Result<Self> {
+        let get = |tag| {
+            get_one_prop(message, tag, |value| match value {
+                PropValueData::Boolean(value) => Some(value != 0),
+                _ => None,
+            })
+            .map(Option::unwrap_or_default)
+        };
+        Ok(Self {
+            read_receipt: get(sys::PR_READ_RECEIPT_REQUESTED)?,
+            non_read_receipt: get(sys::PR_NON_RECEIPT_NOTIFICATION_REQUESTED)?,
+            delivery_report: get(sys::PR_ORIGINATOR_DELIVERY_REPORT_REQUESTED)?,
+            non_delivery_report: get(sys::PR_ORIGINATOR_NON_DELIVERY_REPORT_REQUESTED)?,
+        })
+    }
+
+    /// Set all of the request properties on a message being composed. Every property is written,
+    /// so this also clears requests which were copied from another message (e.g. when
+    /// forwarding).
+    pub fn apply(&self, message: &sys::IMessage) -> Result<()> {
+        set_props(
+            message,
+            &[
+                (
+                    sys::PR_READ_RECEIPT_REQUESTED,
+                    SetPropValue::Boolean(self.read_receipt),
+                ),
+                (
+                    sys::PR_NON_RECEIPT_NOTIFICATION_REQUESTED,
+                    SetPropValue::Boolean(self.non_read_receipt),
+                ),
+                (
+                    sys::PR_ORIGINATOR_DELIVERY_REPORT_REQUESTED,
+                    SetPropValue::Boolean(self.delivery_report),
+                ),
+                (
+                    sys::PR_ORIGINATOR_NON_DELIVERY_REPORT_REQUESTED,
+                    SetPropValue::Boolean(self.non_delivery_report),
+                ),
+            ],
+        )
+    }
+}
+
+/// What [`mark_read`] should do about a pending read receipt. Each choice converts to the
+/// equivalent [`ReadFlags`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReceiptHandling {
+    /// Let the store send the read receipt if the sender requested one, which is what happens when
+    /// a user opens the message in Outlook.
+    #[default]
+    Send,
+
+    /// Pass [`sys::SUPPRESS_RECEIPT`], which marks the message as read and clears the pending read
+    /// receipt without sending it. Use this when processing mail on the user's behalf.
+    Suppress,
+
+    /// Pass [`sys::GENERATE_RECEIPT_ONLY`], which sends a pending read receipt without changing the
+    /// read state of the message.
+    SendOnly,
+}
+
+impl From<ReceiptHandling> for ReadFlags {
+    fn from(value: ReceiptHandling) -> Self {
+        match value {
+            ReceiptHandling::Send => Default::default(),
+            ReceiptHandling::Suppress => Self::read_without_receipt(),
+            ReceiptHandling::SendOnly => Self {
+                generate_receipt_only: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Status of the receipts for a received message, from [`sys::PR_MESSAGE_FLAGS`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReceiptStatus {
+    /// Set if [`sys::MSGFLAG_READ`] is present.
+    pub read: bool,
+
+    /// Set if [`sys::MSGFLAG_RN_PENDING`] is present, i.e. the store will send a read receipt the
+    /// next time the message is marked as read.
+    pub read_receipt_pending: bool,
+
+    /// Set if [`sys::MSGFLAG_NRN_PENDING`] is present, i.e. the store will send a non-read receipt
+    /// if the message is deleted without being read.
+    pub non_read_receipt_pending: bool,
+}
+
+impl ReceiptStatus {
+    /// Read [`sys::PR_MESSAGE_FLAGS`] from a message.
+    pub fn read(message: &sys::IMessage) -> Result<Self> {
+        let flags = get_one_prop(message, sys::PR_MESSAGE_FLAGS, |value| match value {
+            PropValueData::Long(value) => Some(value as u32),
+            _ => None,
+        })?
+        .unwrap_or_default();
+        Ok(Self {
+            read: flags & sys::MSGFLAG_READ != 0,
+            read_receipt_pending: flags & sys::MSGFLAG_RN_PENDING != 0,
+            non_read_receipt_pending: flags & sys::MSGFLAG_NRN_PENDING != 0,
+        })
+    }
+}
+
+/// Mark a received message as read with [`sys::IMessage::SetReadFlag`], choosing explicitly
+/// whether the store should send a pending read receipt. Same as [`Message::set_read_flag`] with
+/// the [`ReadFlags`] for `receipt`.
+pub fn mark_read(message: &sys::IMessage, receipt: ReceiptHandling) -> Result<()> {
+    set_read_flag(message, ReadFlags::from(receipt))
+}
+
+/// Mark a received message as unread with [`sys::CLEAR_READ_FLAG`].
+///
+/// If `clear_pending` is `true`, this also passes [`sys::CLEAR_RN_PENDING`] and
+/// [`sys::CLEAR_NRN_PENDING`], so no read or non-read receipt is sent for the message later
+/// (see [`ReadFlags::unread_without_receipt`]). Otherwise, the pending receipts are left alone,
+/// and will be sent the next time the message is marked as read (or deleted).
+pub fn mark_unread(message: &sys::IMessage, clear_pending: bool) -> Result<()> {
+    let flags = if clear_pending {
+        ReadFlags::unread_without_receipt()
+    } else {
+        ReadFlags {
+            clear_read_flag: true,
+            ..Default::default()
+        }
+    };
+    set_read_flag(message, flags)
+}
+
+/// Call [`sys::IMessage::SetReadFlag`] with [`ReadFlags`].
+fn set_read_flag(message: &sys::IMessage, flags: ReadFlags) -> Result<()> {
+    unsafe { message.SetReadFlag(u32::from(flags)) }
+}
+
+/// Set of flags that can be passed to [`Message::set_read_flag`] and [`Folder::set_read_flags`].
+///
+/// With none of them set, the messages are marked as read, and the store sends any pending read
+/// receipts, which is what happens when a user opens them in Outlook.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReadFlags {
+    /// Pass [`sys::CLEAR_READ_FLAG`], which marks the messages as unread instead.
+    pub clear_read_flag: bool,
+
+    /// Pass [`sys::SUPPRESS_RECEIPT`], which clears a pending read receipt without sending it.
+    pub suppress_receipt: bool,
+
+    /// Pass [`sys::GENERATE_RECEIPT_ONLY`], which sends a pending read receipt without changing
+    /// the read state.
+    pub generate_receipt_only: bool,
+
+    /// Pass [`sys::CLEAR_RN_PENDING`], which clears [`sys::MSGFLAG_RN_PENDING`] so no read receipt
+    /// is sent later.
+    pub clear_rn_pending: bool,
+
+    /// Pass [`sys::CLEAR_NRN_PENDING`], which clears [`sys::MSGFLAG_NRN_PENDING`] so no non-read
+    /// receipt is sent later.
+    pub clear_nrn_pending: bool,
+
+    /// Pass [`sys::MAPI_DEFERRED_ERRORS`]. Only [`Folder::set_read_flags`] accepts this.
+    pub deferred_errors: bool,
+}
+
+impl ReadFlags {
+    /// Mark as read without sending a pending read receipt, which is what an automated process
+    /// acting on the user's behalf usually wants.
+    pub fn read_without_receipt() -> Self {
+        Self {
+            suppress_receipt: true,
+            ..Default::default()
+        }
+    }
+
+    /// Mark as unread, and clear the pending receipts so none are sent later.
+    pub fn unread_without_receipt() -> Self {
+        Self {
+            clear_read_flag: true,
+            clear_rn_pending: true,
+            clear_nrn_pending: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<ReadFlags> for u32 {
+    fn from(value: ReadFlags) -> Self {
+        let clear_read_flag = if value.clear_read_flag {
+            sys::CLEAR_READ_FLAG
+        } else {
+            0
+        };
+        let suppress_receipt = if value.suppress_receipt {
+            sys::SUPPRESS_RECEIPT
+        } else {
+            0
+        };
+        let generate_receipt_only = if value.generate_receipt_only {
+            sys::GENERATE_RECEIPT_ONLY
+        } else {
+            0
+        };
+        let clear_rn_pending = if value.clear_rn_pending {
+            sys::CLEAR_RN_PENDING
+        } else {
+            0
+        };
+        let clear_nrn_pending = if value.clear_nrn_pending {
+            sys::CLEAR_NRN_PENDING
+        } else {
+            0
+        };
+        let deferred_errors = if value.deferred_errors {
+            sys::MAPI_DEFERRED_ERRORS
+        } else {
+            0
+        };
+
+        clear_read_flag
+            | suppress_receipt
+            | generate_receipt_only
+            | clear_rn_pending
+            | clear_nrn_pending
+            | deferred_errors
+    }
+}
+
+impl Message {
+    /// Mark this message as read or unread with [`sys::IMessage::SetReadFlag`], leaving the
+    /// receipts to the store's default behavior. Use [`Message::set_read_flag`] to control them.
+    pub fn set_read(&self, read: bool) -> Result<()> {
+        self.set_read_flag(ReadFlags {
+            clear_read_flag: !read,
+            ..Default::default()
+        })
+    }
+
+    /// Call [`sys::IMessage::SetReadFlag`] with explicit [`ReadFlags`].
+    pub fn set_read_flag(&self, flags: ReadFlags) -> Result<()> {
+        set_read_flag(self.message(), flags)
+    }
+}
+
+impl Folder {
+    /// Mark the messages in `entry_ids` as read or unread in one call to
+    /// [`sys::IMAPIFolder::SetReadFlags`], which is much faster than opening each message.
+    pub fn set_read_flags(&self, entry_ids: &EntryList, flags: ReadFlags) -> Result<()> {
+        let mut entry_list = entry_ids
+            .build()
+            .map_err(|_| Error::from(sys::MAPI_E_NOT_ENOUGH_MEMORY))?;
+        unsafe {
+            self.folder().SetReadFlags(
+                entry_list.as_mut_ptr(),
+                0,
+                None::<&sys::IMAPIProgress>,
+                u32::from(flags),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_flags() {
+        assert_eq!(u32::from(ReadFlags::default()), 0);
+        assert_eq!(
+            u32::from(ReadFlags::read_without_receipt()),
+            sys::SUPPRESS_RECEIPT
+        );
+        assert_eq!(
+            u32::from(ReadFlags::unread_without_receipt()),
+            sys::CLEAR_READ_FLAG | sys::CLEAR_RN_PENDING | sys::CLEAR_NRN_PENDING
+        );
+        assert_eq!(u32::from(ReadFlags::from(ReceiptHandling::Send)), 0);
+        assert_eq!(
+            u32::from(ReadFlags::from(ReceiptHandling::Suppress)),
+            sys::SUPPRESS_RECEIPT
+        );
+        assert_eq!(
+            u32::from(ReadFlags::from(ReceiptHandling::SendOnly)),
+            sys::GENERATE_RECEIPT_ONLY
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Define [`Recipient`], [`RecipientType`], and [`RecipientChange`].
+
+use crate::{
+    sys, AdrList, Message, OwnedPropValue, OwnedPropValueData, PropTag, PropValue,
+    PropValueBuilder, PropValueData, TableQuery, TableRows,
+};
+use windows_core::*;
+
+/// Value of [`sys::PR_RECIPIENT_TYPE`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RecipientType {
+    /// [`sys::MAPI_ORIG`]
+    Originator,
+
+    /// [`sys::MAPI_TO`]
+    #[default]
+    To,
+
+    /// [`sys::MAPI_CC`]
+    Cc,
+
+    /// [`sys::MAPI_BCC`]
+    Bcc,
+}
+
+impl From<RecipientType> for u32 {
+    fn from(value: RecipientType) -> Self {
+        match value {
+            RecipientType::Originator => sys::MAPI_ORIG,
+            RecipientType::To => sys::MAPI_TO,
+            RecipientType::Cc => sys::MAPI_CC,
+            RecipientType::Bcc => sys::MAPI_BCC,
+        }
+    }
+}
+
+impl From<u32> for RecipientType {
+    /// Convert a [`sys::PR_RECIPIENT_TYPE`] value, ignoring the [`sys::MAPI_P1`] and
+    /// [`sys::MAPI_SUBMITTED`] flags. Unknown values are treated as [`RecipientType::To`].
+    fn from(value: u32) -> Self {
+        match value & !(sys::MAPI_P1 | sys::MAPI_SUBMITTED) {
+            sys::MAPI_ORIG => Self::Originator,
+            sys::MAPI_CC => Self::Cc,
+            sys::MAPI_BCC => Self::Bcc,
+            _ => Self::To,
+        }
+    }
+}
+
+/// Operation performed by [`Message::modify_recipients`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RecipientChange {
+    /// Pass no flags, which replaces every recipient on the message with the new list.
+    #[default]
+    Replace,
+
+    /// Pass [`sys::MODRECIP_ADD`], to add the entries as new recipients.
+    Add,
+
+    /// Pass [`sys::MODRECIP_MODIFY`], to replace the properties of the recipients identified by
+    /// the [`sys::PR_ROWID`] in each entry.
+    Modify,
+
+    /// Pass [`sys::MODRECIP_REMOVE`], to remove the recipients identified by the
+    /// [`sys::PR_ROWID`] in each entry.
+    Remove,
+}
+
+impl From<RecipientChange> for u32 {
+    fn from(value: RecipientChange) -> Self {
+        match value {
+            RecipientChange::Replace => 0,
+            RecipientChange::Add => sys::MODRECIP_ADD,
+            RecipientChange::Modify => sys::MODRECIP_MODIFY,
+            RecipientChange::Remove => sys::MODRECIP_REMOVE,
+        }
+    }
+}
+
+/// Columns read by [`Message::recipients`] if the caller does not choose any.
+pub const DEFAULT_RECIPIENT_COLUMNS: [u32; 6] = [
+    sys::PR_ROWID,
+    sys::PR_RECIPIENT_TYPE,
+    sys::PR_DISPLAY_NAME_W,
+    sys::PR_EMAIL_ADDRESS_W,
+    sys::PR_ADDRTYPE_W,
+    sys::PR_ENTRYID,
+];
+
+/// Properties of a single recipient, which can be converted to an entry in an [`AdrList`] with
+/// [`Recipient::to_props`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Recipient {
+    /// [`sys::PR_ROWID`], which identifies an existing recipient for [`RecipientChange::Modify`]
+    /// and [`RecipientChange::Remove`].
+    pub row_id: Option<i32>,
+
+    /// [`sys::PR_RECIPIENT_TYPE`]
+    pub recipient_type: RecipientType,
+
+    /// [`sys::PR_DISPLAY_NAME_W`]
+    pub display_name: String,
+
+    /// [`sys::PR_EMAIL_ADDRESS_W`]
+    pub email_address: String,
+
+    /// [`sys::PR_ADDRTYPE_W`], e.g. `"SMTP"` or `"EX"`.
+    pub address_type: String,
+
+    /// [`sys::PR_ENTRYID`] of the address book entry, if the recipient has already been resolved.
+    pub entry_id: Option<Vec<u8>>,
+}
+
+impl Recipient {
+    /// One-off recipient with an SMTP address, which does not need to be in the address book.
+    pub fn smtp(
+        display_name: impl Into<String>,
+        email_address: impl Into<String>,
+        recipient_type: RecipientType,
+    ) -> Self {
+        Self {
+            recipient_type,
+            display_name: display_name.into(),
+            email_address: email_address.into(),
+            address_type: "SMTP".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Entry for [`RecipientChange::Remove`], which only needs the [`sys::PR_ROWID`].
+    pub fn remove(row_id: i32) -> Self {
+        Self {
+            row_id: Some(row_id),
+            ..Default::default()
+        }
+    }
+
+    /// Read a recipient from a set of property values, e.g. a row from [`Message::recipients`] or
+    /// an entry resolved by [`crate::AddressBook::resolve_name`]. Properties which are missing
+    /// or have an unexpected type are left at their defaults.
+    pub fn from_props<'a>(values: impl IntoIterator<Item = PropValue<'a>>) -> Self {
+        let mut recipient = Self::default();
+        for PropValue { tag, value } in values {
+            let string = |value: PCWSTR| unsafe { value.to_string() }.unwrap_or_default();
+            match (u32::from(tag), value) {
+                (sys::PR_ROWID, PropValueData::Long(value)) => recipient.row_id = Some(value),
+                (sys::PR_RECIPIENT_TYPE, PropValueData::Long(value)) => {
+                    recipient.recipient_type = RecipientType::from(value as u32)
+                }
+                (sys::PR_DISPLAY_NAME_W, PropValueData::Unicode(value)) => {
+                    recipient.display_name = string(value)
+                }
+                (sys::PR_EMAIL_ADDRESS_W, PropValueData::Unicode(value)) => {
+                    recipient.email_address = string(value)
+                }
+                (sys::PR_ADDRTYPE_W, PropValueData::Unicode(value)) => {
+                    recipient.address_type = string(value)
+                }
+                (sys::PR_ENTRYID, PropValueData::Binary(value)) => {
+                    recipient.entry_id = Some(value.to_vec())
+                }
+                _ => {}
+            }
+        }
+        recipient
+    }
+
+    /// Convert the recipient to a set of property values for an [`AdrList`]. Empty strings are
+    /// left out, and a recipient with only a [`Recipient::row_id`] just has [`sys::PR_ROWID`].
+    pub fn to_props(&self) -> PropValueBuilder {
+        let prop = |tag: u32, value| OwnedPropValue {
+            tag: PropTag(tag),
+            value,
+        };
+        let mut props = PropValueBuilder::new();
+        if let Some(row_id) = self.row_id {
+            props = props.push(prop(sys::PR_ROWID, OwnedPropValueData::Long(row_id)));
+            if self.display_name.is_empty() && self.email_address.is_empty() {
+                return props;
+            }
+        }
+        props = props.push(prop(
+            sys::PR_RECIPIENT_TYPE,
+            OwnedPropValueData::Long(u32::from(self.recipient_type) as i32),
+        ));
+        for (tag, value) in [
+            (sys::PR_DISPLAY_NAME_W, &self.display_name),
+            (sys::PR_EMAIL_ADDRESS_W, &self.email_address),
+            (sys::PR_ADDRTYPE_W, &self.address_type),
+        ] {
+            if !value.is_empty() {
+                props = props.push(prop(tag, OwnedPropValueData::Unicode(value.clone())));
+            }
+        }
+        if let Some(entry_id) = self.entry_id.as_ref() {
+            props = props.push(prop(
+                sys::PR_ENTRYID,
+                OwnedPropValueData::Binary(entry_id.clone()),
+            ));
+        }
+        props
+    }
+}
+
+impl<'a> FromIterator<&'a Recipient> for AdrList {
+    fn from_iter<T: IntoIterator<Item = &'a Recipient>>(iter: T) -> Self {
+        iter.into_iter().map(Recipient::to_props).collect()
+    }
+}
+
+impl Message {
+    /// Read the [`Message::recipient_table`] with `columns`, or with
+    /// [`DEFAULT_RECIPIENT_COLUMNS`] if `columns` is empty.
+    pub fn recipients(&self, columns: &[PropTag]) -> Result<TableRows> {
+        let default_columns: Vec<_> = DEFAULT_RECIPIENT_COLUMNS.map(PropTag).to_vec();
+        let columns = if columns.is_empty() {
+            default_columns.as_slice()
+        } else {
+            columns
+        };
+        TableQuery::new(self.recipient_table()?)
+            .columns(columns)
+            .execute()
+    }
+
+    /// Change the recipients with [`sys::IMessage::ModifyRecipients`]. The changes are not
+    /// persisted until [`Message::save_changes`] is called.
+    pub fn modify_recipients(&self, change: RecipientChange, recipients: &AdrList) -> Result<()> {
+        let mut adr_list = recipients
+            .build()
+            .map_err(|_| Error::from(sys::MAPI_E_NOT_ENOUGH_MEMORY))?;
+        unsafe {
+            self.message()
+                .ModifyRecipients(change.into(), adr_list.as_mut_ptr())
+        }
+    }
+
+    /// Add recipients with [`Message::modify_recipients`] and [`RecipientChange::Add`].
+    pub fn add_recipients(&self, recipients: &[Recipient]) -> Result<()> {
+        self.modify_recipients(RecipientChange::Add, &recipients.iter().collect())
+    }
+
+    /// Remove recipients by [`sys::PR_ROWID`] with [`Message::modify_recipients`] and
+    /// [`RecipientChange::Remove`].
+    pub fn remove_recipients(&self, row_ids: &[i32]) -> Result<()> {
+        let recipients: Vec<_> = row_ids.iter().copied().map(Recipient::remove).collect();
+        self.modify_recipients(RecipientChange::Remove, &recipients.iter().collect())
+    }
+}
@@ -0,0 +1,115 @@
+//! Define [`Redactor`], [`HashRedactor`], [`set_redactor`], and [`redactor`].
+
+use crate::{sys, PropTag};
+use std::sync::{Arc, RwLock};
+
+static REDACTOR: RwLock<Option<Arc<dyn Redactor>>> = RwLock::new(None);
+
+/// Hook for hiding mailbox content in diagnostic output, so logs, dumps, and exports can be shared
+/// without leaking it.
+///
+/// The text renderers consult the process-wide [`redactor`] for every string value when
+/// [`crate::RenderOptions::redact`] is set, which covers [`crate::render_prop`],
+/// [`crate::diff_messages_with`], and [`crate::rows_to_writer_with`].
+pub trait Redactor: Send + Sync {
+    /// Return the replacement for a string value of the property `tag`, or `None` to render it
+    /// unchanged.
+    fn redact(&self, tag: PropTag, value: &str) -> Option<String>;
+}
+
+/// The default [`Redactor`], which replaces subjects, bodies, names, and addresses with a hash of
+/// the value, e.g. `<redacted:4f1c2a9be0d37c65>`.
+///
+/// Equal values get equal hashes, so a redacted log still shows which messages share a subject
+/// or a sender. The hash is not keyed, so it does not protect short or guessable values from a
+/// dictionary attack; plug in a [`Redactor`] which drops the value entirely if that matters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HashRedactor;
+
+/// Properties which [`HashRedactor`] redacts. They are matched by `PROP_ID`, so the
+/// [`sys::PT_STRING8`] and [`sys::PT_UNICODE`] variants are both covered.
+const SENSITIVE_PROPS: &[u32] = &[
+    sys::PR_SUBJECT_W,
+    sys::PR_NORMALIZED_SUBJECT_W,
+    sys::PR_CONVERSATION_TOPIC_W,
+    sys::PR_SUBJECT_PREFIX_W,
+    sys::PR_BODY_W,
+    sys::PR_TRANSPORT_MESSAGE_HEADERS_W,
+    sys::PR_INTERNET_MESSAGE_ID_W,
+    sys::PR_DISPLAY_NAME_W,
+    sys::PR_EMAIL_ADDRESS_W,
+    sys::PR_SMTP_ADDRESS_W,
+    sys::PR_DISPLAY_TO_W,
+    sys::PR_DISPLAY_CC_W,
+    sys::PR_DISPLAY_BCC_W,
+    sys::PR_ORIGINAL_DISPLAY_TO_W,
+    sys::PR_SENDER_NAME_W,
+    sys::PR_SENDER_EMAIL_ADDRESS_W,
+    sys::PR_SENT_REPRESENTING_NAME_W,
+    sys::PR_SENT_REPRESENTING_EMAIL_ADDRESS_W,
+    sys::PR_RECEIVED_BY_NAME_W,
+    sys::PR_RECEIVED_BY_EMAIL_ADDRESS_W,
+];
+
+impl HashRedactor {
+    /// Test if [`HashRedactor`] redacts the property `tag`.
+    pub fn is_sensitive(tag: PropTag) -> bool {
+        SENSITIVE_PROPS
+            .iter()
+            .any(|sensitive| PropTag(*sensitive).prop_id() == tag.prop_id())
+    }
+
+    /// Hash a value with 64-bit FNV-1a.
+    fn hash(value: &str) -> u64 {
+        value.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01B3)
+        })
+    }
+}
+
+impl Redactor for HashRedactor {
+    fn redact(&self, tag: PropTag, value: &str) -> Option<String> {
+        Self::is_sensitive(tag).then(|| format!("<redacted:{:016x}>", Self::hash(value)))
+    }
+}
+
+/// Replace the process-wide [`Redactor`]. Output which was already rendered is not affected.
+pub fn set_redactor(redactor: impl Redactor + 'static) {
+    *REDACTOR.write().unwrap_or_else(|err| err.into_inner()) = Some(Arc::new(redactor));
+}
+
+/// Get the process-wide [`Redactor`], which is [`HashRedactor`] unless it was replaced with
+/// [`set_redactor`].
+pub fn redactor() -> Arc<dyn Redactor> {
+    REDACTOR
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+        .unwrap_or_else(|| Arc::new(HashRedactor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_redactor() {
+        let redactor = HashRedactor;
+        let subject = redactor.redact(PropTag(sys::PR_SUBJECT_W), "quarterly results");
+        assert!(subject
+            .as_deref()
+            .is_some_and(|subject| subject.starts_with("<redacted:")));
+        assert_eq!(
+            subject,
+            redactor.redact(PropTag(sys::PR_SUBJECT_A), "quarterly results")
+        );
+        assert_ne!(
+            subject,
+            redactor.redact(PropTag(sys::PR_SUBJECT_W), "quarterly result")
+        );
+        assert_eq!(
+            redactor.redact(PropTag(sys::PR_MESSAGE_CLASS_W), "IPM.Note"),
+            None
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Define [`Reminder`] and [`reminders`].
+
+use crate::{
+    mapi_prop::{get_named_prop_tags, open_store_entry},
+    query_view, sys, MapiProp, OpenFlags, PropTag, PropValue, PropValueData, Row, TypedView,
+};
+use windows::Win32::Foundation::FILETIME;
+use windows_core::*;
+
+/// `PR_REM_ONLINE_ENTRYID` is missing from the generated bindings.
+pub const PR_REM_ONLINE_ENTRYID: u32 = 0x36D5_0102;
+
+/// `PR_REM_OFFLINE_ENTRYID` is missing from the generated bindings.
+pub const PR_REM_OFFLINE_ENTRYID: u32 = 0x36D6_0102;
+
+/// `PidLidReminderTime` in [`sys::PSETID_Common`].
+pub const LID_REMINDER_TIME: i32 = 0x8502;
+
+/// `PidLidReminderSet` in [`sys::PSETID_Common`].
+pub const LID_REMINDER_SET: i32 = 0x8503;
+
+/// `PidLidReminderSignalTime` in [`sys::PSETID_Common`].
+pub const LID_REMINDER_SIGNAL_TIME: i32 = 0x8560;
+
+/// Typed row from the contents table of the reminders search folder.
+#[derive(Clone, Debug, Default)]
+pub struct Reminder {
+    /// [`sys::PR_ENTRYID`] of the item with the reminder.
+    pub entry_id: Vec<u8>,
+
+    /// [`sys::PR_SUBJECT_W`] of the item.
+    pub subject: Option<String>,
+
+    /// [`sys::PR_MESSAGE_CLASS_W`] of the item.
+    pub message_class: Option<String>,
+
+    /// `PidLidReminderSignalTime`, which is when the reminder should fire next, taking snoozing
+    /// into account.
+    pub signal_time: Option<FILETIME>,
+
+    /// `PidLidReminderTime`, which is when the reminder was originally due.
+    pub reminder_time: Option<FILETIME>,
+
+    /// Set if `PidLidReminderSet` is `false` or missing, which is how a dismissed reminder is
+    /// represented.
+    pub dismissed: bool,
+}
+
+/// Open the reminders search folder, identified by [`PR_REM_ONLINE_ENTRYID`] (or
+/// [`PR_REM_OFFLINE_ENTRYID`] in a cached mode store) on the root folder of the store.
+pub fn open_reminders_folder(store: &sys::IMsgStore) -> Result<sys::IMAPIFolder> {
+    let root: sys::IMAPIFolder = open_store_entry(store, &[], OpenFlags::default())?;
+    let entry_id = match root.get_binary(PropTag(PR_REM_ONLINE_ENTRYID)) {
+        Err(err) if err.code() == sys::MAPI_E_NOT_FOUND => {
+            root.get_binary(PropTag(PR_REM_OFFLINE_ENTRYID))?
+        }
+        result => result?,
+    };
+    open_store_entry(store, &entry_id, OpenFlags::default())
+}
+
+/// Read the contents of the reminders search folder as typed [`Reminder`] rows.
+///
+/// The reminder properties are named properties in [`sys::PSETID_Common`]. If the store has never
+/// mapped them, there cannot be any reminders, so this returns an empty list.
+pub fn reminders(store: &sys::IMsgStore) -> Result<Vec<Reminder>> {
+    let folder = open_reminders_folder(store)?;
+    let table = unsafe { folder.GetContentsTable(sys::MAPI_UNICODE)? };
+    query_view(store, &table)
+}
+
+impl TypedView for Reminder {
+    fn columns(store: &sys::IMsgStore) -> Result<Option<Vec<PropTag>>> {
+        let named_tags = get_named_prop_tags(
+            store,
+            &sys::PSETID_Common,
+            &[
+                (LID_REMINDER_SIGNAL_TIME, sys::PT_SYSTIME),
+                (LID_REMINDER_TIME, sys::PT_SYSTIME),
+                (LID_REMINDER_SET, sys::PT_BOOLEAN),
+            ],
+            0,
+        )?;
+        let [Some(signal_time), Some(reminder_time), Some(reminder_set)] = named_tags[..] else {
+            return Ok(None);
+        };
+        Ok(Some(vec![
+            PropTag(sys::PR_ENTRYID),
+            PropTag(sys::PR_SUBJECT_W),
+            PropTag(sys::PR_MESSAGE_CLASS_W),
+            signal_time,
+            reminder_time,
+            reminder_set,
+        ]))
+    }
+
+    fn from_row(row: &Row, columns: &[PropTag]) -> Self {
+        let named_tag = |index: usize| columns.get(index).copied().map(u32::from);
+        let (signal_time, reminder_time, reminder_set) = (named_tag(3), named_tag(4), named_tag(5));
+
+        let mut reminder = Reminder {
+            dismissed: true,
+            ..Default::default()
+        };
+        for PropValue { tag, value } in row.iter() {
+            match (u32::from(tag), value) {
+                (sys::PR_ENTRYID, PropValueData::Binary(value)) => {
+                    reminder.entry_id = value.to_vec()
+                }
+                (sys::PR_SUBJECT_W, PropValueData::Unicode(value)) => {
+                    reminder.subject = unsafe { value.to_string() }.ok()
+                }
+                (sys::PR_MESSAGE_CLASS_W, PropValueData::Unicode(value)) => {
+                    reminder.message_class = unsafe { value.to_string() }.ok()
+                }
+                (tag, PropValueData::FileTime(value)) if Some(tag) == signal_time => {
+                    reminder.signal_time = Some(value)
+                }
+                (tag, PropValueData::FileTime(value)) if Some(tag) == reminder_time => {
+                    reminder.reminder_time = Some(value)
+                }
+                (tag, PropValueData::Boolean(value)) if Some(tag) == reminder_set => {
+                    reminder.dismissed = value == 0
+                }
+                _ => {}
+            }
+        }
+        reminder
+    }
+}
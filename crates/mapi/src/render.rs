@@ -0,0 +1,263 @@
+//! Define [`RenderOptions`], [`TimeFormat`], [`TagNaming`], [`render_prop_value`], and
+//! [`render_prop`].
+
+use crate::{prop_tag_name, redactor, HexDisplay, PropTag, PropValue, PropValueData};
+use core::slice;
+use windows::Win32::Foundation::FILETIME;
+
+/// How [`render_prop_value`] formats [`crate::sys::PT_SYSTIME`] values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// UTC in ISO 8601 format, e.g. `2024-08-01T12:34:56Z`, with 100ns precision if the value has
+    /// a fractional part.
+    #[default]
+    Iso8601,
+
+    /// The raw 64-bit [`FILETIME`] value in hex.
+    Hex,
+}
+
+/// How [`RenderOptions::format_tag`] names properties.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TagNaming {
+    /// Use [`prop_tag_name`] if the name is known, or the hex value of the tag otherwise.
+    #[default]
+    Name,
+
+    /// Always use the hex value of the tag.
+    Hex,
+
+    /// Use both the name and the hex value, e.g. `PR_SUBJECT (0x0037001F)`.
+    NameAndHex,
+}
+
+/// Options shared by the diagnostic, dump, and export paths which render property values as text.
+/// The [`Default`] options render everything in full, which is what exports need.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Stop rendering binary values after this many bytes.
+    pub max_binary_bytes: Option<usize>,
+
+    /// Truncate string values after this many characters.
+    pub max_string_len: Option<usize>,
+
+    /// Format of [`crate::sys::PT_SYSTIME`] values.
+    pub time_format: TimeFormat,
+
+    /// Format of property tags.
+    pub tag_naming: TagNaming,
+
+    /// Pass string values through the process-wide [`crate::Redactor`] before rendering them.
+    /// This only applies where the tag is known, e.g. [`render_prop`] rather than
+    /// [`render_prop_value`].
+    pub redact: bool,
+}
+
+impl RenderOptions {
+    /// Options which keep log lines short.
+    pub fn for_logs() -> Self {
+        Self {
+            max_binary_bytes: Some(32),
+            max_string_len: Some(128),
+            ..Default::default()
+        }
+    }
+
+    /// Format a [`PropTag`] according to [`RenderOptions::tag_naming`].
+    pub fn format_tag(&self, tag: PropTag) -> String {
+        let hex = format!("0x{:08X}", u32::from(tag));
+        match (self.tag_naming, prop_tag_name(tag)) {
+            (TagNaming::Name, Some(name)) => name.to_string(),
+            (TagNaming::NameAndHex, Some(name)) => format!("{name} ({hex})"),
+            _ => hex,
+        }
+    }
+
+    /// Truncate a string according to [`RenderOptions::max_string_len`], and append `...` if it
+    /// was truncated.
+    pub fn truncate_string(&self, value: String) -> String {
+        match self.max_string_len {
+            Some(limit) if value.chars().count() > limit => {
+                let mut value: String = value.chars().take(limit).collect();
+                value.push_str("...");
+                value
+            }
+            _ => value,
+        }
+    }
+
+    /// Apply the [`crate::Redactor`] to a string value of the property `tag` if
+    /// [`RenderOptions::redact`] is set, and then truncate it with
+    /// [`RenderOptions::truncate_string`].
+    pub fn redact_string(&self, tag: Option<PropTag>, value: String) -> String {
+        let value = match tag {
+            Some(tag) if self.redact => redactor().redact(tag, &value).unwrap_or(value),
+            _ => value,
+        };
+        self.truncate_string(value)
+    }
+
+    /// Format a [`FILETIME`] according to [`RenderOptions::time_format`].
+    pub fn format_time(&self, value: &FILETIME) -> String {
+        let intervals = (u64::from(value.dwHighDateTime) << 32) | u64::from(value.dwLowDateTime);
+        match self.time_format {
+            TimeFormat::Hex => format!("0x{intervals:016X}"),
+            TimeFormat::Iso8601 => format_iso8601(intervals),
+        }
+    }
+
+    fn format_binary(&self, value: &[u8]) -> String {
+        match self.max_binary_bytes {
+            Some(limit) => HexDisplay::new(value).with_limit(limit).to_string(),
+            None => HexDisplay::new(value).to_string(),
+        }
+    }
+}
+
+/// Render a [`PropValueData`] as text for diagnostic output, e.g. in [`crate::diff_messages`].
+/// Strings are quoted, binary values are rendered with [`HexDisplay`], and multi-valued
+/// properties are rendered as a list.
+pub fn render_prop_value(value: &PropValueData, options: &RenderOptions) -> String {
+    render_value(value, options, None)
+}
+
+/// Render a [`PropValue`] like [`render_prop_value`], but with its tag, so string values can be
+/// redacted if [`RenderOptions::redact`] is set.
+pub fn render_prop(value: &PropValue, options: &RenderOptions) -> String {
+    render_value(&value.value, options, Some(value.tag))
+}
+
+fn render_value(value: &PropValueData, options: &RenderOptions, tag: Option<PropTag>) -> String {
+    let string = |value: String| format!("{:?}", options.redact_string(tag, value));
+    let list = |values: Vec<String>| format!("[{}]", values.join(", "));
+    unsafe {
+        match value {
+            PropValueData::Null => "null".to_string(),
+            PropValueData::Short(value) => value.to_string(),
+            PropValueData::Long(value) => value.to_string(),
+            PropValueData::Pointer(value) => format!("{value:p}"),
+            PropValueData::Float(value) => value.to_string(),
+            PropValueData::Double(value) => value.to_string(),
+            PropValueData::Boolean(value) => (*value != 0).to_string(),
+            PropValueData::Currency(value) => value.to_string(),
+            PropValueData::AppTime(value) => value.to_string(),
+            PropValueData::FileTime(value) => options.format_time(value),
+            PropValueData::AnsiString(value) => string(value.to_string().unwrap_or_default()),
+            PropValueData::Binary(value) => options.format_binary(value),
+            PropValueData::Unicode(value) => string(value.to_string().unwrap_or_default()),
+            PropValueData::Guid(value) => format!("{value:?}"),
+            PropValueData::LargeInteger(value) => value.to_string(),
+            PropValueData::ShortArray(values) => list(values.iter().map(i16::to_string).collect()),
+            PropValueData::LongArray(values) => list(values.iter().map(i32::to_string).collect()),
+            PropValueData::FloatArray(values) => list(values.iter().map(f32::to_string).collect()),
+            PropValueData::DoubleArray(values) | PropValueData::AppTimeArray(values) => {
+                list(values.iter().map(f64::to_string).collect())
+            }
+            PropValueData::CurrencyArray(values) => {
+                list(values.iter().map(|value| value.int64.to_string()).collect())
+            }
+            PropValueData::FileTimeArray(values) => list(
+                values
+                    .iter()
+                    .map(|value| options.format_time(value))
+                    .collect(),
+            ),
+            PropValueData::BinaryArray(values) => list(
+                values
+                    .iter()
+                    .map(|value| {
+                        if value.lpb.is_null() {
+                            String::new()
+                        } else {
+                            options
+                                .format_binary(slice::from_raw_parts(value.lpb, value.cb as usize))
+                        }
+                    })
+                    .collect(),
+            ),
+            PropValueData::AnsiStringArray(values) => list(
+                values
+                    .iter()
+                    .map(|value| string(value.to_string().unwrap_or_default()))
+                    .collect(),
+            ),
+            PropValueData::UnicodeArray(values) => list(
+                values
+                    .iter()
+                    .map(|value| string(value.to_string().unwrap_or_default()))
+                    .collect(),
+            ),
+            PropValueData::GuidArray(values) => {
+                list(values.iter().map(|value| format!("{value:?}")).collect())
+            }
+            PropValueData::LargeIntegerArray(values) => {
+                list(values.iter().map(i64::to_string).collect())
+            }
+            PropValueData::Error(err) => format!("error 0x{:08X}", err.0),
+            PropValueData::Object(_) => "object".to_string(),
+        }
+    }
+}
+
+/// Number of 100ns intervals in a day.
+const INTERVALS_PER_DAY: u64 = 864_000_000_000;
+
+/// Number of days between 1601-01-01 and 0000-03-01 in the proleptic Gregorian calendar, which is
+/// the epoch of the `civil_from_days` algorithm.
+const EPOCH_DAYS: i64 = 584_694;
+
+fn format_iso8601(intervals: u64) -> String {
+    let days = (intervals / INTERVALS_PER_DAY) as i64 + EPOCH_DAYS;
+    let remainder = intervals % INTERVALS_PER_DAY;
+
+    // Convert days since 0000-03-01 to a civil date, from Howard Hinnant's `civil_from_days`.
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    let seconds = remainder / 10_000_000;
+    let fraction = remainder % 10_000_000;
+    let (hour, minute, second) = (seconds / 3_600, seconds / 60 % 60, seconds % 60);
+    if fraction == 0 {
+        format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z")
+    } else {
+        format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}.{fraction:07}Z")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iso8601() {
+        assert_eq!(format_iso8601(0), "1601-01-01T00:00:00Z");
+        assert_eq!(
+            format_iso8601(116_444_736_000_000_000),
+            "1970-01-01T00:00:00Z"
+        );
+        // 2024-02-29T12:34:56.5Z
+        assert_eq!(
+            format_iso8601(133_536_836_965_000_000),
+            "2024-02-29T12:34:56.5000000Z"
+        );
+    }
+
+    #[test]
+    fn truncate() {
+        let options = RenderOptions::for_logs();
+        assert_eq!(options.truncate_string("short".to_string()), "short");
+        let long = "x".repeat(200);
+        assert_eq!(options.truncate_string(long).len(), 128 + 3);
+    }
+}
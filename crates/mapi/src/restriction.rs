@@ -0,0 +1,468 @@
+//! Define [`Restriction`], [`RestrictionValue`], [`Relop`], and [`RestrictionBuffer`].
+
+use crate::{sys, OwnedPropValueData, PropTag, PropType, PropValue};
+use core::slice;
+use std::iter;
+use windows::Win32::Foundation::FILETIME;
+use windows_core::PWSTR;
+
+/// Relational operator for [`Restriction::Property`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Relop {
+    /// [`sys::RELOP_LT`]
+    Lt,
+
+    /// [`sys::RELOP_LE`]
+    Le,
+
+    /// [`sys::RELOP_GT`]
+    Gt,
+
+    /// [`sys::RELOP_GE`]
+    Ge,
+
+    /// [`sys::RELOP_EQ`]
+    Eq,
+
+    /// [`sys::RELOP_NE`]
+    Ne,
+}
+
+impl TryFrom<u32> for Relop {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            sys::RELOP_LT => Ok(Self::Lt),
+            sys::RELOP_LE => Ok(Self::Le),
+            sys::RELOP_GT => Ok(Self::Gt),
+            sys::RELOP_GE => Ok(Self::Ge),
+            sys::RELOP_EQ => Ok(Self::Eq),
+            sys::RELOP_NE => Ok(Self::Ne),
+            value => Err(value),
+        }
+    }
+}
+
+impl From<Relop> for u32 {
+    fn from(value: Relop) -> Self {
+        match value {
+            Relop::Lt => sys::RELOP_LT,
+            Relop::Le => sys::RELOP_LE,
+            Relop::Gt => sys::RELOP_GT,
+            Relop::Ge => sys::RELOP_GE,
+            Relop::Eq => sys::RELOP_EQ,
+            Relop::Ne => sys::RELOP_NE,
+        }
+    }
+}
+
+/// Owned value to compare against in a [`Restriction::Property`]. The `PROP_TYPE` of the tag in
+/// the restriction is replaced to match the value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RestrictionValue {
+    /// [`sys::PT_LONG`]
+    Long(i32),
+
+    /// [`sys::PT_BOOLEAN`]
+    Boolean(bool),
+
+    /// [`sys::PT_I8`]
+    LargeInteger(i64),
+
+    /// [`sys::PT_SYSTIME`]
+    FileTime(FILETIME),
+
+    /// [`sys::PT_UNICODE`]
+    Unicode(String),
+
+    /// [`sys::PT_BINARY`]
+    Binary(Vec<u8>),
+}
+
+impl RestrictionValue {
+    fn from_owned(value: OwnedPropValueData) -> Option<Self> {
+        match value {
+            OwnedPropValueData::Long(value) => Some(Self::Long(value)),
+            OwnedPropValueData::Boolean(value) => Some(Self::Boolean(value)),
+            OwnedPropValueData::LargeInteger(value) => Some(Self::LargeInteger(value)),
+            OwnedPropValueData::FileTime(value) => Some(Self::FileTime(value)),
+            OwnedPropValueData::Unicode(value) | OwnedPropValueData::AnsiString(value) => {
+                Some(Self::Unicode(value))
+            }
+            OwnedPropValueData::Binary(value) => Some(Self::Binary(value)),
+            _ => None,
+        }
+    }
+
+    fn prop_type(&self) -> u32 {
+        match self {
+            Self::Long(_) => sys::PT_LONG,
+            Self::Boolean(_) => sys::PT_BOOLEAN,
+            Self::LargeInteger(_) => sys::PT_I8,
+            Self::FileTime(_) => sys::PT_SYSTIME,
+            Self::Unicode(_) => sys::PT_UNICODE,
+            Self::Binary(_) => sys::PT_BINARY,
+        }
+    }
+}
+
+/// Owned tree of restrictions, which can be converted to a [`sys::SRestriction`] with
+/// [`Restriction::build`] to pass to [`sys::IMAPITable::Restrict`] or
+/// [`sys::IMAPITable::FindRow`].
+#[derive(Clone, Debug)]
+pub enum Restriction {
+    /// [`sys::RES_AND`]
+    And(Vec<Restriction>),
+
+    /// [`sys::RES_OR`]
+    Or(Vec<Restriction>),
+
+    /// [`sys::RES_NOT`]
+    Not(Box<Restriction>),
+
+    /// [`sys::RES_EXIST`]
+    Exists(PropTag),
+
+    /// [`sys::RES_PROPERTY`]
+    Property {
+        /// How the property value is compared with `value`.
+        relop: Relop,
+
+        /// Property to compare.
+        tag: PropTag,
+
+        /// Value to compare the property with.
+        value: RestrictionValue,
+    },
+
+    /// [`sys::RES_CONTENT`] on a [`sys::PT_UNICODE`] property, with a combination of `FL_*` flags
+    /// such as [`sys::FL_SUBSTRING`] | [`sys::FL_IGNORECASE`].
+    Content {
+        /// Property to search.
+        tag: PropTag,
+
+        /// String to search for.
+        value: String,
+
+        /// Combination of `FL_*` flags, passed as [`sys::SContentRestriction::ulFuzzyLevel`].
+        fuzzy_level: u32,
+    },
+
+    /// [`sys::RES_BITMASK`], which matches if any of the bits in `mask` are set (with
+    /// [`sys::BMR_NEZ`]), or if none of them are set (with [`sys::BMR_EQZ`]).
+    BitMask {
+        /// [`sys::PT_LONG`] property to test.
+        tag: PropTag,
+
+        /// Bits to test in the property value.
+        mask: u32,
+
+        /// Pass [`sys::BMR_NEZ`] if set, or [`sys::BMR_EQZ`] if not.
+        any_set: bool,
+    },
+}
+
+impl Restriction {
+    /// Shorthand for a [`Restriction::Property`] with [`Relop::Eq`].
+    pub fn eq(tag: PropTag, value: RestrictionValue) -> Self {
+        Self::Property {
+            relop: Relop::Eq,
+            tag,
+            value,
+        }
+    }
+
+    /// Shorthand for a case-insensitive substring [`Restriction::Content`].
+    pub fn contains(tag: PropTag, value: impl Into<String>) -> Self {
+        Self::Content {
+            tag,
+            value: value.into(),
+            fuzzy_level: sys::FL_SUBSTRING | sys::FL_IGNORECASE,
+        }
+    }
+
+    /// Build the [`sys::SRestriction`] tree. The [`RestrictionBuffer`] owns all of the nested
+    /// allocations, so it must outlive any use of [`RestrictionBuffer::as_mut_ptr`].
+    pub fn build(&self) -> RestrictionBuffer {
+        let mut buffer = RestrictionBuffer {
+            root: Default::default(),
+            restrictions: Vec::new(),
+            values: Vec::new(),
+            strings: Vec::new(),
+            binaries: Vec::new(),
+        };
+        let mut root: Box<sys::SRestriction> = Default::default();
+        buffer.fill(self, &mut root);
+        buffer.root = root;
+        buffer
+    }
+
+    /// Copy a [`sys::SRestriction`] tree, e.g. one returned by
+    /// [`sys::IMAPIContainer::GetSearchCriteria`], into an owned [`Restriction`]. Returns `None` if
+    /// the tree uses a restriction type or a value type which [`Restriction`] cannot represent,
+    /// e.g. [`sys::RES_SUBRESTRICTION`].
+    ///
+    /// # Safety
+    ///
+    /// Every pointer in the tree must be valid.
+    pub unsafe fn from_raw(restriction: &sys::SRestriction) -> Option<Self> {
+        let children = |count: u32, children: *const sys::SRestriction| {
+            if count == 0 || children.is_null() {
+                Some(Vec::new())
+            } else {
+                slice::from_raw_parts(children, count as usize)
+                    .iter()
+                    .map(|child| Self::from_raw(child))
+                    .collect()
+            }
+        };
+        let value = |prop: *const sys::SPropValue| {
+            prop.as_ref().and_then(|prop| {
+                RestrictionValue::from_owned(PropValue::from(prop).to_owned().value)
+            })
+        };
+        match restriction.rt {
+            sys::RES_AND => {
+                let res = &restriction.res.resAnd;
+                children(res.cRes, res.lpRes).map(Self::And)
+            }
+            sys::RES_OR => {
+                let res = &restriction.res.resOr;
+                children(res.cRes, res.lpRes).map(Self::Or)
+            }
+            sys::RES_NOT => {
+                let child = restriction.res.resNot.lpRes.as_ref()?;
+                Self::from_raw(child).map(|child| Self::Not(Box::new(child)))
+            }
+            sys::RES_EXIST => Some(Self::Exists(PropTag(restriction.res.resExist.ulPropTag))),
+            sys::RES_PROPERTY => {
+                let res = &restriction.res.resProperty;
+                Some(Self::Property {
+                    relop: Relop::try_from(res.relop).ok()?,
+                    tag: PropTag(res.ulPropTag),
+                    value: value(res.lpProp)?,
+                })
+            }
+            sys::RES_CONTENT => {
+                let res = &restriction.res.resContent;
+                let RestrictionValue::Unicode(value) = value(res.lpProp)? else {
+                    return None;
+                };
+                Some(Self::Content {
+                    tag: PropTag(res.ulPropTag),
+                    value,
+                    fuzzy_level: res.ulFuzzyLevel,
+                })
+            }
+            sys::RES_BITMASK => {
+                let res = &restriction.res.resBitMask;
+                Some(Self::BitMask {
+                    tag: PropTag(res.ulPropTag),
+                    mask: res.ulMask,
+                    any_set: res.relBMR == sys::BMR_NEZ,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Storage for a [`sys::SRestriction`] tree built by [`Restriction::build`]. Every nested
+/// allocation is boxed, so the pointers stay valid when the [`RestrictionBuffer`] is moved.
+pub struct RestrictionBuffer {
+    root: Box<sys::SRestriction>,
+    restrictions: Vec<Box<[sys::SRestriction]>>,
+    #[allow(clippy::vec_box)] // The boxes keep the addresses stable while the Vec grows.
+    values: Vec<Box<sys::SPropValue>>,
+    strings: Vec<Vec<u16>>,
+    binaries: Vec<Vec<u8>>,
+}
+
+impl RestrictionBuffer {
+    /// Get a pointer to the root [`sys::SRestriction`].
+    pub fn as_mut_ptr(&mut self) -> *mut sys::SRestriction {
+        self.root.as_mut()
+    }
+
+    fn fill(&mut self, restriction: &Restriction, target: &mut sys::SRestriction) {
+        match restriction {
+            Restriction::And(children) | Restriction::Or(children) => {
+                let mut nested: Box<[sys::SRestriction]> = iter::repeat_with(Default::default)
+                    .take(children.len())
+                    .collect();
+                for (child, target) in children.iter().zip(nested.iter_mut()) {
+                    self.fill(child, target);
+                }
+                let res = sys::SAndRestriction {
+                    cRes: children.len() as u32,
+                    lpRes: nested.as_mut_ptr(),
+                };
+                self.restrictions.push(nested);
+                if matches!(restriction, Restriction::And(_)) {
+                    target.rt = sys::RES_AND;
+                    target.res.resAnd = res;
+                } else {
+                    target.rt = sys::RES_OR;
+                    target.res.resOr = sys::SOrRestriction {
+                        cRes: res.cRes,
+                        lpRes: res.lpRes,
+                    };
+                }
+            }
+            Restriction::Not(child) => {
+                let mut nested: Box<[sys::SRestriction]> = Box::new([Default::default()]);
+                self.fill(child, &mut nested[0]);
+                target.rt = sys::RES_NOT;
+                target.res.resNot = sys::SNotRestriction {
+                    ulReserved: 0,
+                    lpRes: nested.as_mut_ptr(),
+                };
+                self.restrictions.push(nested);
+            }
+            Restriction::Exists(tag) => {
+                target.rt = sys::RES_EXIST;
+                target.res.resExist = sys::SExistRestriction {
+                    ulReserved1: 0,
+                    ulPropTag: u32::from(*tag),
+                    ulReserved2: 0,
+                };
+            }
+            Restriction::Property { relop, tag, value } => {
+                let tag = tag.change_prop_type(PropType::new(value.prop_type() as u16));
+                let prop = self.prop_value(u32::from(tag), value);
+                target.rt = sys::RES_PROPERTY;
+                target.res.resProperty = sys::SPropertyRestriction {
+                    relop: u32::from(*relop),
+                    ulPropTag: u32::from(tag),
+                    lpProp: prop,
+                };
+            }
+            Restriction::Content {
+                tag,
+                value,
+                fuzzy_level,
+            } => {
+                let tag = tag.change_prop_type(PropType::new(sys::PT_UNICODE as u16));
+                let prop =
+                    self.prop_value(u32::from(tag), &RestrictionValue::Unicode(value.clone()));
+                target.rt = sys::RES_CONTENT;
+                target.res.resContent = sys::SContentRestriction {
+                    ulFuzzyLevel: *fuzzy_level,
+                    ulPropTag: u32::from(tag),
+                    lpProp: prop,
+                };
+            }
+            Restriction::BitMask { tag, mask, any_set } => {
+                target.rt = sys::RES_BITMASK;
+                target.res.resBitMask = sys::SBitMaskRestriction {
+                    relBMR: if *any_set { sys::BMR_NEZ } else { sys::BMR_EQZ },
+                    ulPropTag: u32::from(*tag),
+                    ulMask: *mask,
+                };
+            }
+        }
+    }
+
+    fn prop_value(&mut self, tag: u32, value: &RestrictionValue) -> *mut sys::SPropValue {
+        let mut prop: Box<sys::SPropValue> = Box::new(sys::SPropValue {
+            ulPropTag: tag,
+            ..Default::default()
+        });
+        match value {
+            RestrictionValue::Long(value) => prop.Value.l = *value,
+            RestrictionValue::Boolean(value) => prop.Value.b = u16::from(*value),
+            RestrictionValue::LargeInteger(value) => prop.Value.li = *value,
+            RestrictionValue::FileTime(value) => prop.Value.ft = *value,
+            RestrictionValue::Unicode(value) => {
+                let mut value: Vec<u16> = value.encode_utf16().chain(iter::once(0)).collect();
+                prop.Value.lpszW = PWSTR(value.as_mut_ptr());
+                self.strings.push(value);
+            }
+            RestrictionValue::Binary(value) => {
+                let mut value = value.clone();
+                prop.Value.bin = sys::SBinary {
+                    cb: value.len() as u32,
+                    lpb: value.as_mut_ptr(),
+                };
+                self.binaries.push(value);
+            }
+        }
+        let result: *mut sys::SPropValue = prop.as_mut();
+        self.values.push(prop);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_tree() {
+        let restriction = Restriction::And(vec![
+            Restriction::Exists(PropTag(sys::PR_SUBJECT_W)),
+            Restriction::Not(Box::new(Restriction::contains(
+                PropTag(sys::PR_SUBJECT_W),
+                "draft",
+            ))),
+        ]);
+        let mut buffer = restriction.build();
+        unsafe {
+            let root = &*buffer.as_mut_ptr();
+            assert_eq!(root.rt, sys::RES_AND);
+            assert_eq!(root.res.resAnd.cRes, 2);
+            let children = std::slice::from_raw_parts(root.res.resAnd.lpRes, 2);
+            assert_eq!(children[0].rt, sys::RES_EXIST);
+            assert_eq!(children[1].rt, sys::RES_NOT);
+            let content = &*children[1].res.resNot.lpRes;
+            assert_eq!(content.rt, sys::RES_CONTENT);
+            let value = &*content.res.resContent.lpProp;
+            assert_eq!(value.Value.lpszW.to_string().unwrap(), "draft");
+        }
+    }
+
+    #[test]
+    fn from_raw() {
+        let restriction = Restriction::Or(vec![
+            Restriction::eq(
+                PropTag(sys::PR_MESSAGE_FLAGS),
+                RestrictionValue::Long(sys::MSGFLAG_READ as i32),
+            ),
+            Restriction::Not(Box::new(Restriction::contains(
+                PropTag(sys::PR_SUBJECT_W),
+                "draft",
+            ))),
+            Restriction::BitMask {
+                tag: PropTag(sys::PR_MESSAGE_FLAGS),
+                mask: sys::MSGFLAG_UNSENT,
+                any_set: true,
+            },
+        ]);
+        let mut buffer = restriction.build();
+        let copy = unsafe { Restriction::from_raw(&*buffer.as_mut_ptr()) }.unwrap();
+        let Restriction::Or(children) = copy else {
+            panic!("expected RES_OR");
+        };
+        assert_eq!(children.len(), 3);
+        assert!(matches!(
+            &children[0],
+            Restriction::Property {
+                relop: Relop::Eq,
+                value: RestrictionValue::Long(value),
+                ..
+            } if *value == sys::MSGFLAG_READ as i32
+        ));
+        assert!(matches!(
+            &children[1],
+            Restriction::Not(child) if matches!(
+                child.as_ref(),
+                Restriction::Content { value, .. } if value == "draft"
+            )
+        ));
+        assert!(matches!(
+            &children[2],
+            Restriction::BitMask { any_set: true, .. }
+        ));
+    }
+}
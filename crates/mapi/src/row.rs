@@ -1,6 +1,6 @@
-//! Define [`Row`].
+//! Define [`Row`] and [`RowRef`].
 
-use crate::{sys, PropValue};
+use crate::{sys, PropTag, PropValue};
 use core::{mem, slice};
 use std::ptr;
 
@@ -39,6 +39,11 @@ impl Row {
         }
     }
 
+    /// Get the [`sys::SPropValue`] pointer, which may be `null`.
+    pub(crate) fn as_ptr(&self) -> *const sys::SPropValue {
+        self.props
+    }
+
     /// Iterate over the [`sys::SPropValue`] column values in the [`Row`].
     pub fn iter(&self) -> impl Iterator<Item = PropValue> {
         if self.props.is_null() {
@@ -52,6 +57,13 @@ impl Row {
         }
         .into_iter()
     }
+
+    /// Find the column value for a requested `tag`, matching it with
+    /// [`PropTag::matches_response`], so a missing value which came back as [`sys::PT_ERROR`] is
+    /// still found.
+    pub fn find(&self, tag: PropTag) -> Option<PropValue> {
+        self.iter().find(|value| tag.matches_response(value.tag))
+    }
 }
 
 impl Drop for Row {
@@ -64,3 +76,48 @@ impl Drop for Row {
         }
     }
 }
+
+/// Borrowed view of a [`sys::SRow`] which is still owned by a [`crate::RowSet`], returned from
+/// [`crate::RowSet::get`]. Unlike [`Row`], it does not take ownership of the
+/// [`sys::SRow::lpProps`] pointer, so the rest of the [`crate::RowSet`] is left intact.
+#[derive(Clone, Copy)]
+pub struct RowRef<'a> {
+    row: &'a sys::SRow,
+}
+
+impl<'a> RowRef<'a> {
+    /// Test for a count of 0 properties or a null [`sys::SPropValue`] pointer.
+    pub fn is_empty(&self) -> bool {
+        self.row.cValues == 0 || self.row.lpProps.is_null()
+    }
+
+    /// Get the number of [`sys::SPropValue`] column values in the [`RowRef`].
+    pub fn len(&self) -> usize {
+        if self.row.lpProps.is_null() {
+            0
+        } else {
+            self.row.cValues as usize
+        }
+    }
+
+    /// Iterate over the [`sys::SPropValue`] column values in the [`RowRef`].
+    pub fn iter(&self) -> impl Iterator<Item = PropValue<'a>> {
+        let data: &'a [sys::SPropValue] = if self.row.lpProps.is_null() {
+            &[]
+        } else {
+            unsafe { slice::from_raw_parts(self.row.lpProps, self.row.cValues as usize) }
+        };
+        data.iter().map(PropValue::from)
+    }
+
+    /// Find the column value for a requested `tag`, like [`Row::find`].
+    pub fn find(&self, tag: PropTag) -> Option<PropValue<'a>> {
+        self.iter().find(|value| tag.matches_response(value.tag))
+    }
+}
+
+impl<'a> From<&'a sys::SRow> for RowRef<'a> {
+    fn from(row: &'a sys::SRow) -> Self {
+        Self { row }
+    }
+}
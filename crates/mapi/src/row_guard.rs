@@ -0,0 +1,183 @@
+//! Define [`Row::check_memory`] and [`is_readable`], which back the guarded mode of
+//! [`crate::TableQuery::guarded`].
+
+use crate::{sys, PropTag, Row};
+use core::{
+    ffi::c_void,
+    mem::{align_of, size_of},
+    slice,
+};
+use windows::Win32::System::Memory::{
+    VirtualQuery, MEMORY_BASIC_INFORMATION, MEM_COMMIT, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE,
+    PAGE_EXECUTE_WRITECOPY, PAGE_GUARD, PAGE_NOACCESS, PAGE_READONLY, PAGE_READWRITE,
+    PAGE_WRITECOPY,
+};
+use windows_core::*;
+
+/// Longest string, in characters, which [`Row::check_memory`] will scan for a terminator before
+/// treating the value as corrupt.
+pub const MAX_GUARDED_STRING_LEN: usize = 64 * 1024 * 1024;
+
+const READABLE_PAGES: u32 = PAGE_READONLY.0
+    | PAGE_READWRITE.0
+    | PAGE_WRITECOPY.0
+    | PAGE_EXECUTE_READ.0
+    | PAGE_EXECUTE_READWRITE.0
+    | PAGE_EXECUTE_WRITECOPY.0;
+
+/// Get the end of the committed, readable region which contains `address`, or `None` if the page
+/// at `address` cannot be read.
+fn readable_end(address: usize) -> Option<usize> {
+    let mut info = MEMORY_BASIC_INFORMATION::default();
+    let size = unsafe {
+        VirtualQuery(
+            Some(address as *const c_void),
+            &mut info,
+            size_of::<MEMORY_BASIC_INFORMATION>(),
+        )
+    };
+    let protect = info.Protect.0;
+    if size == 0
+        || info.State != MEM_COMMIT
+        || protect & (PAGE_NOACCESS.0 | PAGE_GUARD.0) != 0
+        || protect & READABLE_PAGES == 0
+    {
+        return None;
+    }
+    Some(info.BaseAddress as usize + info.RegionSize)
+}
+
+/// Test if every page in the `len` bytes starting at `ptr` is committed and readable, with
+/// [`VirtualQuery`] rather than `IsBadReadPtr`, so it never touches the memory itself.
+///
+/// This is only a snapshot: it cannot protect against memory which another thread frees after
+/// the check.
+pub fn is_readable(ptr: *const c_void, len: usize) -> bool {
+    if len == 0 {
+        return true;
+    }
+    if ptr.is_null() {
+        return false;
+    }
+    let mut address = ptr as usize;
+    let Some(end) = address.checked_add(len) else {
+        return false;
+    };
+    while address < end {
+        match readable_end(address) {
+            Some(region_end) => address = region_end,
+            None => return false,
+        }
+    }
+    true
+}
+
+fn is_readable_array<T>(ptr: *const T, count: u32) -> bool {
+    count == 0
+        || (ptr as usize % align_of::<T>() == 0
+            && is_readable(ptr as *const c_void, count as usize * size_of::<T>()))
+}
+
+/// Scan for the `0` terminator one readable region at a time, so the scan never reads past the
+/// end of a region which has not been checked.
+unsafe fn is_readable_string<T>(ptr: *const T) -> bool
+where
+    T: Copy + Default + PartialEq,
+{
+    if ptr.is_null() || ptr as usize % align_of::<T>() != 0 {
+        return false;
+    }
+    let mut address = ptr as usize;
+    let limit = address.saturating_add(MAX_GUARDED_STRING_LEN * size_of::<T>());
+    while address < limit {
+        let Some(region_end) = readable_end(address) else {
+            return false;
+        };
+        let region_end = region_end.min(limit);
+        while address + size_of::<T>() <= region_end {
+            if *(address as *const T) == T::default() {
+                return true;
+            }
+            address += size_of::<T>();
+        }
+    }
+    false
+}
+
+unsafe fn is_valid_value(value: &sys::SPropValue) -> bool {
+    let tag = PropTag(value.ulPropTag);
+    let prop_type = if tag.is_mv_instance() {
+        tag.base_type()
+    } else {
+        tag.prop_type()
+    };
+    let value = &value.Value;
+    match u32::from(prop_type) {
+        sys::PT_STRING8 => is_readable_string(value.lpszA.0 as *const u8),
+        sys::PT_UNICODE => is_readable_string(value.lpszW.0 as *const u16),
+        sys::PT_BINARY => is_readable_array(value.bin.lpb, value.bin.cb),
+        sys::PT_CLSID => is_readable_array(value.lpguid, 1),
+        sys::PT_MV_SHORT => is_readable_array(value.MVi.lpi, value.MVi.cValues),
+        sys::PT_MV_LONG => is_readable_array(value.MVl.lpl, value.MVl.cValues),
+        sys::PT_MV_FLOAT => is_readable_array(value.MVflt.lpflt, value.MVflt.cValues),
+        sys::PT_MV_DOUBLE => is_readable_array(value.MVdbl.lpdbl, value.MVdbl.cValues),
+        sys::PT_MV_CURRENCY => is_readable_array(value.MVcur.lpcur, value.MVcur.cValues),
+        sys::PT_MV_APPTIME => is_readable_array(value.MVat.lpat, value.MVat.cValues),
+        sys::PT_MV_SYSTIME => is_readable_array(value.MVft.lpft, value.MVft.cValues),
+        sys::PT_MV_CLSID => is_readable_array(value.MVguid.lpguid, value.MVguid.cValues),
+        sys::PT_MV_LONGLONG => is_readable_array(value.MVli.lpli, value.MVli.cValues),
+        sys::PT_MV_BINARY => {
+            let values = value.MVbin;
+            is_readable_array(values.lpbin, values.cValues)
+                && (values.cValues == 0
+                    || slice::from_raw_parts(values.lpbin, values.cValues as usize)
+                        .iter()
+                        .all(|value| is_readable_array(value.lpb, value.cb)))
+        }
+        sys::PT_MV_STRING8 => {
+            let values = value.MVszA;
+            is_readable_array(values.lppszA, values.cValues)
+                && (values.cValues == 0
+                    || slice::from_raw_parts(values.lppszA, values.cValues as usize)
+                        .iter()
+                        .all(|value| is_readable_string(value.0 as *const u8)))
+        }
+        sys::PT_MV_UNICODE => {
+            let values = value.MVszW;
+            is_readable_array(values.lppszW, values.cValues)
+                && (values.cValues == 0
+                    || slice::from_raw_parts(values.lppszW, values.cValues as usize)
+                        .iter()
+                        .all(|value| is_readable_string(value.0 as *const u16)))
+        }
+        _ => true,
+    }
+}
+
+impl Row {
+    /// Check that the [`sys::SPropValue`] array and every string, binary, and multi-valued
+    /// buffer it points to is readable, before anything forms a slice over them with
+    /// [`Row::iter`]. Fails with [`sys::MAPI_E_CORRUPT_DATA`] otherwise.
+    ///
+    /// Rust cannot catch the structured exception a misbehaving provider raises when it hands
+    /// back a dangling pointer ([`std::panic::catch_unwind`] only handles Rust panics), so this
+    /// checks the pages with [`is_readable`] instead of touching them. It does not check that the
+    /// values make sense, only that reading them will not fault.
+    pub fn check_memory(&self) -> Result<()> {
+        let count = self.len();
+        if count == 0 {
+            return Ok(());
+        }
+        let props = self.as_ptr();
+        unsafe {
+            if !is_readable_array(props, count as u32)
+                || !slice::from_raw_parts(props, count)
+                    .iter()
+                    .all(|value| is_valid_value(value))
+            {
+                return Err(Error::from(sys::MAPI_E_CORRUPT_DATA));
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 //! Define [`RowSet`].
 
-use crate::{sys, Row};
-use core::{ptr, slice};
+use crate::{sys, Row, RowRef};
+use core::{ops::Index, ptr, slice};
 
 /// Container for a [`sys::SRowSet`] structure, such as the rows returned from
 /// [`sys::IMAPITable::QueryRows`].
@@ -40,6 +40,32 @@ impl RowSet {
                 .unwrap_or_default()
         }
     }
+
+    /// Borrow the [`sys::SRow`] structures contained in the [`sys::SRowSet`].
+    pub fn rows(&self) -> &[sys::SRow] {
+        unsafe {
+            self.rows
+                .as_ref()
+                .map(|rows| slice::from_raw_parts(rows.aRow.as_ptr(), rows.cRows as usize))
+                .unwrap_or_default()
+        }
+    }
+
+    /// Borrow a single row by `index` without taking ownership of its
+    /// [`sys::SPropValue`] pointer, e.g. when a query is known to return exactly one row.
+    pub fn get(&self, index: usize) -> Option<RowRef> {
+        self.rows().get(index).map(RowRef::from)
+    }
+}
+
+impl Index<usize> for RowSet {
+    type Output = sys::SRow;
+
+    /// Borrow the [`sys::SRow`] at `index`. Panics if `index` is out of bounds, like
+    /// [`slice`] indexing. Use [`RowSet::get`] for a checked [`RowRef`].
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.rows()[index]
+    }
 }
 
 impl Default for RowSet {
@@ -0,0 +1,281 @@
+//! Define [`SearchFolder`], [`SearchFlags`], [`SearchState`], and [`SearchCriteria`].
+
+use crate::{
+    mapi_prop::get_one_prop, sys, EntryList, Folder, MAPIOutParam, PropValueData, Restriction,
+    Store, StoreCapabilities,
+};
+use core::slice;
+use windows_core::*;
+
+/// `CONTENT_INDEXED_SEARCH` is missing from the generated bindings.
+pub const CONTENT_INDEXED_SEARCH: u32 = 0x0001_0000;
+
+/// `NON_CONTENT_INDEXED_SEARCH` is missing from the generated bindings.
+pub const NON_CONTENT_INDEXED_SEARCH: u32 = 0x0002_0000;
+
+/// `STATIC_SEARCH` is missing from the generated bindings.
+pub const STATIC_SEARCH: u32 = 0x0004_0000;
+
+/// Set of flags that can be passed to [`SearchFolder::set_criteria`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchFlags {
+    /// Pass [`sys::STOP_SEARCH`].
+    pub stop: bool,
+
+    /// Pass [`sys::RESTART_SEARCH`], which starts the search, or starts it over with the new
+    /// criteria if it is already running.
+    pub restart: bool,
+
+    /// Pass [`sys::RECURSIVE_SEARCH`], which includes the subfolders of the search scope.
+    pub recursive: bool,
+
+    /// Pass [`sys::SHALLOW_SEARCH`], which only searches the folders in the search scope.
+    pub shallow: bool,
+
+    /// Pass [`sys::FOREGROUND_SEARCH`].
+    pub foreground: bool,
+
+    /// Pass [`sys::BACKGROUND_SEARCH`].
+    pub background: bool,
+
+    /// Pass [`CONTENT_INDEXED_SEARCH`], which asks the store to use its content indexer.
+    pub content_indexed: bool,
+
+    /// Pass [`NON_CONTENT_INDEXED_SEARCH`], which asks the store not to use its content indexer.
+    pub non_content_indexed: bool,
+
+    /// Pass [`STATIC_SEARCH`], which populates the folder once instead of keeping it up to date.
+    pub static_search: bool,
+}
+
+impl SearchFlags {
+    /// Start the search over the search scope and all of its subfolders, and keep it up to date.
+    pub fn recursive() -> Self {
+        Self {
+            restart: true,
+            recursive: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<SearchFlags> for u32 {
+    fn from(value: SearchFlags) -> Self {
+        let stop = if value.stop { sys::STOP_SEARCH } else { 0 };
+        let restart = if value.restart {
+            sys::RESTART_SEARCH
+        } else {
+            0
+        };
+        let recursive = if value.recursive {
+            sys::RECURSIVE_SEARCH
+        } else {
+            0
+        };
+        let shallow = if value.shallow {
+            sys::SHALLOW_SEARCH
+        } else {
+            0
+        };
+        let foreground = if value.foreground {
+            sys::FOREGROUND_SEARCH
+        } else {
+            0
+        };
+        let background = if value.background {
+            sys::BACKGROUND_SEARCH
+        } else {
+            0
+        };
+        let content_indexed = if value.content_indexed {
+            CONTENT_INDEXED_SEARCH
+        } else {
+            0
+        };
+        let non_content_indexed = if value.non_content_indexed {
+            NON_CONTENT_INDEXED_SEARCH
+        } else {
+            0
+        };
+        let static_search = if value.static_search {
+            STATIC_SEARCH
+        } else {
+            0
+        };
+
+        stop | restart
+            | recursive
+            | shallow
+            | foreground
+            | background
+            | content_indexed
+            | non_content_indexed
+            | static_search
+    }
+}
+
+/// State of a search folder, reported by [`SearchFolder::get_criteria`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchState {
+    /// Set if [`sys::SEARCH_RUNNING`] is present, i.e. the search is active.
+    pub running: bool,
+
+    /// Set if [`sys::SEARCH_REBUILD`] is present, i.e. the folder is still being populated.
+    pub rebuild: bool,
+
+    /// Set if [`sys::SEARCH_RECURSIVE`] is present.
+    pub recursive: bool,
+
+    /// Set if [`sys::SEARCH_FOREGROUND`] is present.
+    pub foreground: bool,
+}
+
+impl From<u32> for SearchState {
+    fn from(value: u32) -> Self {
+        Self {
+            running: value & sys::SEARCH_RUNNING != 0,
+            rebuild: value & sys::SEARCH_REBUILD != 0,
+            recursive: value & sys::SEARCH_RECURSIVE != 0,
+            foreground: value & sys::SEARCH_FOREGROUND != 0,
+        }
+    }
+}
+
+/// Search criteria read by [`SearchFolder::get_criteria`].
+#[derive(Clone)]
+pub struct SearchCriteria {
+    /// The restriction, or `None` if it uses a restriction type which [`Restriction`] cannot
+    /// represent.
+    pub restriction: Option<Restriction>,
+
+    /// Entry IDs of the folders in the search scope.
+    pub folders: EntryList,
+
+    /// Current state of the search.
+    pub state: SearchState,
+}
+
+/// Wrapper for a [`sys::IMAPIFolder`] which was created with [`sys::FOLDER_SEARCH`], by
+/// [`Folder::create_search_folder`].
+///
+/// A search folder has no messages of its own. Its contents table lists the messages in the
+/// search scope which match the restriction, and the store keeps it up to date (unless it was
+/// started with [`SearchFlags::static_search`]), so it works as a persistent query.
+#[derive(Clone)]
+pub struct SearchFolder {
+    folder: Folder,
+}
+
+impl SearchFolder {
+    /// Borrow the [`Folder`], e.g. to read its [`Folder::contents_table`].
+    pub fn folder(&self) -> &Folder {
+        &self.folder
+    }
+
+    /// Set the `restriction` and the `folders` to search with
+    /// [`sys::IMAPIContainer::SetSearchCriteria`]. Pass [`SearchFlags::restart`] to start the
+    /// search.
+    pub fn set_criteria(
+        &self,
+        restriction: &Restriction,
+        folders: &EntryList,
+        flags: SearchFlags,
+    ) -> Result<()> {
+        let mut restriction = restriction.build();
+        let mut folders = folders
+            .build()
+            .map_err(|_| Error::from(sys::MAPI_E_NOT_ENOUGH_MEMORY))?;
+        unsafe {
+            self.folder.folder().SetSearchCriteria(
+                restriction.as_mut_ptr(),
+                folders.as_mut_ptr(),
+                u32::from(flags),
+            )
+        }
+    }
+
+    /// Read the current criteria and state with [`sys::IMAPIContainer::GetSearchCriteria`]. Fails
+    /// with [`sys::MAPI_E_NOT_INITIALIZED`] if [`SearchFolder::set_criteria`] was never called.
+    pub fn get_criteria(&self) -> Result<SearchCriteria> {
+        let mut restriction: MAPIOutParam<sys::SRestriction> = Default::default();
+        let mut folders: MAPIOutParam<sys::SBinaryArray> = Default::default();
+        let mut state = 0;
+        unsafe {
+            self.folder.folder().GetSearchCriteria(
+                sys::MAPI_UNICODE,
+                restriction.as_mut_ptr(),
+                folders.as_mut_ptr(),
+                &mut state,
+            )?;
+            let restriction = restriction
+                .as_mut()
+                .and_then(|restriction| Restriction::from_raw(restriction));
+            let folders = match folders.as_mut() {
+                Some(folders) if folders.cValues > 0 && !folders.lpbin.is_null() => {
+                    slice::from_raw_parts(folders.lpbin, folders.cValues as usize)
+                        .iter()
+                        .map(|entry_id| {
+                            if entry_id.cb == 0 || entry_id.lpb.is_null() {
+                                &[][..]
+                            } else {
+                                slice::from_raw_parts(entry_id.lpb, entry_id.cb as usize)
+                            }
+                        })
+                        .collect()
+                }
+                _ => EntryList::new(),
+            };
+            Ok(SearchCriteria {
+                restriction,
+                folders,
+                state: SearchState::from(state),
+            })
+        }
+    }
+}
+
+impl From<SearchFolder> for Folder {
+    fn from(value: SearchFolder) -> Self {
+        value.folder
+    }
+}
+
+impl Folder {
+    /// Create a search folder under this folder with [`sys::IMAPIFolder::CreateFolder`] and
+    /// [`sys::FOLDER_SEARCH`]. In most stores, search folders can only be created in the
+    /// `Finder` folder, which is identified by [`sys::PR_FINDER_ENTRYID`] on the store. The
+    /// folder stays empty until [`SearchFolder::set_criteria`] is called. Use
+    /// [`Store::create_search_folder`] to create it there, after checking that the store supports
+    /// searches.
+    pub fn create_search_folder(&self, name: &str) -> Result<SearchFolder> {
+        let folder = self.create_folder(sys::FOLDER_SEARCH, name)?;
+        Ok(SearchFolder {
+            folder: Folder::from(folder),
+        })
+    }
+
+    /// Treat this folder as a [`SearchFolder`], e.g. one which was opened by entry ID. Calls to
+    /// [`SearchFolder::set_criteria`] and [`SearchFolder::get_criteria`] fail with
+    /// [`sys::MAPI_E_NO_SUPPORT`] if it is not a search folder.
+    pub fn into_search_folder(self) -> SearchFolder {
+        SearchFolder { folder: self }
+    }
+}
+
+impl Store {
+    /// Create a search folder in the `Finder` folder of this store, which is identified by
+    /// [`sys::PR_FINDER_ENTRYID`]. See [`Folder::create_search_folder`].
+    ///
+    /// Fails with [`sys::MAPI_E_NO_SUPPORT`] if the [`Store::capabilities`] do not include
+    /// [`StoreCapabilities::search`], or if the store has no `Finder` folder.
+    pub fn create_search_folder(&self, name: &str) -> Result<SearchFolder> {
+        StoreCapabilities::check(self.store(), |capabilities| capabilities.search)?;
+        let finder = get_one_prop(self.store(), sys::PR_FINDER_ENTRYID, |value| match value {
+            PropValueData::Binary(value) => Some(value.to_vec()),
+            _ => None,
+        })?
+        .filter(|entry_id| !entry_id.is_empty())
+        .ok_or_else(|| Error::from(sys::MAPI_E_NO_SUPPORT))?;
+        self.open_folder(&finder)?.create_search_folder(name)
+    }
+}
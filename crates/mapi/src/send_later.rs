@@ -0,0 +1,133 @@
+//! Define [`deferred_messages`], [`cancel_deferred`], and [`DeferredMessage`].
+
+use crate::{
+    from_file_time,
+    mapi_prop::{delete_props, open_store_entry, set_props, SetPropValue},
+    query_all_rows, sys, to_file_time, MapiProp, Message, OpenFlags, PropTag, PropValue,
+    PropValueData,
+};
+use std::time::SystemTime;
+use windows_core::*;
+
+impl Message {
+    /// Submit this message, but ask the transport to hold it until `at`.
+    ///
+    /// This sets [`sys::PR_DEFERRED_DELIVERY_TIME`] (which is what Outlook sets for "Do not
+    /// deliver before") and [`sys::PR_DEFERRED_SEND_TIME`], and deletes
+    /// [`sys::PR_DEFERRED_SEND_NUMBER`] and [`sys::PR_DEFERRED_SEND_UNITS`], which would otherwise
+    /// take precedence over the time. Then it calls [`sys::IMessage::SubmitMessage`], which is why
+    /// this consumes the [`Message`].
+    pub fn send_later(self, at: SystemTime) -> Result<()> {
+        let message = self.message();
+        let at = to_file_time(at);
+        set_props(
+            message,
+            &[
+                (sys::PR_DEFERRED_DELIVERY_TIME, SetPropValue::FileTime(at)),
+                (sys::PR_DEFERRED_SEND_TIME, SetPropValue::FileTime(at)),
+            ],
+        )?;
+        delete_props(
+            message,
+            &[sys::PR_DEFERRED_SEND_NUMBER, sys::PR_DEFERRED_SEND_UNITS],
+        )?;
+        unsafe { message.SubmitMessage(0) }
+    }
+}
+
+/// Typed row for a deferred message in the Outbox.
+#[derive(Clone, Debug)]
+pub struct DeferredMessage {
+    /// [`sys::PR_ENTRYID`] of the message, which can be passed to [`cancel_deferred`].
+    pub entry_id: Vec<u8>,
+
+    /// [`sys::PR_SUBJECT_W`] of the message.
+    pub subject: Option<String>,
+
+    /// [`sys::PR_DEFERRED_SEND_TIME`] of the message, or `None` if it is outside the range of
+    /// [`SystemTime`] (see [`from_file_time`]).
+    pub send_time: Option<SystemTime>,
+
+    /// Set if [`sys::MSGFLAG_SUBMIT`] is present in [`sys::PR_MESSAGE_FLAGS`], i.e. the message is
+    /// still queued for the transport.
+    pub submitted: bool,
+}
+
+/// Open the Outbox of a store with [`sys::PR_IPM_OUTBOX_ENTRYID`].
+pub fn open_outbox(store: &sys::IMsgStore) -> Result<sys::IMAPIFolder> {
+    let entry_id = store.get_binary(PropTag(sys::PR_IPM_OUTBOX_ENTRYID))?;
+    open_store_entry(store, &entry_id, OpenFlags::default())
+}
+
+/// List the messages in the Outbox which have a [`sys::PR_DEFERRED_SEND_TIME`].
+pub fn deferred_messages(store: &sys::IMsgStore) -> Result<Vec<DeferredMessage>> {
+    let outbox = open_outbox(store)?;
+    let table = unsafe { outbox.GetContentsTable(sys::MAPI_UNICODE)? };
+    let rows = query_all_rows(
+        &table,
+        &[
+            PropTag(sys::PR_ENTRYID),
+            PropTag(sys::PR_SUBJECT_W),
+            PropTag(sys::PR_DEFERRED_SEND_TIME),
+            PropTag(sys::PR_MESSAGE_FLAGS),
+        ],
+        None,
+        None,
+        0,
+    )?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let mut entry_id = None;
+            let mut subject = None;
+            let mut send_time = None;
+            let mut submitted = false;
+            for PropValue { tag, value } in row.iter() {
+                match (u32::from(tag), value) {
+                    (sys::PR_ENTRYID, PropValueData::Binary(value)) => {
+                        entry_id = Some(value.to_vec())
+                    }
+                    (sys::PR_SUBJECT_W, PropValueData::Unicode(value)) => {
+                        subject = unsafe { value.to_string() }.ok()
+                    }
+                    (sys::PR_DEFERRED_SEND_TIME, PropValueData::FileTime(value)) => {
+                        send_time = Some(from_file_time(value))
+                    }
+                    (sys::PR_MESSAGE_FLAGS, PropValueData::Long(value)) => {
+                        submitted = value as u32 & sys::MSGFLAG_SUBMIT != 0
+                    }
+                    _ => {}
+                }
+            }
+            Some(DeferredMessage {
+                entry_id: entry_id?,
+                subject,
+                send_time: send_time?,
+                submitted,
+            })
+        })
+        .collect())
+}
+
+/// Cancel a deferred message with [`sys::IMsgStore::AbortSubmit`], and delete the deferred
+/// delivery properties so it can be edited and sent again. The message stays in the Outbox.
+///
+/// A message which is no longer queued (e.g. because the transport already sent it) fails with
+/// [`sys::MAPI_E_NOT_IN_QUEUE`].
+pub fn cancel_deferred(store: &sys::IMsgStore, entry_id: &[u8]) -> Result<()> {
+    unsafe {
+        store.AbortSubmit(entry_id.len() as u32, entry_id.as_ptr() as *mut _, 0)?;
+    }
+    let message: sys::IMessage = open_store_entry(store, entry_id, OpenFlags::modify())?;
+    delete_props(
+        &message,
+        &[
+            sys::PR_DEFERRED_DELIVERY_TIME,
+            sys::PR_DEFERRED_SEND_TIME,
+            sys::PR_DEFERRED_SEND_NUMBER,
+            sys::PR_DEFERRED_SEND_UNITS,
+        ],
+    )?;
+    unsafe { message.SaveChanges(0) }
+}
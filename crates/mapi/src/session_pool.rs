@@ -0,0 +1,157 @@
+//! Define [`SessionPool`] and [`PooledSession`].
+
+use crate::{sys, Initialize, InitializeFlags, Logon, OpenStoreFlags, Store};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+use windows_core::*;
+
+type Job = Box<dyn FnOnce(&PooledSession) + Send>;
+
+/// Per-thread state which [`SessionPool`] passes to each job: the [`Logon`] for that worker
+/// thread, and a cache of the stores it has opened so far.
+pub struct PooledSession {
+    // Release the cached stores before the session.
+    stores: RefCell<HashMap<Vec<u8>, sys::IMsgStore>>,
+    logon: Logon,
+}
+
+impl PooledSession {
+    /// Borrow the [`Logon`] for this worker thread.
+    pub fn logon(&self) -> &Logon {
+        &self.logon
+    }
+
+    /// Open a store with [`Logon::open_store`], or reuse it if an earlier job on this worker
+    /// thread already opened it.
+    pub fn store(&self, entry_id: &[u8]) -> Result<Store> {
+        if let Some(store) = self.stores.borrow().get(entry_id) {
+            return Ok(Store::from(store.clone()));
+        }
+        let store = self.logon.open_store(
+            entry_id,
+            OpenStoreFlags {
+                best_access: true,
+                deferred_errors: true,
+                no_dialog: true,
+                no_mail: true,
+                ..Default::default()
+            },
+        )?;
+        self.stores
+            .borrow_mut()
+            .insert(entry_id.to_vec(), store.store().clone());
+        Ok(store)
+    }
+}
+
+/// Fixed set of worker threads, each with its own [`sys::MAPIInitialize`] and [`Logon`], which
+/// run jobs submitted from any thread.
+///
+/// A [`Logon`] cannot be sent between threads, so the pool takes a `logon` function which runs
+/// once on each worker thread. Pass [`crate::LogonFlags::allow_others`] on the first logon and
+/// [`crate::LogonFlags::use_default`] (or the same profile name) in `logon` to share the
+/// underlying MAPI session instead of creating a new one per thread.
+///
+/// Dropping the pool finishes the jobs which were already submitted and then joins the threads.
+pub struct SessionPool {
+    sender: Option<mpsc::Sender<Job>>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl SessionPool {
+    /// Start `workers` threads (at least 1), and call `logon` on each of them. Fails with the
+    /// first error from [`Initialize::new`] or `logon`, after stopping any threads which did start.
+    pub fn new<F>(workers: usize, logon: F) -> Result<Self>
+    where
+        F: Fn(Arc<Initialize>) -> Result<Logon> + Send + Sync + 'static,
+    {
+        let logon = Arc::new(logon);
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let (ready_sender, ready_receiver) = mpsc::channel::<Result<()>>();
+        let mut pool = Self {
+            sender: Some(sender),
+            workers: Vec::new(),
+        };
+
+        for _ in 0..workers.max(1) {
+            let logon = logon.clone();
+            let receiver = receiver.clone();
+            let ready = ready_sender.clone();
+            pool.workers.push(thread::spawn(move || {
+                let session = match Initialize::new(InitializeFlags::default())
+                    .and_then(|initialized| logon(initialized))
+                {
+                    Ok(logon) => {
+                        let _ = ready.send(Ok(()));
+                        PooledSession {
+                            stores: Default::default(),
+                            logon,
+                        }
+                    }
+                    Err(err) => {
+                        let _ = ready.send(Err(err));
+                        return;
+                    }
+                };
+                drop(ready);
+
+                loop {
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => break,
+                    };
+                    match job {
+                        Ok(job) => {
+                            // Keep the worker alive if the job panics, so one bad job does not
+                            // shrink the pool.
+                            let _ = panic::catch_unwind(AssertUnwindSafe(|| job(&session)));
+                        }
+                        Err(_) => break,
+                    }
+                }
+            }));
+        }
+        drop(ready_sender);
+
+        for ready in ready_receiver.iter() {
+            ready?;
+        }
+        Ok(pool)
+    }
+
+    /// Number of worker threads.
+    pub fn workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Queue a job to run on the next idle worker thread. A job which panics is abandoned, but the
+    /// worker thread keeps running the jobs after it.
+    ///
+    /// Fails with [`sys::MAPI_E_END_OF_SESSION`] if every worker thread has exited, in which case
+    /// the job would never run.
+    pub fn submit<F>(&self, job: F) -> Result<()>
+    where
+        F: FnOnce(&PooledSession) + Send + 'static,
+    {
+        self.sender
+            .as_ref()
+            .ok_or_else(|| Error::from(sys::MAPI_E_END_OF_SESSION))?
+            .send(Box::new(job))
+            .map_err(|_| Error::from(sys::MAPI_E_END_OF_SESSION))
+    }
+}
+
+impl Drop for SessionPool {
+    fn drop(&mut self) {
+        self.sender = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
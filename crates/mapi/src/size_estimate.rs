@@ -0,0 +1,143 @@
+//! Define [`MessageSizeEstimate`] and [`FolderSizeEstimate`].
+
+use crate::{sys, Folder, Message, PropTag, PropValue, PropValueData, Row, TableQuery};
+use windows_core::*;
+
+const SIZE_COLUMNS: [u32; 2] = [sys::PR_MESSAGE_SIZE_EXTENDED, sys::PR_MESSAGE_SIZE];
+
+/// Estimated size of a single message, returned by [`Message::estimated_size`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MessageSizeEstimate {
+    /// [`sys::PR_MESSAGE_SIZE_EXTENDED`], or [`sys::PR_MESSAGE_SIZE`] if the store does not
+    /// support the 64-bit property.
+    pub message_size: Option<u64>,
+
+    /// Sum of [`sys::PR_ATTACH_SIZE`] from the [`Message::attachment_table`].
+    pub attachment_size: u64,
+
+    /// Number of rows in the [`Message::attachment_table`].
+    pub attachment_count: usize,
+}
+
+impl MessageSizeEstimate {
+    /// Number of bytes to expect when copying or exporting the message.
+    ///
+    /// Most stores include the attachments in [`sys::PR_MESSAGE_SIZE`], but some only count the
+    /// message body and properties, so this is the larger of the two sizes rather than the sum.
+    pub fn total(&self) -> u64 {
+        self.message_size
+            .unwrap_or_default()
+            .max(self.attachment_size)
+    }
+}
+
+/// Estimated size of the messages in a folder, returned by [`Folder::estimate_export_size`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FolderSizeEstimate {
+    /// Number of messages in the contents table.
+    pub message_count: usize,
+
+    /// Sum of the message sizes in the contents table.
+    pub total_size: u64,
+
+    /// Number of messages which did not report a size, and which are not included in
+    /// [`FolderSizeEstimate::total_size`].
+    pub unknown_size_count: usize,
+}
+
+impl FolderSizeEstimate {
+    /// Add the size from a row with [`sys::PR_MESSAGE_SIZE_EXTENDED`] and
+    /// [`sys::PR_MESSAGE_SIZE`] columns.
+    fn add_row(&mut self, row: &Row) {
+        self.message_count += 1;
+        match message_size(row) {
+            Some(size) => self.total_size += size,
+            None => self.unknown_size_count += 1,
+        }
+    }
+}
+
+/// Read [`sys::PR_MESSAGE_SIZE_EXTENDED`], and fall back to [`sys::PR_MESSAGE_SIZE`].
+fn message_size(row: &Row) -> Option<u64> {
+    let mut extended = None;
+    let mut size = None;
+    for PropValue { tag, value } in row.iter() {
+        match (u32::from(tag), value) {
+            (sys::PR_MESSAGE_SIZE_EXTENDED, PropValueData::LargeInteger(value)) => {
+                extended = u64::try_from(value).ok()
+            }
+            (sys::PR_MESSAGE_SIZE, PropValueData::Long(value)) => size = Some(value as u32 as u64),
+            _ => {}
+        }
+    }
+    extended.or(size)
+}
+
+impl Message {
+    /// Estimate the size of this message before copying or exporting it, from
+    /// [`sys::PR_MESSAGE_SIZE_EXTENDED`] and the [`sys::PR_ATTACH_SIZE`] of each row in the
+    /// [`Message::attachment_table`]. None of the attachments are opened.
+    pub fn estimated_size(&self) -> Result<MessageSizeEstimate> {
+        let columns = SIZE_COLUMNS.map(PropTag);
+        let message_size = message_size(&self.get_props(&columns)?);
+        let mut estimate = MessageSizeEstimate {
+            message_size,
+            ..Default::default()
+        };
+        let rows = TableQuery::new(self.attachment_table()?)
+            .columns(&[PropTag(sys::PR_ATTACH_SIZE)])
+            .execute()?;
+        for row in rows {
+            let row = row?;
+            estimate.attachment_count += 1;
+            for PropValue { tag, value } in row.iter() {
+                if let (sys::PR_ATTACH_SIZE, PropValueData::Long(value)) = (u32::from(tag), value) {
+                    estimate.attachment_size += value as u32 as u64;
+                }
+            }
+        }
+        Ok(estimate)
+    }
+}
+
+impl Folder {
+    /// Estimate the size of exporting every message in this folder, by summing
+    /// [`sys::PR_MESSAGE_SIZE_EXTENDED`] over the contents table. The messages are never opened,
+    /// so this is cheap enough to run before an export to show accurate progress totals.
+    ///
+    /// Subfolders are not included; use [`crate::FolderWalker`] to add up a whole tree.
+    pub fn estimate_export_size(&self) -> Result<FolderSizeEstimate> {
+        let columns = SIZE_COLUMNS.map(PropTag);
+        let rows = TableQuery::new(self.contents_table()?)
+            .columns(&columns)
+            .batch(500)
+            .execute()?;
+        let mut estimate = FolderSizeEstimate::default();
+        for row in rows {
+            estimate.add_row(&row?);
+        }
+        Ok(estimate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn total_prefers_larger_size() {
+        let estimate = MessageSizeEstimate {
+            message_size: Some(1000),
+            attachment_size: 600,
+            attachment_count: 1,
+        };
+        assert_eq!(estimate.total(), 1000);
+
+        let estimate = MessageSizeEstimate {
+            message_size: None,
+            attachment_size: 600,
+            attachment_count: 1,
+        };
+        assert_eq!(estimate.total(), 600);
+    }
+}
@@ -0,0 +1,446 @@
+//! Define [`PropTagArrayBuf`], [`SortOrderSetBuf`], [`EntryIdBuf`], and [`RowSetBuf`].
+
+use crate::{
+    sys, CbNewENTRYID, CbNewSPropTagArray, CbNewSRowSet, CbNewSSortOrderSet, PropTag, SortDirection,
+};
+use core::{mem, ptr, slice};
+
+/// Heap-backed [`sys::SPropTagArray`] with a count which is only known at runtime, e.g. a column
+/// set built from user input. Use [`crate::SizedSPropTagArray`] when the count is a constant.
+///
+/// Every member of [`sys::SPropTagArray`] is a `u32`, so the buffer is a `Vec<u32>` with
+/// [`CbNewSPropTagArray`] bytes, which has the same layout and alignment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PropTagArrayBuf {
+    buffer: Vec<u32>,
+}
+
+impl PropTagArrayBuf {
+    /// Build the [`sys::SPropTagArray`] from a list of tags.
+    pub fn new(tags: impl IntoIterator<Item = PropTag>) -> Self {
+        let mut buffer = vec![0];
+        buffer.extend(tags.into_iter().map(u32::from));
+        buffer[0] = (buffer.len() - 1) as u32;
+        debug_assert_eq!(
+            buffer.len() * mem::size_of::<u32>(),
+            CbNewSPropTagArray(buffer.len() - 1)
+        );
+        Self { buffer }
+    }
+
+    /// Get the number of tags in [`sys::SPropTagArray::aulPropTag`].
+    pub fn len(&self) -> usize {
+        self.buffer[0] as usize
+    }
+
+    /// Test for an empty [`sys::SPropTagArray::aulPropTag`].
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the tags.
+    pub fn iter(&self) -> impl Iterator<Item = PropTag> + '_ {
+        self.buffer.iter().skip(1).copied().map(PropTag)
+    }
+
+    /// Get a pointer to the [`sys::SPropTagArray`], which is valid as long as the
+    /// [`PropTagArrayBuf`] is not modified or dropped.
+    pub fn as_ptr(&self) -> *const sys::SPropTagArray {
+        self.buffer.as_ptr() as *const _
+    }
+
+    /// Get a mutable pointer to the [`sys::SPropTagArray`], e.g. to pass to
+    /// [`sys::IMAPITable::SetColumns`].
+    pub fn as_mut_ptr(&mut self) -> *mut sys::SPropTagArray {
+        self.buffer.as_mut_ptr() as *mut _
+    }
+}
+
+impl Default for PropTagArrayBuf {
+    fn default() -> Self {
+        Self::new([])
+    }
+}
+
+impl From<&[PropTag]> for PropTagArrayBuf {
+    fn from(value: &[PropTag]) -> Self {
+        Self::new(value.iter().copied())
+    }
+}
+
+impl From<Vec<PropTag>> for PropTagArrayBuf {
+    fn from(value: Vec<PropTag>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl FromIterator<PropTag> for PropTagArrayBuf {
+    fn from_iter<T: IntoIterator<Item = PropTag>>(iter: T) -> Self {
+        Self::new(iter)
+    }
+}
+
+/// Number of `u32` members in [`sys::SSortOrderSet`] before [`sys::SSortOrderSet::aSort`]:
+/// `cSorts`, `cCategories`, and `cExpanded`.
+const SORT_ORDER_SET_HEADER: usize = 3;
+
+/// Heap-backed [`sys::SSortOrderSet`] with a count which is only known at runtime. Use
+/// [`crate::SizedSSortOrderSet`] when the count is a constant.
+///
+/// Like [`PropTagArrayBuf`], every member of [`sys::SSortOrderSet`] and [`sys::SSortOrder`] is a
+/// `u32`, so the buffer is a `Vec<u32>` with [`CbNewSSortOrderSet`] bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortOrderSetBuf {
+    buffer: Vec<u32>,
+}
+
+impl SortOrderSetBuf {
+    /// Build a [`sys::SSortOrderSet`] with no categories from a list of [`sys::SSortOrder`].
+    pub fn new(sorts: impl IntoIterator<Item = sys::SSortOrder>) -> Self {
+        Self::categorized(sorts, 0, 0)
+    }
+
+    /// Build a [`sys::SSortOrderSet`] for a categorized table. The first `categories` sort keys
+    /// are category columns, and the first `expanded` of those categories start out expanded.
+    pub fn categorized(
+        sorts: impl IntoIterator<Item = sys::SSortOrder>,
+        categories: u32,
+        expanded: u32,
+    ) -> Self {
+        let mut buffer = vec![0, categories, expanded];
+        buffer.extend(
+            sorts
+                .into_iter()
+                .flat_map(|sort| [sort.ulPropTag, sort.ulOrder]),
+        );
+        let count = (buffer.len() - SORT_ORDER_SET_HEADER) / 2;
+        buffer[0] = count as u32;
+        debug_assert_eq!(
+            buffer.len() * mem::size_of::<u32>(),
+            CbNewSSortOrderSet(count)
+        );
+        Self { buffer }
+    }
+
+    /// Get the number of sort keys in [`sys::SSortOrderSet::aSort`].
+    pub fn len(&self) -> usize {
+        self.buffer[0] as usize
+    }
+
+    /// Test for an empty [`sys::SSortOrderSet::aSort`].
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get a pointer to the [`sys::SSortOrderSet`], which is valid as long as the
+    /// [`SortOrderSetBuf`] is not modified or dropped.
+    pub fn as_ptr(&self) -> *const sys::SSortOrderSet {
+        self.buffer.as_ptr() as *const _
+    }
+
+    /// Get a mutable pointer to the [`sys::SSortOrderSet`], e.g. to pass to
+    /// [`sys::IMAPITable::SortTable`].
+    pub fn as_mut_ptr(&mut self) -> *mut sys::SSortOrderSet {
+        self.buffer.as_mut_ptr() as *mut _
+    }
+}
+
+impl Default for SortOrderSetBuf {
+    fn default() -> Self {
+        Self::new([])
+    }
+}
+
+impl From<&[(PropTag, SortDirection)]> for SortOrderSetBuf {
+    fn from(value: &[(PropTag, SortDirection)]) -> Self {
+        Self::new(value.iter().map(|(tag, direction)| sys::SSortOrder {
+            ulPropTag: u32::from(*tag),
+            ulOrder: u32::from(*direction),
+        }))
+    }
+}
+
+impl From<Vec<sys::SSortOrder>> for SortOrderSetBuf {
+    fn from(value: Vec<sys::SSortOrder>) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Number of bytes in [`sys::ENTRYID::abFlags`].
+const ENTRYID_FLAGS: usize = 4;
+
+/// Heap-backed [`sys::ENTRYID`] with a length which is only known at runtime, which is almost
+/// always the case since each provider chooses its own entry ID format. Use
+/// [`crate::SizedENTRYID`] when the length is a constant.
+///
+/// [`sys::ENTRYID`] does not store its own length, so [`EntryIdBuf::len`] is the `cbEntryID` to
+/// pass along with [`EntryIdBuf::as_ptr`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EntryIdBuf {
+    buffer: Vec<u8>,
+}
+
+impl EntryIdBuf {
+    /// Build a [`sys::ENTRYID`] from [`sys::ENTRYID::abFlags`] and the provider specific bytes in
+    /// [`sys::ENTRYID::ab`].
+    pub fn new(flags: [u8; ENTRYID_FLAGS], ab: &[u8]) -> Self {
+        let mut buffer = Vec::with_capacity(CbNewENTRYID(ab.len()));
+        buffer.extend_from_slice(&flags);
+        buffer.extend_from_slice(ab);
+        Self { buffer }
+    }
+
+    /// Copy a whole entry ID, including the [`sys::ENTRYID::abFlags`], e.g. the value of a
+    /// [`sys::PR_ENTRYID`] property.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            buffer: bytes.to_vec(),
+        }
+    }
+
+    /// Get the size of the entry ID in bytes, i.e. the `cbEntryID`.
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Test for an empty entry ID, which [`sys::IMsgStore::OpenEntry`] treats as the root folder.
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Get the [`sys::ENTRYID::abFlags`]. If the entry ID is too short to have all of them, the
+    /// missing flags are 0.
+    pub fn flags(&self) -> [u8; ENTRYID_FLAGS] {
+        let mut flags = [0; ENTRYID_FLAGS];
+        let len = self.buffer.len().min(ENTRYID_FLAGS);
+        flags[..len].copy_from_slice(&self.buffer[..len]);
+        flags
+    }
+
+    /// Get the provider specific bytes in [`sys::ENTRYID::ab`].
+    pub fn ab(&self) -> &[u8] {
+        self.buffer.get(ENTRYID_FLAGS..).unwrap_or_default()
+    }
+
+    /// Get the whole entry ID as bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Get a pointer to the [`sys::ENTRYID`], which is valid for [`EntryIdBuf::len`] bytes as long
+    /// as the [`EntryIdBuf`] is not modified or dropped.
+    pub fn as_ptr(&self) -> *const sys::ENTRYID {
+        self.buffer.as_ptr() as *const _
+    }
+
+    /// Get a mutable pointer to the [`sys::ENTRYID`], e.g. to pass to
+    /// [`sys::IMsgStore::OpenEntry`].
+    pub fn as_mut_ptr(&mut self) -> *mut sys::ENTRYID {
+        self.buffer.as_mut_ptr() as *mut _
+    }
+}
+
+impl From<&[u8]> for EntryIdBuf {
+    fn from(value: &[u8]) -> Self {
+        Self::from_bytes(value)
+    }
+}
+
+impl From<Vec<u8>> for EntryIdBuf {
+    fn from(value: Vec<u8>) -> Self {
+        Self { buffer: value }
+    }
+}
+
+impl From<EntryIdBuf> for Vec<u8> {
+    fn from(value: EntryIdBuf) -> Self {
+        value.buffer
+    }
+}
+
+impl AsRef<[u8]> for EntryIdBuf {
+    fn as_ref(&self) -> &[u8] {
+        &self.buffer
+    }
+}
+
+/// Heap-backed [`sys::SRowSet`] (or [`sys::ADRLIST`], which has the same layout) with a number of
+/// rows which is only known at runtime. Use [`crate::SizedSRowSet`] when the count is a constant.
+///
+/// [`sys::SRow`] holds a pointer, so unlike [`PropTagArrayBuf`] the buffer is a `Vec<u64>`, which
+/// is at least as aligned as [`sys::SRowSet`] on every target. Rows are copied into the buffer as
+/// they are pushed, but the [`sys::SRow::lpProps`] they point to are still owned by the caller and
+/// must outlive the [`RowSetBuf`]. Dropping it does not call [`sys::FreeProws`].
+#[derive(Clone, Debug)]
+pub struct RowSetBuf {
+    buffer: Vec<u64>,
+    capacity: usize,
+}
+
+impl RowSetBuf {
+    /// Allocate an empty [`sys::SRowSet`] with room for `capacity` rows.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let size = CbNewSRowSet(capacity);
+        let buffer = vec![0; size.div_ceil(mem::size_of::<u64>())];
+        debug_assert!(mem::align_of::<sys::SRowSet>() <= mem::align_of::<u64>());
+        Self { buffer, capacity }
+    }
+
+    /// Get the number of rows in [`sys::SRowSet::aRow`].
+    pub fn len(&self) -> usize {
+        unsafe { (*self.as_ptr()).cRows as usize }
+    }
+
+    /// Test for an empty [`sys::SRowSet::aRow`].
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of rows which fit in the buffer before [`RowSetBuf::push`] has to grow it.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Append a row, growing the buffer if it is full.
+    pub fn push(&mut self, row: sys::SRow) {
+        let len = self.len();
+        if len == self.capacity {
+            let mut grown = Self::with_capacity((self.capacity * 2).max(1));
+            for row in self.rows() {
+                grown.push(*row);
+            }
+            *self = grown;
+        }
+        unsafe {
+            let row_set = self.as_mut_ptr();
+            let rows = ptr::addr_of_mut!((*row_set).aRow) as *mut sys::SRow;
+            rows.add(len).write(row);
+            (*row_set).cRows += 1;
+        }
+    }
+
+    /// Get the rows which have been pushed so far.
+    pub fn rows(&self) -> &[sys::SRow] {
+        unsafe {
+            let row_set = self.as_ptr();
+            let rows = ptr::addr_of!((*row_set).aRow) as *const sys::SRow;
+            slice::from_raw_parts(rows, self.len())
+        }
+    }
+
+    /// Get a pointer to the [`sys::SRowSet`], which is valid as long as the [`RowSetBuf`] is not
+    /// modified or dropped.
+    pub fn as_ptr(&self) -> *const sys::SRowSet {
+        self.buffer.as_ptr() as *const _
+    }
+
+    /// Get a mutable pointer to the [`sys::SRowSet`], e.g. to pass to
+    /// [`sys::ITableData::HrModifyRows`].
+    pub fn as_mut_ptr(&mut self) -> *mut sys::SRowSet {
+        self.buffer.as_mut_ptr() as *mut _
+    }
+
+    /// Get a mutable pointer to the same buffer as an [`sys::ADRLIST`], e.g. to pass to
+    /// [`sys::IMessage::ModifyRecipients`].
+    pub fn as_mut_adr_list(&mut self) -> *mut sys::ADRLIST {
+        self.buffer.as_mut_ptr() as *mut _
+    }
+}
+
+impl Default for RowSetBuf {
+    fn default() -> Self {
+        Self::with_capacity(0)
+    }
+}
+
+impl From<&[sys::SRow]> for RowSetBuf {
+    fn from(value: &[sys::SRow]) -> Self {
+        let mut row_set = Self::with_capacity(value.len());
+        for row in value {
+            row_set.push(*row);
+        }
+        row_set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prop_tag_array_layout() {
+        let mut tags =
+            PropTagArrayBuf::from(&[PropTag(sys::PR_ENTRYID), PropTag(sys::PR_SUBJECT_W)][..]);
+        assert_eq!(tags.len(), 2);
+        let tags = unsafe { &*tags.as_mut_ptr() };
+        assert_eq!(tags.cValues, 2);
+        assert_eq!(tags.aulPropTag[0], sys::PR_ENTRYID);
+
+        let empty = PropTagArrayBuf::default();
+        assert!(empty.is_empty());
+        assert_eq!(unsafe { (*empty.as_ptr()).cValues }, 0);
+    }
+
+    #[test]
+    fn sort_order_set_layout() {
+        let mut sorts = SortOrderSetBuf::categorized(
+            [
+                sys::SSortOrder {
+                    ulPropTag: sys::PR_CONVERSATION_TOPIC_W,
+                    ulOrder: sys::TABLE_SORT_DESCEND,
+                },
+                sys::SSortOrder {
+                    ulPropTag: sys::PR_MESSAGE_DELIVERY_TIME,
+                    ulOrder: sys::TABLE_SORT_ASCEND,
+                },
+            ],
+            1,
+            1,
+        );
+        assert_eq!(sorts.len(), 2);
+        let sorts = unsafe { &*sorts.as_mut_ptr() };
+        assert_eq!(sorts.cSorts, 2);
+        assert_eq!(sorts.cCategories, 1);
+        assert_eq!(sorts.cExpanded, 1);
+        assert_eq!(sorts.aSort[0].ulPropTag, sys::PR_CONVERSATION_TOPIC_W);
+    }
+
+    #[test]
+    fn entry_id_layout() {
+        let mut entry_id = EntryIdBuf::new([0, 0, 0, 1], &[0xAB, 0xCD]);
+        assert_eq!(entry_id.len(), CbNewENTRYID(2));
+        assert_eq!(entry_id.ab(), &[0xAB, 0xCD]);
+        let ptr = entry_id.as_mut_ptr();
+        assert_eq!(unsafe { (*ptr).abFlags }, [0, 0, 0, 1]);
+        assert_eq!(unsafe { (*ptr).ab[0] }, 0xAB);
+
+        let short = EntryIdBuf::from_bytes(&[1, 2]);
+        assert_eq!(short.flags(), [1, 2, 0, 0]);
+        assert!(short.ab().is_empty());
+    }
+
+    #[test]
+    fn row_set_layout() {
+        let mut rows = RowSetBuf::with_capacity(1);
+        assert!(rows.is_empty());
+        for values in 1..=3 {
+            rows.push(sys::SRow {
+                ulAdrEntryPad: 0,
+                cValues: values,
+                lpProps: ptr::null_mut(),
+            });
+        }
+        assert_eq!(rows.len(), 3);
+        assert!(rows.capacity() >= 3);
+        let row_set = unsafe { &*rows.as_mut_ptr() };
+        assert_eq!(row_set.cRows, 3);
+        assert_eq!(row_set.aRow[0].cValues, 1);
+        assert_eq!(
+            rows.rows()
+                .iter()
+                .map(|row| row.cValues)
+                .collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+    }
+}
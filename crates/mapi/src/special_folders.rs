@@ -0,0 +1,213 @@
+//! Define [`SpecialFolder`].
+
+use crate::{
+    mapi_prop::{get_one_prop, inbox_entry_id, open_inbox, open_store_entry},
+    sys, Folder, MapiProp, OpenFlags, PropValueData, Store, StoreCapabilities,
+    PR_IPM_JOURNAL_ENTRYID, PR_IPM_NOTE_ENTRYID,
+};
+use core::slice;
+use windows_core::*;
+
+/// `PR_ADDITIONAL_REN_ENTRYIDS` is missing from the generated bindings.
+pub const PR_ADDITIONAL_REN_ENTRYIDS: u32 = 0x36D8_1102;
+
+/// Index of the Junk E-mail folder in [`PR_ADDITIONAL_REN_ENTRYIDS`].
+const JUNK_EMAIL_INDEX: usize = 4;
+
+/// The well-known folders which can be found without walking the folder hierarchy.
+///
+/// Each store type records them in a different place: the Inbox is the receive folder for the
+/// `IPM` message class, a few folders have entry IDs on the store itself, and the rest have entry
+/// IDs on the Inbox, or on the root folder if the store has no Inbox.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SpecialFolder {
+    /// [`sys::PR_IPM_SUBTREE_ENTRYID`] on the store, i.e. the top of the folders the user sees.
+    /// Public folder stores use [`sys::PR_IPM_PUBLIC_FOLDERS_ENTRYID`] instead.
+    IpmSubtree,
+
+    /// [`sys::PR_NON_IPM_SUBTREE_ENTRYID`] on the store, which holds the folders the user does
+    /// not see, e.g. views and forms.
+    NonIpmSubtree,
+
+    /// The default receive folder for `IPM` messages.
+    Inbox,
+
+    /// [`sys::PR_IPM_OUTBOX_ENTRYID`] on the store.
+    Outbox,
+
+    /// [`sys::PR_IPM_SENTMAIL_ENTRYID`] on the store.
+    SentItems,
+
+    /// [`sys::PR_IPM_WASTEBASKET_ENTRYID`] on the store.
+    DeletedItems,
+
+    /// [`sys::PR_IPM_DRAFTS_ENTRYID`] on the Inbox or root folder.
+    Drafts,
+
+    /// [`sys::PR_IPM_APPOINTMENT_ENTRYID`] on the Inbox or root folder.
+    Calendar,
+
+    /// [`sys::PR_IPM_CONTACT_ENTRYID`] on the Inbox or root folder.
+    Contacts,
+
+    /// [`sys::PR_IPM_TASK_ENTRYID`] on the Inbox or root folder.
+    Tasks,
+
+    /// [`PR_IPM_NOTE_ENTRYID`] on the Inbox or root folder.
+    Notes,
+
+    /// [`PR_IPM_JOURNAL_ENTRYID`] on the Inbox or root folder.
+    Journal,
+
+    /// The 5th value of [`PR_ADDITIONAL_REN_ENTRYIDS`] on the Inbox or root folder.
+    JunkEmail,
+}
+
+impl SpecialFolder {
+    /// Find the entry ID of the folder in a store. Fails with [`sys::MAPI_E_NOT_FOUND`] if the
+    /// store does not have this folder.
+    pub fn entry_id(self, store: &sys::IMsgStore) -> Result<Vec<u8>> {
+        let tag = match self {
+            Self::Inbox => return inbox_entry_id(store),
+            Self::IpmSubtree => return ipm_subtree_entry_id(store),
+            Self::NonIpmSubtree => sys::PR_NON_IPM_SUBTREE_ENTRYID,
+            Self::Outbox => sys::PR_IPM_OUTBOX_ENTRYID,
+            Self::SentItems => sys::PR_IPM_SENTMAIL_ENTRYID,
+            Self::DeletedItems => sys::PR_IPM_WASTEBASKET_ENTRYID,
+            Self::Drafts => return find_on_inbox_or_root(store, sys::PR_IPM_DRAFTS_ENTRYID),
+            Self::Calendar => return find_on_inbox_or_root(store, sys::PR_IPM_APPOINTMENT_ENTRYID),
+            Self::Contacts => return find_on_inbox_or_root(store, sys::PR_IPM_CONTACT_ENTRYID),
+            Self::Tasks => return find_on_inbox_or_root(store, sys::PR_IPM_TASK_ENTRYID),
+            Self::Notes => return find_on_inbox_or_root(store, PR_IPM_NOTE_ENTRYID),
+            Self::Journal => return find_on_inbox_or_root(store, PR_IPM_JOURNAL_ENTRYID),
+            Self::JunkEmail => return find_on_inbox_or_root(store, PR_ADDITIONAL_REN_ENTRYIDS),
+        };
+        get_entry_id(store.as_mapi_prop(), tag)?.ok_or_else(|| Error::from(sys::MAPI_E_NOT_FOUND))
+    }
+}
+
+/// Read an entry ID property, or one of the values of [`PR_ADDITIONAL_REN_ENTRYIDS`]. Returns
+/// `None` if the property (or value) is missing or empty.
+fn get_entry_id(prop: &sys::IMAPIProp, tag: u32) -> Result<Option<Vec<u8>>> {
+    let entry_id = get_one_prop(prop, tag, |value| match value {
+        PropValueData::Binary(value) => Some(value.to_vec()),
+        PropValueData::BinaryArray(values) => Some(
+            values
+                .get(JUNK_EMAIL_INDEX)
+                .filter(|value| value.cb > 0)
+                .map(|value| unsafe { slice::from_raw_parts(value.lpb, value.cb as usize) })
+                .unwrap_or_default()
+                .to_vec(),
+        ),
+        _ => None,
+    })?;
+    Ok(entry_id.filter(|entry_id| !entry_id.is_empty()))
+}
+
+/// Read the entry ID of the IPM subtree. A private store sets [`sys::PR_IPM_SUBTREE_ENTRYID`], but
+/// a public folder store (see [`StoreCapabilities::public_folders`]) sets
+/// [`sys::PR_IPM_PUBLIC_FOLDERS_ENTRYID`], so check the one which matches the store type first and
+/// fall back to the other, in case the provider does not set [`sys::PR_STORE_SUPPORT_MASK`].
+fn ipm_subtree_entry_id(store: &sys::IMsgStore) -> Result<Vec<u8>> {
+    let public = StoreCapabilities::from_store(store)
+        .map(|capabilities| capabilities.public_folders)
+        .unwrap_or_default();
+    let tags = if public {
+        [
+            sys::PR_IPM_PUBLIC_FOLDERS_ENTRYID,
+            sys::PR_IPM_SUBTREE_ENTRYID,
+        ]
+    } else {
+        [
+            sys::PR_IPM_SUBTREE_ENTRYID,
+            sys::PR_IPM_PUBLIC_FOLDERS_ENTRYID,
+        ]
+    };
+    for tag in tags {
+        if let Some(entry_id) = get_entry_id(store.as_mapi_prop(), tag)? {
+            return Ok(entry_id);
+        }
+    }
+    Err(Error::from(sys::MAPI_E_NOT_FOUND))
+}
+
+/// Look for an entry ID on the Inbox first, which is where Outlook looks, and then on the root
+/// folder, e.g. for a PST which has never had an Inbox.
+fn find_on_inbox_or_root(store: &sys::IMsgStore, tag: u32) -> Result<Vec<u8>> {
+    if let Ok(inbox) = open_inbox(store) {
+        if let Some(entry_id) = get_entry_id(inbox.as_mapi_prop(), tag)? {
+            return Ok(entry_id);
+        }
+    }
+    let root: sys::IMAPIFolder = open_store_entry(store, &[], OpenFlags::default())?;
+    get_entry_id(root.as_mapi_prop(), tag)?.ok_or_else(|| Error::from(sys::MAPI_E_NOT_FOUND))
+}
+
+impl Store {
+    /// Open one of the [`SpecialFolder`] folders in this store.
+    pub fn special_folder(&self, folder: SpecialFolder) -> Result<Folder> {
+        let entry_id = folder.entry_id(self.store())?;
+        self.open_folder(&entry_id)
+    }
+
+    /// Open the root folder of the store, which is the parent of the [`SpecialFolder::IpmSubtree`]
+    /// and [`SpecialFolder::NonIpmSubtree`]. Same as [`Store::open_root_folder`].
+    pub fn root_folder(&self) -> Result<Folder> {
+        self.open_root_folder()
+    }
+
+    /// Open the [`SpecialFolder::IpmSubtree`], in either a private or a public folder store.
+    pub fn ipm_subtree(&self) -> Result<Folder> {
+        self.special_folder(SpecialFolder::IpmSubtree)
+    }
+
+    /// Open the [`SpecialFolder::NonIpmSubtree`].
+    pub fn non_ipm_subtree(&self) -> Result<Folder> {
+        self.special_folder(SpecialFolder::NonIpmSubtree)
+    }
+
+    /// Open the [`SpecialFolder::Inbox`].
+    pub fn inbox(&self) -> Result<Folder> {
+        self.special_folder(SpecialFolder::Inbox)
+    }
+
+    /// Open the [`SpecialFolder::Outbox`].
+    pub fn outbox(&self) -> Result<Folder> {
+        self.special_folder(SpecialFolder::Outbox)
+    }
+
+    /// Open the [`SpecialFolder::SentItems`].
+    pub fn sent_items(&self) -> Result<Folder> {
+        self.special_folder(SpecialFolder::SentItems)
+    }
+
+    /// Open the [`SpecialFolder::DeletedItems`].
+    pub fn deleted_items(&self) -> Result<Folder> {
+        self.special_folder(SpecialFolder::DeletedItems)
+    }
+
+    /// Open the [`SpecialFolder::Drafts`].
+    pub fn drafts(&self) -> Result<Folder> {
+        self.special_folder(SpecialFolder::Drafts)
+    }
+
+    /// Open the [`SpecialFolder::Calendar`].
+    pub fn calendar(&self) -> Result<Folder> {
+        self.special_folder(SpecialFolder::Calendar)
+    }
+
+    /// Open the [`SpecialFolder::Contacts`].
+    pub fn contacts(&self) -> Result<Folder> {
+        self.special_folder(SpecialFolder::Contacts)
+    }
+
+    /// Open the [`SpecialFolder::Tasks`].
+    pub fn tasks(&self) -> Result<Folder> {
+        self.special_folder(SpecialFolder::Tasks)
+    }
+
+    /// Open the [`SpecialFolder::JunkEmail`].
+    pub fn junk_email(&self) -> Result<Folder> {
+        self.special_folder(SpecialFolder::JunkEmail)
+    }
+}
@@ -0,0 +1,96 @@
+//! Define [`Store`] and [`OpenStoreFlags`].
+
+use crate::sys;
+
+/// Set of flags that can be passed to [`sys::IMAPISession::OpenMsgStore`] by
+/// [`crate::Logon::open_store`].
+///
+/// If neither [`OpenStoreFlags::best_access`] nor [`OpenStoreFlags::write`] is set, the store is
+/// opened read-only. Setting both asks for write access, but still opens the store read-only if
+/// the user does not have permission to modify it.
+#[derive(Default)]
+pub struct OpenStoreFlags {
+    /// Pass [`sys::MAPI_BEST_ACCESS`].
+    pub best_access: bool,
+
+    /// Pass [`sys::MAPI_DEFERRED_ERRORS`].
+    pub deferred_errors: bool,
+
+    /// Pass [`sys::MDB_NO_DIALOG`].
+    pub no_dialog: bool,
+
+    /// Pass [`sys::MDB_NO_MAIL`].
+    pub no_mail: bool,
+
+    /// Pass [`sys::MDB_ONLINE`].
+    pub online: bool,
+
+    /// Pass [`sys::MDB_TEMPORARY`].
+    pub temporary: bool,
+
+    /// Pass [`sys::MDB_WRITE`].
+    pub write: bool,
+}
+
+impl From<OpenStoreFlags> for u32 {
+    fn from(value: OpenStoreFlags) -> Self {
+        let best_access = if value.best_access {
+            sys::MAPI_BEST_ACCESS
+        } else {
+            0
+        };
+        let deferred_errors = if value.deferred_errors {
+            sys::MAPI_DEFERRED_ERRORS
+        } else {
+            0
+        };
+        let no_dialog = if value.no_dialog {
+            sys::MDB_NO_DIALOG
+        } else {
+            0
+        };
+        let no_mail = if value.no_mail { sys::MDB_NO_MAIL } else { 0 };
+        let online = if value.online { sys::MDB_ONLINE } else { 0 };
+        let temporary = if value.temporary {
+            sys::MDB_TEMPORARY
+        } else {
+            0
+        };
+        let write = if value.write { sys::MDB_WRITE } else { 0 };
+
+        best_access | deferred_errors | no_dialog | no_mail | online | temporary | write
+    }
+}
+
+/// Wrapper for a [`sys::IMsgStore`] opened with [`crate::Logon::open_store`].
+///
+/// Like [`crate::Logon`], a [`Store`] belongs to the thread which opened it, and it must not
+/// outlive the [`crate::Logon`] it came from.
+///
+/// Cloning a [`Store`] calls `AddRef` on the same [`sys::IMsgStore`] rather than opening it again,
+/// so every clone shares the provider's state for that store, e.g. notification advise sinks and
+/// pending changes. The last clone to be dropped releases it.
+#[derive(Clone)]
+pub struct Store {
+    store: sys::IMsgStore,
+}
+
+impl Store {
+    /// Borrow the [`sys::IMsgStore`], e.g. to pass it to the functions in this crate which take a
+    /// `&sys::IMsgStore`, or to call methods which do not have a wrapper yet.
+    pub fn store(&self) -> &sys::IMsgStore {
+        &self.store
+    }
+}
+
+impl From<sys::IMsgStore> for Store {
+    fn from(store: sys::IMsgStore) -> Self {
+        Self { store }
+    }
+}
+
+impl From<Store> for sys::IMsgStore {
+    fn from(value: Store) -> Self {
+        value.store
+    }
+}
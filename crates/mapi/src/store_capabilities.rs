@@ -0,0 +1,221 @@
+//! Define [`StoreCapabilities`].
+
+use crate::{sys, MAPIOutParam, PropValue, PropValueData, Store};
+use windows::Win32::Foundation::E_UNEXPECTED;
+use windows_core::*;
+
+/// `STORE_HTML_OK` is missing from the generated bindings.
+const STORE_HTML_OK: u32 = 0x0001_0000;
+
+/// `STORE_ANSI_OK` is missing from the generated bindings.
+const STORE_ANSI_OK: u32 = 0x0002_0000;
+
+/// Set of features decoded from the [`sys::PR_STORE_SUPPORT_MASK`] property of an
+/// [`sys::IMsgStore`].
+///
+/// Providers differ quite a bit in what they support, so higher level helpers should consult
+/// these flags before attempting an operation, and fail with [`sys::MAPI_E_NO_SUPPORT`] (see
+/// [`StoreCapabilities::require`]) rather than relying on whatever error the provider returns.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StoreCapabilities {
+    /// Set if [`sys::STORE_ENTRYID_UNIQUE`] is present.
+    pub entry_id_unique: bool,
+
+    /// Set if [`sys::STORE_READONLY`] is present.
+    pub read_only: bool,
+
+    /// Set if [`sys::STORE_SEARCH_OK`] is present.
+    pub search: bool,
+
+    /// Set if [`sys::STORE_MODIFY_OK`] is present.
+    pub modify: bool,
+
+    /// Set if [`sys::STORE_CREATE_OK`] is present.
+    pub create: bool,
+
+    /// Set if [`sys::STORE_ATTACH_OK`] is present.
+    pub attach: bool,
+
+    /// Set if [`sys::STORE_OLE_OK`] is present.
+    pub ole: bool,
+
+    /// Set if [`sys::STORE_SUBMIT_OK`] is present.
+    pub submit: bool,
+
+    /// Set if [`sys::STORE_NOTIFY_OK`] is present.
+    pub notifications: bool,
+
+    /// Set if [`sys::STORE_MV_PROPS_OK`] is present.
+    pub multi_valued_props: bool,
+
+    /// Set if [`sys::STORE_CATEGORIZE_OK`] is present.
+    pub categorized_tables: bool,
+
+    /// Set if [`sys::STORE_RTF_OK`] is present.
+    pub rtf: bool,
+
+    /// Set if [`sys::STORE_RESTRICTION_OK`] is present.
+    pub restrictions: bool,
+
+    /// Set if [`sys::STORE_SORT_OK`] is present.
+    pub sort: bool,
+
+    /// Set if [`sys::STORE_PUBLIC_FOLDERS`] is present.
+    pub public_folders: bool,
+
+    /// Set if [`sys::STORE_UNCOMPRESSED_RTF`] is present.
+    pub uncompressed_rtf: bool,
+
+    /// Set if `STORE_HTML_OK` is present.
+    pub html: bool,
+
+    /// Set if `STORE_ANSI_OK` is present.
+    pub ansi: bool,
+
+    /// Set if [`sys::STORE_UNICODE_OK`] is present.
+    pub unicode: bool,
+
+    /// Set if [`sys::STORE_ITEMPROC`] is present.
+    pub item_proc: bool,
+
+    /// Set if [`sys::STORE_HAS_SEARCHES`] is present.
+    pub has_searches: bool,
+}
+
+impl StoreCapabilities {
+    /// Read and decode the [`sys::PR_STORE_SUPPORT_MASK`] property from an [`sys::IMsgStore`].
+    pub fn from_store(store: &sys::IMsgStore) -> Result<Self> {
+        Self::from_prop(store)
+    }
+
+    fn from_prop(store: &sys::IMAPIProp) -> Result<Self> {
+        let mut prop: MAPIOutParam<sys::SPropValue> = Default::default();
+        unsafe {
+            sys::HrGetOneProp(store, sys::PR_STORE_SUPPORT_MASK, prop.as_mut_ptr())?;
+            match prop.as_mut().map(|prop| PropValue::from(&*prop).value) {
+                Some(PropValueData::Long(mask)) => Ok(Self::from(mask as u32)),
+                Some(PropValueData::Error(err)) => Err(Error::from(err)),
+                _ => Err(Error::from(E_UNEXPECTED)),
+            }
+        }
+    }
+
+    /// Convert the result of a capability check into a [`Result`], so callers can bail out early
+    /// with [`sys::MAPI_E_NO_SUPPORT`] when the store does not support an operation.
+    pub fn require(supported: bool) -> Result<()> {
+        if supported {
+            Ok(())
+        } else {
+            Err(Error::from(sys::MAPI_E_NO_SUPPORT))
+        }
+    }
+
+    /// Check a capability of `store` with [`StoreCapabilities::require`] before a wrapper calls
+    /// the provider. A store which does not set [`sys::PR_STORE_SUPPORT_MASK`] is not rejected,
+    /// since the provider can still report its own error.
+    pub(crate) fn check(
+        store: &sys::IMAPIProp,
+        supported: impl FnOnce(&Self) -> bool,
+    ) -> Result<()> {
+        match Self::from_prop(store) {
+            Ok(capabilities) => Self::require(supported(&capabilities)),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+impl Store {
+    /// Read the [`StoreCapabilities`] of this store from its [`sys::PR_STORE_SUPPORT_MASK`].
+    pub fn capabilities(&self) -> Result<StoreCapabilities> {
+        StoreCapabilities::from_store(self.store())
+    }
+}
+
+impl From<u32> for StoreCapabilities {
+    /// Decode a [`sys::PR_STORE_SUPPORT_MASK`] value.
+    fn from(value: u32) -> Self {
+        Self {
+            entry_id_unique: value & sys::STORE_ENTRYID_UNIQUE != 0,
+            read_only: value & sys::STORE_READONLY != 0,
+            search: value & sys::STORE_SEARCH_OK != 0,
+            modify: value & sys::STORE_MODIFY_OK != 0,
+            create: value & sys::STORE_CREATE_OK != 0,
+            attach: value & sys::STORE_ATTACH_OK != 0,
+            ole: value & sys::STORE_OLE_OK != 0,
+            submit: value & sys::STORE_SUBMIT_OK != 0,
+            notifications: value & sys::STORE_NOTIFY_OK != 0,
+            multi_valued_props: value & sys::STORE_MV_PROPS_OK != 0,
+            categorized_tables: value & sys::STORE_CATEGORIZE_OK != 0,
+            rtf: value & sys::STORE_RTF_OK != 0,
+            restrictions: value & sys::STORE_RESTRICTION_OK != 0,
+            sort: value & sys::STORE_SORT_OK != 0,
+            public_folders: value & sys::STORE_PUBLIC_FOLDERS != 0,
+            uncompressed_rtf: value & sys::STORE_UNCOMPRESSED_RTF != 0,
+            html: value & STORE_HTML_OK != 0,
+            ansi: value & STORE_ANSI_OK != 0,
+            unicode: value & sys::STORE_UNICODE_OK != 0,
+            item_proc: value & sys::STORE_ITEMPROC != 0,
+            has_searches: value & sys::STORE_HAS_SEARCHES != 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MapiProp, MemoryProp, OwnedPropValue, OwnedPropValueData, PropTag};
+
+    fn store(mask: u32) -> MemoryProp {
+        MemoryProp::from_iter([OwnedPropValue {
+            tag: PropTag(sys::PR_STORE_SUPPORT_MASK),
+            value: OwnedPropValueData::Long(mask as i32),
+        }])
+    }
+
+    #[test]
+    fn empty_mask() {
+        let capabilities = StoreCapabilities::from(0);
+        assert!(!capabilities.search);
+        assert!(!capabilities.notifications);
+        assert!(!capabilities.unicode);
+        assert!(StoreCapabilities::require(capabilities.search).is_err());
+    }
+
+    #[test]
+    fn decode_mask() {
+        let capabilities = StoreCapabilities::from(
+            sys::STORE_SEARCH_OK
+                | sys::STORE_NOTIFY_OK
+                | sys::STORE_CATEGORIZE_OK
+                | STORE_HTML_OK
+                | sys::STORE_UNICODE_OK
+                | sys::STORE_RESTRICTION_OK,
+        );
+        assert!(capabilities.search);
+        assert!(capabilities.notifications);
+        assert!(capabilities.categorized_tables);
+        assert!(capabilities.html);
+        assert!(capabilities.unicode);
+        assert!(capabilities.restrictions);
+        assert!(!capabilities.ansi);
+        assert!(!capabilities.read_only);
+        assert!(StoreCapabilities::require(capabilities.search).is_ok());
+    }
+
+    #[test]
+    fn check_unsupported() {
+        let store = store(sys::STORE_SEARCH_OK);
+        assert!(StoreCapabilities::check(store.as_mapi_prop(), |caps| caps.search).is_ok());
+        assert_eq!(
+            StoreCapabilities::check(store.as_mapi_prop(), |caps| caps.notifications)
+                .map_err(|err| err.code()),
+            Err(sys::MAPI_E_NO_SUPPORT)
+        );
+    }
+
+    #[test]
+    fn check_missing_mask() {
+        let store = MemoryProp::new();
+        assert!(StoreCapabilities::check(store.as_mapi_prop(), |caps| caps.notifications).is_ok());
+    }
+}
@@ -0,0 +1,231 @@
+//! Define [`TableQuery`], [`TableRows`], and [`SortDirection`].
+
+use crate::{sys, Bookmark, Limits, MapiTable, PropTag, Restriction, Row};
+use std::{mem, vec};
+use windows_core::*;
+
+/// Default number of rows [`TableRows`] requests from [`sys::IMAPITable::QueryRows`] at a time.
+pub const DEFAULT_QUERY_BATCH_SIZE: usize = 100;
+
+/// Direction of a sort key in [`TableQuery::sort`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortDirection {
+    /// [`sys::TABLE_SORT_ASCEND`]
+    #[default]
+    Ascending,
+
+    /// [`sys::TABLE_SORT_DESCEND`]
+    Descending,
+}
+
+impl From<SortDirection> for u32 {
+    fn from(value: SortDirection) -> Self {
+        match value {
+            SortDirection::Ascending => sys::TABLE_SORT_ASCEND,
+            SortDirection::Descending => sys::TABLE_SORT_DESCEND,
+        }
+    }
+}
+
+/// Fluent builder which combines [`sys::IMAPITable::SetColumns`], [`sys::IMAPITable::Restrict`],
+/// and [`sys::IMAPITable::SortTable`], and then reads the rows lazily with [`TableRows`].
+///
+/// ```no_run
+/// # use outlook_mapi::{sys, *};
+/// # fn example(folder: &sys::IMAPIFolder) -> windows_core::Result<()> {
+/// let rows = TableQuery::contents(folder)?
+///     .columns(&[PropTag(sys::PR_ENTRYID), PropTag(sys::PR_SUBJECT_W)])
+///     .sort(PropTag(sys::PR_MESSAGE_DELIVERY_TIME), SortDirection::Descending)
+///     .restrict(Restriction::contains(PropTag(sys::PR_SUBJECT_W), "invoice"))
+///     .batch(500)
+///     .execute()?;
+/// for row in rows {
+///     let row = row?;
+///     // ...
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct TableQuery {
+    table: sys::IMAPITable,
+    columns: Vec<PropTag>,
+    sort: Vec<(PropTag, SortDirection)>,
+    restriction: Option<Restriction>,
+    batch: usize,
+    skip: usize,
+    guarded: bool,
+}
+
+impl TableQuery {
+    /// Start a query on any [`sys::IMAPITable`].
+    pub fn new(table: sys::IMAPITable) -> Self {
+        Self {
+            table,
+            columns: Vec::new(),
+            sort: Vec::new(),
+            restriction: None,
+            batch: DEFAULT_QUERY_BATCH_SIZE,
+            skip: 0,
+            guarded: false,
+        }
+    }
+
+    /// Start a query on the [`sys::IMAPIContainer::GetContentsTable`] of a folder.
+    pub fn contents(folder: &sys::IMAPIContainer) -> Result<Self> {
+        Ok(Self::new(unsafe {
+            folder.GetContentsTable(sys::MAPI_UNICODE)?
+        }))
+    }
+
+    /// Start a query on the [`sys::IMAPIContainer::GetHierarchyTable`] of a folder.
+    pub fn hierarchy(folder: &sys::IMAPIContainer) -> Result<Self> {
+        Ok(Self::new(unsafe {
+            folder.GetHierarchyTable(sys::MAPI_UNICODE)?
+        }))
+    }
+
+    /// Replace the column set. If this is never called, the table keeps its default columns.
+    pub fn columns(mut self, columns: &[PropTag]) -> Self {
+        self.columns = columns.to_vec();
+        self
+    }
+
+    /// Add a sort key. The first key added is the primary sort key.
+    pub fn sort(mut self, tag: PropTag, direction: SortDirection) -> Self {
+        self.sort.push((tag, direction));
+        self
+    }
+
+    /// Replace the [`Restriction`].
+    pub fn restrict(mut self, restriction: Restriction) -> Self {
+        self.restriction = Some(restriction);
+        self
+    }
+
+    /// Set the number of rows requested from [`sys::IMAPITable::QueryRows`] at a time. Values
+    /// less than 1 are treated as 1.
+    pub fn batch(mut self, batch: usize) -> Self {
+        self.batch = batch.max(1);
+        self
+    }
+
+    /// Skip the first `rows` rows of the table, e.g. the rows which were already processed before
+    /// a [`crate::Checkpoint`] was saved.
+    pub fn skip(mut self, rows: usize) -> Self {
+        self.skip = rows;
+        self
+    }
+
+    /// Check each row with [`Row::check_memory`] before returning it, so a corrupt row from a
+    /// misbehaving provider comes back as an [`Error`] in its place instead of crashing the
+    /// process when it is read. See [`TableRows::guarded`].
+    pub fn guarded(mut self) -> Self {
+        self.guarded = true;
+        self
+    }
+
+    /// Apply the columns, restriction, and sort order to the table, and return an iterator which
+    /// reads the rows in batches.
+    pub fn execute(self) -> Result<TableRows> {
+        let table = MapiTable::from(self.table);
+        if !self.columns.is_empty() {
+            table.set_columns(&self.columns)?;
+        }
+        if let Some(restriction) = self.restriction.as_ref() {
+            table.restrict(Some(restriction))?;
+        }
+        if !self.sort.is_empty() {
+            table.sort(&self.sort)?;
+        }
+        table.seek_row(Bookmark::Beginning, self.skip.min(i32::MAX as usize) as i32)?;
+
+        let rows = TableRows::new(table, self.batch);
+        Ok(if self.guarded { rows.guarded() } else { rows })
+    }
+}
+
+/// Lazy [`Iterator`] over the rows of a table, returned from [`TableQuery::execute`] or
+/// [`MapiTable::iter_rows`]. Each call to [`sys::IMAPITable::QueryRows`] fetches the next batch
+/// when the previous one runs out, so only one batch of rows is held in memory at a time.
+///
+/// If the table has more rows than the [`Limits::max_rows`] which were in effect when the
+/// iterator was created, it returns a [`crate::LimitError::TooManyRows`] error after the last
+/// row it is allowed to return, and then stops.
+pub struct TableRows {
+    table: MapiTable,
+    batch: usize,
+    rows: vec::IntoIter<Row>,
+    done: bool,
+    guarded: bool,
+    limits: Limits,
+    returned: usize,
+}
+
+impl TableRows {
+    pub(crate) fn new(table: MapiTable, batch: usize) -> Self {
+        Self {
+            table,
+            batch: batch.max(1),
+            rows: Vec::new().into_iter(),
+            done: false,
+            guarded: false,
+            limits: Limits::current(),
+            returned: 0,
+        }
+    }
+
+    /// Check each row with [`Row::check_memory`], and return an [`Error`] with
+    /// [`sys::MAPI_E_CORRUPT_DATA`] in place of any row which fails. The iterator keeps going
+    /// with the next row after a corrupt one, so the caller can skip it and report it.
+    pub fn guarded(mut self) -> Self {
+        self.guarded = true;
+        self
+    }
+
+    /// Get the underlying [`sys::IMAPITable`].
+    pub fn table(&self) -> &sys::IMAPITable {
+        self.table.table()
+    }
+}
+
+impl Iterator for TableRows {
+    type Item = Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(row) = self.rows.next() {
+                self.returned += 1;
+                if let Err(err) = self.limits.check_rows(self.returned) {
+                    self.done = true;
+                    self.rows = Vec::new().into_iter();
+                    return Some(Err(err.into()));
+                }
+                if self.guarded {
+                    if let Err(err) = row.check_memory() {
+                        // Leak the corrupt row rather than handing its pointers to
+                        // MAPIFreeBuffer.
+                        mem::forget(row);
+                        return Some(Err(err));
+                    }
+                }
+                return Some(Ok(row));
+            }
+            if self.done {
+                return None;
+            }
+
+            let rows = match self.table.query_rows(self.batch) {
+                Ok(rows) => rows,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            };
+            if rows.is_empty() {
+                self.done = true;
+                return None;
+            }
+            self.rows = rows.into_iter();
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Define [`Tnef`], [`TnefProblem`], and the [`ITnef`] interface, for reading and writing
+//! Transport Neutral Encapsulation Format (TNEF) streams, i.e. `winmail.dat` attachments.
+
+use crate::{
+    mapi_prop::{memory_stream, read_whole_stream},
+    sys, MAPIOutParam, Message, PropTag,
+};
+use core::{ffi::c_void, mem, ptr, slice};
+use std::sync::OnceLock;
+use windows::Win32::{
+    Foundation::{E_UNEXPECTED, HMODULE},
+    System::{
+        Com::IStream,
+        LibraryLoader::{GetProcAddress, LoadLibraryW},
+    },
+};
+use windows_core::*;
+use windows_interface::interface;
+
+/// `TNEF_DECODE` is missing from the generated bindings.
+pub const TNEF_DECODE: u32 = 0x0000_0000;
+
+/// `TNEF_ENCODE` is missing from the generated bindings.
+pub const TNEF_ENCODE: u32 = 0x0000_0002;
+
+/// `TNEF_PURE` is missing from the generated bindings.
+pub const TNEF_PURE: u32 = 0x0001_0000;
+
+/// `TNEF_COMPATIBILITY` is missing from the generated bindings.
+pub const TNEF_COMPATIBILITY: u32 = 0x0002_0000;
+
+/// `TNEF_BEST_DATA` is missing from the generated bindings.
+pub const TNEF_BEST_DATA: u32 = 0x0004_0000;
+
+/// `TNEF_PROP_INCLUDE` is missing from the generated bindings.
+pub const TNEF_PROP_INCLUDE: u32 = 0x0000_0001;
+
+/// `TNEF_PROP_EXCLUDE` is missing from the generated bindings.
+pub const TNEF_PROP_EXCLUDE: u32 = 0x0000_0002;
+
+/// Stream name passed to `OpenTnefStreamEx`, which only uses it to label the stream.
+const STREAM_NAME: PCSTR = s!("winmail.dat");
+
+/// Key which ties the attachments in the TNEF stream to their positions in the message body.
+/// Decoding ignores it, but encoding requires a non-zero value.
+const TNEF_KEY: u16 = 0x01AF;
+
+/// `STnefProblem` is missing from the generated bindings.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+#[allow(non_snake_case)]
+pub struct STnefProblem {
+    /// Attachment number of the component which had the problem, or `0` for the message itself.
+    pub ulComponent: u32,
+
+    /// TNEF attribute which had the problem.
+    pub ulAttribute: u32,
+
+    /// Property tag which had the problem.
+    pub ulPropTag: u32,
+
+    /// Error code for the problem.
+    pub scode: i32,
+}
+
+/// `STnefProblemArray` is missing from the generated bindings.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+#[allow(non_snake_case)]
+pub struct STnefProblemArray {
+    /// Number of entries in `aProblem`.
+    pub cProblem: u32,
+
+    /// Variable length array of [`STnefProblem`] entries, with `cProblem` elements.
+    pub aProblem: [STnefProblem; 1],
+}
+
+/// `ITnef` is missing from the generated bindings.
+#[interface("00020319-0000-0000-c000-000000000046")]
+pub unsafe trait ITnef: IUnknown {
+    fn AddProps(
+        &self,
+        ulflags: u32,
+        ulelemid: u32,
+        lpvdata: *mut c_void,
+        lpproplist: *mut sys::SPropTagArray,
+    ) -> HRESULT;
+    fn ExtractProps(
+        &self,
+        ulflags: u32,
+        lpproplist: *mut sys::SPropTagArray,
+        lppproblems: *mut *mut STnefProblemArray,
+    ) -> HRESULT;
+    fn Finish(
+        &self,
+        ulflags: u32,
+        lpkey: *mut u16,
+        lppproblems: *mut *mut STnefProblemArray,
+    ) -> HRESULT;
+    fn OpenTaggedBody(
+        &self,
+        lpmessage: *mut c_void,
+        ulflags: u32,
+        lppstream: *mut *mut c_void,
+    ) -> HRESULT;
+    fn SetProps(
+        &self,
+        ulflags: u32,
+        ulelemid: u32,
+        cvalues: u32,
+        lpprops: *mut sys::SPropValue,
+    ) -> HRESULT;
+    fn EncodeRecips(&self, ulflags: u32, lprecipienttable: *mut c_void) -> HRESULT;
+    fn FinishComponent(
+        &self,
+        ulflags: u32,
+        ulcomponentid: u32,
+        lpcustomproplist: *mut sys::SPropTagArray,
+        lpcustomprops: *mut sys::SPropValue,
+        lpproplist: *mut sys::SPropTagArray,
+        lppproblems: *mut *mut STnefProblemArray,
+    ) -> HRESULT;
+}
+
+type OpenTnefStreamExFn = unsafe extern "system" fn(
+    lpvsupport: *mut c_void,
+    lpstream: *mut c_void,
+    lpszstreamname: PCSTR,
+    ulflags: u32,
+    lpmessage: *mut c_void,
+    wkeyval: u16,
+    lpadressbook: *mut c_void,
+    lpptnef: *mut *mut c_void,
+) -> HRESULT;
+
+/// `OpenTnefStreamEx` is missing from the generated bindings, so look it up in the same DLL as
+/// the rest of MAPI.
+fn open_tnef_stream_ex() -> Result<OpenTnefStreamExFn> {
+    static EXPORT: OnceLock<Option<usize>> = OnceLock::new();
+
+    let export = EXPORT.get_or_init(|| unsafe {
+        let module = mapi_module().ok()?;
+
+        #[cfg(target_pointer_width = "32")]
+        let proc_name = s!("OpenTnefStreamEx@32");
+        #[cfg(not(target_pointer_width = "32"))]
+        let proc_name = s!("OpenTnefStreamEx");
+
+        GetProcAddress(module, proc_name).map(|export| export as usize)
+    });
+    match export {
+        Some(export) => Ok(unsafe { mem::transmute::<usize, OpenTnefStreamExFn>(*export) }),
+        None => Err(Error::from(sys::MAPI_E_NO_SUPPORT)),
+    }
+}
+
+#[cfg(feature = "olmapi32")]
+fn mapi_module() -> Result<HMODULE> {
+    outlook_mapi_sys::ensure_olmapi32().or_else(|_| unsafe { LoadLibraryW(w!("mapi32")) })
+}
+
+#[cfg(not(feature = "olmapi32"))]
+fn mapi_module() -> Result<HMODULE> {
+    unsafe { LoadLibraryW(w!("mapi32")) }
+}
+
+/// Owned copy of an [`STnefProblem`], which reports a property or attribute that could not be
+/// encoded or decoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TnefProblem {
+    /// `0` for the message, or the [`sys::PR_ATTACH_NUM`] of an attachment.
+    pub component: u32,
+
+    /// TNEF attribute ID, for problems with a legacy attribute rather than a property.
+    pub attribute: u32,
+
+    /// Tag of the property.
+    pub tag: PropTag,
+
+    /// Error for this property.
+    pub hresult: HRESULT,
+}
+
+fn copy_problems(problems: &mut MAPIOutParam<STnefProblemArray>) -> Vec<TnefProblem> {
+    let Some(problems) = (unsafe { problems.as_mut() }) else {
+        return Vec::new();
+    };
+    unsafe { slice::from_raw_parts(problems.aProblem.as_ptr(), problems.cProblem as usize) }
+        .iter()
+        .map(|problem| TnefProblem {
+            component: problem.ulComponent,
+            attribute: problem.ulAttribute,
+            tag: PropTag(problem.ulPropTag),
+            hresult: HRESULT(problem.scode),
+        })
+        .collect()
+}
+
+/// Wrapper for an [`ITnef`] opened with `OpenTnefStreamEx`.
+///
+/// Most callers only need [`Message::import_tnef`] and [`Message::export_tnef`], which wrap the
+/// whole round trip.
+pub struct Tnef {
+    tnef: ITnef,
+    stream: IStream,
+}
+
+impl Tnef {
+    fn open(
+        stream: IStream,
+        flags: u32,
+        message: &Message,
+        address_book: Option<&sys::IAddrBook>,
+    ) -> Result<Self> {
+        let open = open_tnef_stream_ex()?;
+        let mut tnef = ptr::null_mut();
+        unsafe {
+            open(
+                ptr::null_mut(),
+                stream.as_raw(),
+                STREAM_NAME,
+                flags,
+                message.message().as_raw(),
+                TNEF_KEY,
+                address_book.map_or(ptr::null_mut(), Interface::as_raw),
+                &mut tnef,
+            )
+            .ok()?;
+            if tnef.is_null() {
+                return Err(Error::from(E_UNEXPECTED));
+            }
+            Ok(Self {
+                tnef: ITnef::from_raw(tnef),
+                stream,
+            })
+        }
+    }
+
+    /// Open a TNEF stream for decoding into `message`, with [`TNEF_DECODE`].
+    pub fn decode(
+        data: &[u8],
+        message: &Message,
+        address_book: Option<&sys::IAddrBook>,
+    ) -> Result<Self> {
+        Self::open(memory_stream(data)?, TNEF_DECODE, message, address_book)
+    }
+
+    /// Open an empty TNEF stream for encoding `message`, with [`TNEF_ENCODE`] and
+    /// [`TNEF_BEST_DATA`].
+    pub fn encode(message: &Message, address_book: Option<&sys::IAddrBook>) -> Result<Self> {
+        Self::open(
+            memory_stream(&[])?,
+            TNEF_ENCODE | TNEF_BEST_DATA,
+            message,
+            address_book,
+        )
+    }
+
+    /// Borrow the [`ITnef`], e.g. to call methods which do not have a wrapper yet.
+    pub fn tnef(&self) -> &ITnef {
+        &self.tnef
+    }
+
+    /// Decode every property, recipient, and attachment into the message with
+    /// [`ITnef::ExtractProps`]. The caller still needs to save the message.
+    pub fn extract_props(&self) -> Result<Vec<TnefProblem>> {
+        let mut exclude = [0_u32];
+        let mut problems: MAPIOutParam<STnefProblemArray> = Default::default();
+        unsafe {
+            self.tnef
+                .ExtractProps(
+                    TNEF_PROP_EXCLUDE,
+                    exclude.as_mut_ptr() as *mut _,
+                    problems.as_mut_ptr(),
+                )
+                .ok()?;
+        }
+        Ok(copy_problems(&mut problems))
+    }
+
+    /// Add every property and attachment of the message, except for `exclude`, with
+    /// [`ITnef::AddProps`], and its recipients with [`ITnef::EncodeRecips`].
+    pub fn add_props(&self, message: &Message, exclude: &[PropTag]) -> Result<()> {
+        let mut exclude: Vec<u32> = core::iter::once(exclude.len() as u32)
+            .chain(exclude.iter().copied().map(u32::from))
+            .collect();
+        let recipients = message.recipient_table()?;
+        unsafe {
+            self.tnef
+                .AddProps(
+                    TNEF_PROP_EXCLUDE,
+                    0,
+                    ptr::null_mut(),
+                    exclude.as_mut_ptr() as *mut _,
+                )
+                .ok()?;
+            self.tnef.EncodeRecips(0, recipients.as_raw()).ok()
+        }
+    }
+
+    /// Write the TNEF stream with [`ITnef::Finish`], and return the encoded bytes.
+    pub fn finish(self) -> Result<(Vec<u8>, Vec<TnefProblem>)> {
+        let mut key = TNEF_KEY;
+        let mut problems: MAPIOutParam<STnefProblemArray> = Default::default();
+        unsafe {
+            self.tnef.Finish(0, &mut key, problems.as_mut_ptr()).ok()?;
+        }
+        let problems = copy_problems(&mut problems);
+        Ok((read_whole_stream(&self.stream)?, problems))
+    }
+}
+
+impl Message {
+    /// Decode a TNEF stream, e.g. the data of a `winmail.dat` attachment, into this message with
+    /// [`Tnef::extract_props`], so the original properties and attachments can be read with the
+    /// rest of this crate. Pass the [`crate::AddressBook::address_book`] to resolve the
+    /// recipients. The changes are not persisted until [`Message::save_changes`] is called.
+    pub fn import_tnef(
+        &self,
+        data: &[u8],
+        address_book: Option<&sys::IAddrBook>,
+    ) -> Result<Vec<TnefProblem>> {
+        Tnef::decode(data, self, address_book)?.extract_props()
+    }
+
+    /// Encode this message, with its recipients and attachments, as a TNEF stream. Returns the
+    /// encoded bytes along with any [`TnefProblem`] for properties which could not be encoded.
+    pub fn export_tnef(
+        &self,
+        address_book: Option<&sys::IAddrBook>,
+    ) -> Result<(Vec<u8>, Vec<TnefProblem>)> {
+        let tnef = Tnef::encode(self, address_book)?;
+        tnef.add_props(self, &[])?;
+        tnef.finish()
+    }
+}
@@ -0,0 +1,83 @@
+//! Define [`charset_code_page`] and [`decode_header_value`], which extend the RFC 2047 decoding
+//! in [`crate::decode_encoded_words`] with the Windows code pages.
+
+use crate::{
+    decode_charset, decode_encoded_words_with, find_header_with, mapi_prop::decode_ansi, sys,
+    Message, PropTag, PropValueData,
+};
+use windows_core::*;
+
+/// Map a MIME charset name to the Windows code page which [`decode_header_value`] passes to
+/// `MultiByteToWideChar`, for the charsets which [`decode_charset`] does not handle itself.
+pub fn charset_code_page(charset: &str) -> Option<u32> {
+    let charset = charset.to_ascii_lowercase();
+    let code_page = match charset.as_str() {
+        "utf-7" => 65000,
+        "utf-8" => 65001,
+        "us-ascii" => 20127,
+        "shift_jis" | "shift-jis" | "x-sjis" | "cp932" => 932,
+        "gb2312" | "gbk" | "cp936" => 936,
+        "ks_c_5601-1987" | "euc-kr" | "cp949" => 949,
+        "big5" | "cp950" => 950,
+        "koi8-r" => 20866,
+        "koi8-u" => 21866,
+        "euc-jp" => 51932,
+        "iso-2022-jp" => 50220,
+        "iso-2022-kr" => 50225,
+        "gb18030" => 54936,
+        _ => {
+            if let Some(part) = charset.strip_prefix("iso-8859-") {
+                match part.parse::<u32>().ok()? {
+                    part @ 1..=9 => 28590 + part,
+                    13 => 28603,
+                    15 => 28605,
+                    _ => return None,
+                }
+            } else if let Some(code_page) = charset
+                .strip_prefix("windows-")
+                .or_else(|| charset.strip_prefix("cp"))
+            {
+                match code_page.parse::<u32>().ok()? {
+                    code_page @ (874 | 1250..=1258) => code_page,
+                    _ => return None,
+                }
+            } else {
+                return None;
+            }
+        }
+    };
+    Some(code_page)
+}
+
+fn decode_with_code_page(charset: &str, bytes: &[u8]) -> Option<String> {
+    decode_charset(charset, bytes).or_else(|| decode_ansi(bytes, charset_code_page(charset)?).ok())
+}
+
+/// Decode the RFC 2047 encoded-words in a header value, such as a `Subject` copied from
+/// [`sys::PR_TRANSPORT_MESSAGE_HEADERS_W`] or a legacy TNEF attribute, so it matches the value
+/// which the transport stored in the decoded property, e.g. [`sys::PR_SUBJECT_W`]. Charsets which
+/// [`decode_charset`] does not handle are decoded with [`charset_code_page`].
+pub fn decode_header_value(value: &str) -> String {
+    decode_encoded_words_with(value, decode_with_code_page)
+}
+
+impl Message {
+    /// Read the raw [`sys::PR_TRANSPORT_MESSAGE_HEADERS_W`] of a message which was received over
+    /// the internet. Returns `None` if the message does not have any.
+    pub fn transport_headers(&self) -> Result<Option<String>> {
+        let tag = PropTag(sys::PR_TRANSPORT_MESSAGE_HEADERS_W);
+        let row = self.get_props(&[tag])?;
+        match row.find(tag).map(|value| value.value) {
+            Some(PropValueData::Unicode(value)) => Ok(Some(unsafe { value.to_string() }?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Find and unfold a single header by `name` in the [`Message::transport_headers`], and
+    /// decode it with [`decode_header_value`].
+    pub fn transport_header(&self, name: &str) -> Result<Option<String>> {
+        Ok(self
+            .transport_headers()?
+            .and_then(|headers| find_header_with(&headers, name, decode_with_code_page)))
+    }
+}
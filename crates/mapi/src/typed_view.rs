@@ -0,0 +1,32 @@
+//! Define [`TypedView`] and [`query_view`].
+
+use crate::{query_all_rows, sys, PropTag, Row};
+use windows_core::*;
+
+/// A typed row parsed from a MAPI table, such as [`crate::Reminder`].
+///
+/// The columns are derived from the same type which parses the rows, so the columns set on the
+/// table always match what [`TypedView::from_row`] expects. Named properties have different tags
+/// in every store, so [`TypedView::columns`] resolves them against the store each time.
+pub trait TypedView: Sized {
+    /// Get the columns which [`TypedView::from_row`] reads, including the named property tags for
+    /// this store. Returns `None` if the store has never mapped one of the required named
+    /// properties, in which case no row in the store can have a value for it.
+    fn columns(store: &sys::IMsgStore) -> Result<Option<Vec<PropTag>>>;
+
+    /// Parse a row which was read with the `columns` returned by [`TypedView::columns`].
+    fn from_row(row: &Row, columns: &[PropTag]) -> Self;
+}
+
+/// Read every row of `table` as a [`TypedView`], with the columns from [`TypedView::columns`].
+/// Returns an empty list if the store has not mapped the named properties the view requires.
+pub fn query_view<T: TypedView>(store: &sys::IMsgStore, table: &sys::IMAPITable) -> Result<Vec<T>> {
+    let Some(columns) = T::columns(store)? else {
+        return Ok(Vec::new());
+    };
+    let rows = query_all_rows(table, &columns, None, None, 0)?;
+    Ok(rows
+        .into_iter()
+        .map(|row| T::from_row(&row, &columns))
+        .collect())
+}
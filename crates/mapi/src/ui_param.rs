@@ -0,0 +1,55 @@
+//! Define [`UiParam`].
+
+use windows::Win32::Foundation::HWND;
+
+#[cfg(feature = "raw-window-handle")]
+use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+#[cfg(feature = "raw-window-handle")]
+use windows::Win32::Foundation::E_INVALIDARG;
+#[cfg(feature = "raw-window-handle")]
+use windows_core::*;
+
+/// Parent window for any dialogs MAPI shows, e.g. the profile picker in [`crate::Logon::new`].
+///
+/// Functions which take a `ui_param` accept `impl Into<UiParam>`, so an [`HWND`] still works as
+/// before. With the `raw-window-handle` feature, GUI frameworks which implement
+/// [`raw_window_handle::HasWindowHandle`] (e.g. `winit` or `eframe`) can be passed to
+/// [`UiParam::from_window`] instead of extracting the [`HWND`] by hand.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UiParam {
+    hwnd: HWND,
+}
+
+impl UiParam {
+    /// Get the [`HWND`] of the parent window, which is null if there is none.
+    pub fn hwnd(&self) -> HWND {
+        self.hwnd
+    }
+
+    /// Get the parent window from a [`HasWindowHandle`]. Fails with `E_INVALIDARG` if the handle
+    /// is unavailable or is not a [`RawWindowHandle::Win32`] handle.
+    #[cfg(feature = "raw-window-handle")]
+    pub fn from_window(window: &impl HasWindowHandle) -> Result<Self> {
+        let handle = window
+            .window_handle()
+            .map_err(|_| Error::from(E_INVALIDARG))?;
+        match handle.as_raw() {
+            RawWindowHandle::Win32(handle) => Ok(Self {
+                hwnd: HWND(handle.hwnd.get() as *mut _),
+            }),
+            _ => Err(Error::from(E_INVALIDARG)),
+        }
+    }
+}
+
+impl From<HWND> for UiParam {
+    fn from(hwnd: HWND) -> Self {
+        Self { hwnd }
+    }
+}
+
+impl From<UiParam> for HWND {
+    fn from(value: UiParam) -> Self {
+        value.hwnd
+    }
+}
@@ -0,0 +1,67 @@
+//! Define [`voting_options`], [`set_voting_options`], and [`vote_response`].
+
+use crate::{
+    mapi_prop::{get_named_prop_tags, get_one_prop, set_props, SetPropValue},
+    sys, MapiProp, PropValueData, VerbStream,
+};
+use windows_core::*;
+
+/// `PidLidVerbStream` in [`sys::PSETID_Common`].
+pub const LID_VERB_STREAM: i32 = 0x8520;
+
+/// `PidLidVerbResponse` in [`sys::PSETID_Common`].
+pub const LID_VERB_RESPONSE: i32 = 0x8524;
+
+/// Read and parse the `PidLidVerbStream` on a message. Returns `None` if the message does not have
+/// any voting buttons.
+pub fn voting_options(message: &sys::IMessage) -> Result<Option<VerbStream>> {
+    let [Some(tag)] = get_named_prop_tags(
+        message,
+        &sys::PSETID_Common,
+        &[(LID_VERB_STREAM, sys::PT_BINARY)],
+        0,
+    )?[..] else {
+        return Ok(None);
+    };
+    let value = match message.get_binary(tag) {
+        Err(err) if err.code() == sys::MAPI_E_NOT_FOUND => return Ok(None),
+        result => result?,
+    };
+    VerbStream::parse(&value)
+        .map(Some)
+        .map_err(|_| Error::from(sys::MAPI_E_CORRUPT_DATA))
+}
+
+/// Serialize and set the `PidLidVerbStream` on a message being composed, e.g. with
+/// [`VerbStream::from_labels`]. The caller still needs to call [`sys::IMAPIProp::SaveChanges`].
+pub fn set_voting_options(message: &sys::IMessage, verbs: &VerbStream) -> Result<()> {
+    let value = verbs
+        .to_bytes()
+        .map_err(|_| Error::from(sys::MAPI_E_INVALID_PARAMETER))?;
+    let [Some(tag)] = get_named_prop_tags(
+        message,
+        &sys::PSETID_Common,
+        &[(LID_VERB_STREAM, sys::PT_BINARY)],
+        sys::MAPI_CREATE,
+    )?[..] else {
+        return Err(Error::from(sys::MAPI_E_NOT_FOUND));
+    };
+    set_props(message, &[(u32::from(tag), SetPropValue::Binary(&value))])
+}
+
+/// Read the `PidLidVerbResponse` on a response message, which is the
+/// [`crate::VoteOption::display_name`] of the button the recipient chose.
+pub fn vote_response(message: &sys::IMessage) -> Result<Option<String>> {
+    let [Some(tag)] = get_named_prop_tags(
+        message,
+        &sys::PSETID_Common,
+        &[(LID_VERB_RESPONSE, sys::PT_UNICODE)],
+        0,
+    )?[..] else {
+        return Ok(None);
+    };
+    get_one_prop(message, u32::from(tag), |value| match value {
+        PropValueData::Unicode(value) => unsafe { value.to_string() }.ok(),
+        _ => None,
+    })
+}
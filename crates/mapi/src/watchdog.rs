@@ -0,0 +1,268 @@
+//! Define [`Watchdog`] and [`SlowCall`].
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Condvar, Mutex},
+    thread::{self, ThreadId},
+    time::{Duration, Instant},
+};
+
+/// Report passed to the [`Watchdog`] callback when a watched call runs longer than the threshold.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlowCall {
+    /// Name passed to [`Watchdog::watch`], e.g. `"IMAPITable::QueryRows"`.
+    pub name: &'static str,
+
+    /// Thread which made the call.
+    pub thread: ThreadId,
+
+    /// How long the call had been running when the report was made.
+    pub elapsed: Duration,
+
+    /// `false` for the first report, which the watchdog thread makes while the call is still
+    /// running, and `true` for the second report with the total duration once it returns.
+    pub finished: bool,
+}
+
+type Reporter = dyn Fn(&SlowCall) + Send + Sync;
+
+struct ActiveCall {
+    name: &'static str,
+    thread: ThreadId,
+    started: Instant,
+    reported: bool,
+}
+
+#[derive(Default)]
+struct WatchdogState {
+    next_id: u64,
+    calls: HashMap<u64, ActiveCall>,
+    stopped: bool,
+}
+
+struct Shared {
+    state: Mutex<WatchdogState>,
+    wake: Condvar,
+    threshold: Duration,
+    reporter: Box<Reporter>,
+}
+
+/// Opt-in watchdog which reports calls that take longer than a threshold, to diagnose hangs
+/// against slow providers or servers.
+///
+/// MAPI calls cannot be safely cancelled, so the watchdog never interrupts a call. Each call runs
+/// to completion on the calling thread, while a separate watchdog thread reports it with
+/// [`SlowCall::finished`] set to `false` as soon as it passes the threshold. If the call does
+/// return, it is reported again with the total duration.
+///
+/// The reports go to a callback rather than a particular logging framework, e.g. to forward them
+/// to `tracing::warn!` or to a log file.
+///
+/// ```no_run
+/// # use outlook_mapi::*;
+/// # use std::time::Duration;
+/// # fn example(table: &MapiTable) -> windows_core::Result<()> {
+/// let watchdog = Watchdog::new(Duration::from_secs(30), |call| {
+///     eprintln!("{} has been running for {:?}", call.name, call.elapsed);
+/// });
+/// let rows = watchdog.watch("IMAPITable::QueryRows", || table.query_rows(100))?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Watchdog {
+    shared: Arc<Shared>,
+    monitor: Option<thread::JoinHandle<()>>,
+}
+
+impl Watchdog {
+    /// Start the watchdog thread, which passes a [`SlowCall`] to `reporter` whenever a call takes
+    /// longer than `threshold`.
+    pub fn new<F>(threshold: Duration, reporter: F) -> Self
+    where
+        F: Fn(&SlowCall) + Send + Sync + 'static,
+    {
+        let shared = Arc::new(Shared {
+            state: Default::default(),
+            wake: Condvar::new(),
+            threshold,
+            reporter: Box::new(reporter),
+        });
+        let monitor = {
+            let shared = shared.clone();
+            thread::spawn(move || monitor(&shared))
+        };
+        Self {
+            shared,
+            monitor: Some(monitor),
+        }
+    }
+
+    /// Get the threshold passed to [`Watchdog::new`].
+    pub fn threshold(&self) -> Duration {
+        self.shared.threshold
+    }
+
+    /// Run `call` on the calling thread, and report it if it takes longer than the
+    /// [`Watchdog::threshold`]. `name` identifies the call in the [`SlowCall`] report.
+    pub fn watch<T>(&self, name: &'static str, call: impl FnOnce() -> T) -> T {
+        let _guard = WatchGuard::new(&self.shared, name);
+        call()
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.shared
+            .state
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .stopped = true;
+        self.shared.wake.notify_all();
+        if let Some(monitor) = self.monitor.take() {
+            let _ = monitor.join();
+        }
+    }
+}
+
+/// Register a call with the watchdog thread, and remove it again when dropped, even if the call
+/// panics.
+struct WatchGuard<'a> {
+    shared: &'a Shared,
+    id: u64,
+    started: Instant,
+}
+
+impl<'a> WatchGuard<'a> {
+    fn new(shared: &'a Shared, name: &'static str) -> Self {
+        let started = Instant::now();
+        let mut state = shared.state.lock().unwrap_or_else(|err| err.into_inner());
+        let id = state.next_id;
+        state.next_id += 1;
+        state.calls.insert(
+            id,
+            ActiveCall {
+                name,
+                thread: thread::current().id(),
+                started,
+                reported: false,
+            },
+        );
+        drop(state);
+        shared.wake.notify_all();
+        Self {
+            shared,
+            id,
+            started,
+        }
+    }
+}
+
+impl Drop for WatchGuard<'_> {
+    fn drop(&mut self) {
+        let call = self
+            .shared
+            .state
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .calls
+            .remove(&self.id);
+        if let Some(call) = call.filter(|call| call.reported) {
+            (self.shared.reporter)(&SlowCall {
+                name: call.name,
+                thread: call.thread,
+                elapsed: self.started.elapsed(),
+                finished: true,
+            });
+        }
+    }
+}
+
+/// Body of the watchdog thread. It sleeps until the earliest call which has not been reported yet
+/// reaches the threshold, or until a new call is registered.
+fn monitor(shared: &Shared) {
+    let mut state = shared.state.lock().unwrap_or_else(|err| err.into_inner());
+    loop {
+        if state.stopped {
+            break;
+        }
+
+        let now = Instant::now();
+        let mut reports = Vec::new();
+        let mut next_deadline = None;
+        for call in state.calls.values_mut().filter(|call| !call.reported) {
+            let deadline = call.started + shared.threshold;
+            if deadline <= now {
+                call.reported = true;
+                reports.push(SlowCall {
+                    name: call.name,
+                    thread: call.thread,
+                    elapsed: now - call.started,
+                    finished: false,
+                });
+            } else {
+                next_deadline =
+                    Some(next_deadline.map_or(deadline, |next: Instant| next.min(deadline)));
+            }
+        }
+
+        if !reports.is_empty() {
+            drop(state);
+            for report in reports.iter() {
+                (shared.reporter)(report);
+            }
+            state = shared.state.lock().unwrap_or_else(|err| err.into_inner());
+            continue;
+        }
+
+        state = match next_deadline {
+            Some(deadline) => {
+                shared
+                    .wake
+                    .wait_timeout(state, deadline.saturating_duration_since(now))
+                    .unwrap_or_else(|err| err.into_inner())
+                    .0
+            }
+            None => shared
+                .wake
+                .wait(state)
+                .unwrap_or_else(|err| err.into_inner()),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn fast_call() {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        let watchdog = Watchdog::new(Duration::from_secs(60), move |call| {
+            let _ = sender.lock().unwrap().send(call.clone());
+        });
+        assert_eq!(watchdog.watch("fast", || 42), 42);
+        drop(watchdog);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn slow_call() {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        let watchdog = Watchdog::new(Duration::from_millis(10), move |call| {
+            let _ = sender.lock().unwrap().send(call.clone());
+        });
+        watchdog.watch("slow", || thread::sleep(Duration::from_millis(200)));
+        drop(watchdog);
+
+        let reports: Vec<_> = receiver.try_iter().collect();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].name, "slow");
+        assert!(!reports[0].finished);
+        assert!(reports[1].finished);
+        assert!(reports[1].elapsed >= Duration::from_millis(200));
+        assert_eq!(reports[1].thread, thread::current().id());
+    }
+}
@@ -0,0 +1,81 @@
+//! Define [`WeakLogon`] and [`WeakStore`].
+
+use crate::{Logon, Store};
+use std::rc::{self, Rc};
+
+/// Non-owning handle to a [`Logon`] shared with [`Rc`], created with [`Logon::downgrade`].
+///
+/// An advise sink or a cache which holds an `Rc<Logon>` keeps the session, and the
+/// [`crate::Initialize`] behind it, alive for as long as it exists, which often means until the
+/// process exits if the sink is registered with the session itself. Holding a [`WeakLogon`]
+/// instead lets the owner drop the last [`Rc`] and log off, and [`WeakLogon::upgrade`] then
+/// returns `None`.
+#[derive(Clone, Default)]
+pub struct WeakLogon {
+    logon: rc::Weak<Logon>,
+}
+
+impl WeakLogon {
+    /// Get a strong reference to the [`Logon`] if it is still alive.
+    pub fn upgrade(&self) -> Option<Rc<Logon>> {
+        self.logon.upgrade()
+    }
+
+    /// Test if the [`Logon`] has not been dropped yet.
+    pub fn is_alive(&self) -> bool {
+        self.logon.strong_count() > 0
+    }
+}
+
+impl From<&Rc<Logon>> for WeakLogon {
+    fn from(value: &Rc<Logon>) -> Self {
+        Self {
+            logon: Rc::downgrade(value),
+        }
+    }
+}
+
+impl Logon {
+    /// Create a [`WeakLogon`] which does not keep this session alive.
+    pub fn downgrade(this: &Rc<Self>) -> WeakLogon {
+        WeakLogon::from(this)
+    }
+}
+
+/// Non-owning handle to a [`Store`] shared with [`Rc`], created with [`Store::downgrade`].
+///
+/// Cloning a [`Store`] adds a COM reference, and COM has no weak references of its own, so a
+/// [`WeakStore`] only works for a [`Store`] which is shared through an [`Rc`]. Once the last
+/// [`Rc`] is dropped, the [`sys::IMsgStore`](crate::sys::IMsgStore) is released and
+/// [`WeakStore::upgrade`] returns `None`.
+#[derive(Clone, Default)]
+pub struct WeakStore {
+    store: rc::Weak<Store>,
+}
+
+impl WeakStore {
+    /// Get a strong reference to the [`Store`] if it is still alive.
+    pub fn upgrade(&self) -> Option<Rc<Store>> {
+        self.store.upgrade()
+    }
+
+    /// Test if the [`Store`] has not been dropped yet.
+    pub fn is_alive(&self) -> bool {
+        self.store.strong_count() > 0
+    }
+}
+
+impl From<&Rc<Store>> for WeakStore {
+    fn from(value: &Rc<Store>) -> Self {
+        Self {
+            store: Rc::downgrade(value),
+        }
+    }
+}
+
+impl Store {
+    /// Create a [`WeakStore`] which does not keep this store open.
+    pub fn downgrade(this: &Rc<Self>) -> WeakStore {
+        WeakStore::from(this)
+    }
+}
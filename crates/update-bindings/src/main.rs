@@ -1,8 +1,34 @@
 extern crate windows_bindgen;
 
+const USAGE: &str = "usage: cargo xtask [--check]
+
+Regenerate crates/mapi-sys/src/Microsoft.rs from the winmd file.
+
+    --check    Fail without writing anything if Microsoft.rs is out of date";
+
 fn main() -> Result<()> {
-    if mapi_bindgen::update_mapi_sys()? {
-        println!("Microsoft.rs changed");
+    let mut mode = mapi_bindgen::Mode::Update;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--check" => mode = mapi_bindgen::Mode::Check,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => {
+                eprintln!("{USAGE}");
+                return Err(Error::UnknownArgument(arg));
+            }
+        }
+    }
+
+    match (mode, mapi_bindgen::update_mapi_sys(mode)?) {
+        (mapi_bindgen::Mode::Update, true) => println!("Microsoft.rs changed"),
+        (mapi_bindgen::Mode::Check, true) => {
+            eprintln!("Microsoft.rs is out of date, run `cargo xtask` to regenerate it");
+            std::process::exit(1);
+        }
+        (_, false) => println!("Microsoft.rs is up to date"),
     }
 
     Ok(())
@@ -12,6 +38,8 @@ fn main() -> Result<()> {
 pub enum Error {
     #[error("Missing Parent")]
     MissingParent(std::path::PathBuf),
+    #[error("Unknown Argument")]
+    UnknownArgument(String),
     #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error(transparent)]
@@ -58,7 +86,17 @@ mod mapi_bindgen {
 
     use super::mapi_path::*;
 
-    pub fn update_mapi_sys() -> super::Result<bool> {
+    /// What [`update_mapi_sys`] should do with the regenerated bindings.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Mode {
+        /// Overwrite `Microsoft.rs` if it changed.
+        Update,
+
+        /// Only report whether `Microsoft.rs` is out of date.
+        Check,
+    }
+
+    pub fn update_mapi_sys(mode: Mode) -> super::Result<bool> {
         let source_path = generate_mapi_sys()?;
         format_mapi_sys(&source_path)?;
         let source = read_mapi_sys(&source_path)?;
@@ -69,7 +107,9 @@ mod mapi_bindgen {
         let dest = read_mapi_sys(&dest_path)?;
 
         if source != dest {
-            fs::copy(&source_path, &dest_path)?;
+            if mode == Mode::Update {
+                fs::copy(&source_path, &dest_path)?;
+            }
             Ok(true)
         } else {
             Ok(false)